@@ -1,3 +1,5 @@
+#![allow(clippy::manual_div_ceil, clippy::assign_op_pattern)]
+
 pub mod safe_math;
 
 use uint::construct_uint;
@@ -41,10 +41,12 @@ pub trait CheckedDivFloor<RHS = Self> {
     fn checked_div_down(self, denom: RHS) -> Option<Self::Output>;
 }
 
+#[allow(clippy::wrong_self_convention)]
 pub trait Upcast {
     fn as_u192(self) -> U192;
 }
 
+#[allow(clippy::wrong_self_convention)]
 pub trait Downcast {
     fn as_u64(self) -> Option<u64>;
 }
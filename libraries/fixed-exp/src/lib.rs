@@ -180,6 +180,7 @@ macro_rules! impl_fixed_pow {
 
 impl_fixed_pow!(FixedU64, LeEqU64, U63);
 
+#[allow(clippy::wrong_self_convention)]
 trait Helper {
     const NUM_BITS: u32;
     fn is_one(self) -> bool;
@@ -1,6 +0,0 @@
-pub const AUTHORITY: &[u8] = b"AUTHORITY";
-pub const MINT: &[u8] = b"MINT";
-
-pub const WEIGHT_POOL: &[u8] = b"WEIGHT_POOL";
-pub const STABLE_POOL: &[u8] = b"STABLE_POOL";
-pub const POOL_VAULT: &[u8] = b"POOL_VAULT";
\ No newline at end of file
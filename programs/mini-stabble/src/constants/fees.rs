@@ -0,0 +1,15 @@
+//! Bounds on the swap fees pools may charge, so no setter can configure a pool that keeps
+//! nearly all of every trade
+
+use crate::math::fixed::{ONE_U64, SCALE};
+
+/// Denominator of every swap fee, the fixed-point SCALE (1e9 = 100%)
+pub const FEE_DENOMINATOR: u64 = ONE_U64;
+
+/// Largest swap fee any pool may charge (10%)
+pub const MAX_SWAP_FEE: u64 = FEE_DENOMINATOR / 10;
+
+// Fees are applied with the fixed-point helpers, so they must share their scale, and a pool at
+// the cap must still pay out most of a trade
+const _: () = assert!(FEE_DENOMINATOR as u128 == SCALE);
+const _: () = assert!(MAX_SWAP_FEE > 0 && MAX_SWAP_FEE < FEE_DENOMINATOR);
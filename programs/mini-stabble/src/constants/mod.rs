@@ -0,0 +1,31 @@
+pub const AUTHORITY: &[u8] = b"AUTHORITY";
+pub const MINT: &[u8] = b"MINT";
+
+pub const WEIGHT_POOL: &[u8] = b"WEIGHT_POOL";
+pub const STABLE_POOL: &[u8] = b"STABLE_POOL";
+pub const POOL_VAULT: &[u8] = b"POOL_VAULT";
+pub const CONFIG: &[u8] = b"CONFIG";
+pub const WITHDRAW_REQUEST: &[u8] = b"WITHDRAW_REQUEST";
+pub const TOKEN_BADGE: &[u8] = b"TOKEN_BADGE";
+pub const PROTOCOL_LP: &[u8] = b"PROTOCOL_LP";
+pub const USER_STATS: &[u8] = b"USER_STATS";
+pub const INTENT_NONCE: &[u8] = b"INTENT_NONCE";
+pub const DEPOSIT_DELEGATE: &[u8] = b"DEPOSIT_DELEGATE";
+pub const PEG_KEEPER: &[u8] = b"PEG_KEEPER";
+pub const USER_REBATE_STATS: &[u8] = b"USER_REBATE_STATS";
+pub const CREATOR_LOCK: &[u8] = b"CREATOR_LOCK";
+pub const POOL_PARAMS: &[u8] = b"POOL_PARAMS";
+pub const PROPOSAL: &[u8] = b"PROPOSAL";
+pub const PROPOSAL_ESCROW: &[u8] = b"PROPOSAL_ESCROW";
+pub const VOTE_RECORD: &[u8] = b"VOTE_RECORD";
+pub const POOL_FEE_STATS: &[u8] = b"POOL_FEE_STATS";
+pub const TRADER_ALLOWLIST: &[u8] = b"TRADER_ALLOWLIST";
+pub const SWAP_CHECKPOINT: &[u8] = b"SWAP_CHECKPOINT";
+pub const VIRTUAL_PRICE_CHECKPOINT: &[u8] = b"VIRTUAL_PRICE_CHECKPOINT";
+pub const INTERNAL_BALANCE: &[u8] = b"INTERNAL_BALANCE";
+pub const LP_POSITION: &[u8] = b"LP_POSITION";
+pub const UPGRADE_GUARD: &[u8] = b"UPGRADE_GUARD";
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+pub mod fees;
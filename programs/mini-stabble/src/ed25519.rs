@@ -0,0 +1,131 @@
+//! Checks that a transaction carries an ed25519 signature by a given key over a given message.
+//! The runtime verifies the signature itself when it executes the ed25519 program instruction;
+//! all we do is read that instruction back through the instructions sysvar and check what it
+//! verified.
+
+use anchor_lang::prelude::*;
+use solana_instructions_sysvar::get_instruction_relative;
+use solana_sdk_ids::ed25519_program;
+
+use crate::errors::MiniStabbleError;
+
+/// Size of the signature count header preceding the offsets
+const HEADER_LEN: usize = 2;
+
+/// Size of one `Ed25519SignatureOffsets` entry (seven little-endian u16s)
+const OFFSETS_LEN: usize = 14;
+
+/// `instruction_index` value meaning "this same instruction"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Requires the instruction right before the current one to be an ed25519 program instruction
+/// verifying exactly one signature by `signer` over `message`
+pub fn require_preceding_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let instruction = get_instruction_relative(-1, instructions_sysvar)
+        .map_err(|_| MiniStabbleError::InvalidIntentSignature)?;
+
+    require!(
+        instruction.program_id == ed25519_program::ID
+            && instruction.accounts.is_empty()
+            && verifies_signature(&instruction.data, signer, message),
+        MiniStabbleError::InvalidIntentSignature
+    );
+
+    Ok(())
+}
+
+/// Whether ed25519 program instruction `data` verifies a single signature by `signer` over
+/// `message`, with every part stored inline in that instruction
+fn verifies_signature(data: &[u8], signer: &Pubkey, message: &[u8]) -> bool {
+    if data.len() < HEADER_LEN + OFFSETS_LEN || data[0] != 1 {
+        return false;
+    }
+
+    let offset = |index: usize| {
+        let at = HEADER_LEN + 2 * index;
+        u16::from_le_bytes([data[at], data[at + 1]])
+    };
+    let [signature_offset, signature_instruction, public_key_offset, public_key_instruction, message_offset, message_size, message_instruction] =
+        std::array::from_fn(offset);
+
+    // Pieces read from other instructions could say anything
+    if [
+        signature_instruction,
+        public_key_instruction,
+        message_instruction,
+    ]
+    .iter()
+    .any(|&index| index != CURRENT_INSTRUCTION)
+    {
+        return false;
+    }
+
+    let slice = |start: u16, len: usize| data.get(start as usize..start as usize + len);
+
+    slice(signature_offset, 64).is_some()
+        && slice(public_key_offset, 32) == Some(signer.as_ref())
+        && message_size as usize == message.len()
+        && slice(message_offset, message.len()) == Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Instruction data laid out the way `new_ed25519_instruction` builds it
+    fn instruction_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let public_key_offset = (HEADER_LEN + OFFSETS_LEN) as u16;
+        let signature_offset = public_key_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for value in [
+            signature_offset,
+            CURRENT_INSTRUCTION,
+            public_key_offset,
+            CURRENT_INSTRUCTION,
+            message_offset,
+            message.len() as u16,
+            CURRENT_INSTRUCTION,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn test_verifies_matching_signer_and_message() {
+        let signer = Pubkey::new_unique();
+        let data = instruction_data(&signer, b"intent");
+
+        assert!(verifies_signature(&data, &signer, b"intent"));
+        assert!(!verifies_signature(&data, &Pubkey::new_unique(), b"intent"));
+        assert!(!verifies_signature(&data, &signer, b"intent2"));
+        assert!(!verifies_signature(&data, &signer, b"inten"));
+        assert!(!verifies_signature(
+            &data[..data.len() - 1],
+            &signer,
+            b"intent"
+        ));
+    }
+
+    #[test]
+    fn test_rejects_data_from_other_instructions() {
+        let signer = Pubkey::new_unique();
+
+        let mut data = instruction_data(&signer, b"intent");
+        data[HEADER_LEN + 4..HEADER_LEN + 6].copy_from_slice(&0u16.to_le_bytes());
+        assert!(!verifies_signature(&data, &signer, b"intent"));
+
+        let mut data = instruction_data(&signer, b"intent");
+        data[0] = 2;
+        assert!(!verifies_signature(&data, &signer, b"intent"));
+    }
+}
@@ -33,5 +33,222 @@ pub enum MiniStabbleError {
     AmpTooLow,
 
     #[msg("AmpTooHigh")]
-    AmpTooHigh
+    AmpTooHigh,
+
+    #[msg("Signer is not the protocol admin")]
+    Unauthorized,
+
+    #[msg("Withdraw queue is not enabled for this pool")]
+    WithdrawQueueDisabled,
+
+    #[msg("Invalid withdraw queue configuration")]
+    InvalidWithdrawQueueConfig,
+
+    #[msg("Nothing to claim yet")]
+    NothingToClaim,
+
+    #[msg("Swap fee is below the minimum allowed for this amp")]
+    FeeBelowAmpFloor,
+
+    #[msg("Invalid amp ramp")]
+    InvalidAmpRamp,
+
+    #[msg("Exit exceeds the withdraw queue threshold, use request_withdraw")]
+    ExitRequiresWithdrawQueue,
+
+    #[msg("Invalid account provided")]
+    InvalidAccount,
+
+    #[msg("Mint has extensions that require a token badge")]
+    TokenBadgeRequired,
+
+    #[msg("Mint is not supported")]
+    UnsupportedMint,
+
+    #[msg("Signer is neither the owner nor an approved delegate of the LP account")]
+    LpBurnNotAuthorized,
+
+    #[msg("Dry run: instruction succeeded and was reverted")]
+    DryRun,
+
+    #[msg("Trade is too small to produce any output")]
+    AmountTooSmall,
+
+    #[msg("Deposit ratio is too far from the pool ratio")]
+    DepositTooImbalanced,
+
+    #[msg("Pool must be paused for this operation")]
+    PoolNotPaused,
+
+    #[msg("Scaling factor must be a power of ten")]
+    InvalidScalingFactor,
+
+    #[msg("Swap rate limit reached for this window, retry later")]
+    RateLimitExceeded,
+
+    #[msg("Invalid swap rate limit configuration")]
+    InvalidRateLimitConfig,
+
+    #[msg("Joining and exiting straight away would return more than was deposited")]
+    RoundTripProfit,
+
+    #[msg("Swap moves the spot price further than the pool allows")]
+    PriceMoveTooLarge,
+
+    #[msg("Slippage tolerance must be at most 10000 bps")]
+    InvalidSlippage,
+
+    #[msg("Split must be at most 10000 bps and needs a secondary recipient")]
+    InvalidSplit,
+
+    #[msg("Drawdown guard threshold must be at most 10000 bps")]
+    InvalidDrawdownGuard,
+
+    #[msg("Swap would drop the invariant per LP token past the drawdown guard")]
+    DrawdownGuardTripped,
+
+    #[msg("Fee autopilot needs min fee <= max fee < 100% and a positive volatility cap")]
+    InvalidFeeAutopilot,
+
+    #[msg("Fee autopilot is only available on 80/20 governance pools")]
+    NotGovernancePool,
+
+    #[msg("Fee autopilot is not enabled on this pool")]
+    FeeAutopilotDisabled,
+
+    #[msg("Fee was updated too recently")]
+    FeeUpdateTooEarly,
+
+    #[msg("Missing or invalid ed25519 signature over the intent")]
+    InvalidIntentSignature,
+
+    #[msg("Intent has expired")]
+    IntentExpired,
+
+    #[msg("Intent nonce does not match the next expected nonce")]
+    InvalidNonce,
+
+    #[msg("Invalid peg keeper configuration")]
+    InvalidPegKeeper,
+
+    #[msg("Peg keeper has nothing to do at the current price")]
+    PegKeeperNoAction,
+
+    #[msg("Peg keeper acted too recently")]
+    PegKeeperCooldown,
+
+    #[msg("Only the epoch before the current one can be claimed")]
+    RebateNotClaimable,
+
+    #[msg("Rebate already claimed for this epoch and token")]
+    RebateAlreadyClaimed,
+
+    #[msg("Invalid creator lock duration")]
+    InvalidCreatorLock,
+
+    #[msg("Creator liquidity is still locked")]
+    CreatorLockActive,
+
+    #[msg("Pool must be paused first")]
+    PoolStillActive,
+
+    #[msg("Pool still has LP outstanding")]
+    PoolNotEmpty,
+
+    #[msg("Only the pool's initial depositor can make the first deposit")]
+    InitialDepositorOnly,
+
+    #[msg("Invalid proposal")]
+    InvalidProposal,
+
+    #[msg("Proposal is no longer open")]
+    ProposalClosed,
+
+    #[msg("Proposal is still open")]
+    ProposalOpen,
+
+    #[msg("Proposal has not been approved")]
+    ProposalNotApproved,
+
+    #[msg("Trader is not on the pool's allowlist")]
+    TraderNotAllowlisted,
+
+    #[msg("Swap output exceeds the pool's max out ratio")]
+    MaxOutRatioExceeded,
+
+    #[msg("Swaps are disabled for this token")]
+    TokenSwapsDisabled,
+
+    #[msg("Crank keeper limit reached")]
+    TooManyCrankKeepers,
+
+    #[msg("Virtual price checkpoint is too old")]
+    StaleVirtualPriceCheckpoint,
+
+    #[msg("Virtual price is not below the checkpoint")]
+    InvariantDecreaseNotProven,
+
+    #[msg("Virtual price is below the checkpoint; report it instead")]
+    VirtualPriceBelowCheckpoint,
+
+    #[msg("Internal balance too low")]
+    InsufficientInternalBalance,
+
+    #[msg("An LP can't refer itself")]
+    InvalidReferrer,
+
+    #[msg("No upgrade with this hash is committed")]
+    UpgradeNotCommitted,
+
+    #[msg("The committed upgrade's timelock hasn't passed")]
+    UpgradeTimelocked,
+
+    #[msg("The program hasn't been redeployed since the last acknowledged upgrade")]
+    UpgradeNotDeployed,
+
+    #[msg("Every program deploy is acknowledged")]
+    UpgradeAcknowledged,
+
+    #[msg("Pool is within its drawdown guard")]
+    DrawdownGuardNotTripped,
+
+    #[msg("Proposal is not in its voting period")]
+    ProposalNotVoting,
+
+    #[msg("Proposal's execution delay hasn't passed")]
+    ProposalTimelocked,
+
+    #[msg("Escrowed LP has already voted")]
+    AlreadyVoted,
+
+    #[msg("Upgrade signers must be distinct, at most 8, and cover the threshold")]
+    InvalidUpgradeSigners,
+
+    #[msg("Not enough upgrade signers approved")]
+    UpgradeQuorumNotMet,
+}
+
+/// Last step of a handler taking `dry_run`: everything before it ran for real, so failing here
+/// reverts it and leaves the would-be event and result in the simulation logs
+pub fn end_dry_run(dry_run: bool) -> Result<()> {
+    require!(!dry_run, MiniStabbleError::DryRun);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_end_dry_run() {
+        assert!(end_dry_run(false).is_ok());
+        assert_eq!(end_dry_run(true), Err(MiniStabbleError::DryRun.into()));
+    }
+
+    #[test]
+    fn test_dry_run_error_code_is_stable() {
+        // Integrators match this code in `simulateTransaction` results
+        assert_eq!(u32::from(MiniStabbleError::DryRun), 6022);
+    }
 }
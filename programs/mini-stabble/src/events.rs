@@ -0,0 +1,364 @@
+use anchor_lang::{prelude::*, solana_program::log::sol_log_data};
+
+use crate::state::{DustDestination, ProposedChange};
+
+#[event]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+
+    /// Raw amount sent by the user
+    pub amount_in: u64,
+
+    /// Raw amount sent from the vault
+    pub amount_out: u64,
+}
+
+/// Outcome of a weighted or stable swap, set as the instruction's return data for CPI callers
+/// and logged as one `Program data:` entry (plain Borsh, no discriminator) for indexers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapResult {
+    /// Raw amount sent by the user
+    pub amount_in: u64,
+
+    /// Raw amount sent from the vault
+    pub amount_out: u64,
+
+    /// Swap fee kept by the pool, in raw units of the out token
+    pub fee: u64,
+
+    /// Share of `fee` taken by the protocol, in raw units of the out token
+    pub protocol_fee: u64,
+
+    /// Price of the in token in units of the out token after the swap (SCALE = 1e9), 0 if it
+    /// can't be quoted
+    pub spot_price_after: u128,
+}
+
+impl SwapResult {
+    pub fn log(&self) -> Result<()> {
+        sol_log_data(&[&self.try_to_vec()?]);
+        Ok(())
+    }
+}
+
+#[event]
+pub struct DepositEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+
+    /// Raw amounts sent by the user, in `[mint_a, mint_b]` order
+    pub amount_a: u64,
+    pub amount_b: u64,
+
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct WithdrawRequestEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub lp_amount: u64,
+
+    /// Raw amounts owed, in pool token order
+    pub amounts_out: Vec<u64>,
+
+    pub end_slot: u64,
+}
+
+#[event]
+pub struct ScalingRepairedEvent {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub token_index: u8,
+
+    pub old_decimals: u8,
+    pub new_decimals: u8,
+    pub old_scaling_factor: u64,
+    pub new_scaling_factor: u64,
+
+    /// Cached scaled balance before and after the repair
+    pub old_balance: u64,
+    pub new_balance: u64,
+
+    /// Raw amount held by the vault at repair time
+    pub vault_amount: u64,
+}
+
+#[event]
+pub struct ProtocolLiquidityReleasedEvent {
+    pub lp_mint: Pubkey,
+    pub destination: Pubkey,
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct DrawdownGuardTrippedEvent {
+    pub pool: Pubkey,
+
+    /// Invariant per LP token the pool was paused at (SCALE = 1e9)
+    pub virtual_price: u128,
+
+    pub high_water_mark: u128,
+}
+
+#[event]
+pub struct FeeUpdatedEvent {
+    pub pool: Pubkey,
+
+    /// Volatility estimate the fee was derived from, in bps per sample
+    pub volatility_bps: u64,
+
+    pub swap_fee: u64,
+}
+
+#[event]
+pub struct PegKeeperActionEvent {
+    pub pool: Pubkey,
+
+    /// Price of the stable in the paired token before the action (SCALE = 1e9)
+    pub price: u64,
+
+    /// Whether the keeper minted stable into the pool (true) or withdrew and burned it
+    pub provided: bool,
+
+    /// Raw stable minted or burned
+    pub amount: u64,
+
+    /// LP received or burned by the keeper
+    pub lp_amount: u64,
+
+    /// Keeper debt after the action
+    pub debt: u64,
+}
+
+#[event]
+pub struct RebateClaimedEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub epoch: u64,
+
+    /// Raw amount paid out
+    pub amount: u64,
+}
+
+#[event]
+pub struct CreatorLiquidityLockedEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub lp_amount: u64,
+    pub unlock_ts: i64,
+}
+
+#[event]
+pub struct CreatorLiquidityReleasedEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct PoolArchivedEvent {
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+
+    /// Full account data of the pool before it was archived
+    pub data: Vec<u8>,
+
+    /// Lamports moved to the rent recipient
+    pub reclaimed: u64,
+}
+
+#[event]
+pub struct ProposalCreatedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub change: ProposedChange,
+    pub voting_starts_ts: i64,
+    pub voting_ends_ts: i64,
+}
+
+#[event]
+pub struct ProposalExecutedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub change: ProposedChange,
+    pub votes: u64,
+}
+
+#[event]
+pub struct PoolFeeStatsCollectedEvent {
+    pub pool: Pubkey,
+    pub invariant_per_lp: u128,
+    pub fee_apr: u64,
+    pub earned_since_inception: u64,
+}
+
+#[event]
+pub struct BalanceLossEvent {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+
+    /// Cached balance before and after reconciling with the vault (scaled)
+    pub balance_before: u64,
+    pub balance_after: u64,
+}
+
+#[event]
+pub struct MultiTokenDepositEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+
+    /// Raw amounts sent by the user, in pool token order (0 = token left out)
+    pub amounts_in: Vec<u64>,
+
+    pub lp_amount: u64,
+}
+
+/// Swaps of one pool over a window of slots, emitted instead of a `SwapEvent` per trade for
+/// swaps that passed the pool's `SwapCheckpoint`. Amounts are raw, per pool token.
+#[event]
+pub struct SwapCheckpointEvent {
+    pub pool: Pubkey,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub swap_count: u64,
+    pub volume_in: Vec<u128>,
+    pub volume_out: Vec<u128>,
+    pub fees: Vec<u128>,
+}
+
+/// A stable pool paused by `report_invariant_decrease`
+#[event]
+pub struct InvariantDecreaseReportedEvent {
+    pub pool: Pubkey,
+    pub reporter: Pubkey,
+    pub checkpoint_slot: u64,
+
+    /// Invariant per LP token at the checkpoint and now (SCALE = 1e9)
+    pub checkpoint_virtual_price: u128,
+    pub virtual_price: u128,
+}
+
+#[event]
+pub struct WithdrawEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+
+    /// Raw amounts received by the user, in `[mint_a, mint_b]` order
+    pub amount_a: u64,
+    pub amount_b: u64,
+
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct MultiTokenWithdrawEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+
+    /// Raw amounts received by the user, in pool token order (0 = token left out)
+    pub amounts_out: Vec<u64>,
+
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct RoundingDustSweptEvent {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub destination: DustDestination,
+
+    /// Scaled dust handed over; sub-unit remainders bound for the protocol stay behind
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReferralRewardsClaimedEvent {
+    pub pool: Pubkey,
+
+    /// Referred LP
+    pub owner: Pubkey,
+    pub referrer: Pubkey,
+    pub mint: Pubkey,
+
+    /// Raw amount paid
+    pub amount: u64,
+}
+
+/// A stable swap ran in stress mode: one token is under `STRESS_BALANCE_SHARE_BPS` of the pool
+#[event]
+pub struct StablePoolStressedEvent {
+    pub pool: Pubkey,
+
+    /// Mint of the scarce token
+    pub mint: Pubkey,
+
+    /// Its share of the pool's scaled balances before the swap, in bps
+    pub share_bps: u64,
+}
+
+/// One way a pool's wiring differs from what the program would have created
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolAuditFinding {
+    /// The pool isn't the PDA of its LP mint, or its stored bump isn't the canonical one
+    PoolAddress,
+
+    /// The stored authority isn't the program's authority PDA
+    Authority,
+
+    /// The LP mint passed isn't the pool's, or can't be read as a mint
+    LpMint,
+
+    /// The LP mint can be minted by someone other than the authority PDA, or frozen
+    LpMintAuthority,
+
+    /// Pool token `index` records a token account that isn't its vault PDA
+    VaultAddress { index: u8 },
+
+    /// The vault passed for pool token `index` isn't the recorded one, or can't be read
+    VaultAccount { index: u8 },
+
+    /// The vault of pool token `index` holds another mint or isn't owned by the authority PDA
+    VaultState { index: u8 },
+
+    /// Pool token `index` repeats an earlier token's mint
+    DuplicateMint { index: u8 },
+}
+
+/// Result of `audit_pool`; `findings` is empty when the pool passed
+#[event]
+pub struct PoolAuditEvent {
+    pub pool: Pubkey,
+    pub passed: bool,
+    pub findings: Vec<PoolAuditFinding>,
+}
+
+#[event]
+pub struct UpgradeCommittedEvent {
+    /// Hash of the program binary the next upgrade will deploy
+    pub hash: [u8; 32],
+
+    /// Earliest time it can be revealed
+    pub ready_ts: i64,
+}
+
+#[event]
+pub struct UpgradeRevealedEvent {
+    pub hash: [u8; 32],
+
+    /// Program data slot of the deploy it acknowledges
+    pub deploy_slot: u64,
+}
+
+/// A pool opted into `FEATURE_PAUSE_ON_UNACKNOWLEDGED_UPGRADE` was paused after a deploy the
+/// upgrade guard hasn't acknowledged
+#[event]
+pub struct UnacknowledgedUpgradePauseEvent {
+    pub pool: Pubkey,
+    pub deploy_slot: u64,
+}
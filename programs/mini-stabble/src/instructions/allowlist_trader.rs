@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{POOL_PARAMS, TRADER_ALLOWLIST},
+    errors::MiniStabbleError,
+    state::{PoolParams, TraderAllowlistEntry},
+};
+
+#[derive(Accounts)]
+#[instruction(trader: Pubkey)]
+pub struct AllowlistTrader<'info> {
+    #[account(
+        seeds = [POOL_PARAMS, pool_params.pool.as_ref()],
+        bump = pool_params.bump,
+        has_one = compliance_authority @ MiniStabbleError::Unauthorized
+    )]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(
+        init,
+        seeds = [TRADER_ALLOWLIST, pool_params.pool.as_ref(), trader.as_ref()],
+        bump,
+        payer = compliance_authority,
+        space = TraderAllowlistEntry::LEN
+    )]
+    pub trader_allowlist_entry: Account<'info, TraderAllowlistEntry>,
+
+    #[account(mut)]
+    pub compliance_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Lets `trader` swap in the pool while it is permissioned
+pub fn handler(ctx: Context<AllowlistTrader>, trader: Pubkey) -> Result<()> {
+    let entry = &mut ctx.accounts.trader_allowlist_entry;
+    entry.pool = ctx.accounts.pool_params.pool;
+    entry.trader = trader;
+    entry.bump = ctx.bumps.trader_allowlist_entry;
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::token::Mint;
+
+use crate::{
+    constants::{CONFIG, POOL_PARAMS, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    events::PoolArchivedEvent,
+    state::{ArchivedPool, Config, PoolParams, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct ArchivePool<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Weighted pool, deserialized in the handler since it is rewritten as a tombstone
+    #[account(mut, owner = crate::ID, seeds = [WEIGHT_POOL, lp_mint.key().as_ref()], bump)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, close = rent_recipient)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    /// CHECK: Receives the reclaimed rent
+    #[account(mut)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Archives a paused weighted pool with no LP left: emits its final state and shrinks the
+/// account to an `ArchivedPool` tombstone, sending the freed rent to `rent_recipient`
+pub fn handler(ctx: Context<ArchivePool>) -> Result<()> {
+    let pool = WeightedPool::try_deserialize(&mut &ctx.accounts.pool.try_borrow_data()?[..])?;
+    require!(!pool.is_active, MiniStabbleError::PoolStillActive);
+    require!(
+        ctx.accounts.lp_mint.supply == 0,
+        MiniStabbleError::PoolNotEmpty
+    );
+    // Internal balances, queued withdrawals and referral rewards are still paid out through
+    // the pool
+    require!(
+        pool.tokens.iter().all(|token| token.internal_balances == 0
+            && token.queued_withdrawals == 0
+            && token.referral_rewards == 0),
+        MiniStabbleError::PoolNotEmpty
+    );
+
+    archive(
+        &ctx.accounts.pool,
+        &ctx.accounts.rent_recipient,
+        ctx.accounts.lp_mint.key(),
+    )
+}
+
+/// Emits `pool`'s current data and rewrites it as an `ArchivedPool`, moving the rent no longer
+/// needed to `rent_recipient`
+pub(crate) fn archive<'info>(
+    pool: &AccountInfo<'info>,
+    rent_recipient: &AccountInfo<'info>,
+    lp_mint: Pubkey,
+) -> Result<()> {
+    let archived_ts = Clock::get()?.unix_timestamp;
+    let data = pool.try_borrow_data()?.to_vec();
+
+    pool.resize(ArchivedPool::LEN)?;
+    let mut tombstone = Vec::with_capacity(ArchivedPool::LEN);
+    tombstone.extend_from_slice(ArchivedPool::DISCRIMINATOR);
+    ArchivedPool {
+        lp_mint,
+        archived_ts,
+    }
+    .serialize(&mut tombstone)?;
+    pool.try_borrow_mut_data()?.copy_from_slice(&tombstone);
+
+    let rent_exempt = Rent::get()?.minimum_balance(ArchivedPool::LEN);
+    let reclaimed = pool.lamports().saturating_sub(rent_exempt);
+    **pool.try_borrow_mut_lamports()? -= reclaimed;
+    **rent_recipient.try_borrow_mut_lamports()? += reclaimed;
+
+    emit!(PoolArchivedEvent {
+        pool: pool.key(),
+        lp_mint,
+        data,
+        reclaimed,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use anchor_lang::{prelude::*, CheckOwner};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    errors::MiniStabbleError,
+    events::{PoolAuditEvent, PoolAuditFinding},
+    instructions::rebalance::{LegPool, PoolKind},
+    pda,
+};
+
+#[derive(Accounts)]
+pub struct AuditPool<'info> {
+    /// CHECK: The LP mint the audited pool should have; decoded in the handler so a wrong or
+    /// malformed account is reported as a finding instead of failing the audit
+    pub lp_mint: UncheckedAccount<'info>,
+}
+
+/// Permissionless: re-derives the PDAs a pool should be wired to and compares them with its
+/// stored state and the accounts themselves, emitting every mismatch in a `PoolAuditEvent`.
+/// Remaining accounts are `[pool, vault for each pool token, in pool token order]`.
+/// A failed audit is reported, not returned as an error, so integrators always get the event.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AuditPool<'info>>,
+    pool_kind: PoolKind,
+) -> Result<()> {
+    let [pool_info, vault_infos @ ..] = ctx.remaining_accounts else {
+        return err!(MiniStabbleError::InvalidAccount);
+    };
+
+    let pool = LegPool::load(pool_kind, pool_info)?;
+    let tokens = pool.tokens();
+    require!(
+        vault_infos.len() == tokens.len(),
+        MiniStabbleError::InvalidAccount
+    );
+
+    let mut findings = Vec::new();
+    let (authority, _) = pda::pool_authority_address(&pool.key());
+
+    if pda::pool_address(pool_kind, &pool.lp_mint()) != (pool.key(), pool.bump()) {
+        findings.push(PoolAuditFinding::PoolAddress);
+    }
+    if pool.authority() != authority {
+        findings.push(PoolAuditFinding::Authority);
+    }
+
+    match decode_mint(&ctx.accounts.lp_mint) {
+        Ok(lp_mint) if ctx.accounts.lp_mint.key() == pool.lp_mint() => {
+            if lp_mint.mint_authority != Some(authority).into()
+                || lp_mint.freeze_authority.is_some()
+            {
+                findings.push(PoolAuditFinding::LpMintAuthority);
+            }
+        }
+        _ => findings.push(PoolAuditFinding::LpMint),
+    }
+
+    for (index, (token, vault_info)) in tokens.iter().zip(vault_infos).enumerate() {
+        let index = index as u8;
+        if tokens[..index as usize]
+            .iter()
+            .any(|earlier| earlier.mint == token.mint)
+        {
+            findings.push(PoolAuditFinding::DuplicateMint { index });
+        }
+        if pda::vault_address(&pool.key(), &token.mint).0 != token.token_account {
+            findings.push(PoolAuditFinding::VaultAddress { index });
+        }
+
+        match InterfaceAccount::<TokenAccount>::try_from(vault_info) {
+            Ok(vault) if vault.key() == token.token_account => {
+                if vault.mint != token.mint || vault.owner != authority {
+                    findings.push(PoolAuditFinding::VaultState { index });
+                }
+            }
+            _ => findings.push(PoolAuditFinding::VaultAccount { index }),
+        }
+    }
+
+    emit!(PoolAuditEvent {
+        pool: pool.key(),
+        passed: findings.is_empty(),
+        findings,
+    });
+
+    Ok(())
+}
+
+/// `info` as a mint of either token program, checked the way `InterfaceAccount` would
+fn decode_mint(info: &AccountInfo) -> Result<Mint> {
+    Mint::check_owner(info.owner)?;
+    Mint::try_deserialize(&mut &info.try_borrow_data()?[..])
+}
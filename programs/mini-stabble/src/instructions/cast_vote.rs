@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    constants::VOTE_RECORD,
+    errors::MiniStabbleError,
+    state::{Proposal, VoteRecord},
+};
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut, has_one = lp_mint)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [VOTE_RECORD, proposal.key().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    pub voter: Signer<'info>,
+}
+
+/// Votes for or against the proposal with all the LP the voter escrowed before voting started.
+/// The first vote takes the LP supply snapshot the quorum is measured against.
+pub fn handler(ctx: Context<CastVote>, in_favour: bool) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    require!(
+        proposal.is_voting(Clock::get()?.unix_timestamp),
+        MiniStabbleError::ProposalNotVoting
+    );
+
+    let vote_record = &mut ctx.accounts.vote_record;
+    require!(!vote_record.voted, MiniStabbleError::AlreadyVoted);
+    require!(vote_record.lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    if proposal.lp_supply_snapshot == 0 {
+        proposal.lp_supply_snapshot = ctx.accounts.lp_mint.supply;
+    }
+
+    let votes = if in_favour {
+        &mut proposal.votes
+    } else {
+        &mut proposal.votes_against
+    };
+    *votes = votes
+        .checked_add(vote_record.lp_amount)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    vote_record.voted = true;
+
+    Ok(())
+}
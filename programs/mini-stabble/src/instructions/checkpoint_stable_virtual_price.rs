@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    constants::{STABLE_POOL, VIRTUAL_PRICE_CHECKPOINT},
+    errors::MiniStabbleError,
+    state::{StablePool, VirtualPriceCheckpoint},
+};
+
+#[derive(Accounts)]
+pub struct CheckpointStableVirtualPrice<'info> {
+    #[account(seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        seeds = [VIRTUAL_PRICE_CHECKPOINT, pool.key().as_ref()],
+        bump,
+        payer = payer,
+        space = VirtualPriceCheckpoint::LEN
+    )]
+    pub checkpoint: Account<'info, VirtualPriceCheckpoint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless: moves the pool's virtual price checkpoint to now. Refuses to record a
+/// virtual price that proves a decrease against the current checkpoint, so the drop has to
+/// be reported rather than papered over.
+pub fn handler(ctx: Context<CheckpointStableVirtualPrice>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pool = &ctx.accounts.pool;
+    let amp = pool
+        .get_current_amp(clock.unix_timestamp)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let virtual_price = pool
+        .virtual_price(ctx.accounts.lp_mint.supply, clock.unix_timestamp)
+        .ok_or(MiniStabbleError::InvalidAmount)?;
+
+    let checkpoint = &mut ctx.accounts.checkpoint;
+    if checkpoint.pool == pool.key() {
+        require!(
+            !checkpoint.proves_decrease(amp, virtual_price),
+            MiniStabbleError::VirtualPriceBelowCheckpoint
+        );
+    }
+
+    checkpoint.pool = pool.key();
+    checkpoint.slot = clock.slot;
+    checkpoint.amp = amp;
+    checkpoint.virtual_price = virtual_price;
+    checkpoint.bump = ctx.bumps.checkpoint;
+
+    Ok(())
+}
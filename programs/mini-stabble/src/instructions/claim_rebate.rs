@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    constants::{AUTHORITY, POOL_VAULT, STABLE_POOL, USER_REBATE_STATS},
+    errors::MiniStabbleError,
+    events::RebateClaimedEvent,
+    pool_authority_signer_seeds,
+    state::{StablePool, UserRebateStats},
+    transfers::transfer_from_vault,
+};
+
+#[derive(Accounts)]
+pub struct ClaimRebate<'info> {
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(
+        mut,
+        seeds = [USER_REBATE_STATS, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_rebate_stats.bump,
+        has_one = user,
+    )]
+    pub user_rebate_stats: Account<'info, UserRebateStats>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, seeds = [POOL_VAULT, pool.key().as_ref(), mint.key().as_ref()], bump, token::mint = mint, token::token_program = token_program, token::authority = authority)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub user: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Claims the user's rebate in `mint` for `epoch`, pro rata to their share of the epoch's
+/// counted volume. Only the epoch right before the current one can be claimed.
+pub fn handler(ctx: Context<ClaimRebate>, epoch: u64) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let user_rebate_stats = &mut ctx.accounts.user_rebate_stats;
+
+    pool.roll_rebate_epoch(Clock::get()?.unix_timestamp)?;
+    user_rebate_stats.roll(pool.rebate.epoch);
+    require!(
+        epoch.checked_add(1) == Some(pool.rebate.epoch),
+        MiniStabbleError::RebateNotClaimable
+    );
+
+    let mint = ctx.accounts.mint.key();
+    let index = pool
+        .get_token_index(&mint)
+        .ok_or(MiniStabbleError::InvalidMint)?;
+    let token_bit = 1u8 << index;
+    require!(
+        user_rebate_stats.claimed_tokens & token_bit == 0,
+        MiniStabbleError::RebateAlreadyClaimed
+    );
+    user_rebate_stats.claimed_tokens |= token_bit;
+
+    let share = pool
+        .rebate
+        .claim(index, user_rebate_stats.previous_epoch_volume)?;
+    let amount = pool.tokens[index].scale_amount_down(share);
+    require!(amount > 0, MiniStabbleError::InvalidAmount);
+
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.mint,
+        &ctx.accounts.vault,
+        &ctx.accounts.destination,
+        &ctx.accounts.authority,
+        signer_seeds,
+        amount,
+    )?;
+
+    emit!(RebateClaimedEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        mint,
+        epoch,
+        amount,
+    });
+
+    Ok(())
+}
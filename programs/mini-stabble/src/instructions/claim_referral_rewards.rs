@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    constants::{AUTHORITY, LP_POSITION, POOL_VAULT, STABLE_POOL},
+    errors::MiniStabbleError,
+    events::ReferralRewardsClaimedEvent,
+    pool_authority_signer_seeds,
+    state::{LpPosition, StablePool},
+    transfers::transfer_from_vault,
+};
+
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(
+        mut,
+        seeds = [LP_POSITION, pool.key().as_ref(), lp_position.owner.as_ref()],
+        bump = lp_position.bump,
+        has_one = referrer @ MiniStabbleError::Unauthorized,
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, seeds = [POOL_VAULT, pool.key().as_ref(), mint.key().as_ref()], bump, token::mint = mint, token::token_program = token_program, token::authority = authority)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub referrer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pays the referrer what has been taken out of the referred LP's exits in `mint` for it. The
+/// tokens were already set aside from the pool's balance then, so no other LP funds them.
+pub fn handler(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let lp_position = &mut ctx.accounts.lp_position;
+
+    let mint = ctx.accounts.mint.key();
+    let index = pool
+        .get_token_index(&mint)
+        .ok_or(MiniStabbleError::InvalidMint)?;
+    let amount = lp_position
+        .referrer_claimable
+        .get(index)
+        .copied()
+        .unwrap_or(0);
+    require!(amount > 0, MiniStabbleError::InvalidAmount);
+
+    lp_position.referrer_claimable[index] = 0;
+    pool.tokens[index].referral_rewards = pool.tokens[index]
+        .referral_rewards
+        .checked_sub(amount)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.mint,
+        &ctx.accounts.vault,
+        &ctx.accounts.destination,
+        &ctx.accounts.authority,
+        signer_seeds,
+        amount,
+    )?;
+
+    emit!(ReferralRewardsClaimedEvent {
+        pool: pool.key(),
+        owner: lp_position.owner,
+        referrer: ctx.accounts.referrer.key(),
+        mint,
+        amount,
+    });
+
+    Ok(())
+}
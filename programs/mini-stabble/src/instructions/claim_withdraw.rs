@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    constants::{AUTHORITY, WITHDRAW_REQUEST},
+    errors::MiniStabbleError,
+    pool_authority_signer_seeds,
+    state::{release_queued_withdrawals, update_pool_tokens, WithdrawRequest},
+    transfers::{load_token_program, transfer_from_vault},
+};
+
+/// Remaining accounts per queued token: `[mint, vault, user_token, token_program]`
+pub const ACCOUNTS_PER_CLAIMED_TOKEN: usize = 4;
+
+#[derive(Accounts)]
+pub struct ClaimWithdraw<'info> {
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, withdraw_request.pool.as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [WITHDRAW_REQUEST, withdraw_request.pool.as_ref(), user.key().as_ref()],
+        bump = withdraw_request.bump,
+        constraint = withdraw_request.owner == user.key() @ MiniStabbleError::Unauthorized,
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    /// CHECK: Weighted or stable pool the request exits, decoded in the handler to release what
+    /// it pays out
+    #[account(mut, address = withdraw_request.pool)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+/// Checks that `vault` is the pool's own vault for `mint`, not just any account the authority
+/// happens to own
+fn check_vault(
+    vault_key: &Pubkey,
+    vault: &TokenAccount,
+    pool_vault: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(*vault_key, *pool_vault, MiniStabbleError::InvalidAccount);
+    require_keys_eq!(vault.mint, *mint, MiniStabbleError::InvalidMint);
+    require_keys_eq!(vault.owner, *authority, MiniStabbleError::InvalidAccount);
+
+    Ok(())
+}
+
+/// Pays out everything the request has unlocked so far, for every queued token at once.
+/// Remaining accounts are `[mint, vault, user_token, token_program]` for each queued token, in
+/// request order.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimWithdraw<'info>>) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    let withdraw_request = &mut ctx.accounts.withdraw_request;
+    require!(
+        ctx.remaining_accounts.len() == withdraw_request.tokens.len() * ACCOUNTS_PER_CLAIMED_TOKEN,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let claimable = (0..withdraw_request.tokens.len())
+        .map(|index| {
+            withdraw_request
+                .claimable(index, slot)
+                .ok_or(MiniStabbleError::MathOverflow.into())
+        })
+        .collect::<Result<Vec<u64>>>()?;
+    require!(
+        claimable.iter().any(|&amount| amount > 0),
+        MiniStabbleError::NothingToClaim
+    );
+
+    let vaults = update_pool_tokens(&ctx.accounts.pool, |tokens| {
+        release_queued_withdrawals(tokens, &withdraw_request.tokens, &claimable)
+    })?;
+
+    let pool_key = withdraw_request.pool;
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, ctx.bumps.authority);
+
+    let accounts = ctx.remaining_accounts.chunks(ACCOUNTS_PER_CLAIMED_TOKEN);
+    for (((token, &amount), pool_vault), accounts) in withdraw_request
+        .tokens
+        .iter_mut()
+        .zip(&claimable)
+        .zip(&vaults)
+        .zip(accounts)
+    {
+        let [mint_info, vault_info, user_token_info, token_program_info] = accounts else {
+            return err!(MiniStabbleError::InvalidAccount);
+        };
+
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+        let user_token = InterfaceAccount::<TokenAccount>::try_from(user_token_info)?;
+        let token_program = load_token_program(token_program_info, &mint)?;
+        require_keys_eq!(mint.key(), token.mint, MiniStabbleError::InvalidMint);
+        check_vault(
+            &vault.key(),
+            &vault,
+            pool_vault,
+            &ctx.accounts.authority.key(),
+            &token.mint,
+        )?;
+        require_keys_eq!(user_token.mint, token.mint, MiniStabbleError::InvalidMint);
+
+        if amount > 0 {
+            transfer_from_vault(
+                &token_program,
+                &mint,
+                &vault,
+                &user_token,
+                &ctx.accounts.authority,
+                signer_seeds,
+                amount,
+            )?;
+        }
+
+        token.claimed = token
+            .claimed
+            .checked_add(amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+    }
+
+    // Return the rent once everything has been paid out
+    if withdraw_request.is_fully_claimed() {
+        withdraw_request.close(ctx.accounts.user.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{
+        solana_program::{program_option::COption, program_pack::Pack},
+        AccountDeserialize,
+    };
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+
+    fn token_account(mint: Pubkey, owner: Pubkey) -> TokenAccount {
+        let account = spl_token::state::Account {
+            mint,
+            owner,
+            amount: 1_000,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        let mut data = [0u8; spl_token::state::Account::LEN];
+        account.pack_into_slice(&mut data);
+
+        TokenAccount::try_deserialize_unchecked(&mut data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_only_the_pool_vault_pays_out_a_claim() {
+        let (mint, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pool_vault = Pubkey::new_unique();
+        let vault = token_account(mint, authority);
+
+        assert!(check_vault(&pool_vault, &vault, &pool_vault, &authority, &mint).is_ok());
+
+        // Same mint and owner, but another account the authority holds (e.g. a fee or rebate
+        // escrow) must not be drained by a claim
+        let other = Pubkey::new_unique();
+        assert!(check_vault(&other, &vault, &pool_vault, &authority, &mint).is_err());
+
+        let foreign = token_account(mint, Pubkey::new_unique());
+        assert!(check_vault(&pool_vault, &foreign, &pool_vault, &authority, &mint).is_err());
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    constants::{POOL_FEE_STATS, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    events::PoolFeeStatsCollectedEvent,
+    state::{PoolFeeStats, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct CollectPoolFeeStats<'info> {
+    #[account(seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [POOL_FEE_STATS, pool.key().as_ref()], bump = pool_fee_stats.bump, has_one = pool)]
+    pub pool_fee_stats: Account<'info, PoolFeeStats>,
+}
+
+/// Permissionless crank: records the pool's invariant per LP token and the fee APR since the
+/// last collection
+pub fn handler(ctx: Context<CollectPoolFeeStats>) -> Result<()> {
+    let invariant_per_lp = ctx
+        .accounts
+        .pool
+        .virtual_price(ctx.accounts.lp_mint.supply)
+        .ok_or(MiniStabbleError::InvalidAmount)?;
+
+    let pool_fee_stats = &mut ctx.accounts.pool_fee_stats;
+    pool_fee_stats.collect(invariant_per_lp, Clock::get()?.unix_timestamp);
+
+    emit!(PoolFeeStatsCollectedEvent {
+        pool: pool_fee_stats.pool,
+        invariant_per_lp,
+        fee_apr: pool_fee_stats.fee_apr,
+        earned_since_inception: pool_fee_stats.earned_since_inception,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::UPGRADE_GUARD,
+    events::UpgradeCommittedEvent,
+    state::{signed_keys, UpgradeGuard},
+};
+
+#[derive(Accounts)]
+pub struct CommitUpgrade<'info> {
+    #[account(mut, seeds = [UPGRADE_GUARD], bump = upgrade_guard.bump)]
+    pub upgrade_guard: Account<'info, UpgradeGuard>,
+}
+
+/// Publishes the hash of the program binary the next upgrade will deploy, starting its timelock.
+/// Remaining accounts are the approving upgrade signers, at least the guard's threshold of them.
+pub fn handler(ctx: Context<CommitUpgrade>, hash: [u8; 32]) -> Result<()> {
+    let upgrade_guard = &mut ctx.accounts.upgrade_guard;
+    upgrade_guard.require_quorum(&signed_keys(ctx.remaining_accounts))?;
+    let ready_ts = upgrade_guard.commit(hash, Clock::get()?.unix_timestamp)?;
+
+    emit!(UpgradeCommittedEvent { hash, ready_ts });
+
+    Ok(())
+}
@@ -0,0 +1,498 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Mint, Token},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    errors::MiniStabbleError,
+    instructions::rebalance::{
+        exit_proportional, join_proportional, load_lp_accounts, load_pool_params, LegPool,
+        PairAccounts, PairPool, PoolKind,
+    },
+    pool_authority_signer_seeds,
+    state::PoolToken,
+    transfers::{transfer_fee, transfer_from_user, transfer_from_vault},
+};
+
+/// One step of a compound operation. Joins consume
+/// `[pool, pool_authority, lp_mint, vault_token_a, vault_token_b, user_lp]` from the remaining
+/// accounts, exits the same followed by `pool_params`, swaps
+/// `[pool, pool_authority, lp_mint, vault_token_a, vault_token_b]`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum CompoundStep {
+    /// Burn `lp_amount` of the pool's LP and withdraw both tokens proportionally
+    Exit { pool_kind: PoolKind, lp_amount: u64 },
+
+    /// Swap `amount_in` raw units of token A (`a_to_b`) or token B for the other; 0 swaps
+    /// everything earlier steps produced of the input token
+    Swap {
+        pool_kind: PoolKind,
+        a_to_b: bool,
+        amount_in: u64,
+    },
+
+    /// Mint `lp_amount` by depositing proportionally; 0 deposits as much of what earlier steps
+    /// produced as fits the pool's ratio
+    Join { pool_kind: PoolKind, lp_amount: u64 },
+}
+
+#[derive(Accounts)]
+pub struct CompoundOperation<'info> {
+    #[account(constraint = mint_a.key() != mint_b.key(), mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::mint = mint_a, token::token_program = token_program_a, token::authority = user)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::mint = mint_b, token::token_program = token_program_b, token::authority = user)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+/// Runs `steps` in order against pools of one token pair, e.g. exit a weighted pool and join
+/// a stable pool to migrate liquidity in one transaction. Tokens produced by an exit or swap
+/// can be consumed by later steps. `min_amount_out` bounds the output of the last step: LP
+/// minted by a join or tokens received from a swap, so a program can't end with an exit.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CompoundOperation<'info>>,
+    steps: Vec<CompoundStep>,
+    min_amount_out: u64,
+) -> Result<()> {
+    validate_steps(&steps)?;
+
+    let clock = Clock::get()?;
+    let pair = PairAccounts {
+        token_program: &ctx.accounts.token_program,
+        token_programs: [&ctx.accounts.token_program_a, &ctx.accounts.token_program_b],
+        user: &ctx.accounts.user,
+        mints: [&ctx.accounts.mint_a, &ctx.accounts.mint_b],
+        user_tokens: [&ctx.accounts.user_token_a, &ctx.accounts.user_token_b],
+    };
+
+    // Raw amounts earlier steps sent to the user that later steps may spend
+    let mut produced = [0u64; 2];
+    let mut last_output = 0u64;
+    let mut accounts = ctx.remaining_accounts.iter();
+    let mut next_account = || accounts.next().ok_or(MiniStabbleError::InvalidAccount);
+
+    for step in &steps {
+        match *step {
+            CompoundStep::Exit {
+                pool_kind,
+                lp_amount,
+            } => {
+                require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+                let pool_info = next_account()?;
+                let authority_info = next_account()?;
+                let lp_mint_info = next_account()?;
+                let mut pool = PairPool::load(
+                    pool_kind,
+                    pool_info,
+                    authority_info,
+                    next_account()?,
+                    next_account()?,
+                    &pair,
+                )?;
+                let (lp_mint, user_lp) = load_lp_accounts(&pool, lp_mint_info, next_account()?)?;
+                let pool_params = load_pool_params(&pool, next_account()?)?;
+
+                let withdrawn = exit_proportional(
+                    &pair,
+                    &mut pool,
+                    &pool_params,
+                    &lp_mint,
+                    &user_lp,
+                    lp_amount,
+                )?;
+
+                for i in 0..2 {
+                    let token = &pool.pool.tokens()[pool.indices[i]];
+                    produced[i] = produced[i]
+                        .checked_add(token.scale_amount_down(withdrawn[i]))
+                        .ok_or(MiniStabbleError::MathOverflow)?;
+                }
+                pool.pool.persist()?;
+            }
+
+            CompoundStep::Swap {
+                pool_kind,
+                a_to_b,
+                amount_in,
+            } => {
+                let pool_info = next_account()?;
+                let authority_info = next_account()?;
+                let lp_mint_info = next_account()?;
+                let mut pool = PairPool::load(
+                    pool_kind,
+                    pool_info,
+                    authority_info,
+                    next_account()?,
+                    next_account()?,
+                    &pair,
+                )?;
+                let lp_mint = Account::<Mint>::try_from(lp_mint_info)?;
+                require_keys_eq!(
+                    lp_mint.key(),
+                    pool.pool.lp_mint(),
+                    MiniStabbleError::InvalidAccount
+                );
+
+                let (side_in, side_out) = if a_to_b { (0, 1) } else { (1, 0) };
+                let amount_in = match amount_in {
+                    0 => produced[side_in],
+                    amount_in => amount_in,
+                };
+                require!(amount_in > 0, MiniStabbleError::InvalidAmount);
+
+                let received = swap_exact_in(
+                    &pair,
+                    &mut pool,
+                    side_in,
+                    side_out,
+                    amount_in,
+                    lp_mint.supply,
+                    clock.slot,
+                    clock.unix_timestamp,
+                )?;
+
+                produced[side_in] = produced[side_in].saturating_sub(amount_in);
+                produced[side_out] = produced[side_out]
+                    .checked_add(received)
+                    .ok_or(MiniStabbleError::MathOverflow)?;
+                last_output = received;
+                pool.pool.persist()?;
+            }
+
+            CompoundStep::Join {
+                pool_kind,
+                lp_amount,
+            } => {
+                let pool_info = next_account()?;
+                let authority_info = next_account()?;
+                let lp_mint_info = next_account()?;
+                let mut pool = PairPool::load(
+                    pool_kind,
+                    pool_info,
+                    authority_info,
+                    next_account()?,
+                    next_account()?,
+                    &pair,
+                )?;
+                let (lp_mint, user_lp) = load_lp_accounts(&pool, lp_mint_info, next_account()?)?;
+
+                let lp_amount = match lp_amount {
+                    0 => lp_out_for_produced(&pair, &pool, lp_mint.supply, produced)?,
+                    lp_amount => lp_amount,
+                };
+                require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+                let deposited = join_proportional(&pair, &mut pool, &lp_mint, &user_lp, lp_amount)?;
+
+                for i in 0..2 {
+                    let token = &pool.pool.tokens()[pool.indices[i]];
+                    produced[i] = produced[i].saturating_sub(token.scale_amount_down(deposited[i]));
+                }
+                last_output = lp_amount;
+                pool.pool.persist()?;
+            }
+        }
+    }
+
+    require!(accounts.next().is_none(), MiniStabbleError::InvalidAccount);
+    require!(
+        last_output >= min_amount_out,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    Ok(())
+}
+
+/// Checks `steps` ends with a step whose output `min_amount_out` can bound
+fn validate_steps(steps: &[CompoundStep]) -> Result<()> {
+    require!(
+        matches!(
+            steps.last(),
+            Some(CompoundStep::Join { .. } | CompoundStep::Swap { .. })
+        ),
+        MiniStabbleError::InvalidAmount
+    );
+
+    Ok(())
+}
+
+/// Largest LP amount whose proportional deposit the `produced` raw amounts cover, after
+/// transfer fees
+pub(crate) fn lp_out_for_produced(
+    pair: &PairAccounts,
+    pool: &PairPool,
+    lp_supply: u64,
+    produced: [u64; 2],
+) -> Result<u64> {
+    let mut available = [0u64; 2];
+    for i in 0..2 {
+        available[i] = produced[i].saturating_sub(transfer_fee(pair.mints[i], produced[i])?);
+    }
+
+    lp_out_for_available(
+        pool.indices.map(|index| &pool.pool.tokens()[index]),
+        lp_supply,
+        available,
+    )
+}
+
+/// Largest LP amount whose proportional deposit into a pool holding `tokens` fits within the
+/// `available` raw amounts as credited to its vaults
+pub(crate) fn lp_out_for_available(
+    tokens: [&PoolToken; 2],
+    lp_supply: u64,
+    available: [u64; 2],
+) -> Result<u64> {
+    let mut lp_amount = u64::MAX;
+
+    for (token, available) in tokens.into_iter().zip(available) {
+        // One raw unit of headroom absorbs the rounding up of the deposit amounts
+        let available = available.saturating_sub(1);
+        let lp_for_token = (token.scale_amount_up(available) as u128)
+            .checked_mul(lp_supply as u128)
+            .and_then(|v| v.checked_div(token.balance as u128))
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        lp_amount = lp_amount.min(u64::try_from(lp_for_token).unwrap_or(u64::MAX));
+    }
+
+    Ok(lp_amount)
+}
+
+/// Swaps `amount_in` raw units of the pair token at `side_in` through `pool`, priced the same
+/// way as `swap` / `stable_swap`. Returns the raw amount the user received.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn swap_exact_in<'info>(
+    pair: &PairAccounts<'_, 'info>,
+    pool: &mut PairPool<'info>,
+    side_in: usize,
+    side_out: usize,
+    amount_in: u64,
+    lp_supply: u64,
+    slot: u64,
+    now: i64,
+) -> Result<u64> {
+    // Only what reaches the vault after any transfer fee is swapped
+    let amount_in_net = amount_in
+        .checked_sub(transfer_fee(pair.mints[side_in], amount_in)?)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    let amount_out = apply_swap_exact_in(
+        &mut pool.pool,
+        pool.indices[side_in],
+        pool.indices[side_out],
+        amount_in_net,
+        lp_supply,
+        slot,
+        now,
+    )?;
+
+    transfer_from_user(
+        pair.token_program,
+        pair.mints[side_in],
+        pair.user_tokens[side_in],
+        &pool.vaults[side_in],
+        pair.user,
+        amount_in,
+    )?;
+    let pool_key = pool.pool.key();
+    transfer_from_vault(
+        pair.token_program,
+        pair.mints[side_out],
+        &pool.vaults[side_out],
+        pair.user_tokens[side_out],
+        &pool.authority,
+        pool_authority_signer_seeds!(pool_key, pool.pool.authority_bump()),
+        amount_out,
+    )
+}
+
+/// Books a swap of `amount_in_net` raw units, as credited to the vault, of `tokens[index_in]`
+/// on the pool's balances and guards without moving any tokens. Returns the raw amount of
+/// `tokens[index_out]` the vault owes.
+pub(crate) fn apply_swap_exact_in(
+    pool: &mut LegPool,
+    index_in: usize,
+    index_out: usize,
+    amount_in_net: u64,
+    lp_supply: u64,
+    slot: u64,
+    now: i64,
+) -> Result<u64> {
+    require!(pool.is_active(), MiniStabbleError::PoolInActive);
+    // Routed swaps can't carry an allowlist entry per pool
+    require!(
+        !pool.trader_allowlist(),
+        MiniStabbleError::TraderNotAllowlisted
+    );
+
+    let scaled_amount_in = pool.tokens()[index_in].scale_amount_up(amount_in_net);
+    let (scaled_amount_out, scaled_fee) =
+        pool.quote_swap(index_in, index_out, scaled_amount_in, now)?;
+
+    match pool {
+        LegPool::Weighted(pool) => pool.check_spot_price_move(
+            index_in,
+            index_out,
+            pool.tokens[index_in]
+                .balance
+                .checked_add(scaled_amount_in)
+                .ok_or(MiniStabbleError::MathOverflow)?,
+            pool.tokens[index_out]
+                .balance
+                .checked_sub(scaled_amount_out)
+                .ok_or(MiniStabbleError::MathOverflow)?,
+        )?,
+        LegPool::Stable(pool) => {
+            let balances = pool.get_balances();
+            pool.swap_rate_limit.record_swap(
+                slot,
+                &balances,
+                index_in,
+                scaled_amount_in,
+                index_out,
+                scaled_amount_out,
+            )?;
+            pool.accrue_swap_fee(index_out, scaled_fee, lp_supply);
+        }
+    }
+
+    let amount_out = pool.tokens()[index_out].scale_amount_down(scaled_amount_out);
+    require!(amount_out > 0, MiniStabbleError::AmountTooSmall);
+
+    let tokens = pool.tokens_mut();
+    tokens[index_in].balance = tokens[index_in]
+        .balance
+        .checked_add(scaled_amount_in)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    tokens[index_out].debit_payout(scaled_amount_out, amount_out)?;
+
+    // Like a direct swap, a routed swap past the guard is refused
+    require!(
+        pool.trips_drawdown_guard(lp_supply, now).is_none(),
+        MiniStabbleError::DrawdownGuardTripped
+    );
+
+    Ok(amount_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{common::calc_tokens_in_proportional, fixed::ONE_U64};
+
+    fn token(decimals: u8, balance: u64) -> PoolToken {
+        PoolToken {
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            decimals,
+            scaling_factor: 10u64.pow(9 - decimals as u32),
+            balance,
+            weight: ONE_U64 / 2,
+            swaps_disabled: false,
+            rounding_dust: 0,
+            internal_balances: 0,
+            queued_withdrawals: 0,
+            referral_rewards: 0,
+        }
+    }
+
+    #[test]
+    fn test_steps_must_end_with_a_bounded_output() {
+        let exit = CompoundStep::Exit {
+            pool_kind: PoolKind::Weighted,
+            lp_amount: 1,
+        };
+        let join = CompoundStep::Join {
+            pool_kind: PoolKind::Stable,
+            lp_amount: 0,
+        };
+        let swap = CompoundStep::Swap {
+            pool_kind: PoolKind::Stable,
+            a_to_b: true,
+            amount_in: 0,
+        };
+
+        assert!(validate_steps(&[exit.clone(), join]).is_ok());
+        assert!(validate_steps(&[exit.clone(), swap]).is_ok());
+        assert_eq!(
+            validate_steps(&[exit]),
+            Err(MiniStabbleError::InvalidAmount.into())
+        );
+        assert_eq!(
+            validate_steps(&[]),
+            Err(MiniStabbleError::InvalidAmount.into())
+        );
+    }
+
+    #[test]
+    fn test_lp_out_for_available_deposit_fits_what_is_available() {
+        // 6-decimal token A and 9-decimal token B, 2 B per A
+        let tokens = [token(6, 1_000_000 * ONE_U64), token(9, 2_000_000 * ONE_U64)];
+        let lp_supply = 1_000_000 * ONE_U64;
+
+        for available in [
+            [1_000_000, 5 * ONE_U64],
+            [10_000_000, ONE_U64],
+            [123_456_789, 987_654_321_000],
+        ] {
+            let lp_amount =
+                lp_out_for_available([&tokens[0], &tokens[1]], lp_supply, available).unwrap();
+            assert!(lp_amount > 0);
+
+            // What `join_proportional` would pull for that LP amount, rounded up to raw units
+            let balances = [tokens[0].balance, tokens[1].balance];
+            let amounts_in = calc_tokens_in_proportional(&balances, lp_amount, lp_supply).unwrap();
+            for i in 0..2 {
+                assert!(amounts_in[i].div_ceil(tokens[i].scaling_factor) <= available[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lp_out_for_available_is_bound_by_the_scarcer_token() {
+        let tokens = [token(9, 1_000 * ONE_U64), token(9, 1_000 * ONE_U64)];
+        let lp_supply = 1_000 * ONE_U64;
+
+        // Plenty of A, 10 B: about 10 LP
+        let lp_amount = lp_out_for_available(
+            [&tokens[0], &tokens[1]],
+            lp_supply,
+            [500 * ONE_U64, 10 * ONE_U64],
+        )
+        .unwrap();
+        assert_eq!(lp_amount, 10 * ONE_U64 - 1);
+
+        // Nothing produced of one side mints nothing
+        assert_eq!(
+            lp_out_for_available([&tokens[0], &tokens[1]], lp_supply, [500 * ONE_U64, 0]).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_lp_out_for_available_rejects_an_empty_pool() {
+        let tokens = [token(9, 0), token(9, 1_000 * ONE_U64)];
+
+        assert_eq!(
+            lp_out_for_available([&tokens[0], &tokens[1]], ONE_U64, [ONE_U64, ONE_U64]),
+            Err(MiniStabbleError::MathOverflow.into())
+        );
+    }
+}
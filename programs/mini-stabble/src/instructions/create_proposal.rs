@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    constants::{fees::MAX_SWAP_FEE, AUTHORITY, POOL_PARAMS, PROPOSAL, PROPOSAL_ESCROW},
+    errors::MiniStabbleError,
+    events::ProposalCreatedEvent,
+    instructions::rebalance::PoolKind,
+    pda,
+    state::{PoolParams, Proposal, ProposedChange, PROPOSAL_ESCROW_PERIOD, PROPOSAL_VOTING_PERIOD},
+};
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    /// CHECK: Weighted or stable pool, checked against `lp_mint` and `pool_kind` in the handler
+    #[account(owner = crate::ID)]
+    pub pool: UncheckedAccount<'info>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(
+        init,
+        seeds = [PROPOSAL, pool.key().as_ref(), &pool_params.proposal_count.to_le_bytes()],
+        bump,
+        payer = proposer,
+        space = Proposal::LEN
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Holds the LP voters escrow in the proposal
+    #[account(
+        init,
+        seeds = [PROPOSAL_ESCROW, proposal.key().as_ref()],
+        bump,
+        payer = proposer,
+        token::mint = lp_mint,
+        token::authority = authority
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA that owns the escrow
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Opens a vote among `pool`'s LP holders on `change`, anyone can propose. LP can be escrowed
+/// for `PROPOSAL_ESCROW_PERIOD`, then votes for `PROPOSAL_VOTING_PERIOD`.
+pub fn handler(
+    ctx: Context<CreateProposal>,
+    pool_kind: PoolKind,
+    change: ProposedChange,
+) -> Result<()> {
+    let pool = ctx.accounts.pool.key();
+    let lp_mint = &ctx.accounts.lp_mint;
+    let (expected_pool, _) = pda::pool_address(pool_kind, &lp_mint.key());
+    require_keys_eq!(expected_pool, pool, MiniStabbleError::InvalidAccount);
+    require!(lp_mint.supply > 0, MiniStabbleError::InvalidProposal);
+
+    // Values are fully checked on execution; reject what can never apply
+    match change {
+        ProposedChange::SwapFee { swap_fee } => {
+            require!(swap_fee <= MAX_SWAP_FEE, MiniStabbleError::InvalidProposal)
+        }
+        ProposedChange::Amp { .. } => require!(
+            pool_kind == PoolKind::Stable,
+            MiniStabbleError::InvalidProposal
+        ),
+    }
+
+    let pool_params = &mut ctx.accounts.pool_params;
+    let voting_starts_ts = Clock::get()?
+        .unix_timestamp
+        .checked_add(PROPOSAL_ESCROW_PERIOD)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let voting_ends_ts = voting_starts_ts
+        .checked_add(PROPOSAL_VOTING_PERIOD)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.pool = pool;
+    proposal.lp_mint = lp_mint.key();
+    proposal.id = pool_params.proposal_count;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.change = change;
+    proposal.lp_supply_snapshot = 0;
+    proposal.votes = 0;
+    proposal.votes_against = 0;
+    proposal.voting_starts_ts = voting_starts_ts;
+    proposal.voting_ends_ts = voting_ends_ts;
+    proposal.executed = false;
+    proposal.bump = ctx.bumps.proposal;
+
+    pool_params.proposal_count = pool_params
+        .proposal_count
+        .checked_add(1)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    emit!(ProposalCreatedEvent {
+        pool,
+        proposal: proposal.key(),
+        proposer: proposal.proposer,
+        change,
+        voting_starts_ts,
+        voting_ends_ts,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, TOKEN_BADGE},
+    errors::MiniStabbleError,
+    state::{Config, TokenBadge},
+};
+
+/// Revokes a badge. Existing pools keep working, only new pool creation is affected.
+#[derive(Accounts)]
+pub struct DeleteTokenBadge<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [TOKEN_BADGE, token_badge.mint.as_ref()],
+        bump = token_badge.bump,
+        close = admin
+    )]
+    pub token_badge: Account<'info, TokenBadge>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(_ctx: Context<DeleteTokenBadge>) -> Result<()> {
+    Ok(())
+}
@@ -1,14 +1,20 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, MintTo, Token, TokenAccount, Transfer},
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
 };
 
 use crate::{
-    constants::{AUTHORITY, POOL_VAULT, WEIGHT_POOL},
-    errors::MiniStabbleError,
-    math::fixed::FixedDiv,
-    state::WeightedPool,
+    constants::{AUTHORITY, CREATOR_LOCK, POOL_PARAMS, POOL_VAULT, WEIGHT_POOL},
+    errors::{end_dry_run, MiniStabbleError},
+    events::{CreatorLiquidityLockedEvent, DepositEvent},
+    math::common::calc_tokens_in_proportional,
+    pool_authority_signer_seeds,
+    state::{PoolParams, WeightedPool},
+    transfers::{gross_amount_for_net, mint_lp, transfer_fee, transfer_from_user},
 };
 
 #[derive(Accounts)]
@@ -16,45 +22,75 @@ pub struct Deposit<'info> {
     #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
     pub pool: Account<'info, WeightedPool>,
 
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
     // Mint Accounts
     #[account(mut, address = pool.lp_mint.key())]
     pub lp_mint: Account<'info, Mint>,
-    #[account(constraint = token_a_mint.key() != token_b_mint.key())]
-    pub token_a_mint: Account<'info, Mint>,
-    pub token_b_mint: Account<'info, Mint>,
+    #[account(constraint = token_a_mint.key() != token_b_mint.key(), mint::token_program = token_program_a)]
+    pub token_a_mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub token_b_mint: InterfaceAccount<'info, InterfaceMint>,
 
-    #[account(mut, token::authority = user, token::mint = token_a_mint)]
-    pub user_token_a: Account<'info, TokenAccount>,
+    #[account(mut, token::authority = user, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
 
-    #[account(mut, token::authority = user, token::mint = token_b_mint)]
-    pub user_token_b: Account<'info, TokenAccount>,
+    #[account(mut, token::authority = user, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
 
     // Vault Tokens
-    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_a_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_a_mint)]
-    pub vault_token_a: Account<'info, TokenAccount>,
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_a_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub vault_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
 
-    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_b_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_b_mint)]
-    pub vault_token_b: Account<'info, TokenAccount>,
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_b_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub vault_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
 
     // user lp account
     #[account(init_if_needed, associated_token::mint = lp_mint, associated_token::authority = user, payer = user)]
     pub user_lp: Account<'info, TokenAccount>,
 
+    /// Escrow for the first deposit's LP, required when the pool has a creator lock
+    #[account(init, seeds = [CREATOR_LOCK, pool.key().as_ref()], bump, payer = user, token::mint = lp_mint, token::authority = authority)]
+    pub creator_lock_escrow: Option<Account<'info, TokenAccount>>,
+
     /// CHECK: Authority PDA used for signing
-    #[account(seeds=[AUTHORITY], bump)]
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
     pub authority: UncheckedAccount<'info>,
 
     // Programs - token program. system program
     pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 pub fn handler(
-    ctx: Context<Deposit>,
+    mut ctx: Context<Deposit>,
+    lp_amount: u64,
+    input_token_a_amount: u64,
+    input_token_b_amount: u64,
+    dry_run: bool,
+) -> Result<()> {
+    execute(
+        &mut ctx,
+        lp_amount,
+        input_token_a_amount,
+        input_token_b_amount,
+    )?;
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
+
+/// The deposit itself, for instructions that do more in the same transaction
+pub(crate) fn execute(
+    ctx: &mut Context<Deposit>,
     lp_amount: u64,
     input_token_a_amount: u64,
     input_token_b_amount: u64,
@@ -67,11 +103,8 @@ pub fn handler(
 
     let lp = &ctx.accounts.lp_mint;
 
-    let token_a_index = pool
-        .get_token_index(&token_a_mint.key())
-        .ok_or(MiniStabbleError::InvalidMint)?;
-    let token_b_index = pool
-        .get_token_index(&token_b_mint.key())
+    let [token_a_index, token_b_index] = pool
+        .get_token_pair_indices(&token_a_mint.key(), &token_b_mint.key())
         .ok_or(MiniStabbleError::InvalidMint)?;
 
     let vault_a_balance = pool.tokens[token_a_index].balance;
@@ -79,14 +112,23 @@ pub fn handler(
 
     let lp_supply = lp.supply;
 
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_supply);
+
     let (lp_to_mint, token_a_required, token_b_required) = if lp_supply == 0 {
+        ctx.accounts
+            .pool_params
+            .check_first_depositor(&ctx.accounts.user.key())?;
         require!(input_token_a_amount > 0, MiniStabbleError::InvalidAmount);
         require!(input_token_b_amount > 0, MiniStabbleError::InvalidAmount);
 
-        let scaled_input_token_a_amount =
-            pool.tokens[token_a_index].scale_amount_up(input_token_a_amount);
-        let scaled_input_token_b_amount =
-            pool.tokens[token_b_index].scale_amount_up(input_token_b_amount);
+        // Credit only what reaches the vaults after any transfer fee
+        let scaled_input_token_a_amount = pool.tokens[token_a_index].scale_amount_up(
+            input_token_a_amount - transfer_fee(token_a_mint, input_token_a_amount)?,
+        );
+        let scaled_input_token_b_amount = pool.tokens[token_b_index].scale_amount_up(
+            input_token_b_amount - transfer_fee(token_b_mint, input_token_b_amount)?,
+        );
 
         // First deposit of the pool
         let amount_product = (scaled_input_token_a_amount as u128)
@@ -101,76 +143,92 @@ pub fn handler(
     } else {
         require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
         // Normal Deposit
-        let token_a_required = u64::try_from(
-            (lp_amount as u128)
-                .checked_mul(vault_a_balance as u128)
-                .ok_or(MiniStabbleError::MathOverflow)?
-                .div_up(lp_supply as u128)?,
-        )?;
-
-        let token_b_required = u64::try_from(
-            ((lp_amount as u128)
-                .checked_mul(vault_b_balance as u128)
-                .ok_or(MiniStabbleError::MathOverflow)?)
-            .div_up(lp_supply as u128)?,
-        )?;
+        let amounts_in =
+            calc_tokens_in_proportional(&[vault_a_balance, vault_b_balance], lp_amount, lp_supply)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+        let (token_a_required, token_b_required) = (amounts_in[0], amounts_in[1]);
         (lp_amount, token_a_required, token_b_required)
     };
 
-    // Slippage check - compare actual transfer amounts (scaled down) to user's max
+    // Gross up for transfer fees so the vaults receive the required amounts
+    let transfer_amount_a = gross_amount_for_net(
+        token_a_mint,
+        pool.tokens[token_a_index].scale_amount_down(token_a_required),
+    )?;
+    let transfer_amount_b = gross_amount_for_net(
+        token_b_mint,
+        pool.tokens[token_b_index].scale_amount_down(token_b_required),
+    )?;
+
+    // Slippage check - compare actual transfer amounts to user's max
     require!(
-        pool.tokens[token_a_index].scale_amount_down(token_a_required) <= input_token_a_amount,
+        transfer_amount_a <= input_token_a_amount,
         MiniStabbleError::SlippageExceeded
     );
     require!(
-        pool.tokens[token_b_index].scale_amount_down(token_b_required) <= input_token_b_amount,
+        transfer_amount_b <= input_token_b_amount,
         MiniStabbleError::SlippageExceeded
     );
 
     // Transfer tokens - have lp_to_mint, token_a_required, token_b_required
     // Token 1
-    let cpi_accounts_a = Transfer {
-        from: ctx.accounts.user_token_a.to_account_info(),
-        to: ctx.accounts.vault_token_a.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-    };
-
-    token::transfer(
-        CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_a),
-        pool.tokens[token_a_index].scale_amount_down(token_a_required),
+    transfer_from_user(
+        &ctx.accounts.token_program_a,
+        token_a_mint,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.vault_token_a,
+        &ctx.accounts.user,
+        transfer_amount_a,
     )?;
 
     // Token 2
-    let cpi_accounts_b = Transfer {
-        from: ctx.accounts.user_token_b.to_account_info(),
-        to: ctx.accounts.vault_token_b.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-    };
-
-    token::transfer(
-        CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_b),
-        pool.tokens[token_b_index].scale_amount_down(token_b_required),
+    transfer_from_user(
+        &ctx.accounts.token_program_b,
+        token_b_mint,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.vault_token_b,
+        &ctx.accounts.user,
+        transfer_amount_b,
     )?;
 
-    // Mint
-    let mint_accounts = MintTo {
-        authority: ctx.accounts.authority.to_account_info(),
-        to: ctx.accounts.user_lp.to_account_info(),
-        mint: ctx.accounts.lp_mint.to_account_info(),
-    };
+    // Mint; a locked pool's first LP goes to the creator lock escrow
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
 
-    let seeds = &[AUTHORITY, &[ctx.bumps.authority]];
-    let signer_seeds = &[&seeds[..]];
+    let pool_params = &mut ctx.accounts.pool_params;
+    let lock_lp = lp_supply == 0 && pool_params.creator_lock.is_enabled();
+    let lp_destination = if lock_lp {
+        ctx.accounts
+            .creator_lock_escrow
+            .as_ref()
+            .ok_or(MiniStabbleError::InvalidAccount)?
+    } else {
+        &ctx.accounts.user_lp
+    };
 
-    token::mint_to(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            mint_accounts,
-            signer_seeds,
-        ),
+    mint_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        lp_destination,
+        &ctx.accounts.authority,
+        signer_seeds,
         lp_to_mint,
     )?;
 
+    if lock_lp {
+        pool_params.creator_lock.lock(
+            ctx.accounts.user.key(),
+            lp_to_mint,
+            Clock::get()?.unix_timestamp,
+        )?;
+        emit!(CreatorLiquidityLockedEvent {
+            pool: pool.key(),
+            owner: ctx.accounts.user.key(),
+            lp_amount: lp_to_mint,
+            unlock_ts: pool_params.creator_lock.unlock_ts,
+        });
+    }
+
     // Update pool state
     pool.tokens[token_a_index].balance = pool.tokens[token_a_index]
         .balance
@@ -181,5 +239,19 @@ pub fn handler(
         .checked_add(token_b_required)
         .ok_or(MiniStabbleError::MathOverflow)?;
 
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(lp_supply + lp_to_mint),
+    );
+
+    emit!(DepositEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amount_a: transfer_amount_a,
+        amount_b: transfer_amount_b,
+        lp_amount: lp_to_mint,
+    });
+
     Ok(())
 }
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+    token_interface::{Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount},
+};
+
+use crate::{
+    constants::{AUTHORITY, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    events::MultiTokenDepositEvent,
+    math::weighted::calc_lp_out_for_join,
+    pool_authority_signer_seeds,
+    state::WeightedPool,
+    transfers::{load_token_program, mint_lp, transfer_fee, transfer_from_user},
+};
+
+/// Remaining accounts per deposited token: `[mint, user_token, vault, token_program]`
+pub const ACCOUNTS_PER_DEPOSITED_TOKEN: usize = 4;
+
+#[derive(Accounts)]
+pub struct DepositExactTokensIn<'info> {
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(init_if_needed, associated_token::mint = lp_mint, associated_token::authority = user, payer = user)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Joins with exactly `amounts_in` of every pool token (raw, in pool token order), priced like
+/// `deposit_unbalanced`: the largest proportional part joins for free and the excess pays the
+/// swap fee. Remaining accounts are `[mint, user_token, vault, token_program]` for each pool
+/// token, in pool token order.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DepositExactTokensIn<'info>>,
+    amounts_in: Vec<u64>,
+    min_lp_amount: u64,
+) -> Result<()> {
+    require!(min_lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    let pool = &ctx.accounts.pool;
+    require!(pool.is_active, MiniStabbleError::PoolInActive);
+    require!(
+        amounts_in.len() == pool.tokens.len() && amounts_in.iter().all(|&amount| amount > 0),
+        MiniStabbleError::InvalidAmount
+    );
+    require!(
+        ctx.remaining_accounts.len() == amounts_in.len() * ACCOUNTS_PER_DEPOSITED_TOKEN,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let lp_supply = ctx.accounts.lp_mint.supply;
+    // The first deposit sets the pool's ratio and goes through `deposit`
+    require!(lp_supply > 0, MiniStabbleError::InvalidAmount);
+
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_supply);
+
+    let mut accounts = ctx.remaining_accounts.chunks(ACCOUNTS_PER_DEPOSITED_TOKEN);
+    let mut transfers = Vec::with_capacity(amounts_in.len());
+    let mut scaled_amounts_in = vec![0u64; amounts_in.len()];
+    for (index, &amount) in amounts_in.iter().enumerate() {
+        let token = &pool.tokens[index];
+        let [mint_info, user_token_info, vault_info, token_program_info] =
+            accounts.next().ok_or(MiniStabbleError::InvalidAccount)?
+        else {
+            return err!(MiniStabbleError::InvalidAccount);
+        };
+
+        let mint = InterfaceAccount::<InterfaceMint>::try_from(mint_info)?;
+        let user_token = InterfaceAccount::<InterfaceTokenAccount>::try_from(user_token_info)?;
+        let token_program = load_token_program(token_program_info, &mint)?;
+        require_keys_eq!(mint.key(), token.mint, MiniStabbleError::InvalidMint);
+        require_keys_eq!(
+            vault_info.key(),
+            token.token_account,
+            MiniStabbleError::InvalidAccount
+        );
+        require_keys_eq!(user_token.mint, token.mint, MiniStabbleError::InvalidMint);
+        require_keys_eq!(
+            user_token.owner,
+            ctx.accounts.user.key(),
+            MiniStabbleError::InvalidAccount
+        );
+
+        // Only what reaches the vault after any transfer fee is deposited
+        scaled_amounts_in[index] = token.scale_amount_up(
+            amount
+                .checked_sub(transfer_fee(&mint, amount)?)
+                .ok_or(MiniStabbleError::MathOverflow)?,
+        );
+        transfers.push((token_program, mint, user_token_info, vault_info, amount));
+    }
+
+    let balances: Vec<u128> = pool.get_balances().iter().map(|&b| b.into()).collect();
+    let weights: Vec<u128> = pool.get_weights().iter().map(|&w| w.into()).collect();
+    let amounts: Vec<u128> = scaled_amounts_in.iter().map(|&a| a.into()).collect();
+    let lp_to_mint = u64::try_from(calc_lp_out_for_join(
+        &balances,
+        &weights,
+        &amounts,
+        lp_supply.into(),
+        pool.swap_fee.into(),
+    )?)
+    .map_err(|_| MiniStabbleError::MathOverflow)?;
+    require!(
+        lp_to_mint >= min_lp_amount,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    for (token_program, mint, user_token_info, vault_info, amount) in &transfers {
+        transfer_from_user(
+            token_program,
+            mint,
+            *user_token_info,
+            *vault_info,
+            &ctx.accounts.user,
+            *amount,
+        )?;
+    }
+
+    let pool_key = ctx.accounts.pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, ctx.accounts.pool.authority_bump);
+    mint_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.authority,
+        signer_seeds,
+        lp_to_mint,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    for (token, scaled_amount) in pool.tokens.iter_mut().zip(&scaled_amounts_in) {
+        token.balance = token
+            .balance
+            .checked_add(*scaled_amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+    }
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(lp_supply + lp_to_mint),
+    );
+
+    emit!(MultiTokenDepositEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amounts_in,
+        lp_amount: lp_to_mint,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    constants::{AUTHORITY, INTERNAL_BALANCE, POOL_VAULT},
+    errors::MiniStabbleError,
+    state::{hold_internal_balance, update_pool_tokens, InternalBalance},
+    transfers::{transfer_fee, transfer_from_user},
+};
+
+#[derive(Accounts)]
+pub struct DepositInternalBalance<'info> {
+    /// CHECK: Weighted or stable pool, decoded in the handler to book the internal balance
+    #[account(mut, owner = crate::ID)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, seeds = [POOL_VAULT, pool.key().as_ref(), mint.key().as_ref()], bump, token::mint = mint, token::token_program = token_program, token::authority = authority)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint, token::token_program = token_program, token::authority = user)]
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        seeds = [INTERNAL_BALANCE, pool.key().as_ref(), user.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = InternalBalance::LEN
+    )]
+    pub internal_balance: Account<'info, InternalBalance>,
+
+    /// CHECK: Authority PDA owning the vaults
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves `amount` raw from the user into the pool's vault as internal balance, credited with
+/// what arrives after any transfer fee and booked on the pool as held outside its balance
+pub fn handler(ctx: Context<DepositInternalBalance>, amount: u64) -> Result<()> {
+    require!(amount > 0, MiniStabbleError::InvalidAmount);
+
+    let received = amount
+        .checked_sub(transfer_fee(&ctx.accounts.mint, amount)?)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    transfer_from_user(
+        &ctx.accounts.token_program,
+        &ctx.accounts.mint,
+        &ctx.accounts.user_token,
+        &ctx.accounts.vault,
+        &ctx.accounts.user,
+        amount,
+    )?;
+
+    let internal_balance = &mut ctx.accounts.internal_balance;
+    internal_balance.owner = ctx.accounts.user.key();
+    internal_balance.pool = ctx.accounts.pool.key();
+    internal_balance.mint = ctx.accounts.mint.key();
+    internal_balance.bump = ctx.bumps.internal_balance;
+    internal_balance.credit(received)?;
+
+    let mint = ctx.accounts.mint.key();
+    update_pool_tokens(&ctx.accounts.pool, |tokens| {
+        hold_internal_balance(tokens, &mint, received)
+    })
+}
@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    constants::{AUTHORITY, POOL_VAULT, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    events::MultiTokenDepositEvent,
+    math::weighted::calc_lp_out_given_exact_token_in,
+    pool_authority_signer_seeds,
+    state::WeightedPool,
+    transfers::{mint_lp, transfer_fee, transfer_from_user},
+};
+
+#[derive(Accounts)]
+pub struct DepositSingle<'info> {
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(mint::token_program = token_program_in)]
+    pub token_in_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::authority = user, token::mint = token_in_mint, token::token_program = token_program_in)]
+    pub user_token_in: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_in_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_in_mint, token::token_program = token_program_in)]
+    pub vault_token_in: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(init_if_needed, associated_token::mint = lp_mint, associated_token::authority = user, payer = user)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_in: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Deposits exactly `amount_in` raw of `token_in_mint` alone, minting LP for it with the swap fee
+/// charged on the share a proportional join would have paid in the other tokens. Fails if that
+/// mints less than `min_lp_amount`.
+pub fn handler(ctx: Context<DepositSingle>, amount_in: u64, min_lp_amount: u64) -> Result<()> {
+    require!(amount_in > 0, MiniStabbleError::InvalidAmount);
+
+    let pool = &ctx.accounts.pool;
+    require!(pool.is_active, MiniStabbleError::PoolInActive);
+
+    let lp_supply = ctx.accounts.lp_mint.supply;
+    // The first deposit sets the pool's ratio and goes through `deposit`
+    require!(lp_supply > 0, MiniStabbleError::InvalidAmount);
+
+    let token_index = pool
+        .tokens
+        .iter()
+        .position(|token| token.mint == ctx.accounts.token_in_mint.key())
+        .ok_or(MiniStabbleError::InvalidMint)?;
+    let token = &pool.tokens[token_index];
+
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_supply);
+
+    // The pool is only credited what reaches the vault
+    let scaled_amount_in = token.scale_amount_up(
+        amount_in
+            .checked_sub(transfer_fee(&ctx.accounts.token_in_mint, amount_in)?)
+            .ok_or(MiniStabbleError::MathOverflow)?,
+    );
+
+    let lp_amount = u64::try_from(calc_lp_out_given_exact_token_in(
+        token.balance.into(),
+        token.weight.into(),
+        scaled_amount_in.into(),
+        lp_supply.into(),
+        pool.swap_fee.into(),
+    )?)
+    .map_err(|_| MiniStabbleError::MathOverflow)?;
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+    require!(
+        lp_amount >= min_lp_amount,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    transfer_from_user(
+        &ctx.accounts.token_program_in,
+        &ctx.accounts.token_in_mint,
+        &ctx.accounts.user_token_in,
+        &ctx.accounts.vault_token_in,
+        &ctx.accounts.user,
+        amount_in,
+    )?;
+
+    let pool_key = ctx.accounts.pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, ctx.accounts.pool.authority_bump);
+    mint_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.authority,
+        signer_seeds,
+        lp_amount,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    let token = &mut pool.tokens[token_index];
+    token.balance = token
+        .balance
+        .checked_add(scaled_amount_in)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(lp_supply + lp_amount),
+    );
+
+    let mut amounts_in = vec![0; pool.tokens.len()];
+    amounts_in[token_index] = amount_in;
+    emit!(MultiTokenDepositEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amounts_in,
+        lp_amount,
+    });
+
+    Ok(())
+}
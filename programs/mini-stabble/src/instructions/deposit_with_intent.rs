@@ -0,0 +1,257 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    constants::{AUTHORITY, DEPOSIT_DELEGATE, INTENT_NONCE, POOL_VAULT, WEIGHT_POOL},
+    ed25519::require_preceding_signature,
+    errors::{end_dry_run, MiniStabbleError},
+    events::DepositEvent,
+    math::common::calc_tokens_in_proportional,
+    pool_authority_signer_seeds,
+    state::{IntentNonce, WeightedPool},
+    transfers::{gross_amount_for_net, mint_lp, transfer_from_delegated_user},
+};
+use solana_sdk_ids::sysvar::instructions as instructions_sysvar;
+
+/// Domain separator prefixed to every signed deposit intent, so the signature can't be
+/// replayed as a message for anything else
+pub const DEPOSIT_INTENT_DOMAIN: &[u8] = b"mini-stabble:deposit-intent";
+
+/// What the user signs: a proportional deposit of `lp_amount` into `pool`, paying at most
+/// `max_amount_a` of `mint_a` and `max_amount_b` of `mint_b` (raw)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DepositIntent {
+    pub pool: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub lp_amount: u64,
+    pub max_amount_a: u64,
+    pub max_amount_b: u64,
+
+    /// Unix timestamp after which the intent can no longer execute
+    pub expiry: i64,
+
+    /// Must equal the user's `IntentNonce::next_nonce`
+    pub nonce: u64,
+}
+
+impl DepositIntent {
+    /// Bytes the user signs with ed25519
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = DEPOSIT_INTENT_DOMAIN.to_vec();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+#[derive(Accounts)]
+pub struct DepositWithIntent<'info> {
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    /// CHECK: Authenticated by the ed25519 signature over the intent
+    pub user: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [INTENT_NONCE, user.key().as_ref()],
+        bump,
+        payer = relayer,
+        space = IntentNonce::LEN
+    )]
+    pub intent_nonce: Account<'info, IntentNonce>,
+
+    // Mint Accounts
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(constraint = token_a_mint.key() != token_b_mint.key(), mint::token_program = token_program_a)]
+    pub token_a_mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub token_b_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    /// User accounts, which must have approved `deposit_delegate` for at least the amounts paid
+    #[account(mut, token::authority = user, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::authority = user, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    // Vault Tokens
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_a_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub vault_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_b_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub vault_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(init_if_needed, associated_token::mint = lp_mint, associated_token::authority = user, payer = relayer)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Delegate PDA users approve on their token accounts to allow relayed deposits;
+    /// it only ever signs transfers into pool vaults against a verified intent
+    #[account(seeds = [DEPOSIT_DELEGATE], bump)]
+    pub deposit_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Submits the transaction and pays its fees and any rent
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, read to find the ed25519 signature check
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Proportional deposit submitted by a relayer on behalf of a user who signed `intent`. The
+/// transaction must verify the user's ed25519 signature over `intent.message()` in the
+/// instruction right before this one.
+pub fn handler(
+    ctx: Context<DepositWithIntent>,
+    intent: DepositIntent,
+    dry_run: bool,
+) -> Result<()> {
+    let user = ctx.accounts.user.key();
+    require_preceding_signature(&ctx.accounts.instructions, &user, &intent.message()?)?;
+
+    require_keys_eq!(
+        intent.pool,
+        ctx.accounts.pool.key(),
+        MiniStabbleError::InvalidAccount
+    );
+    require!(
+        Clock::get()?.unix_timestamp <= intent.expiry,
+        MiniStabbleError::IntentExpired
+    );
+
+    let intent_nonce = &mut ctx.accounts.intent_nonce;
+    if intent_nonce.user == Pubkey::default() {
+        intent_nonce.user = user;
+        intent_nonce.bump = ctx.bumps.intent_nonce;
+    }
+    require!(
+        intent.nonce == intent_nonce.next_nonce,
+        MiniStabbleError::InvalidNonce
+    );
+    intent_nonce.next_nonce = intent_nonce
+        .next_nonce
+        .checked_add(1)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    let pool = &mut ctx.accounts.pool;
+    require!(pool.is_active, MiniStabbleError::PoolInActive);
+    require!(intent.lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    let token_a_mint = &ctx.accounts.token_a_mint;
+    let token_b_mint = &ctx.accounts.token_b_mint;
+    // The caps only mean something for the mints they were signed for
+    require_keys_eq!(
+        intent.mint_a,
+        token_a_mint.key(),
+        MiniStabbleError::InvalidMint
+    );
+    require_keys_eq!(
+        intent.mint_b,
+        token_b_mint.key(),
+        MiniStabbleError::InvalidMint
+    );
+    let [token_a_index, token_b_index] = pool
+        .get_token_pair_indices(&token_a_mint.key(), &token_b_mint.key())
+        .ok_or(MiniStabbleError::InvalidMint)?;
+
+    // Only joins of a live pool; the first deposit sets the price and stays with the user
+    let lp_supply = ctx.accounts.lp_mint.supply;
+    require!(lp_supply > 0, MiniStabbleError::InvalidAmount);
+
+    let amounts_in = calc_tokens_in_proportional(
+        &[
+            pool.tokens[token_a_index].balance,
+            pool.tokens[token_b_index].balance,
+        ],
+        intent.lp_amount,
+        lp_supply,
+    )
+    .ok_or(MiniStabbleError::MathOverflow)?;
+    let (token_a_required, token_b_required) = (amounts_in[0], amounts_in[1]);
+
+    // Round the raw transfers up so the pool is never credited more than it receives, then
+    // gross up for transfer fees so the vaults receive them in full
+    let raw_amount_a = token_a_required.div_ceil(pool.tokens[token_a_index].scaling_factor);
+    let raw_amount_b = token_b_required.div_ceil(pool.tokens[token_b_index].scaling_factor);
+    let transfer_amount_a = gross_amount_for_net(token_a_mint, raw_amount_a)?;
+    let transfer_amount_b = gross_amount_for_net(token_b_mint, raw_amount_b)?;
+    require!(
+        transfer_amount_a <= intent.max_amount_a && transfer_amount_b <= intent.max_amount_b,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    let delegate_seeds = [DEPOSIT_DELEGATE, &[ctx.bumps.deposit_delegate]];
+    let delegate_signer_seeds = &[&delegate_seeds[..]];
+
+    transfer_from_delegated_user(
+        &ctx.accounts.token_program_a,
+        token_a_mint,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.vault_token_a,
+        &ctx.accounts.deposit_delegate,
+        delegate_signer_seeds,
+        transfer_amount_a,
+    )?;
+    transfer_from_delegated_user(
+        &ctx.accounts.token_program_b,
+        token_b_mint,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.vault_token_b,
+        &ctx.accounts.deposit_delegate,
+        delegate_signer_seeds,
+        transfer_amount_b,
+    )?;
+
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+
+    mint_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.authority,
+        signer_seeds,
+        intent.lp_amount,
+    )?;
+
+    let token_a = &mut pool.tokens[token_a_index];
+    token_a.balance = token_a
+        .balance
+        .checked_add(token_a.scale_amount_up(raw_amount_a))
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let token_b = &mut pool.tokens[token_b_index];
+    token_b.balance = token_b
+        .balance
+        .checked_add(token_b.scale_amount_up(raw_amount_b))
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    emit!(DepositEvent {
+        pool: pool.key(),
+        user,
+        amount_a: transfer_amount_a,
+        amount_b: transfer_amount_b,
+        lp_amount: intent.lp_amount,
+    });
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::end_dry_run,
+    instructions::{deposit, Deposit},
+    transfers::close_native_account,
+};
+
+/// `deposit` for zaps that fund it from temporary wrapped SOL accounts: afterwards each input
+/// account of the native mint is closed, so the SOL the deposit didn't use and the account's
+/// rent go back to the user instead of staying stranded in it
+pub fn handler(
+    mut ctx: Context<Deposit>,
+    lp_amount: u64,
+    input_token_a_amount: u64,
+    input_token_b_amount: u64,
+    dry_run: bool,
+) -> Result<()> {
+    deposit::execute(
+        &mut ctx,
+        lp_amount,
+        input_token_a_amount,
+        input_token_b_amount,
+    )?;
+
+    let accounts = &ctx.accounts;
+    for (token_program, user_token) in [
+        (&accounts.token_program_a, &accounts.user_token_a),
+        (&accounts.token_program_b, &accounts.user_token_b),
+    ] {
+        close_native_account(token_program, user_token, &accounts.user)?;
+    }
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{fees::MAX_SWAP_FEE, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    events::ProposalExecutedEvent,
+    state::{Proposal, ProposedChange, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+}
+
+/// Permissionless: applies an approved proposal to a weighted pool once its execution delay
+/// passed
+pub fn handler(ctx: Context<ExecuteProposal>) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    require_approved(proposal)?;
+
+    let pool = &mut ctx.accounts.pool;
+    match proposal.change {
+        ProposedChange::SwapFee { swap_fee } => {
+            require!(swap_fee <= MAX_SWAP_FEE, MiniStabbleError::InvalidAmount);
+            pool.swap_fee = swap_fee;
+        }
+        ProposedChange::Amp { .. } => return err!(MiniStabbleError::InvalidProposal),
+    }
+    proposal.executed = true;
+
+    emit!(ProposalExecutedEvent {
+        pool: pool.key(),
+        proposal: proposal.key(),
+        change: proposal.change,
+        votes: proposal.votes,
+    });
+
+    Ok(())
+}
+
+/// Checks that `proposal` passed and is inside its execution window
+pub fn require_approved(proposal: &Proposal) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        proposal.executed || now >= proposal.executable_ts(),
+        MiniStabbleError::ProposalTimelocked
+    );
+    require!(
+        proposal.is_executable(now),
+        MiniStabbleError::ProposalClosed
+    );
+    require!(
+        proposal.is_approved(),
+        MiniStabbleError::ProposalNotApproved
+    );
+
+    Ok(())
+}
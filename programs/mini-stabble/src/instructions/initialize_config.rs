@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::CONFIG, program::MiniStabble, state::Config};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(init, seeds = [CONFIG], bump, payer = admin, space = Config::LEN)]
+    pub config: Account<'info, Config>,
+
+    /// Only the program upgrade authority can claim the admin role
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, MiniStabble>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()))]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeConfig>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.admin.key();
+    config.amp_fee_floor_coefficient = 0;
+    config.treasury = Pubkey::default();
+    config.fee_manager = Pubkey::default();
+    config.pauser = Pubkey::default();
+    config.keeper = Pubkey::default();
+    config.bump = ctx.bumps.config;
+
+    Ok(())
+}
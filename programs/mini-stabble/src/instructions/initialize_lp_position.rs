@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{LP_POSITION, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{LpPosition, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct InitializeLpPosition<'info> {
+    #[account(seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(
+        init,
+        seeds = [LP_POSITION, pool.key().as_ref(), owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = LpPosition::LEN
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Opens the owner's position in the pool, attributing the deposits made through it to
+/// `referrer` (`Pubkey::default()` = none). The referrer can't be changed afterwards.
+pub fn handler(ctx: Context<InitializeLpPosition>, referrer: Pubkey) -> Result<()> {
+    require_keys_neq!(
+        referrer,
+        ctx.accounts.owner.key(),
+        MiniStabbleError::InvalidReferrer
+    );
+
+    let lp_position = &mut ctx.accounts.lp_position;
+    lp_position.owner = ctx.accounts.owner.key();
+    lp_position.pool = ctx.accounts.pool.key();
+    lp_position.referrer = referrer;
+    lp_position.fee_growth_snapshot = ctx.accounts.pool.fee_growth.clone();
+    lp_position.referrer_accrued = vec![0; ctx.accounts.pool.fee_growth.len()];
+    lp_position.referrer_claimable = vec![0; ctx.accounts.pool.fee_growth.len()];
+    lp_position.bump = ctx.bumps.lp_position;
+
+    Ok(())
+}
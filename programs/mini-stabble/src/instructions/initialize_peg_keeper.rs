@@ -0,0 +1,76 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::{
+    constants::{CONFIG, PEG_KEEPER, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{validate_peg_keeper, Config, PegKeeper, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct InitializePegKeeper<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    /// Protocol stable, whose mint authority must already be handed to the peg keeper
+    #[account(constraint = stable_mint.mint_authority == COption::Some(peg_keeper.key()) @ MiniStabbleError::InvalidPegKeeper)]
+    pub stable_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [PEG_KEEPER, pool.key().as_ref()],
+        bump,
+        payer = admin,
+        space = PegKeeper::LEN
+    )]
+    pub peg_keeper: Account<'info, PegKeeper>,
+
+    /// Holds the LP the keeper receives for the stable it provides
+    #[account(init, associated_token::mint = lp_mint, associated_token::authority = peg_keeper, payer = admin)]
+    pub keeper_lp: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Attaches a peg keeper to a two-token stable pool pairing the protocol stable
+pub fn handler(
+    ctx: Context<InitializePegKeeper>,
+    debt_ceiling: u64,
+    deviation_threshold_bps: u16,
+    max_amount_per_action: u64,
+) -> Result<()> {
+    validate_peg_keeper(deviation_threshold_bps, max_amount_per_action)?;
+
+    let pool = &ctx.accounts.pool;
+    let stable_mint = ctx.accounts.stable_mint.key();
+    require!(
+        pool.tokens.len() == 2 && pool.get_token_index(&stable_mint).is_some(),
+        MiniStabbleError::InvalidMint
+    );
+
+    let peg_keeper = &mut ctx.accounts.peg_keeper;
+    peg_keeper.pool = pool.key();
+    peg_keeper.stable_mint = stable_mint;
+    peg_keeper.debt = 0;
+    peg_keeper.debt_ceiling = debt_ceiling;
+    peg_keeper.deviation_threshold_bps = deviation_threshold_bps;
+    peg_keeper.max_amount_per_action = max_amount_per_action;
+    peg_keeper.last_action_ts = 0;
+    peg_keeper.bump = ctx.bumps.peg_keeper;
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    constants::{POOL_FEE_STATS, WEIGHT_POOL},
+    state::{PoolFeeStats, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct InitializePoolFeeStats<'info> {
+    #[account(seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [POOL_FEE_STATS, pool.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PoolFeeStats::LEN
+    )]
+    pub pool_fee_stats: Account<'info, PoolFeeStats>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless: starts tracking the pool's fee earnings, from now if it has liquidity or
+/// from the first collection after its first deposit otherwise
+pub fn handler(ctx: Context<InitializePoolFeeStats>) -> Result<()> {
+    let pool_fee_stats = &mut ctx.accounts.pool_fee_stats;
+    pool_fee_stats.pool = ctx.accounts.pool.key();
+    pool_fee_stats.bump = ctx.bumps.pool_fee_stats;
+
+    if let Some(invariant_per_lp) = ctx.accounts.pool.virtual_price(ctx.accounts.lp_mint.supply) {
+        pool_fee_stats.collect(invariant_per_lp, Clock::get()?.unix_timestamp);
+    }
+
+    Ok(())
+}
@@ -1,21 +1,42 @@
-use std::cmp::max;
-
 use crate::{
-    constants::{AUTHORITY, POOL_VAULT, STABLE_POOL},
+    constants::{fees::MAX_SWAP_FEE, AUTHORITY, POOL_PARAMS, POOL_VAULT, STABLE_POOL, TOKEN_BADGE},
     errors::MiniStabbleError,
-    math::{
-        fixed::ONE_U64,
-        stable::{AMP_PRECISION, MAX_AMP, MIN_AMP},
+    instructions::ramp_amp::{amp_ramp_target, AmpRampSchedule},
+    math::stable::{AMP_PRECISION, MAX_AMP, MAX_TOKENS, MIN_AMP},
+    pda,
+    state::{
+        validate_creator_lock_days, validate_pool_mint, CreatorLock, DrawdownGuard, ExitWindow,
+        FeeAutopilot, FeeRebate, InvariantCachePolicy, PoolParams, PoolToken, StablePool,
+        SwapRateLimit, TokenBadge, DEFAULT_MAX_OUT_RATIO_BPS,
     },
-    state::{PoolToken, StablePool},
 };
-use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_lang::{
+    prelude::*,
+    solana_program::program_pack::Pack,
+    system_program::{self, Allocate, Assign, CreateAccount, Transfer},
+};
+use anchor_spl::{
+    token::{spl_token, Mint, Token},
+    token_2022::{
+        self,
+        spl_token_2022::{
+            extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+            state::{Account as Token2022Account, Mint as Token2022Mint},
+        },
+    },
+    token_interface::{
+        self, InitializeAccount3, Mint as InterfaceMint, TokenAccount, TokenInterface,
+    },
+};
+
+/// Remaining accounts per constituent beyond `token_mint_b`:
+/// `[mint, vault, token_program, token_badge]`, with the program ID in place of a missing badge
+pub const ACCOUNTS_PER_EXTRA_TOKEN: usize = 4;
 
 #[derive(Accounts)]
 pub struct InitializeStablePool<'info> {
     /// CHECK: Unchecked
-    #[account(seeds = [AUTHORITY], bump)]
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump)]
     pub authority: UncheckedAccount<'info>,
 
     /// LP Mint - passed in as a new keypair by client
@@ -37,65 +58,139 @@ pub struct InitializeStablePool<'info> {
     )]
     pub pool: Account<'info, StablePool>,
 
+    #[account(
+        init,
+        seeds = [POOL_PARAMS, pool.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PoolParams::LEN
+    )]
+    pub pool_params: Account<'info, PoolParams>,
+
     // Vault Tokens Mint
     #[account(constraint = token_mint_a.key() < token_mint_b.key() @ MiniStabbleError::MintOrderInvalid)]
-    pub token_mint_a: Account<'info, Mint>,
-    pub token_mint_b: Account<'info, Mint>,
+    pub token_mint_a: InterfaceAccount<'info, InterfaceMint>,
+    pub token_mint_b: InterfaceAccount<'info, InterfaceMint>,
+
+    /// Only required for Token-2022 mints with extensions that need a badge
+    #[account(seeds = [TOKEN_BADGE, token_mint_a.key().as_ref()], bump = token_badge_a.bump)]
+    pub token_badge_a: Option<Account<'info, TokenBadge>>,
+
+    #[account(seeds = [TOKEN_BADGE, token_mint_b.key().as_ref()], bump = token_badge_b.bump)]
+    pub token_badge_b: Option<Account<'info, TokenBadge>>,
 
     // Tokens
-    #[account(init, seeds=[POOL_VAULT, pool.key().as_ref(), token_mint_a.key().as_ref()], bump, payer = payer, token::mint = token_mint_a, token::authority = authority)]
-    pub vault_token_a: Account<'info, TokenAccount>,
+    #[account(init, seeds=[POOL_VAULT, pool.key().as_ref(), token_mint_a.key().as_ref()], bump, payer = payer, token::mint = token_mint_a, token::authority = authority, token::token_program = token_program_a)]
+    pub vault_token_a: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(init, seeds=[POOL_VAULT, pool.key().as_ref(), token_mint_b.key().as_ref()], bump, payer = payer, token::mint = token_mint_b, token::authority = authority)]
-    pub vault_token_b: Account<'info, TokenAccount>,
+    #[account(init, seeds=[POOL_VAULT, pool.key().as_ref(), token_mint_b.key().as_ref()], bump, payer = payer, token::mint = token_mint_b, token::authority = authority, token::token_program = token_program_b)]
+    pub vault_token_b: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
 }
 
-pub fn handler(ctx: Context<InitializeStablePool>, swap_fee: u64, amp: u64) -> Result<()> {
+/// Creates a stable pool of `token_mint_a`, `token_mint_b` and any further constituents passed
+/// as remaining accounts (`ACCOUNTS_PER_EXTRA_TOKEN` each), up to `MAX_TOKENS`. Mints must be in
+/// strictly ascending order; each extra constituent gets its vault created here.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, InitializeStablePool<'info>>,
+    swap_fee: u64,
+    amp: u64,
+    creator_lock_days: u16,
+    amp_ramp: Option<AmpRampSchedule>,
+) -> Result<()> {
     // 1. Validate AMP
     require!(amp >= MIN_AMP, MiniStabbleError::AmpTooLow);
     require!(amp <= MAX_AMP, MiniStabbleError::AmpTooHigh);
 
     // 2. Validate swap_fee
-    require!(swap_fee < ONE_U64, MiniStabbleError::InvalidAmount);
+    require!(swap_fee <= MAX_SWAP_FEE, MiniStabbleError::InvalidAmount);
+    validate_creator_lock_days(creator_lock_days)?;
 
-    // 3. Create PoolToken structs
-    let max_decimal = max(
-        ctx.accounts.token_mint_a.decimals,
-        ctx.accounts.token_mint_b.decimals,
-    );
-    let pool_token_a = PoolToken {
-        mint: ctx.accounts.token_mint_a.key(),
-        token_account: ctx.accounts.vault_token_a.key(),
-        decimals: ctx.accounts.token_mint_a.decimals,
-        scaling_factor: 10_u64.pow((max_decimal - ctx.accounts.token_mint_a.decimals) as u32),
-        balance: ctx.accounts.vault_token_a.amount,
-        weight: 0,
-    };
+    // 3. Validate mints (Token-2022 extensions may need a badge)
+    validate_pool_mint(
+        &ctx.accounts.token_mint_a,
+        ctx.accounts.token_badge_a.is_some(),
+    )?;
+    validate_pool_mint(
+        &ctx.accounts.token_mint_b,
+        ctx.accounts.token_badge_b.is_some(),
+    )?;
 
-    let pool_token_b = PoolToken {
-        mint: ctx.accounts.token_mint_b.key(),
-        token_account: ctx.accounts.vault_token_b.key(),
-        decimals: ctx.accounts.token_mint_b.decimals,
-        scaling_factor: 10_u64.pow((max_decimal - ctx.accounts.token_mint_b.decimals) as u32),
-        balance: ctx.accounts.vault_token_b.amount,
-        weight: 0,
-    };
+    let extra_accounts = ctx.remaining_accounts;
+    check_extra_accounts(extra_accounts.len())?;
+
+    let mut constituents = vec![
+        (
+            ctx.accounts.token_mint_a.key(),
+            ctx.accounts.token_mint_a.decimals,
+            ctx.accounts.vault_token_a.key(),
+        ),
+        (
+            ctx.accounts.token_mint_b.key(),
+            ctx.accounts.token_mint_b.decimals,
+            ctx.accounts.vault_token_b.key(),
+        ),
+    ];
+    for accounts in extra_accounts.chunks(ACCOUNTS_PER_EXTRA_TOKEN) {
+        let [mint_info, vault_info, token_program_info, badge_info] = accounts else {
+            return err!(MiniStabbleError::InvalidAccount);
+        };
+        let mint = InterfaceAccount::<InterfaceMint>::try_from(mint_info)?;
+        check_mint_order(&constituents, &mint.key())?;
+
+        let has_badge = badge_info.key() != crate::ID;
+        if has_badge {
+            let badge = Account::<TokenBadge>::try_from(badge_info)?;
+            require_keys_eq!(
+                badge.key(),
+                pda::token_badge_address(&mint.key()).0,
+                MiniStabbleError::InvalidAccount
+            );
+        }
+        validate_pool_mint(&mint, has_badge)?;
 
-    // 4. Set pool fields
+        create_vault(
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &ctx.accounts.pool.key(),
+            mint_info,
+            vault_info,
+            token_program_info,
+            &ctx.accounts.authority,
+        )?;
+        constituents.push((mint.key(), mint.decimals, vault_info.key()));
+    }
+
+    // 4. Create PoolToken structs, scaled up to the most precise constituent
+    let tokens = pool_tokens(constituents);
+
+    // 5. Set pool fields
     let pool = &mut ctx.accounts.pool;
     pool.authority = ctx.accounts.authority.key();
     pool.lp_mint = ctx.accounts.lp_mint.key();
     pool.is_active = true;
     pool.invariant = 0;
     pool.swap_fee = swap_fee;
-    pool.tokens = vec![pool_token_a, pool_token_b];
+    pool.swap_rate_limit = SwapRateLimit::default();
+    pool.fee_growth = vec![0; tokens.len()];
+    pool.invariant_cache_policy = InvariantCachePolicy::Strict;
+    pool.invariant_last_updated_slot = 0;
+    pool.drawdown_guard = DrawdownGuard::default();
+    pool.rebate = FeeRebate::default();
+    pool.trader_allowlist = false;
+    pool.feature_flags = 0;
+    pool.exit_window = ExitWindow::default();
+    pool.max_out_ratio_bps = DEFAULT_MAX_OUT_RATIO_BPS;
+    pool.tokens = tokens;
     pool.bump = ctx.bumps.pool;
+    pool.authority_bump = ctx.bumps.authority;
 
     // AMP Specific
     pool.amp = amp
@@ -106,5 +201,242 @@ pub fn handler(ctx: Context<InitializeStablePool>, swap_fee: u64, amp: u64) -> R
     pool.amp_start_ts = 0;
     pool.amp_end_ts = 0;
 
+    // Bootstrap at a low amp and ramp up, so early imbalanced liquidity isn't priced as if it
+    // were already pegged
+    if let Some(amp_ramp) = amp_ramp {
+        let now = Clock::get()?.unix_timestamp;
+        pool.amp_target = amp_ramp_target(pool.amp, amp_ramp.target_amp, amp_ramp.end_ts, now)?;
+        pool.amp_start_ts = now;
+        pool.amp_end_ts = amp_ramp.end_ts;
+    }
+
+    // 6. Cold parameters
+    let pool_params = &mut ctx.accounts.pool_params;
+    pool_params.pool = pool.key();
+    pool_params.initial_depositor = ctx.accounts.payer.key();
+    pool_params.fee_autopilot = FeeAutopilot::default();
+    pool_params.creator_lock = CreatorLock::new(creator_lock_days);
+    pool_params.withdraw_queue_threshold_bps = 0;
+    pool_params.withdraw_queue_duration = 0;
+    pool_params.bump = ctx.bumps.pool_params;
+
+    Ok(())
+}
+
+/// Checks `len` remaining accounts describe whole extra constituents, at most `MAX_TOKENS` in
+/// all
+fn check_extra_accounts(len: usize) -> Result<()> {
+    require!(
+        len % ACCOUNTS_PER_EXTRA_TOKEN == 0 && 2 + len / ACCOUNTS_PER_EXTRA_TOKEN <= MAX_TOKENS,
+        MiniStabbleError::InvalidAccount
+    );
+
     Ok(())
 }
+
+/// Checks `mint` sorts strictly after every `(mint, decimals, vault)` constituent so far
+fn check_mint_order(constituents: &[(Pubkey, u8, Pubkey)], mint: &Pubkey) -> Result<()> {
+    require!(
+        constituents.last().is_some_and(|(last, _, _)| last < mint),
+        MiniStabbleError::MintOrderInvalid
+    );
+
+    Ok(())
+}
+
+/// Empty `PoolToken`s of the `(mint, decimals, vault)` constituents, scaled up to the most
+/// precise one
+fn pool_tokens(constituents: Vec<(Pubkey, u8, Pubkey)>) -> Vec<PoolToken> {
+    let max_decimal = constituents
+        .iter()
+        .map(|&(_, decimals, _)| decimals)
+        .max()
+        .unwrap_or_default();
+
+    constituents
+        .into_iter()
+        .map(|(mint, decimals, token_account)| PoolToken {
+            mint,
+            token_account,
+            decimals,
+            scaling_factor: 10_u64.pow((max_decimal - decimals) as u32),
+            balance: 0,
+            weight: 0,
+            swaps_disabled: false,
+            rounding_dust: 0,
+            internal_balances: 0,
+            queued_withdrawals: 0,
+            referral_rewards: 0,
+        })
+        .collect()
+}
+
+/// Creates and initializes the vault PDA of `mint` for `pool`, owned by the authority, the way
+/// `init` does for the first two constituents
+fn create_vault<'info>(
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    pool: &Pubkey,
+    mint_info: &'info AccountInfo<'info>,
+    vault_info: &'info AccountInfo<'info>,
+    token_program_info: &'info AccountInfo<'info>,
+    authority: &UncheckedAccount<'info>,
+) -> Result<()> {
+    let (vault, bump) = pda::vault_address(pool, mint_info.key);
+    require_keys_eq!(vault_info.key(), vault, MiniStabbleError::InvalidAccount);
+    require!(
+        (token_program_info.key() == spl_token::ID || token_program_info.key() == token_2022::ID)
+            && mint_info.owner == token_program_info.key,
+        MiniStabbleError::InvalidAccount
+    );
+
+    // Token-2022 vaults need room for the account extensions their mint requires
+    let space = if token_program_info.key() == spl_token::ID {
+        spl_token::state::Account::LEN
+    } else {
+        let data = mint_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+        let extensions =
+            ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?);
+        ExtensionType::try_calculate_account_len::<Token2022Account>(&extensions)?
+    };
+
+    let mint_key = mint_info.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[POOL_VAULT, pool.as_ref(), mint_key.as_ref(), &[bump]]];
+    let rent = Rent::get()?.minimum_balance(space);
+    let lamports = vault_info.lamports();
+    if lamports == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                CreateAccount {
+                    from: payer.to_account_info(),
+                    to: vault_info.clone(),
+                },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            token_program_info.key,
+        )?;
+    } else {
+        // Someone sent lamports to the address first; top it up and claim it instead
+        if lamports < rent {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: vault_info.clone(),
+                    },
+                ),
+                rent - lamports,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                Allocate {
+                    account_to_allocate: vault_info.clone(),
+                },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                Assign {
+                    account_to_assign: vault_info.clone(),
+                },
+                signer_seeds,
+            ),
+            token_program_info.key,
+        )?;
+    }
+
+    token_interface::initialize_account3(CpiContext::new(
+        token_program_info.clone(),
+        InitializeAccount3 {
+            account: vault_info.clone(),
+            mint: mint_info.clone(),
+            authority: authority.to_account_info(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::stable::calc_invariant;
+
+    fn constituents(decimals: &[u8]) -> Vec<(Pubkey, u8, Pubkey)> {
+        let mut mints: Vec<Pubkey> = decimals.iter().map(|_| Pubkey::new_unique()).collect();
+        mints.sort();
+
+        mints
+            .into_iter()
+            .zip(decimals)
+            .map(|(mint, &decimals)| (mint, decimals, Pubkey::new_unique()))
+            .collect()
+    }
+
+    #[test]
+    fn test_extra_accounts_allow_up_to_max_tokens() {
+        assert!(check_extra_accounts(0).is_ok());
+        assert!(check_extra_accounts(ACCOUNTS_PER_EXTRA_TOKEN).is_ok());
+        assert!(check_extra_accounts((MAX_TOKENS - 2) * ACCOUNTS_PER_EXTRA_TOKEN).is_ok());
+
+        assert_eq!(
+            check_extra_accounts((MAX_TOKENS - 1) * ACCOUNTS_PER_EXTRA_TOKEN),
+            Err(MiniStabbleError::InvalidAccount.into())
+        );
+        assert_eq!(
+            check_extra_accounts(ACCOUNTS_PER_EXTRA_TOKEN + 1),
+            Err(MiniStabbleError::InvalidAccount.into())
+        );
+    }
+
+    #[test]
+    fn test_mints_must_be_strictly_ascending() {
+        let constituents = constituents(&[6, 9, 9]);
+        let (first, last) = (constituents[0].0, constituents[2].0);
+
+        assert!(check_mint_order(&constituents[..2], &last).is_ok());
+        assert_eq!(
+            check_mint_order(&constituents, &first),
+            Err(MiniStabbleError::MintOrderInvalid.into())
+        );
+        // A constituent can't be listed twice
+        assert_eq!(
+            check_mint_order(&constituents, &last),
+            Err(MiniStabbleError::MintOrderInvalid.into())
+        );
+    }
+
+    #[test]
+    fn test_pool_tokens_scale_every_constituent_to_the_most_precise() {
+        let decimals = [6, 9, 8, 6, 2, 9, 0, 7];
+        let tokens = pool_tokens(constituents(&decimals));
+
+        assert_eq!(tokens.len(), MAX_TOKENS);
+        for (token, decimals) in tokens.iter().zip(decimals) {
+            assert_eq!(token.decimals, decimals);
+            assert_eq!(token.scaling_factor, 10u64.pow(9 - decimals as u32));
+            assert_eq!(token.balance, 0);
+        }
+    }
+
+    #[test]
+    fn test_eight_token_pool_has_a_balanced_invariant() {
+        let mut tokens = pool_tokens(constituents(&[6; MAX_TOKENS]));
+        for token in &mut tokens {
+            token.balance = token.scale_amount_up(1_000_000 * 10u64.pow(6));
+        }
+        let balances: Vec<u64> = tokens.iter().map(|token| token.balance).collect();
+
+        // A pegged pool's invariant is the sum of its balances
+        let invariant = calc_invariant(100 * AMP_PRECISION, &balances).unwrap();
+        assert_eq!(invariant, balances.iter().sum::<u64>());
+    }
+}
@@ -0,0 +1,82 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{
+    constants::{CONFIG, SWAP_CHECKPOINT},
+    errors::MiniStabbleError,
+    events::SwapCheckpointEvent,
+    state::{Config, StablePool, SwapCheckpoint, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct InitializeSwapCheckpoint<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Weighted or stable pool, checked by discriminator in the handler
+    #[account(owner = crate::ID)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [SWAP_CHECKPOINT, pool.key().as_ref()],
+        bump,
+        payer = admin,
+        space = SwapCheckpoint::LEN
+    )]
+    pub swap_checkpoint: Account<'info, SwapCheckpoint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Lets a busy pool's swaps report through one `SwapCheckpointEvent` every `interval_slots`
+/// instead of a `SwapEvent` each
+pub fn handler(ctx: Context<InitializeSwapCheckpoint>, interval_slots: u64) -> Result<()> {
+    require!(interval_slots > 0, MiniStabbleError::InvalidAmount);
+    let data = ctx.accounts.pool.try_borrow_data()?;
+    require!(
+        data.starts_with(WeightedPool::DISCRIMINATOR)
+            || data.starts_with(StablePool::DISCRIMINATOR),
+        MiniStabbleError::InvalidAccount
+    );
+
+    let swap_checkpoint = &mut ctx.accounts.swap_checkpoint;
+    swap_checkpoint.pool = ctx.accounts.pool.key();
+    swap_checkpoint.interval_slots = interval_slots;
+    swap_checkpoint.window_start_slot = Clock::get()?.slot;
+    swap_checkpoint.bump = ctx.bumps.swap_checkpoint;
+
+    Ok(())
+}
+
+/// Adds a swap to the pool's checkpoint at `slot`, first emitting the window it closes
+#[allow(clippy::too_many_arguments)]
+pub fn record_checkpoint_swap(
+    swap_checkpoint: &mut SwapCheckpoint,
+    slot: u64,
+    token_count: usize,
+    index_in: usize,
+    amount_in: u64,
+    index_out: usize,
+    amount_out: u64,
+    fee: u64,
+) {
+    if swap_checkpoint.is_due(slot) {
+        emit!(SwapCheckpointEvent {
+            pool: swap_checkpoint.pool,
+            start_slot: swap_checkpoint.window_start_slot,
+            end_slot: slot,
+            swap_count: swap_checkpoint.swap_count,
+            volume_in: swap_checkpoint.volume_in.clone(),
+            volume_out: swap_checkpoint.volume_out.clone(),
+            fees: swap_checkpoint.fees.clone(),
+        });
+        swap_checkpoint.reset(slot, token_count);
+    } else if swap_checkpoint.volume_in.len() != token_count {
+        swap_checkpoint.reset(slot, token_count);
+    }
+
+    swap_checkpoint.record(index_in, amount_in, index_out, amount_out, fee);
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    constants::{CONFIG, TOKEN_BADGE},
+    errors::MiniStabbleError,
+    state::{Config, TokenBadge},
+};
+
+#[derive(Accounts)]
+pub struct InitializeTokenBadge<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [TOKEN_BADGE, token_mint.key().as_ref()],
+        bump,
+        payer = admin,
+        space = TokenBadge::LEN
+    )]
+    pub token_badge: Account<'info, TokenBadge>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeTokenBadge>) -> Result<()> {
+    let token_badge = &mut ctx.accounts.token_badge;
+    token_badge.mint = ctx.accounts.token_mint.key();
+    token_badge.bump = ctx.bumps.token_badge;
+
+    Ok(())
+}
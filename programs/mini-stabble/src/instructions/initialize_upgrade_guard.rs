@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, UPGRADE_GUARD},
+    errors::MiniStabbleError,
+    pda,
+    state::{Config, UpgradeGuard},
+};
+
+#[derive(Accounts)]
+pub struct InitializeUpgradeGuard<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        seeds = [UPGRADE_GUARD],
+        bump,
+        payer = admin,
+        space = UpgradeGuard::LEN
+    )]
+    pub upgrade_guard: Account<'info, UpgradeGuard>,
+
+    #[account(address = pda::program_data_address().0)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the upgrade guard with the current deploy acknowledged; every later deploy has to be
+/// committed to `timelock` seconds ahead by `threshold` of `signers`
+pub fn handler(
+    ctx: Context<InitializeUpgradeGuard>,
+    timelock: i64,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    require!(timelock >= 0, MiniStabbleError::InvalidAmount);
+
+    let upgrade_guard = &mut ctx.accounts.upgrade_guard;
+    upgrade_guard.set_signers(signers, threshold)?;
+    upgrade_guard.timelock = timelock;
+    upgrade_guard.acknowledged_slot = ctx.accounts.program_data.slot;
+    upgrade_guard.bump = ctx.bumps.upgrade_guard;
+
+    Ok(())
+}
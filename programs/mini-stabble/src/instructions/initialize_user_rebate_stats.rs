@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{STABLE_POOL, USER_REBATE_STATS},
+    state::{rebate_epoch, StablePool, UserRebateStats},
+};
+
+#[derive(Accounts)]
+pub struct InitializeUserRebateStats<'info> {
+    #[account(seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(
+        init,
+        seeds = [USER_REBATE_STATS, pool.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        space = UserRebateStats::LEN
+    )]
+    pub user_rebate_stats: Account<'info, UserRebateStats>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeUserRebateStats>) -> Result<()> {
+    let user_rebate_stats = &mut ctx.accounts.user_rebate_stats;
+    user_rebate_stats.user = ctx.accounts.user.key();
+    user_rebate_stats.pool = ctx.accounts.pool.key();
+    user_rebate_stats.epoch = rebate_epoch(Clock::get()?.unix_timestamp);
+    user_rebate_stats.bump = ctx.bumps.user_rebate_stats;
+
+    Ok(())
+}
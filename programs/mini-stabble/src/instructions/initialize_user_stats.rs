@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::USER_STATS, state::UserStats};
+
+#[derive(Accounts)]
+pub struct InitializeUserStats<'info> {
+    #[account(
+        init,
+        seeds = [USER_STATS, user.key().as_ref()],
+        bump,
+        payer = user,
+        space = UserStats::LEN
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeUserStats>) -> Result<()> {
+    let user_stats = &mut ctx.accounts.user_stats;
+    user_stats.user = ctx.accounts.user.key();
+    user_stats.bump = ctx.bumps.user_stats;
+
+    Ok(())
+}
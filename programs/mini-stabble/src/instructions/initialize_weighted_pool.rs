@@ -1,16 +1,28 @@
 use std::cmp::max;
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::{
+    token::{Mint, Token},
+    token_interface::{Mint as InterfaceMint, TokenAccount, TokenInterface},
+};
 
 use crate::{
-    constants::{AUTHORITY, POOL_VAULT, WEIGHT_POOL}, errors::MiniStabbleError, math::fixed::{ONE_U64}, state::{PoolToken, WeightedPool}
+    constants::{fees::MAX_SWAP_FEE, AUTHORITY, POOL_PARAMS, POOL_VAULT, TOKEN_BADGE, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    math::{
+        fixed::ONE_U64,
+        weighted::{calc_swap_exponents, validate_weights},
+    },
+    state::{
+        validate_creator_lock_days, validate_pool_mint, CreatorLock, DrawdownGuard, ExitWindow,
+        FeeAutopilot, PoolParams, PoolToken, TokenBadge, WeightedPool,
+    },
 };
 
 #[derive(Accounts)]
 pub struct InitializeWeightedPool<'info> {
-    /// CHECK: Unchecked 
-    #[account(seeds = [AUTHORITY], bump)]
+    /// CHECK: Unchecked
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump)]
     pub authority: UncheckedAccount<'info>,
 
     /// LP Mint - passed in as a new keypair by client
@@ -32,33 +44,72 @@ pub struct InitializeWeightedPool<'info> {
     )]
     pub pool: Account<'info, WeightedPool>,
 
+    #[account(
+        init,
+        seeds = [POOL_PARAMS, pool.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PoolParams::LEN
+    )]
+    pub pool_params: Account<'info, PoolParams>,
+
     // Vault Tokens Mint
     #[account(constraint = token_mint_a.key() < token_mint_b.key() @ MiniStabbleError::MintOrderInvalid)]
-    pub token_mint_a: Account<'info, Mint>,
-    pub token_mint_b: Account<'info, Mint>,
+    pub token_mint_a: InterfaceAccount<'info, InterfaceMint>,
+    pub token_mint_b: InterfaceAccount<'info, InterfaceMint>,
+
+    /// Only required for Token-2022 mints with extensions that need a badge
+    #[account(seeds = [TOKEN_BADGE, token_mint_a.key().as_ref()], bump = token_badge_a.bump)]
+    pub token_badge_a: Option<Account<'info, TokenBadge>>,
+
+    #[account(seeds = [TOKEN_BADGE, token_mint_b.key().as_ref()], bump = token_badge_b.bump)]
+    pub token_badge_b: Option<Account<'info, TokenBadge>>,
 
     // Tokens
-    #[account(init, seeds=[POOL_VAULT, pool.key().as_ref(), token_mint_a.key().as_ref()], bump, payer = payer, token::mint = token_mint_a, token::authority = authority)]
-    pub vault_token_a: Account<'info, TokenAccount>,
+    #[account(init, seeds=[POOL_VAULT, pool.key().as_ref(), token_mint_a.key().as_ref()], bump, payer = payer, token::mint = token_mint_a, token::authority = authority, token::token_program = token_program_a)]
+    pub vault_token_a: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(init, seeds=[POOL_VAULT, pool.key().as_ref(), token_mint_b.key().as_ref()], bump, payer = payer, token::mint = token_mint_b, token::authority = authority)]
-    pub vault_token_b: Account<'info, TokenAccount>,
+    #[account(init, seeds=[POOL_VAULT, pool.key().as_ref(), token_mint_b.key().as_ref()], bump, payer = payer, token::mint = token_mint_b, token::authority = authority, token::token_program = token_program_b)]
+    pub vault_token_b: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
 }
 
-pub fn handler(ctx: Context<InitializeWeightedPool>, swap_fee: u64, only_token_a_weight: u64,) -> Result<()> {
+pub fn handler(
+    ctx: Context<InitializeWeightedPool>,
+    swap_fee: u64,
+    only_token_a_weight: u64,
+    creator_lock_days: u16,
+) -> Result<()> {
     let pool: &mut Account<'_, WeightedPool> = &mut ctx.accounts.pool;
-    
-    require!(only_token_a_weight < ONE_U64, MiniStabbleError::InvalidWeight);
-    require!(only_token_a_weight > 0, MiniStabbleError::InvalidWeight);
-    require!(swap_fee < ONE_U64, MiniStabbleError::InvalidAmount);
 
-    let max_decimal = max(ctx.accounts.token_mint_a.decimals, ctx.accounts.token_mint_b.decimals);
+    let weights = [
+        only_token_a_weight,
+        ONE_U64.saturating_sub(only_token_a_weight),
+    ];
+    validate_weights(&weights)?;
+    require!(swap_fee <= MAX_SWAP_FEE, MiniStabbleError::InvalidAmount);
+    validate_creator_lock_days(creator_lock_days)?;
+
+    validate_pool_mint(
+        &ctx.accounts.token_mint_a,
+        ctx.accounts.token_badge_a.is_some(),
+    )?;
+    validate_pool_mint(
+        &ctx.accounts.token_mint_b,
+        ctx.accounts.token_badge_b.is_some(),
+    )?;
+
+    let max_decimal = max(
+        ctx.accounts.token_mint_a.decimals,
+        ctx.accounts.token_mint_b.decimals,
+    );
 
     let pool_token_a = PoolToken {
         mint: ctx.accounts.token_mint_a.key(),
@@ -66,7 +117,12 @@ pub fn handler(ctx: Context<InitializeWeightedPool>, swap_fee: u64, only_token_a
         decimals: ctx.accounts.token_mint_a.decimals,
         scaling_factor: 10_u64.pow((max_decimal - ctx.accounts.token_mint_a.decimals) as u32),
         balance: ctx.accounts.vault_token_a.amount,
-        weight: only_token_a_weight
+        weight: weights[0],
+        swaps_disabled: false,
+        rounding_dust: 0,
+        internal_balances: 0,
+        queued_withdrawals: 0,
+        referral_rewards: 0,
     };
 
     let pool_token_b = PoolToken {
@@ -75,7 +131,12 @@ pub fn handler(ctx: Context<InitializeWeightedPool>, swap_fee: u64, only_token_a
         decimals: ctx.accounts.token_mint_b.decimals,
         scaling_factor: 10_u64.pow((max_decimal - ctx.accounts.token_mint_b.decimals) as u32),
         balance: ctx.accounts.vault_token_b.amount,
-        weight: ONE_U64.checked_sub(only_token_a_weight).unwrap()
+        weight: weights[1],
+        swaps_disabled: false,
+        rounding_dust: 0,
+        internal_balances: 0,
+        queued_withdrawals: 0,
+        referral_rewards: 0,
     };
 
     pool.authority = ctx.accounts.authority.key();
@@ -83,8 +144,24 @@ pub fn handler(ctx: Context<InitializeWeightedPool>, swap_fee: u64, only_token_a
     pool.is_active = true;
     pool.invariant = 0;
     pool.swap_fee = swap_fee;
+    pool.max_spot_price_move_per_swap_bps = 0;
+    pool.drawdown_guard = DrawdownGuard::default();
+    pool.trader_allowlist = false;
+    pool.feature_flags = 0;
+    pool.exit_window = ExitWindow::default();
+    pool.swap_exponents = calc_swap_exponents(&weights)?;
     pool.tokens = vec![pool_token_a, pool_token_b];
     pool.bump = ctx.bumps.pool;
-    
+    pool.authority_bump = ctx.bumps.authority;
+
+    let pool_params = &mut ctx.accounts.pool_params;
+    pool_params.pool = pool.key();
+    pool_params.initial_depositor = ctx.accounts.payer.key();
+    pool_params.fee_autopilot = FeeAutopilot::default();
+    pool_params.creator_lock = CreatorLock::new(creator_lock_days);
+    pool_params.withdraw_queue_threshold_bps = 0;
+    pool_params.withdraw_queue_duration = 0;
+    pool_params.bump = ctx.bumps.pool_params;
+
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,227 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    errors::MiniStabbleError,
+    instructions::{
+        compound_operation::lp_out_for_produced,
+        rebalance::{
+            exit_proportional, join_proportional, load_lp_accounts, load_pool_params, PairAccounts,
+            PairPool, PoolKind,
+        },
+    },
+};
+
+/// Remaining accounts:
+/// `[from_pool, from_authority, from_lp_mint, from_vault_a, from_vault_b, user_from_lp,
+///   from_pool_params, to_pool, to_authority, to_lp_mint, to_vault_a, to_vault_b, user_to_lp]`
+pub const MIGRATE_ACCOUNTS: usize = 13;
+
+#[derive(Accounts)]
+pub struct MigrateLiquidity<'info> {
+    #[account(constraint = mint_a.key() != mint_b.key(), mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, InterfaceMint>,
+
+    /// Receives the exited tokens and keeps whatever doesn't fit the destination pool's ratio
+    #[account(mut, token::mint = mint_a, token::token_program = token_program_a, token::authority = user)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::mint = mint_b, token::token_program = token_program_b, token::authority = user)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+/// Burns `lp_amount` of the source pool's LP, withdraws proportionally and deposits as much as
+/// fits the destination pool's ratio, minting its LP. Tokens left over from the ratio
+/// difference stay in the user's token accounts.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MigrateLiquidity<'info>>,
+    from_kind: PoolKind,
+    to_kind: PoolKind,
+    lp_amount: u64,
+    min_lp_out: u64,
+) -> Result<()> {
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+    require!(
+        ctx.remaining_accounts.len() == MIGRATE_ACCOUNTS,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let accounts = ctx.remaining_accounts;
+    require_keys_neq!(
+        accounts[0].key(),
+        accounts[7].key(),
+        MiniStabbleError::InvalidAccount
+    );
+
+    let pair = PairAccounts {
+        token_program: &ctx.accounts.token_program,
+        token_programs: [&ctx.accounts.token_program_a, &ctx.accounts.token_program_b],
+        user: &ctx.accounts.user,
+        mints: [&ctx.accounts.mint_a, &ctx.accounts.mint_b],
+        user_tokens: [&ctx.accounts.user_token_a, &ctx.accounts.user_token_b],
+    };
+
+    // 1. Exit the source pool
+    let mut from_pool = PairPool::load(
+        from_kind,
+        &accounts[0],
+        &accounts[1],
+        &accounts[3],
+        &accounts[4],
+        &pair,
+    )?;
+    let (from_lp_mint, user_from_lp) = load_lp_accounts(&from_pool, &accounts[2], &accounts[5])?;
+    let from_pool_params = load_pool_params(&from_pool, &accounts[6])?;
+
+    let withdrawn = exit_proportional(
+        &pair,
+        &mut from_pool,
+        &from_pool_params,
+        &from_lp_mint,
+        &user_from_lp,
+        lp_amount,
+    )?;
+    from_pool.pool.persist()?;
+
+    let mut produced = [0u64; 2];
+    for i in 0..2 {
+        produced[i] = from_pool.pool.tokens()[from_pool.indices[i]].scale_amount_down(withdrawn[i]);
+    }
+
+    // 2. Join the destination pool with what was withdrawn
+    let mut to_pool = PairPool::load(
+        to_kind,
+        &accounts[7],
+        &accounts[8],
+        &accounts[10],
+        &accounts[11],
+        &pair,
+    )?;
+    let (to_lp_mint, user_to_lp) = load_lp_accounts(&to_pool, &accounts[9], &accounts[12])?;
+
+    let lp_out = lp_out_for_produced(&pair, &to_pool, to_lp_mint.supply, produced)?;
+    check_lp_out(lp_out, min_lp_out)?;
+
+    join_proportional(&pair, &mut to_pool, &to_lp_mint, &user_to_lp, lp_out)?;
+    to_pool.pool.persist()?;
+
+    Ok(())
+}
+
+/// Checks the destination pool mints something for the migration and at least `min_lp_out`
+fn check_lp_out(lp_out: u64, min_lp_out: u64) -> Result<()> {
+    require!(lp_out > 0, MiniStabbleError::AmountTooSmall);
+    require!(lp_out >= min_lp_out, MiniStabbleError::SlippageExceeded);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        instructions::compound_operation::lp_out_for_available,
+        math::{common::calc_tokens_out_proportional, fixed::ONE_U64},
+        state::PoolToken,
+    };
+
+    fn token(balance: u64) -> PoolToken {
+        PoolToken {
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            decimals: 9,
+            scaling_factor: 1,
+            balance,
+            weight: ONE_U64 / 2,
+            swaps_disabled: false,
+            rounding_dust: 0,
+            internal_balances: 0,
+            queued_withdrawals: 0,
+            referral_rewards: 0,
+        }
+    }
+
+    /// Destination LP for exiting `lp_amount` of a pool holding `from` into one holding `to`
+    fn migrated_lp_out(
+        from: [u64; 2],
+        from_lp_supply: u64,
+        lp_amount: u64,
+        to: [u64; 2],
+        to_lp_supply: u64,
+    ) -> u64 {
+        let withdrawn = calc_tokens_out_proportional(&from, lp_amount, from_lp_supply).unwrap();
+        let to_tokens = to.map(token);
+
+        lp_out_for_available(
+            [&to_tokens[0], &to_tokens[1]],
+            to_lp_supply,
+            [withdrawn[0], withdrawn[1]],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_migration_between_pools_at_the_same_ratio_keeps_the_share() {
+        // 10% of a 1:1 pool into a 1:1 pool twice its size with the same LP price
+        let lp_out = migrated_lp_out(
+            [1_000 * ONE_U64, 1_000 * ONE_U64],
+            1_000 * ONE_U64,
+            100 * ONE_U64,
+            [2_000 * ONE_U64, 2_000 * ONE_U64],
+            2_000 * ONE_U64,
+        );
+
+        // Only the one raw unit of rounding headroom is lost
+        assert_eq!(lp_out, 100 * ONE_U64 - 1);
+        assert!(check_lp_out(lp_out, 99 * ONE_U64).is_ok());
+    }
+
+    #[test]
+    fn test_migration_into_a_pool_at_another_ratio_is_bound_by_the_scarcer_token() {
+        // Exit 100 A + 100 B into a pool holding 1 A per 2 B: only 50 A fit
+        let lp_out = migrated_lp_out(
+            [1_000 * ONE_U64, 1_000 * ONE_U64],
+            1_000 * ONE_U64,
+            100 * ONE_U64,
+            [1_000 * ONE_U64, 2_000 * ONE_U64],
+            1_000 * ONE_U64,
+        );
+
+        assert_eq!(lp_out, 50 * ONE_U64 - 1);
+        assert_eq!(
+            check_lp_out(lp_out, 60 * ONE_U64),
+            Err(MiniStabbleError::SlippageExceeded.into())
+        );
+    }
+
+    #[test]
+    fn test_dust_migration_is_rejected() {
+        let lp_out = migrated_lp_out(
+            [1_000 * ONE_U64, 1_000 * ONE_U64],
+            1_000 * ONE_U64,
+            1,
+            [1_000 * ONE_U64, 1_000 * ONE_U64],
+            1_000 * ONE_U64,
+        );
+
+        assert_eq!(lp_out, 0);
+        assert_eq!(
+            check_lp_out(lp_out, 0),
+            Err(MiniStabbleError::AmountTooSmall.into())
+        );
+    }
+}
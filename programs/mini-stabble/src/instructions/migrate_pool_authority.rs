@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_2022::{self, spl_token_2022::instruction::AuthorityType, Token2022},
+    token_interface::{set_authority, Mint, SetAuthority, TokenAccount},
+};
+
+use crate::{
+    authority_signer_seeds,
+    constants::AUTHORITY,
+    errors::MiniStabbleError,
+    instructions::rebalance::{LegPool, PoolKind},
+    pda,
+};
+
+#[derive(Accounts)]
+pub struct MigratePoolAuthority<'info> {
+    /// CHECK: Global authority PDA that owned every pool's vaults and LP mint
+    #[account(seeds = [AUTHORITY], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+/// Permissionless: hands a pool created before pools had their own authority over to its
+/// `[AUTHORITY, pool]` PDA. Remaining accounts are
+/// `[pool, pool_authority, lp_mint, vaults in token order..., lp_escrows...]`, where the LP
+/// escrows are any of the pool's creator lock, protocol LP or proposal escrows. Accounts
+/// already owned by the pool authority are left alone, so escrows can be moved in later calls.
+/// Until this runs the pool's `authority_bump` is 0, so every handler's `[AUTHORITY, pool]`
+/// seeds check fails and the pool rejects all other instructions.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MigratePoolAuthority<'info>>,
+    pool_kind: PoolKind,
+) -> Result<()> {
+    let accounts = ctx.remaining_accounts;
+    require!(accounts.len() >= 3, MiniStabbleError::InvalidAccount);
+
+    let mut pool = LegPool::load(pool_kind, &accounts[0])?;
+    let (pool_authority, authority_bump) = pda::pool_authority_address(&pool.key());
+    require_keys_eq!(
+        accounts[1].key(),
+        pool_authority,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let vault_count = pool.tokens().len();
+    require!(
+        accounts.len() >= 3 + vault_count,
+        MiniStabbleError::InvalidAccount
+    );
+    let (vaults, lp_escrows) = accounts[3..].split_at(vault_count);
+
+    let authority = ctx.accounts.authority.key();
+    let signer_seeds = authority_signer_seeds!(ctx.bumps.authority);
+    let hand_over = |account_or_mint: &AccountInfo<'info>, authority_type: AuthorityType| {
+        let token_program = if *account_or_mint.owner == token_2022::ID {
+            ctx.accounts.token_2022_program.to_account_info()
+        } else {
+            ctx.accounts.token_program.to_account_info()
+        };
+
+        set_authority(
+            CpiContext::new_with_signer(
+                token_program,
+                SetAuthority {
+                    current_authority: ctx.accounts.authority.to_account_info(),
+                    account_or_mint: account_or_mint.clone(),
+                },
+                signer_seeds,
+            ),
+            authority_type,
+            Some(pool_authority),
+        )
+    };
+
+    let lp_mint = InterfaceAccount::<Mint>::try_from(&accounts[2])?;
+    require_keys_eq!(
+        lp_mint.key(),
+        pool.lp_mint(),
+        MiniStabbleError::InvalidAccount
+    );
+    if lp_mint.mint_authority.contains(&authority) {
+        hand_over(&accounts[2], AuthorityType::MintTokens)?;
+    }
+
+    for (token, vault_info) in pool.tokens().iter().zip(vaults) {
+        require_keys_eq!(
+            vault_info.key(),
+            token.token_account,
+            MiniStabbleError::InvalidAccount
+        );
+        let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+        if vault.owner == authority {
+            hand_over(vault_info, AuthorityType::AccountOwner)?;
+        }
+    }
+
+    for escrow_info in lp_escrows {
+        let escrow = InterfaceAccount::<TokenAccount>::try_from(escrow_info)?;
+        require_keys_eq!(escrow.mint, pool.lp_mint(), MiniStabbleError::InvalidMint);
+        require!(
+            escrow.owner == authority || escrow.owner == pool_authority,
+            MiniStabbleError::InvalidAccount
+        );
+        if escrow.owner == authority {
+            hand_over(escrow_info, AuthorityType::AccountOwner)?;
+        }
+    }
+
+    pool.set_authority(pool_authority, authority_bump);
+    pool.persist()
+}
@@ -0,0 +1,66 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{
+    constants::POOL_PARAMS,
+    state::{migrate_legacy_pool, PoolParams},
+};
+
+#[derive(Accounts)]
+pub struct MigratePoolParams<'info> {
+    /// CHECK: Weighted or stable pool in the original program's layout, decoded in the
+    /// handler. Every pool created since has its params, so `init` below fails for those.
+    #[account(mut, owner = crate::ID)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [POOL_PARAMS, pool.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PoolParams::LEN
+    )]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless: moves a pool created by the original program into the current layout and
+/// gives it its `PoolParams`. Every setting the original layout didn't have starts off, and the
+/// payer funds the rent of the larger pool account. The pool stays owned by the global
+/// authority until `migrate_pool_authority` runs.
+pub fn handler(ctx: Context<MigratePoolParams>) -> Result<()> {
+    let pool_info = ctx.accounts.pool.to_account_info();
+    let pool_params = &mut ctx.accounts.pool_params;
+    pool_params.pool = pool_info.key();
+    pool_params.bump = ctx.bumps.pool_params;
+
+    let migrated = migrate_legacy_pool(&pool_info.try_borrow_data()?)?;
+
+    let new_len = migrated.space().max(pool_info.data_len());
+    let rent_due = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(pool_info.lamports());
+    if rent_due > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: pool_info.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    pool_info.resize(new_len)?;
+
+    let mut data = pool_info.try_borrow_mut_data()?;
+    data.fill(0);
+    migrated.write(&mut data)
+}
@@ -1,3 +1,5 @@
+#![allow(ambiguous_glob_reexports)]
+
 pub mod initialize_weighted_pool;
 pub use initialize_weighted_pool::*;
 
@@ -17,4 +19,304 @@ pub mod stable_swap;
 pub use stable_swap::*;
 
 pub mod stable_deposit;
-pub use stable_deposit::*;
\ No newline at end of file
+pub use stable_deposit::*;
+
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod set_withdraw_queue;
+pub use set_withdraw_queue::*;
+
+pub mod set_stable_withdraw_queue;
+pub use set_stable_withdraw_queue::*;
+
+pub mod request_withdraw;
+pub use request_withdraw::*;
+
+pub mod stable_request_withdraw;
+pub use stable_request_withdraw::*;
+
+pub mod claim_withdraw;
+pub use claim_withdraw::*;
+
+pub mod set_amp_fee_floor;
+pub use set_amp_fee_floor::*;
+
+pub mod set_stable_swap_fee;
+pub use set_stable_swap_fee::*;
+
+pub mod ramp_amp;
+pub use ramp_amp::*;
+
+pub mod stop_ramp_amp;
+pub use stop_ramp_amp::*;
+
+pub mod rebalance;
+pub use rebalance::*;
+
+pub mod initialize_token_badge;
+pub use initialize_token_badge::*;
+
+pub mod delete_token_badge;
+pub use delete_token_badge::*;
+
+pub mod set_pool_active;
+pub use set_pool_active::*;
+
+pub mod set_stable_pool_active;
+pub use set_stable_pool_active::*;
+
+pub mod repair_scaling;
+pub use repair_scaling::*;
+
+pub mod stable_repair_scaling;
+pub use stable_repair_scaling::*;
+
+pub mod set_stable_rate_limit;
+pub use set_stable_rate_limit::*;
+
+pub mod compound_operation;
+pub use compound_operation::*;
+
+pub mod migrate_liquidity;
+pub use migrate_liquidity::*;
+
+pub mod set_max_spot_price_move;
+pub use set_max_spot_price_move::*;
+
+pub mod quote_route;
+pub use quote_route::*;
+
+pub mod set_stable_invariant_cache_policy;
+pub use set_stable_invariant_cache_policy::*;
+
+pub mod swap_with_slippage_bps;
+
+pub mod stable_swap_with_slippage_bps;
+
+pub mod set_treasury;
+pub use set_treasury::*;
+
+pub mod seed_protocol_liquidity;
+pub use seed_protocol_liquidity::*;
+
+pub mod release_protocol_liquidity;
+pub use release_protocol_liquidity::*;
+
+pub mod stable_swap_split;
+
+pub mod set_drawdown_guard;
+pub use set_drawdown_guard::*;
+
+pub mod set_stable_drawdown_guard;
+pub use set_stable_drawdown_guard::*;
+
+pub mod set_fee_autopilot;
+pub use set_fee_autopilot::*;
+
+pub mod update_fee_from_volatility;
+pub use update_fee_from_volatility::*;
+
+pub mod initialize_user_stats;
+pub use initialize_user_stats::*;
+
+pub mod deposit_with_intent;
+pub use deposit_with_intent::*;
+
+pub mod initialize_peg_keeper;
+pub use initialize_peg_keeper::*;
+
+pub mod set_peg_keeper;
+pub use set_peg_keeper::*;
+
+pub mod update_peg_keeper;
+pub use update_peg_keeper::*;
+
+pub mod initialize_user_rebate_stats;
+pub use initialize_user_rebate_stats::*;
+
+pub mod set_stable_rebate_fee_share;
+pub use set_stable_rebate_fee_share::*;
+
+pub mod claim_rebate;
+pub use claim_rebate::*;
+
+pub mod smart_swap;
+pub use smart_swap::*;
+
+pub mod release_creator_lock;
+pub use release_creator_lock::*;
+
+pub mod stable_release_creator_lock;
+pub use stable_release_creator_lock::*;
+
+pub mod migrate_pool_params;
+pub use migrate_pool_params::*;
+
+pub mod migrate_pool_authority;
+pub use migrate_pool_authority::*;
+
+pub mod archive_pool;
+pub use archive_pool::*;
+
+pub mod stable_archive_pool;
+pub use stable_archive_pool::*;
+
+pub mod create_proposal;
+pub use create_proposal::*;
+
+pub mod vote_on_proposal;
+pub use vote_on_proposal::*;
+
+pub mod cast_vote;
+pub use cast_vote::*;
+
+pub mod withdraw_vote;
+pub use withdraw_vote::*;
+
+pub mod execute_proposal;
+pub use execute_proposal::*;
+
+pub mod stable_execute_proposal;
+pub use stable_execute_proposal::*;
+
+pub mod initialize_pool_fee_stats;
+pub use initialize_pool_fee_stats::*;
+
+pub mod collect_pool_fee_stats;
+pub use collect_pool_fee_stats::*;
+
+pub mod stable_reconcile_balances;
+pub use stable_reconcile_balances::*;
+
+pub mod set_compliance_authority;
+pub use set_compliance_authority::*;
+
+pub mod set_stable_compliance_authority;
+pub use set_stable_compliance_authority::*;
+
+pub mod allowlist_trader;
+pub use allowlist_trader::*;
+
+pub mod remove_allowlisted_trader;
+pub use remove_allowlisted_trader::*;
+
+pub mod deposit_exact_tokens_in;
+pub use deposit_exact_tokens_in::*;
+
+pub mod set_role;
+pub use set_role::*;
+
+pub mod set_stable_max_out_ratio;
+pub use set_stable_max_out_ratio::*;
+
+pub mod set_token_swaps_disabled;
+pub use set_token_swaps_disabled::*;
+
+pub mod set_stable_token_swaps_disabled;
+pub use set_stable_token_swaps_disabled::*;
+
+pub mod set_crank_keeper;
+pub use set_crank_keeper::*;
+
+pub mod initialize_swap_checkpoint;
+pub use initialize_swap_checkpoint::*;
+
+pub mod swap_route;
+pub use swap_route::*;
+
+pub mod checkpoint_stable_virtual_price;
+pub use checkpoint_stable_virtual_price::*;
+
+pub mod report_invariant_decrease;
+pub use report_invariant_decrease::*;
+
+pub mod deposit_single_token_exact_lp_out;
+pub use deposit_single_token_exact_lp_out::*;
+
+pub mod swap_with_fee_reimbursement;
+
+pub mod stable_swap_with_fee_reimbursement;
+
+pub mod withdraw;
+pub use withdraw::*;
+
+pub mod withdraw_unbalanced;
+pub use withdraw_unbalanced::*;
+
+pub mod deposit_internal_balance;
+pub use deposit_internal_balance::*;
+
+pub mod withdraw_internal_balance;
+pub use withdraw_internal_balance::*;
+
+pub mod swap_internal;
+pub use swap_internal::*;
+
+pub mod deposit_single;
+pub use deposit_single::*;
+
+pub mod withdraw_single;
+pub use withdraw_single::*;
+
+pub mod set_dust_destination;
+pub use set_dust_destination::*;
+
+pub mod sweep_rounding_dust;
+pub use sweep_rounding_dust::*;
+
+pub mod query_trade_stats;
+pub use query_trade_stats::*;
+
+pub mod stable_withdraw;
+pub use stable_withdraw::*;
+
+pub mod stable_withdraw_single;
+pub use stable_withdraw_single::*;
+
+pub mod initialize_lp_position;
+pub use initialize_lp_position::*;
+
+pub mod set_referral_fee_share;
+pub use set_referral_fee_share::*;
+
+pub mod claim_referral_rewards;
+pub use claim_referral_rewards::*;
+
+pub mod stable_deposit_unbalanced;
+pub use stable_deposit_unbalanced::*;
+
+pub mod stable_swap_exact_out;
+
+pub mod swap_exact_out;
+
+pub mod audit_pool;
+pub use audit_pool::*;
+
+pub mod deposit_with_refund;
+
+pub mod stable_deposit_with_refund;
+
+pub mod initialize_upgrade_guard;
+pub use initialize_upgrade_guard::*;
+
+pub mod commit_upgrade;
+pub use commit_upgrade::*;
+
+pub mod reveal_upgrade;
+pub use reveal_upgrade::*;
+
+pub mod set_upgrade_signers;
+pub use set_upgrade_signers::*;
+
+pub mod set_upgrade_pause;
+pub use set_upgrade_pause::*;
+
+pub mod pause_on_unacknowledged_upgrade;
+pub use pause_on_unacknowledged_upgrade::*;
+
+pub mod stable_deposit_multi;
+pub use stable_deposit_multi::*;
+
+pub mod trip_breaker;
+pub use trip_breaker::*;
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::UPGRADE_GUARD,
+    errors::MiniStabbleError,
+    events::UnacknowledgedUpgradePauseEvent,
+    instructions::rebalance::{LegPool, PoolKind},
+    pda,
+    state::{UpgradeGuard, FEATURE_PAUSE_ON_UNACKNOWLEDGED_UPGRADE},
+};
+
+#[derive(Accounts)]
+pub struct PauseOnUnacknowledgedUpgrade<'info> {
+    /// Pool passed as the only remaining account
+    #[account(seeds = [UPGRADE_GUARD], bump = upgrade_guard.bump)]
+    pub upgrade_guard: Account<'info, UpgradeGuard>,
+
+    #[account(address = pda::program_data_address().0)]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+/// Permissionless: pauses a pool that opted into `FEATURE_PAUSE_ON_UNACKNOWLEDGED_UPGRADE`
+/// while the program runs a deploy the upgrade guard hasn't acknowledged. Resuming stays with
+/// the admin.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PauseOnUnacknowledgedUpgrade<'info>>,
+    pool_kind: PoolKind,
+) -> Result<()> {
+    let [pool_info] = ctx.remaining_accounts else {
+        return err!(MiniStabbleError::InvalidAccount);
+    };
+    require!(pool_info.is_writable, MiniStabbleError::InvalidAccount);
+
+    let deploy_slot = ctx.accounts.program_data.slot;
+    require!(
+        ctx.accounts.upgrade_guard.is_unacknowledged(deploy_slot),
+        MiniStabbleError::UpgradeAcknowledged
+    );
+
+    let mut pool = LegPool::load(pool_kind, pool_info)?;
+    require!(
+        pool.has_feature(FEATURE_PAUSE_ON_UNACKNOWLEDGED_UPGRADE),
+        MiniStabbleError::Unauthorized
+    );
+    pool.set_active(false);
+    pool.persist()?;
+
+    emit!(UnacknowledgedUpgradePauseEvent {
+        pool: pool.key(),
+        deploy_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{PoolFeeStats, SlotTradeStats};
+
+/// Figures of one slot's swaps, as returned by `query_trade_stats`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SlotTradeSummary {
+    pub slot: u64,
+    pub trade_count: u32,
+
+    /// Volume-weighted average price of `tokens[0]` in `tokens[1]` (SCALE = 1e9)
+    pub vwap: u128,
+
+    /// Spot price of `tokens[0]` in `tokens[1]` before the slot's first swap (SCALE = 1e9)
+    pub open_price: u128,
+
+    /// Furthest the spot price moved away from `open_price` during the slot, in bps
+    pub max_move_bps: u64,
+}
+
+impl From<&SlotTradeStats> for SlotTradeSummary {
+    fn from(trades: &SlotTradeStats) -> Self {
+        Self {
+            slot: trades.slot,
+            trade_count: trades.trade_count,
+            vwap: trades.vwap(),
+            open_price: trades.open_price,
+            max_move_bps: trades.max_move_bps(),
+        }
+    }
+}
+
+/// Returned by `query_trade_stats`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TradeStatsQuote {
+    /// Latest slot the pool traded in
+    pub current: SlotTradeSummary,
+
+    /// Slot it traded in before that
+    pub previous: SlotTradeSummary,
+}
+
+#[derive(Accounts)]
+pub struct QueryTradeStats<'info> {
+    pub pool_fee_stats: Account<'info, PoolFeeStats>,
+}
+
+/// Returns the pool's per-slot VWAP, trade counts and price excursions as return data, for
+/// sandwich monitoring and fee policies that react to them
+pub fn handler(ctx: Context<QueryTradeStats>) -> Result<TradeStatsQuote> {
+    let pool_fee_stats = &ctx.accounts.pool_fee_stats;
+
+    Ok(TradeStatsQuote {
+        current: (&pool_fee_stats.current_slot_trades).into(),
+        previous: (&pool_fee_stats.previous_slot_trades).into(),
+    })
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::BPS_DENOMINATOR,
+    errors::MiniStabbleError,
+    instructions::rebalance::{LegPool, PoolKind},
+    math::fixed::SCALE,
+};
+
+/// One swap of a route; the pool is the next remaining account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RouteHop {
+    pub pool_kind: PoolKind,
+    pub token_in_index: u8,
+    pub token_out_index: u8,
+}
+
+/// Returned by `quote_route`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RouteQuote {
+    /// Raw amount of the last hop's out token
+    pub amount_out: u64,
+
+    /// Shortfall of `amount_out` against trading the whole route at the current spot prices,
+    /// fees included, in bps
+    pub price_impact_bps: u64,
+}
+
+#[derive(Accounts)]
+pub struct QuoteRoute<'info> {
+    /// Time the stable pools' amp ramps are evaluated at
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Walks `hops` read-only, pricing each swap exactly as the swap instructions would (before any
+/// Token-2022 transfer fees), and returns the final amount out and cumulative price impact as
+/// return data. Each hop's out token must be the next hop's in token.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, QuoteRoute<'info>>,
+    hops: Vec<RouteHop>,
+    amount_in: u64,
+) -> Result<RouteQuote> {
+    require!(!hops.is_empty(), MiniStabbleError::InvalidAmount);
+    require!(amount_in > 0, MiniStabbleError::InvalidAmount);
+    require!(
+        ctx.remaining_accounts.len() == hops.len(),
+        MiniStabbleError::InvalidAccount
+    );
+
+    let now = ctx.accounts.clock.unix_timestamp;
+
+    let mut amount = amount_in;
+    // Route output at spot prices, in raw units times SCALE to keep precision across hops
+    let mut ideal_amount = (amount_in as u128)
+        .checked_mul(SCALE)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let mut previous_mint: Option<Pubkey> = None;
+
+    for (hop, pool_info) in hops.iter().zip(ctx.remaining_accounts) {
+        let pool = LegPool::load(hop.pool_kind, pool_info)?;
+        require!(pool.is_active(), MiniStabbleError::PoolInActive);
+
+        let index_in = hop.token_in_index as usize;
+        let index_out = hop.token_out_index as usize;
+        let tokens = pool.tokens();
+        require!(
+            index_in != index_out && index_in < tokens.len() && index_out < tokens.len(),
+            MiniStabbleError::InvalidMint
+        );
+        if let Some(previous_mint) = previous_mint {
+            require_keys_eq!(
+                previous_mint,
+                tokens[index_in].mint,
+                MiniStabbleError::InvalidMint
+            );
+        }
+        previous_mint = Some(tokens[index_out].mint);
+
+        let price = pool.spot_price(index_in, index_out, now)?;
+        ideal_amount = ideal_amount
+            .checked_mul(tokens[index_in].scaling_factor as u128)
+            .and_then(|v| v.checked_div(tokens[index_out].scaling_factor as u128))
+            .and_then(|v| v.checked_mul(price))
+            .and_then(|v| v.checked_div(SCALE))
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        let (scaled_amount_out, _) = pool.quote_swap(
+            index_in,
+            index_out,
+            tokens[index_in].scale_amount_up(amount),
+            now,
+        )?;
+        amount = tokens[index_out].scale_amount_down(scaled_amount_out);
+        require!(amount > 0, MiniStabbleError::AmountTooSmall);
+    }
+
+    let actual_amount = (amount as u128)
+        .checked_mul(SCALE)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let price_impact_bps = match ideal_amount {
+        0 => 0,
+        ideal_amount => {
+            ideal_amount.saturating_sub(actual_amount) * BPS_DENOMINATOR as u128 / ideal_amount
+        }
+    };
+
+    Ok(RouteQuote {
+        amount_out: amount,
+        price_impact_bps: price_impact_bps as u64,
+    })
+}
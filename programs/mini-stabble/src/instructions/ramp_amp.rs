@@ -0,0 +1,93 @@
+use std::cmp::min;
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    instructions::set_stable_swap_fee::require_fee_above_amp_floor,
+    math::stable::{AMP_PRECISION, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_RAMP_DURATION},
+    state::{Config, Role, StablePool},
+};
+
+/// Amp ramp scheduled at pool creation: the pool starts at its initial amp and ramps to
+/// `target_amp` (without AMP_PRECISION) by `end_ts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmpRampSchedule {
+    pub target_amp: u64,
+    pub end_ts: i64,
+}
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::Keeper, keeper.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    /// Admin or keeper
+    pub keeper: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RampAmp>, target_amp: u64, end_ts: i64) -> Result<()> {
+    start_amp_ramp(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.config,
+        target_amp,
+        end_ts,
+        Clock::get()?.unix_timestamp,
+    )
+}
+
+/// Starts ramping `pool`'s amp from its current value at `now` to `target_amp` at `end_ts`
+pub fn start_amp_ramp(
+    pool: &mut StablePool,
+    config: &Config,
+    target_amp: u64,
+    end_ts: i64,
+    now: i64,
+) -> Result<()> {
+    let current_amp = pool
+        .get_current_amp(now)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let amp_target = amp_ramp_target(current_amp, target_amp, end_ts, now)?;
+
+    require_fee_above_amp_floor(pool.swap_fee, min(current_amp, amp_target), config)?;
+
+    pool.amp = current_amp;
+    pool.amp_target = amp_target;
+    pool.amp_start_ts = now;
+    pool.amp_end_ts = end_ts;
+    pool.invalidate_invariant();
+    pool.drawdown_guard.reset();
+
+    Ok(())
+}
+
+/// Checks a ramp from `current_amp` (with AMP_PRECISION) at `now` to `target_amp` (without) at
+/// `end_ts`, and returns the target with AMP_PRECISION
+pub fn amp_ramp_target(current_amp: u64, target_amp: u64, end_ts: i64, now: i64) -> Result<u64> {
+    require!(target_amp >= MIN_AMP, MiniStabbleError::AmpTooLow);
+    require!(target_amp <= MAX_AMP, MiniStabbleError::AmpTooHigh);
+    require!(
+        end_ts >= now.saturating_add(MIN_RAMP_DURATION),
+        MiniStabbleError::InvalidAmpRamp
+    );
+
+    let amp_target = target_amp
+        .checked_mul(AMP_PRECISION)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    // Bound how far a single ramp can move the curve
+    require!(
+        amp_target <= current_amp.saturating_mul(MAX_AMP_CHANGE),
+        MiniStabbleError::InvalidAmpRamp
+    );
+    require!(
+        current_amp <= amp_target.saturating_mul(MAX_AMP_CHANGE),
+        MiniStabbleError::InvalidAmpRamp
+    );
+
+    Ok(amp_target)
+}
@@ -0,0 +1,668 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    constants::AUTHORITY,
+    errors::MiniStabbleError,
+    math::{
+        common::{calc_tokens_in_proportional, calc_tokens_out_proportional},
+        fixed::SCALE,
+        stable::MIN_SWAP_AMOUNT,
+        swap_context::SwapContext,
+    },
+    pool_authority_signer_seeds,
+    state::{check_swaps_enabled, PoolParams, PoolToken, StablePool, WeightedPool},
+    transfers::{
+        burn_lp, gross_amount_for_net, mint_lp, require_lp_burn_authority, transfer_from_user,
+        transfer_from_vault,
+    },
+};
+
+/// Number of remaining accounts consumed by each leg:
+/// `[pool, pool_authority, lp_mint, vault_token_a, vault_token_b, user_lp, pool_params]`, where
+/// `pool_params` is only read by exits
+pub const ACCOUNTS_PER_LEG: usize = 7;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolKind {
+    Weighted,
+    Stable,
+}
+
+/// A single proportional join or exit executed as part of a rebalance
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RebalanceLeg {
+    pub pool_kind: PoolKind,
+
+    /// true = mint `lp_amount` by depositing, false = burn `lp_amount` and withdraw
+    pub is_deposit: bool,
+
+    pub lp_amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct Rebalance<'info> {
+    #[account(constraint = mint_a.key() != mint_b.key(), mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::mint = mint_a, token::token_program = token_program_a, token::authority = user)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::mint = mint_b, token::token_program = token_program_b, token::authority = user)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+/// A weighted or stable pool loaded from a remaining account
+pub(crate) enum LegPool<'info> {
+    Weighted(Box<Account<'info, WeightedPool>>),
+    Stable(Box<Account<'info, StablePool>>),
+}
+
+impl<'info> LegPool<'info> {
+    pub(crate) fn load(kind: PoolKind, info: &'info AccountInfo<'info>) -> Result<Self> {
+        Ok(match kind {
+            PoolKind::Weighted => LegPool::Weighted(Box::new(Account::try_from(info)?)),
+            PoolKind::Stable => LegPool::Stable(Box::new(Account::try_from(info)?)),
+        })
+    }
+
+    pub(crate) fn key(&self) -> Pubkey {
+        match self {
+            LegPool::Weighted(pool) => pool.key(),
+            LegPool::Stable(pool) => pool.key(),
+        }
+    }
+
+    pub(crate) fn lp_mint(&self) -> Pubkey {
+        match self {
+            LegPool::Weighted(pool) => pool.lp_mint,
+            LegPool::Stable(pool) => pool.lp_mint,
+        }
+    }
+
+    pub(crate) fn authority(&self) -> Pubkey {
+        match self {
+            LegPool::Weighted(pool) => pool.authority,
+            LegPool::Stable(pool) => pool.authority,
+        }
+    }
+
+    pub(crate) fn bump(&self) -> u8 {
+        match self {
+            LegPool::Weighted(pool) => pool.bump,
+            LegPool::Stable(pool) => pool.bump,
+        }
+    }
+
+    pub(crate) fn authority_bump(&self) -> u8 {
+        match self {
+            LegPool::Weighted(pool) => pool.authority_bump,
+            LegPool::Stable(pool) => pool.authority_bump,
+        }
+    }
+
+    /// Checks that `authority` is the pool's own `[AUTHORITY, pool]` PDA, which signs for its
+    /// vaults and LP mint
+    pub(crate) fn check_authority(&self, authority: &AccountInfo) -> Result<()> {
+        let pool_key = self.key();
+        let expected = Pubkey::create_program_address(
+            &[AUTHORITY, pool_key.as_ref(), &[self.authority_bump()]],
+            &crate::ID,
+        )
+        .map_err(|_| MiniStabbleError::InvalidAccount)?;
+
+        require_keys_eq!(authority.key(), expected, MiniStabbleError::InvalidAccount);
+
+        Ok(())
+    }
+
+    pub(crate) fn set_authority(&mut self, authority: Pubkey, authority_bump: u8) {
+        match self {
+            LegPool::Weighted(pool) => {
+                pool.authority = authority;
+                pool.authority_bump = authority_bump;
+            }
+            LegPool::Stable(pool) => {
+                pool.authority = authority;
+                pool.authority_bump = authority_bump;
+            }
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        match self {
+            LegPool::Weighted(pool) => pool.is_active,
+            LegPool::Stable(pool) => pool.is_active,
+        }
+    }
+
+    pub(crate) fn set_active(&mut self, is_active: bool) {
+        match self {
+            LegPool::Weighted(pool) => pool.is_active = is_active,
+            LegPool::Stable(pool) => pool.is_active = is_active,
+        }
+    }
+
+    pub(crate) fn has_feature(&self, flag: u64) -> bool {
+        match self {
+            LegPool::Weighted(pool) => pool.has_feature(flag),
+            LegPool::Stable(pool) => pool.has_feature(flag),
+        }
+    }
+
+    pub(crate) fn set_feature(&mut self, flag: u64, enabled: bool) {
+        match self {
+            LegPool::Weighted(pool) => pool.set_feature(flag, enabled),
+            LegPool::Stable(pool) => pool.set_feature(flag, enabled),
+        }
+    }
+
+    pub(crate) fn trader_allowlist(&self) -> bool {
+        match self {
+            LegPool::Weighted(pool) => pool.trader_allowlist,
+            LegPool::Stable(pool) => pool.trader_allowlist,
+        }
+    }
+
+    pub(crate) fn tokens(&self) -> &Vec<PoolToken> {
+        match self {
+            LegPool::Weighted(pool) => &pool.tokens,
+            LegPool::Stable(pool) => &pool.tokens,
+        }
+    }
+
+    pub(crate) fn tokens_mut(&mut self) -> &mut Vec<PoolToken> {
+        match self {
+            LegPool::Weighted(pool) => &mut pool.tokens,
+            LegPool::Stable(pool) => &mut pool.tokens,
+        }
+    }
+
+    /// Feeds the pool's current invariant per LP token to its drawdown guard, returning that
+    /// value if it trips the guard
+    pub(crate) fn trips_drawdown_guard(&mut self, lp_supply: u64, now: i64) -> Option<u128> {
+        match self {
+            LegPool::Weighted(pool) => {
+                let balances = pool.get_balances();
+                pool.trips_drawdown_guard(&balances, lp_supply)
+            }
+            LegPool::Stable(pool) => {
+                let balances = pool.get_balances();
+                pool.trips_drawdown_guard(&balances, lp_supply, now)
+            }
+        }
+    }
+
+    pub(crate) fn drawdown_high_water_mark(&self) -> u128 {
+        match self {
+            LegPool::Weighted(pool) => pool.drawdown_guard.high_water_mark,
+            LegPool::Stable(pool) => pool.drawdown_guard.high_water_mark,
+        }
+    }
+
+    /// Drops a stable pool's cached invariant; weighted pools don't cache one
+    pub(crate) fn invalidate_invariant(&mut self) {
+        if let LegPool::Stable(pool) = self {
+            pool.invalidate_invariant();
+        }
+    }
+
+    pub(crate) fn record_instant_exit(
+        &mut self,
+        pool_params: &PoolParams,
+        lp_amount: u64,
+        lp_supply: u64,
+        slot: u64,
+    ) -> bool {
+        match self {
+            LegPool::Weighted(pool) => {
+                pool.record_instant_exit(pool_params, lp_amount, lp_supply, slot)
+            }
+            LegPool::Stable(pool) => {
+                pool.record_instant_exit(pool_params, lp_amount, lp_supply, slot)
+            }
+        }
+    }
+
+    pub(crate) fn get_token_pair_indices(
+        &self,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+    ) -> Option<[usize; 2]> {
+        match self {
+            LegPool::Weighted(pool) => pool.get_token_pair_indices(mint_a, mint_b),
+            LegPool::Stable(pool) => pool.get_token_pair_indices(mint_a, mint_b),
+        }
+    }
+
+    pub(crate) fn spot_price(&self, index_in: usize, index_out: usize, now: i64) -> Result<u128> {
+        match self {
+            LegPool::Weighted(pool) => pool.spot_price(index_in, index_out),
+            LegPool::Stable(pool) => Ok(pool
+                .spot_price(index_in, index_out, now)
+                .ok_or(MiniStabbleError::MathOverflow)?),
+        }
+    }
+
+    /// Scaled amount of `tokens[index_out]` paid for `scaled_amount_in` of `tokens[index_in]`
+    /// after the swap fee, and the fee itself, priced the same way as `swap` / `stable_swap`
+    pub(crate) fn quote_swap(
+        &self,
+        index_in: usize,
+        index_out: usize,
+        scaled_amount_in: u64,
+        now: i64,
+    ) -> Result<(u64, u64)> {
+        check_swaps_enabled(self.tokens(), [index_in, index_out])?;
+        let context = match self {
+            LegPool::Weighted(pool) => pool.swap_context(index_in, index_out)?,
+            LegPool::Stable(pool) => {
+                require!(
+                    scaled_amount_in >= MIN_SWAP_AMOUNT,
+                    MiniStabbleError::AmountTooSmall
+                );
+                let amp = pool
+                    .get_current_amp(now)
+                    .ok_or(MiniStabbleError::MathOverflow)?;
+                SwapContext::stable(
+                    pool.get_balances(),
+                    amp,
+                    None,
+                    pool.swap_fee,
+                    index_in,
+                    index_out,
+                )?
+            }
+        };
+        let quote = context.calc_out_given_in(scaled_amount_in)?;
+        if let LegPool::Stable(pool) = self {
+            pool.check_max_out_ratio(index_out, quote.amount_out)?;
+        }
+
+        Ok((quote.amount_out, quote.fee))
+    }
+
+    pub(crate) fn persist(&self) -> Result<()> {
+        match self {
+            LegPool::Weighted(pool) => pool.exit(&crate::ID),
+            LegPool::Stable(pool) => pool.exit(&crate::ID),
+        }
+    }
+}
+
+/// Accounts shared by every leg of a multi-pool instruction over one token pair, in
+/// `[token A, token B]` order
+pub(crate) struct PairAccounts<'a, 'info> {
+    /// Program of the LP mints
+    pub token_program: &'a Program<'info, Token>,
+    pub token_programs: [&'a Interface<'info, TokenInterface>; 2],
+    pub user: &'a Signer<'info>,
+    pub mints: [&'a InterfaceAccount<'info, InterfaceMint>; 2],
+    pub user_tokens: [&'a InterfaceAccount<'info, InterfaceTokenAccount>; 2],
+}
+
+/// A pool touched by one leg, with its authority and its vaults for the pair, in
+/// `[token A, token B]` order
+pub(crate) struct PairPool<'info> {
+    pub pool: LegPool<'info>,
+    pub authority: AccountInfo<'info>,
+    pub indices: [usize; 2],
+    pub vaults: [InterfaceAccount<'info, InterfaceTokenAccount>; 2],
+}
+
+impl<'info> PairPool<'info> {
+    /// Loads `pool_info` as a `kind` pool and checks the authority and vaults belong to it
+    pub(crate) fn load(
+        kind: PoolKind,
+        pool_info: &'info AccountInfo<'info>,
+        authority_info: &AccountInfo<'info>,
+        vault_a_info: &'info AccountInfo<'info>,
+        vault_b_info: &'info AccountInfo<'info>,
+        pair: &PairAccounts,
+    ) -> Result<Self> {
+        let pool = LegPool::load(kind, pool_info)?;
+        pool.check_authority(authority_info)?;
+        let vaults = [
+            InterfaceAccount::<InterfaceTokenAccount>::try_from(vault_a_info)?,
+            InterfaceAccount::<InterfaceTokenAccount>::try_from(vault_b_info)?,
+        ];
+
+        let indices = pool
+            .get_token_pair_indices(&pair.mints[0].key(), &pair.mints[1].key())
+            .ok_or(MiniStabbleError::InvalidMint)?;
+        for i in 0..2 {
+            require_keys_eq!(
+                vaults[i].key(),
+                pool.tokens()[indices[i]].token_account,
+                MiniStabbleError::InvalidAccount
+            );
+        }
+
+        Ok(Self {
+            pool,
+            authority: authority_info.clone(),
+            indices,
+            vaults,
+        })
+    }
+
+    /// Scaled balances of the pair in this pool
+    pub(crate) fn balances(&self) -> [u64; 2] {
+        self.indices.map(|index| self.pool.tokens()[index].balance)
+    }
+}
+
+/// Checks that `lp_mint` and `user_lp` are the LP accounts of `pool`
+pub(crate) fn load_lp_accounts<'info>(
+    pool: &PairPool,
+    lp_mint_info: &'info AccountInfo<'info>,
+    user_lp_info: &'info AccountInfo<'info>,
+) -> Result<(Account<'info, Mint>, Account<'info, TokenAccount>)> {
+    let lp_mint = Account::<Mint>::try_from(lp_mint_info)?;
+    let user_lp = Account::<TokenAccount>::try_from(user_lp_info)?;
+
+    require_keys_eq!(
+        lp_mint.key(),
+        pool.pool.lp_mint(),
+        MiniStabbleError::InvalidAccount
+    );
+    require_keys_eq!(
+        user_lp.mint,
+        lp_mint.key(),
+        MiniStabbleError::InvalidAccount
+    );
+    require!(lp_mint.supply > 0, MiniStabbleError::InvalidAmount);
+
+    Ok((lp_mint, user_lp))
+}
+
+/// Checks that `pool_params` is the `PoolParams` of `pool`
+pub(crate) fn load_pool_params<'info>(
+    pool: &PairPool,
+    pool_params_info: &'info AccountInfo<'info>,
+) -> Result<Account<'info, PoolParams>> {
+    let pool_params = Account::<PoolParams>::try_from(pool_params_info)?;
+    require_keys_eq!(
+        pool_params.pool,
+        pool.pool.key(),
+        MiniStabbleError::InvalidAccount
+    );
+
+    Ok(pool_params)
+}
+
+/// Mints `lp_amount` to the user for a proportional deposit of the pair. Returns the scaled
+/// amounts the user sent, including any transfer fees.
+pub(crate) fn join_proportional<'info>(
+    pair: &PairAccounts<'_, 'info>,
+    pool: &mut PairPool<'info>,
+    lp_mint: &Account<'info, Mint>,
+    user_lp: &Account<'info, TokenAccount>,
+    lp_amount: u64,
+) -> Result<[u64; 2]> {
+    require_keys_eq!(
+        user_lp.owner,
+        pair.user.key(),
+        MiniStabbleError::InvalidAccount
+    );
+
+    join_proportional_to(pair, pool, lp_mint, user_lp, lp_amount)
+}
+
+/// Same as `join_proportional`, paid by the user but minting into any LP account `lp_to`
+pub(crate) fn join_proportional_to<'info>(
+    pair: &PairAccounts<'_, 'info>,
+    pool: &mut PairPool<'info>,
+    lp_mint: &Account<'info, Mint>,
+    lp_to: &Account<'info, TokenAccount>,
+    lp_amount: u64,
+) -> Result<[u64; 2]> {
+    require!(pool.pool.is_active(), MiniStabbleError::PoolInActive);
+    // A pair-only join would dilute the pool's other constituents
+    require!(pool.pool.tokens().len() == 2, MiniStabbleError::InvalidMint);
+
+    let amounts_in = calc_tokens_in_proportional(&pool.balances(), lp_amount, lp_mint.supply)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    pool.pool.invalidate_invariant();
+    let mut deposited = [0u64; 2];
+    for i in 0..2 {
+        let token = &mut pool.pool.tokens_mut()[pool.indices[i]];
+        // Round the raw transfer up so the pool is never credited more than it receives
+        let raw_amount = amounts_in[i].div_ceil(token.scaling_factor);
+        let transfer_amount = gross_amount_for_net(pair.mints[i], raw_amount)?;
+
+        transfer_from_user(
+            pair.token_programs[i],
+            pair.mints[i],
+            pair.user_tokens[i],
+            &pool.vaults[i],
+            pair.user,
+            transfer_amount,
+        )?;
+
+        token.balance = token
+            .balance
+            .checked_add(token.scale_amount_up(raw_amount))
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        deposited[i] = token.scale_amount_up(transfer_amount);
+    }
+
+    let pool_key = pool.pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.pool.authority_bump());
+    mint_lp(
+        pair.token_program,
+        lp_mint,
+        lp_to,
+        &pool.authority,
+        signer_seeds,
+        lp_amount,
+    )?;
+
+    Ok(deposited)
+}
+
+/// Burns `lp_amount` from `user_lp` and sends the user its proportional share of the pair,
+/// unless `pool_params` routes an exit that size through the withdraw queue. Returns the scaled
+/// amounts the user received, after any transfer fees.
+pub(crate) fn exit_proportional<'info>(
+    pair: &PairAccounts<'_, 'info>,
+    pool: &mut PairPool<'info>,
+    pool_params: &PoolParams,
+    lp_mint: &Account<'info, Mint>,
+    user_lp: &Account<'info, TokenAccount>,
+    lp_amount: u64,
+) -> Result<[u64; 2]> {
+    let lp_supply = lp_mint.supply;
+    require!(pool.pool.tokens().len() == 2, MiniStabbleError::InvalidMint);
+    require!(
+        !pool
+            .pool
+            .record_instant_exit(pool_params, lp_amount, lp_supply, Clock::get()?.slot),
+        MiniStabbleError::ExitRequiresWithdrawQueue
+    );
+    require_lp_burn_authority(user_lp, &pair.user.key(), lp_amount)?;
+
+    burn_lp(pair.token_program, lp_mint, user_lp, pair.user, lp_amount)?;
+
+    let amounts_out = calc_tokens_out_proportional(&pool.balances(), lp_amount, lp_supply)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    let pool_key = pool.pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.pool.authority_bump());
+    pool.pool.invalidate_invariant();
+    let mut withdrawn = [0u64; 2];
+    for i in 0..2 {
+        let token = &mut pool.pool.tokens_mut()[pool.indices[i]];
+        let raw_amount = token.scale_amount_down(amounts_out[i]);
+
+        let received = transfer_from_vault(
+            pair.token_programs[i],
+            pair.mints[i],
+            &pool.vaults[i],
+            pair.user_tokens[i],
+            &pool.authority,
+            signer_seeds,
+            raw_amount,
+        )?;
+
+        token.debit_payout(amounts_out[i], raw_amount)?;
+        withdrawn[i] = token.scale_amount_up(received);
+    }
+
+    Ok(withdrawn)
+}
+
+/// Executes proportional joins and exits across several pools of the same pair in one
+/// instruction. Legs run in order, so exits should come before the joins they fund.
+/// `min_net_value` bounds the user's net token flow valued in token B (scaled units) at the
+/// first leg's spot price; it may be negative to express a maximum cost.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Rebalance<'info>>,
+    legs: Vec<RebalanceLeg>,
+    min_net_value: i64,
+) -> Result<()> {
+    require!(!legs.is_empty(), MiniStabbleError::InvalidAmount);
+    require!(
+        ctx.remaining_accounts.len() == legs.len() * ACCOUNTS_PER_LEG,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let pair = PairAccounts {
+        token_program: &ctx.accounts.token_program,
+        token_programs: [&ctx.accounts.token_program_a, &ctx.accounts.token_program_b],
+        user: &ctx.accounts.user,
+        mints: [&ctx.accounts.mint_a, &ctx.accounts.mint_b],
+        user_tokens: [&ctx.accounts.user_token_a, &ctx.accounts.user_token_b],
+    };
+
+    // Net scaled flow from the user's perspective (positive = received)
+    let mut net_a: i128 = 0;
+    let mut net_b: i128 = 0;
+    let mut price_a_in_b: Option<u128> = None;
+
+    for (leg, accounts) in legs
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(ACCOUNTS_PER_LEG))
+    {
+        require!(leg.lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+        let mut pool = PairPool::load(
+            leg.pool_kind,
+            &accounts[0],
+            &accounts[1],
+            &accounts[3],
+            &accounts[4],
+            &pair,
+        )?;
+        let (lp_mint, user_lp) = load_lp_accounts(&pool, &accounts[2], &accounts[5])?;
+
+        if price_a_in_b.is_none() {
+            price_a_in_b = Some(
+                pool.pool
+                    .spot_price(pool.indices[0], pool.indices[1], now)?,
+            );
+        }
+
+        if leg.is_deposit {
+            let deposited = join_proportional(&pair, &mut pool, &lp_mint, &user_lp, leg.lp_amount)?;
+
+            net_a -= deposited[0] as i128;
+            net_b -= deposited[1] as i128;
+        } else {
+            let pool_params = load_pool_params(&pool, &accounts[6])?;
+            let withdrawn = exit_proportional(
+                &pair,
+                &mut pool,
+                &pool_params,
+                &lp_mint,
+                &user_lp,
+                leg.lp_amount,
+            )?;
+
+            net_a += withdrawn[0] as i128;
+            net_b += withdrawn[1] as i128;
+        }
+
+        pool.pool.persist()?;
+    }
+
+    // Single net-value slippage check across all legs, valued in token B
+    let price_a_in_b = price_a_in_b.ok_or(MiniStabbleError::MathOverflow)?;
+    check_net_value([net_a, net_b], price_a_in_b, min_net_value)
+}
+
+/// Checks the user's net scaled flow of `[token A, token B]` (positive = received), valued in
+/// token B at `price_a_in_b`, is at least `min_net_value`
+fn check_net_value(net: [i128; 2], price_a_in_b: u128, min_net_value: i64) -> Result<()> {
+    let net_value = i128::try_from(price_a_in_b)
+        .ok()
+        .and_then(|price| net[0].checked_mul(price))
+        .and_then(|v| v.checked_div(SCALE as i128))
+        .and_then(|v| v.checked_add(net[1]))
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    require!(
+        net_value >= min_net_value as i128,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_net_value_is_valued_in_token_b() {
+        // Received 10 A at 2 B each and paid 15 B: worth 5 B
+        let net = [10 * SCALE as i128, -15 * SCALE as i128];
+        let price = 2 * SCALE;
+
+        assert!(check_net_value(net, price, 5 * SCALE as i64).is_ok());
+        assert_eq!(
+            check_net_value(net, price, 5 * SCALE as i64 + 1),
+            Err(MiniStabbleError::SlippageExceeded.into())
+        );
+    }
+
+    #[test]
+    fn test_negative_min_net_value_caps_the_cost() {
+        // Paid 1 A and 1 B at parity to move liquidity: costs 2 B
+        let net = [-(SCALE as i128), -(SCALE as i128)];
+
+        assert!(check_net_value(net, SCALE, -2 * SCALE as i64).is_ok());
+        assert_eq!(
+            check_net_value(net, SCALE, -2 * SCALE as i64 + 1),
+            Err(MiniStabbleError::SlippageExceeded.into())
+        );
+    }
+
+    #[test]
+    fn test_net_value_overflow_is_rejected() {
+        assert_eq!(
+            check_net_value([i128::MAX, 0], 2 * SCALE, 0),
+            Err(MiniStabbleError::MathOverflow.into())
+        );
+        assert_eq!(
+            check_net_value([1, 0], u128::MAX, 0),
+            Err(MiniStabbleError::MathOverflow.into())
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    constants::{AUTHORITY, CREATOR_LOCK, POOL_PARAMS, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    events::CreatorLiquidityReleasedEvent,
+    pool_authority_signer_seeds,
+    state::{PoolParams, WeightedPool},
+    transfers::transfer_from_vault,
+};
+
+#[derive(Accounts)]
+pub struct ReleaseCreatorLock<'info> {
+    #[account(seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(
+        mut,
+        seeds = [POOL_PARAMS, pool.key().as_ref()],
+        bump = pool_params.bump,
+        has_one = pool,
+        constraint = pool_params.creator_lock.owner == owner.key() @ MiniStabbleError::Unauthorized,
+    )]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [CREATOR_LOCK, pool.key().as_ref()], bump, token::mint = lp_mint, token::authority = authority)]
+    pub creator_lock_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = lp_mint, token::authority = owner)]
+    pub owner_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA that owns the escrow
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Hands the creator's escrowed LP back once the lock has expired
+pub fn handler(ctx: Context<ReleaseCreatorLock>) -> Result<()> {
+    let creator_lock = &mut ctx.accounts.pool_params.creator_lock;
+    let lp_amount = creator_lock.lp_amount;
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+    require!(
+        creator_lock.is_unlocked(Clock::get()?.unix_timestamp),
+        MiniStabbleError::CreatorLockActive
+    );
+
+    let pool_key = ctx.accounts.pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, ctx.accounts.pool.authority_bump);
+
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.creator_lock_escrow,
+        &ctx.accounts.owner_lp,
+        &ctx.accounts.authority,
+        signer_seeds,
+        lp_amount,
+    )?;
+    creator_lock.lp_amount = 0;
+
+    emit!(CreatorLiquidityReleasedEvent {
+        pool: ctx.accounts.pool.key(),
+        owner: ctx.accounts.owner.key(),
+        lp_amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    constants::{AUTHORITY, CONFIG, PROTOCOL_LP},
+    errors::MiniStabbleError,
+    events::ProtocolLiquidityReleasedEvent,
+    pool_authority_signer_seeds,
+    state::Config,
+    transfers::transfer_from_vault,
+};
+
+#[derive(Accounts)]
+pub struct ReleaseProtocolLiquidity<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Pool of `lp_mint`; only its authority owns the protocol LP
+    #[account(owner = crate::ID)]
+    pub pool: UncheckedAccount<'info>,
+
+    /// CHECK: Authority PDA of `pool` that owns the protocol LP
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_LP, lp_mint.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = authority,
+    )]
+    pub protocol_lp: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = lp_mint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Moves `lp_amount` of protocol-owned LP to `destination`, from where it can be exited or
+/// redeployed like any other LP
+pub fn handler(ctx: Context<ReleaseProtocolLiquidity>, lp_amount: u64) -> Result<()> {
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    let pool_key = ctx.accounts.pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, ctx.bumps.authority);
+
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.protocol_lp,
+        &ctx.accounts.destination,
+        &ctx.accounts.authority,
+        signer_seeds,
+        lp_amount,
+    )?;
+
+    emit!(ProtocolLiquidityReleasedEvent {
+        lp_mint: ctx.accounts.lp_mint.key(),
+        destination: ctx.accounts.destination.key(),
+        lp_amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{POOL_PARAMS, TRADER_ALLOWLIST},
+    errors::MiniStabbleError,
+    state::{PoolParams, TraderAllowlistEntry},
+};
+
+#[derive(Accounts)]
+pub struct RemoveAllowlistedTrader<'info> {
+    #[account(
+        seeds = [POOL_PARAMS, pool_params.pool.as_ref()],
+        bump = pool_params.bump,
+        has_one = compliance_authority @ MiniStabbleError::Unauthorized
+    )]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(
+        mut,
+        seeds = [TRADER_ALLOWLIST, pool_params.pool.as_ref(), trader_allowlist_entry.trader.as_ref()],
+        bump = trader_allowlist_entry.bump,
+        close = compliance_authority
+    )]
+    pub trader_allowlist_entry: Account<'info, TraderAllowlistEntry>,
+
+    #[account(mut)]
+    pub compliance_authority: Signer<'info>,
+}
+
+/// Revokes a trader's access to the pool; the entry's rent goes back to the compliance authority
+pub fn handler(_ctx: Context<RemoveAllowlistedTrader>) -> Result<()> {
+    Ok(())
+}
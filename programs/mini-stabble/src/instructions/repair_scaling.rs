@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    constants::{CONFIG, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    events::ScalingRepairedEvent,
+    state::{Config, PoolToken, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct RepairScaling<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    /// Mint of the token being repaired, read for its current decimals
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault of the token being repaired
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RepairScaling>, token_index: u8, new_factor: u64) -> Result<()> {
+    let pool_key = ctx.accounts.pool.key();
+    let pool = &mut ctx.accounts.pool;
+    require!(!pool.is_active, MiniStabbleError::PoolNotPaused);
+    pool.drawdown_guard.reset();
+
+    let token = pool
+        .tokens
+        .get_mut(token_index as usize)
+        .ok_or(MiniStabbleError::InvalidAccount)?;
+
+    emit!(repair_token_scaling(
+        pool_key,
+        token_index,
+        token,
+        &ctx.accounts.mint,
+        &ctx.accounts.vault,
+        new_factor,
+    )?);
+
+    Ok(())
+}
+
+/// Moves `token` to `new_factor` and the mint's current decimals, rescaling its cached balance so
+/// it still represents the same raw amount. Shared by the weighted and stable variants, which
+/// check that the pool is paused first.
+pub fn repair_token_scaling(
+    pool: Pubkey,
+    token_index: u8,
+    token: &mut PoolToken,
+    mint: &InterfaceAccount<Mint>,
+    vault: &InterfaceAccount<TokenAccount>,
+    new_factor: u64,
+) -> Result<ScalingRepairedEvent> {
+    require_keys_eq!(mint.key(), token.mint, MiniStabbleError::InvalidMint);
+    require_keys_eq!(
+        vault.key(),
+        token.token_account,
+        MiniStabbleError::InvalidAccount
+    );
+    require!(
+        is_power_of_ten(new_factor),
+        MiniStabbleError::InvalidScalingFactor
+    );
+
+    let old_decimals = token.decimals;
+    let old_scaling_factor = token.scaling_factor;
+    let old_balance = token.balance;
+
+    token
+        .rescale(mint.decimals, new_factor)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    // The repaired balance can't claim more than the vault actually holds
+    require!(
+        token.scale_amount_down(token.balance) <= vault.amount,
+        MiniStabbleError::InvalidAmount
+    );
+
+    Ok(ScalingRepairedEvent {
+        pool,
+        mint: token.mint,
+        token_index,
+        old_decimals,
+        new_decimals: token.decimals,
+        old_scaling_factor,
+        new_scaling_factor: token.scaling_factor,
+        old_balance,
+        new_balance: token.balance,
+        vault_amount: vault.amount,
+    })
+}
+
+fn is_power_of_ten(mut value: u64) -> bool {
+    if value == 0 {
+        return false;
+    }
+    while value % 10 == 0 {
+        value /= 10;
+    }
+    value == 1
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    constants::{STABLE_POOL, VIRTUAL_PRICE_CHECKPOINT},
+    errors::MiniStabbleError,
+    events::InvariantDecreaseReportedEvent,
+    state::{StablePool, VirtualPriceCheckpoint},
+};
+
+#[derive(Accounts)]
+pub struct ReportInvariantDecrease<'info> {
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(seeds = [VIRTUAL_PRICE_CHECKPOINT, pool.key().as_ref()], bump = checkpoint.bump)]
+    pub checkpoint: Account<'info, VirtualPriceCheckpoint>,
+
+    pub reporter: Signer<'info>,
+}
+
+/// Permissionless circuit breaker: pauses the pool when its virtual price is below a recent
+/// checkpoint at the same amp by more than the tolerance. Only the admin can resume it.
+pub fn handler(ctx: Context<ReportInvariantDecrease>) -> Result<()> {
+    let clock = Clock::get()?;
+    let checkpoint = &ctx.accounts.checkpoint;
+    require!(
+        checkpoint.is_recent(clock.slot),
+        MiniStabbleError::StaleVirtualPriceCheckpoint
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    let amp = pool
+        .get_current_amp(clock.unix_timestamp)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let virtual_price = pool
+        .virtual_price(ctx.accounts.lp_mint.supply, clock.unix_timestamp)
+        .ok_or(MiniStabbleError::InvalidAmount)?;
+    require!(
+        checkpoint.proves_decrease(amp, virtual_price),
+        MiniStabbleError::InvariantDecreaseNotProven
+    );
+
+    pool.is_active = false;
+
+    emit!(InvariantDecreaseReportedEvent {
+        pool: pool.key(),
+        reporter: ctx.accounts.reporter.key(),
+        checkpoint_slot: checkpoint.slot,
+        checkpoint_virtual_price: checkpoint.virtual_price,
+        virtual_price,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    constants::{POOL_PARAMS, WEIGHT_POOL, WITHDRAW_REQUEST},
+    errors::{end_dry_run, MiniStabbleError},
+    events::WithdrawRequestEvent,
+    math::common::calc_tokens_out_proportional,
+    state::{PoolParams, QueuedToken, WeightedPool, WithdrawRequest},
+    transfers::{burn_lp, require_lp_burn_authority},
+};
+
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(
+        init,
+        seeds = [WITHDRAW_REQUEST, pool.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        space = WithdrawRequest::LEN
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+
+    /// LP source, owned by `user` or delegated to it
+    #[account(mut, token::mint = lp_mint)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RequestWithdraw>, lp_amount: u64, dry_run: bool) -> Result<()> {
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    let pool = &mut ctx.accounts.pool;
+    require!(
+        ctx.accounts.pool_params.withdraw_queue_threshold_bps > 0,
+        MiniStabbleError::WithdrawQueueDisabled
+    );
+
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(ctx.accounts.lp_mint.supply);
+
+    // Lock in the proportional share at today's balances
+    let amounts_out =
+        calc_tokens_out_proportional(&pool.get_balances(), lp_amount, ctx.accounts.lp_mint.supply)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+    require_lp_burn_authority(&ctx.accounts.user_lp, &ctx.accounts.user.key(), lp_amount)?;
+    burn_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.user,
+        lp_amount,
+    )?;
+
+    // The owed tokens stay in the vaults but no longer back the pool
+    let mut queued_tokens = Vec::with_capacity(pool.tokens.len());
+    for (token, &amount_out) in pool.tokens.iter_mut().zip(amounts_out.iter()) {
+        let amount = token.scale_amount_down(amount_out);
+        token.debit_payout(amount_out, amount)?;
+        token.queued_withdrawals = token
+            .queued_withdrawals
+            .checked_add(amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        queued_tokens.push(QueuedToken {
+            mint: token.mint,
+            amount,
+            claimed: 0,
+        });
+    }
+
+    let start_slot = Clock::get()?.slot;
+
+    let withdraw_request = &mut ctx.accounts.withdraw_request;
+    withdraw_request.owner = ctx.accounts.user.key();
+    withdraw_request.pool = pool.key();
+    withdraw_request.tokens = queued_tokens;
+    withdraw_request.start_slot = start_slot;
+    withdraw_request.end_slot = start_slot
+        .checked_add(ctx.accounts.pool_params.withdraw_queue_duration)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    withdraw_request.bump = ctx.bumps.withdraw_request;
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(ctx.accounts.lp_mint.supply - lp_amount),
+    );
+
+    emit!(WithdrawRequestEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        lp_amount,
+        amounts_out: withdraw_request
+            .tokens
+            .iter()
+            .map(|token| token.amount)
+            .collect(),
+        end_slot: withdraw_request.end_slot,
+    });
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::UPGRADE_GUARD,
+    events::UpgradeRevealedEvent,
+    pda,
+    state::{signed_keys, UpgradeGuard},
+};
+
+#[derive(Accounts)]
+pub struct RevealUpgrade<'info> {
+    #[account(mut, seeds = [UPGRADE_GUARD], bump = upgrade_guard.bump)]
+    pub upgrade_guard: Account<'info, UpgradeGuard>,
+
+    #[account(address = pda::program_data_address().0)]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+/// Acknowledges the current deploy as the committed upgrade `hash` once its timelock has
+/// passed. Keepers check the deployed binary against the hash off-chain. Remaining accounts are
+/// the approving upgrade signers, at least the guard's threshold of them.
+pub fn handler(ctx: Context<RevealUpgrade>, hash: [u8; 32]) -> Result<()> {
+    let deploy_slot = ctx.accounts.program_data.slot;
+    let upgrade_guard = &mut ctx.accounts.upgrade_guard;
+    upgrade_guard.require_quorum(&signed_keys(ctx.remaining_accounts))?;
+    upgrade_guard.reveal(hash, Clock::get()?.unix_timestamp, deploy_slot)?;
+
+    emit!(UpgradeRevealedEvent { hash, deploy_slot });
+
+    Ok(())
+}
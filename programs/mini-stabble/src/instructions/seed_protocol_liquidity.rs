@@ -0,0 +1,261 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    constants::{CONFIG, PROTOCOL_LP},
+    errors::MiniStabbleError,
+    events::DepositEvent,
+    instructions::rebalance::{join_proportional_to, PairAccounts, PairPool, PoolKind},
+    pool_authority_signer_seeds,
+    state::Config,
+    transfers::{mint_lp, transfer_from_user},
+};
+
+/// Remaining accounts: `[pool, vault_token_a, vault_token_b]`
+pub const SEED_ACCOUNTS: usize = 3;
+
+#[derive(Accounts)]
+pub struct SeedProtocolLiquidity<'info> {
+    #[account(
+        seeds = [CONFIG],
+        bump = config.bump,
+        constraint = config.treasury == treasury.key() @ MiniStabbleError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Authority PDA of the pool, checked against it in the handler; also owns the
+    /// protocol LP
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(constraint = mint_a.key() != mint_b.key(), mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::mint = mint_a, token::token_program = token_program_a, token::authority = treasury)]
+    pub treasury_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::mint = mint_b, token::token_program = token_program_b, token::authority = treasury)]
+    pub treasury_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut)]
+    pub lp_mint: Account<'info, Mint>,
+
+    /// Protocol-owned LP of the pool; only the admin can move it out
+    #[account(
+        init_if_needed,
+        seeds = [PROTOCOL_LP, lp_mint.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = authority,
+        payer = treasury,
+    )]
+    pub protocol_lp: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub treasury: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Deposits treasury tokens into a weighted or stable pool, minting the LP to the pool's
+/// protocol LP account. An empty pool takes `max_amount_a`/`max_amount_b` in full, like a first
+/// deposit; otherwise `lp_amount` is minted for a proportional deposit capped by the maximums.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SeedProtocolLiquidity<'info>>,
+    pool_kind: PoolKind,
+    lp_amount: u64,
+    max_amount_a: u64,
+    max_amount_b: u64,
+) -> Result<()> {
+    require!(
+        ctx.remaining_accounts.len() == SEED_ACCOUNTS,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let accounts = ctx.remaining_accounts;
+    let pair = PairAccounts {
+        token_program: &ctx.accounts.token_program,
+        token_programs: [&ctx.accounts.token_program_a, &ctx.accounts.token_program_b],
+        user: &ctx.accounts.treasury,
+        mints: [&ctx.accounts.mint_a, &ctx.accounts.mint_b],
+        user_tokens: [
+            &ctx.accounts.treasury_token_a,
+            &ctx.accounts.treasury_token_b,
+        ],
+    };
+
+    let mut pool = PairPool::load(
+        pool_kind,
+        &accounts[0],
+        &ctx.accounts.authority,
+        &accounts[1],
+        &accounts[2],
+        &pair,
+    )?;
+    let lp_mint = &ctx.accounts.lp_mint;
+    require_keys_eq!(
+        lp_mint.key(),
+        pool.pool.lp_mint(),
+        MiniStabbleError::InvalidAccount
+    );
+
+    let (lp_to_mint, amounts_in) = if lp_mint.supply == 0 {
+        let amounts_in = [max_amount_a, max_amount_b];
+        let lp_to_mint = join_empty_pool(
+            &pair,
+            &mut pool,
+            lp_mint,
+            &ctx.accounts.protocol_lp,
+            amounts_in,
+        )?;
+        (lp_to_mint, amounts_in)
+    } else {
+        require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+        let deposited = join_proportional_to(
+            &pair,
+            &mut pool,
+            lp_mint,
+            &ctx.accounts.protocol_lp,
+            lp_amount,
+        )?;
+        let amounts_in =
+            [0, 1].map(|i| pool.pool.tokens()[pool.indices[i]].scale_amount_down(deposited[i]));
+        (lp_amount, amounts_in)
+    };
+
+    check_max_amounts(amounts_in, [max_amount_a, max_amount_b])?;
+
+    pool.pool.persist()?;
+
+    emit!(DepositEvent {
+        pool: accounts[0].key(),
+        user: ctx.accounts.treasury.key(),
+        amount_a: amounts_in[0],
+        amount_b: amounts_in[1],
+        lp_amount: lp_to_mint,
+    });
+
+    Ok(())
+}
+
+/// First deposit into a pool with no LP outstanding: sends `amounts` in full and mints the same
+/// LP as `deposit` and `stable_deposit` would, the geometric mean of the scaled amounts received
+fn join_empty_pool<'info>(
+    pair: &PairAccounts<'_, 'info>,
+    pool: &mut PairPool<'info>,
+    lp_mint: &Account<'info, Mint>,
+    lp_to: &Account<'info, TokenAccount>,
+    amounts: [u64; 2],
+) -> Result<u64> {
+    require!(pool.pool.is_active(), MiniStabbleError::PoolInActive);
+    require!(
+        amounts.iter().all(|&amount| amount > 0),
+        MiniStabbleError::InvalidAmount
+    );
+
+    pool.pool.invalidate_invariant();
+    let mut scaled_received = [0u64; 2];
+    for i in 0..2 {
+        let received = transfer_from_user(
+            pair.token_program,
+            pair.mints[i],
+            pair.user_tokens[i],
+            &pool.vaults[i],
+            pair.user,
+            amounts[i],
+        )?;
+
+        let token = &mut pool.pool.tokens_mut()[pool.indices[i]];
+        scaled_received[i] = token.scale_amount_up(received);
+        token.balance = token
+            .balance
+            .checked_add(scaled_received[i])
+            .ok_or(MiniStabbleError::MathOverflow)?;
+    }
+
+    let lp_to_mint = initial_lp_amount(scaled_received)?;
+
+    let pool_key = pool.pool.key();
+    mint_lp(
+        pair.token_program,
+        lp_mint,
+        lp_to,
+        &pool.authority,
+        pool_authority_signer_seeds!(pool_key, pool.pool.authority_bump()),
+        lp_to_mint,
+    )?;
+
+    Ok(lp_to_mint)
+}
+
+/// LP minted for the first deposit of `scaled_amounts`: their geometric mean
+fn initial_lp_amount(scaled_amounts: [u64; 2]) -> Result<u64> {
+    let lp_amount = u64::try_from(
+        (scaled_amounts[0] as u128)
+            .checked_mul(scaled_amounts[1] as u128)
+            .ok_or(MiniStabbleError::MathOverflow)?
+            .isqrt(),
+    )?;
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    Ok(lp_amount)
+}
+
+/// Checks the treasury didn't send more than `max_amounts` raw
+fn check_max_amounts(amounts_in: [u64; 2], max_amounts: [u64; 2]) -> Result<()> {
+    require!(
+        amounts_in[0] <= max_amounts[0] && amounts_in[1] <= max_amounts[1],
+        MiniStabbleError::SlippageExceeded
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::fixed::ONE_U64;
+
+    #[test]
+    fn test_initial_lp_amount_is_the_geometric_mean() {
+        assert_eq!(
+            initial_lp_amount([100 * ONE_U64, 100 * ONE_U64]).unwrap(),
+            100 * ONE_U64
+        );
+        assert_eq!(
+            initial_lp_amount([ONE_U64, 4 * ONE_U64]).unwrap(),
+            2 * ONE_U64
+        );
+        assert_eq!(initial_lp_amount([u64::MAX, u64::MAX]).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_initial_lp_amount_rejects_a_one_sided_seed() {
+        assert_eq!(
+            initial_lp_amount([100 * ONE_U64, 0]),
+            Err(MiniStabbleError::InvalidAmount.into())
+        );
+    }
+
+    #[test]
+    fn test_max_amounts_bound_each_token() {
+        assert!(check_max_amounts([10, 20], [10, 20]).is_ok());
+        assert_eq!(
+            check_max_amounts([11, 20], [10, 20]),
+            Err(MiniStabbleError::SlippageExceeded.into())
+        );
+        assert_eq!(
+            check_max_amounts([10, 21], [10, 20]),
+            Err(MiniStabbleError::SlippageExceeded.into())
+        );
+    }
+}
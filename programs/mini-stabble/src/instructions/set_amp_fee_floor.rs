@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{fees::MAX_SWAP_FEE, CONFIG},
+    errors::MiniStabbleError,
+    state::{Config, Role},
+};
+
+#[derive(Accounts)]
+pub struct SetAmpFeeFloor<'info> {
+    #[account(mut, seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::FeeManager, fee_manager.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    /// Admin or fee manager
+    pub fee_manager: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetAmpFeeFloor>, amp_fee_floor_coefficient: u64) -> Result<()> {
+    require!(
+        amp_fee_floor_coefficient <= MAX_SWAP_FEE,
+        MiniStabbleError::InvalidAmount
+    );
+
+    ctx.accounts.config.amp_fee_floor_coefficient = amp_fee_floor_coefficient;
+
+    Ok(())
+}
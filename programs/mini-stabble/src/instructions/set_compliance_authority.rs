@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, POOL_PARAMS, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    state::{Config, PoolParams, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct SetComplianceAuthority<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Makes the pool permissioned, with `compliance_authority` managing who may swap; the default
+/// key opens it to every trader again
+pub fn handler(ctx: Context<SetComplianceAuthority>, compliance_authority: Pubkey) -> Result<()> {
+    ctx.accounts.pool_params.compliance_authority = compliance_authority;
+    ctx.accounts.pool.trader_allowlist = compliance_authority != Pubkey::default();
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, POOL_PARAMS},
+    errors::MiniStabbleError,
+    state::{Config, PoolParams, MAX_CRANK_KEEPERS},
+};
+
+#[derive(Accounts)]
+pub struct SetCrankKeeper<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool_params.pool.as_ref()], bump = pool_params.bump)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Registers `keeper` as one of the pool's crank keepers, or removes it when `registered` is
+/// false. The cranks stay permissionless; keepers only get their shorter intervals.
+pub fn handler(ctx: Context<SetCrankKeeper>, keeper: Pubkey, registered: bool) -> Result<()> {
+    let crank_keepers = &mut ctx.accounts.pool_params.crank_keepers;
+    crank_keepers.retain(|crank_keeper| *crank_keeper != keeper);
+
+    if registered {
+        require!(
+            crank_keepers.len() < MAX_CRANK_KEEPERS,
+            MiniStabbleError::TooManyCrankKeepers
+        );
+        crank_keepers.push(keeper);
+    }
+
+    Ok(())
+}
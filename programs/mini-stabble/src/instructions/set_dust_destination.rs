@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, POOL_PARAMS},
+    errors::MiniStabbleError,
+    state::{Config, DustDestination, PoolParams, Role},
+};
+
+#[derive(Accounts)]
+pub struct SetDustDestination<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::FeeManager, fee_manager.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool_params.pool.as_ref()], bump = pool_params.bump)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    pub fee_manager: Signer<'info>,
+}
+
+/// Sets who the pool's rounding dust is swept to; dust already booked follows the new setting
+pub fn handler(ctx: Context<SetDustDestination>, destination: DustDestination) -> Result<()> {
+    ctx.accounts.pool_params.dust_destination = destination;
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, POOL_PARAMS, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    state::{
+        is_governance_pool_weights, validate_fee_autopilot, Config, FeeAutopilot, PoolParams, Role,
+        WeightedPool,
+    },
+};
+
+#[derive(Accounts)]
+pub struct SetFeeAutopilot<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::FeeManager, fee_manager.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    /// Admin or fee manager
+    pub fee_manager: Signer<'info>,
+}
+
+/// Puts an 80/20 governance pool on the fee autopilot, starting at `min_fee` with the current
+/// spot price as the first sample; `max_fee` = 0 turns it off and keeps the current fee
+pub fn handler(
+    ctx: Context<SetFeeAutopilot>,
+    min_fee: u64,
+    max_fee: u64,
+    max_volatility_bps: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let pool_params = &mut ctx.accounts.pool_params;
+
+    if max_fee == 0 {
+        pool_params.fee_autopilot = FeeAutopilot::default();
+        return Ok(());
+    }
+
+    require!(
+        is_governance_pool_weights(&pool.get_weights()),
+        MiniStabbleError::NotGovernancePool
+    );
+    validate_fee_autopilot(min_fee, max_fee, max_volatility_bps)?;
+
+    pool_params.fee_autopilot = FeeAutopilot {
+        min_fee,
+        max_fee,
+        max_volatility_bps,
+        last_price: pool.spot_price(0, 1).unwrap_or_default(),
+        last_update_ts: Clock::get()?.unix_timestamp,
+        volatility_bps: 0,
+    };
+    pool.swap_fee = min_fee;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{BPS_DENOMINATOR, CONFIG, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    state::{Config, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct SetMaxSpotPriceMove<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Caps how far one swap may move the pool's spot price, as crude manipulation protection for
+/// pools without an oracle; 0 removes the cap
+pub fn handler(
+    ctx: Context<SetMaxSpotPriceMove>,
+    max_spot_price_move_per_swap_bps: u16,
+) -> Result<()> {
+    require!(
+        max_spot_price_move_per_swap_bps as u64 <= BPS_DENOMINATOR,
+        MiniStabbleError::InvalidAmount
+    );
+
+    ctx.accounts.pool.max_spot_price_move_per_swap_bps = max_spot_price_move_per_swap_bps;
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, PEG_KEEPER},
+    errors::MiniStabbleError,
+    state::{validate_peg_keeper, Config, PegKeeper},
+};
+
+#[derive(Accounts)]
+pub struct SetPegKeeper<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [PEG_KEEPER, peg_keeper.pool.as_ref()], bump = peg_keeper.bump)]
+    pub peg_keeper: Account<'info, PegKeeper>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Retunes a peg keeper. A ceiling below the current debt stops new mints but still lets the
+/// keeper withdraw what it already provided.
+pub fn handler(
+    ctx: Context<SetPegKeeper>,
+    debt_ceiling: u64,
+    deviation_threshold_bps: u16,
+    max_amount_per_action: u64,
+) -> Result<()> {
+    validate_peg_keeper(deviation_threshold_bps, max_amount_per_action)?;
+
+    let peg_keeper = &mut ctx.accounts.peg_keeper;
+    peg_keeper.debt_ceiling = debt_ceiling;
+    peg_keeper.deviation_threshold_bps = deviation_threshold_bps;
+    peg_keeper.max_amount_per_action = max_amount_per_action;
+
+    Ok(())
+}
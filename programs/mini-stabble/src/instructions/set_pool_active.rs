@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    state::{Config, Role, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct SetPoolActive<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::Pauser, pauser.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    /// Admin or pauser
+    pub pauser: Signer<'info>,
+}
+
+/// Pauses (`false`) or resumes (`true`) swaps and deposits on the pool. The pauser can only
+/// pause; resuming takes the admin.
+pub fn handler(ctx: Context<SetPoolActive>, is_active: bool) -> Result<()> {
+    require!(
+        !is_active || ctx.accounts.pauser.key() == ctx.accounts.config.admin,
+        MiniStabbleError::Unauthorized
+    );
+
+    ctx.accounts.pool.is_active = is_active;
+
+    Ok(())
+}
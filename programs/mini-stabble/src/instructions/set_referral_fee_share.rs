@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{BPS_DENOMINATOR, CONFIG, POOL_PARAMS},
+    errors::MiniStabbleError,
+    state::{Config, PoolParams, Role},
+};
+
+#[derive(Accounts)]
+pub struct SetReferralFeeShare<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::FeeManager, fee_manager.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool_params.pool.as_ref()], bump = pool_params.bump)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    /// Admin or fee manager
+    pub fee_manager: Signer<'info>,
+}
+
+/// Sets the share of referred LPs' fee earnings paid to their referrers; applies to fees
+/// accrued from the next deposit or claim on
+pub fn handler(ctx: Context<SetReferralFeeShare>, fee_share_bps: u16) -> Result<()> {
+    require!(
+        fee_share_bps as u64 <= BPS_DENOMINATOR,
+        MiniStabbleError::InvalidAmount
+    );
+
+    ctx.accounts.pool_params.referral_fee_share_bps = fee_share_bps;
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::CONFIG,
+    errors::MiniStabbleError,
+    state::{Config, Role},
+};
+
+#[derive(Accounts)]
+pub struct SetRole<'info> {
+    #[account(mut, seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Assigns `role` to `holder`; `Pubkey::default()` leaves it to the admin alone
+pub fn handler(ctx: Context<SetRole>, role: Role, holder: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    match role {
+        Role::FeeManager => config.fee_manager = holder,
+        Role::Pauser => config.pauser = holder,
+        Role::Keeper => config.keeper = holder,
+    }
+
+    Ok(())
+}
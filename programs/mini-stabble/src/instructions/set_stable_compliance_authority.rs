@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, POOL_PARAMS, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{Config, PoolParams, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct SetStableComplianceAuthority<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    pub admin: Signer<'info>,
+}
+
+/// `set_compliance_authority` for stable pools
+pub fn handler(
+    ctx: Context<SetStableComplianceAuthority>,
+    compliance_authority: Pubkey,
+) -> Result<()> {
+    ctx.accounts.pool_params.compliance_authority = compliance_authority;
+    ctx.accounts.pool.trader_allowlist = compliance_authority != Pubkey::default();
+
+    Ok(())
+}
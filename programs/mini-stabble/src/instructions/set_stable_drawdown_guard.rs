@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{Config, Role, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct SetStableDrawdownGuard<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::Keeper, keeper.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    /// Admin or keeper
+    pub keeper: Signer<'info>,
+}
+
+/// Arms the drawdown guard at `max_drawdown_bps`. The admin can also disable it (0), loosen it
+/// and reset its high-water mark, which is how it is re-armed after it paused the pool; a keeper
+/// can only tighten it.
+pub fn handler(ctx: Context<SetStableDrawdownGuard>, max_drawdown_bps: u16) -> Result<()> {
+    let is_admin = ctx.accounts.keeper.key() == ctx.accounts.config.admin;
+    ctx.accounts
+        .pool
+        .drawdown_guard
+        .set(max_drawdown_bps, is_admin)
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{Config, InvariantCachePolicy, Role, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct SetStableInvariantCachePolicy<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::Keeper, keeper.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    /// Admin or keeper
+    pub keeper: Signer<'info>,
+}
+
+/// Switches how swaps obtain the invariant; the next swap recomputes it either way
+pub fn handler(
+    ctx: Context<SetStableInvariantCachePolicy>,
+    policy: InvariantCachePolicy,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.invariant_cache_policy = policy;
+    pool.invalidate_invariant();
+
+    Ok(())
+}
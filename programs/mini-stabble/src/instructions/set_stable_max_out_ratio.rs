@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{BPS_DENOMINATOR, CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{Config, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct SetStableMaxOutRatio<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Caps a single swap's output at `max_out_ratio_bps` of that token's balance; 0 removes the cap
+pub fn handler(ctx: Context<SetStableMaxOutRatio>, max_out_ratio_bps: u16) -> Result<()> {
+    require!(
+        max_out_ratio_bps as u64 <= BPS_DENOMINATOR,
+        MiniStabbleError::InvalidAmount
+    );
+
+    ctx.accounts.pool.max_out_ratio_bps = max_out_ratio_bps;
+
+    Ok(())
+}
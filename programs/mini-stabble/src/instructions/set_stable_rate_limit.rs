@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{validate_swap_rate_limit, Config, StablePool, SwapRateLimit},
+};
+
+#[derive(Accounts)]
+pub struct SetStableRateLimit<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Replaces the pool's swap rate limit; the next swap opens a fresh window
+pub fn handler(
+    ctx: Context<SetStableRateLimit>,
+    window_slots: u64,
+    max_outflow_bps: u16,
+) -> Result<()> {
+    validate_swap_rate_limit(window_slots, max_outflow_bps)?;
+
+    ctx.accounts.pool.swap_rate_limit = SwapRateLimit {
+        window_slots,
+        max_outflow_bps,
+        ..Default::default()
+    };
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{validate_rebate_fee_share, Config, Role, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct SetStableRebateFeeShare<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::FeeManager, fee_manager.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    /// Admin or fee manager
+    pub fee_manager: Signer<'info>,
+}
+
+/// Sets the share of swap fees set aside for trader rebates; 0 stops new accrual while
+/// already accrued rebates stay claimable
+pub fn handler(ctx: Context<SetStableRebateFeeShare>, fee_share_bps: u16) -> Result<()> {
+    validate_rebate_fee_share(fee_share_bps)?;
+
+    ctx.accounts.pool.rebate.fee_share_bps = fee_share_bps;
+
+    Ok(())
+}
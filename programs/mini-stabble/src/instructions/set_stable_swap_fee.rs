@@ -0,0 +1,61 @@
+use std::cmp::min;
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{fees::MAX_SWAP_FEE, CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    math::stable::calc_min_fee_for_amp,
+    state::{Config, Role, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct SetStableSwapFee<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::FeeManager, fee_manager.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    /// Admin or fee manager
+    pub fee_manager: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetStableSwapFee>, swap_fee: u64) -> Result<()> {
+    apply_stable_swap_fee(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.config,
+        swap_fee,
+        Clock::get()?.unix_timestamp,
+    )
+}
+
+/// Sets `pool`'s swap fee, which must stay above the amp fee floor throughout any active ramp
+pub fn apply_stable_swap_fee(
+    pool: &mut StablePool,
+    config: &Config,
+    swap_fee: u64,
+    now: i64,
+) -> Result<()> {
+    require!(swap_fee <= MAX_SWAP_FEE, MiniStabbleError::InvalidAmount);
+
+    let current_amp = pool
+        .get_current_amp(now)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    // The floor is highest at the lowest amp the pool will see during any active ramp
+    require_fee_above_amp_floor(swap_fee, min(current_amp, pool.amp_target), config)?;
+
+    pool.swap_fee = swap_fee;
+
+    Ok(())
+}
+
+pub fn require_fee_above_amp_floor(swap_fee: u64, amp: u64, config: &Config) -> Result<()> {
+    let min_fee = calc_min_fee_for_amp(amp, config.amp_fee_floor_coefficient)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    require!(swap_fee >= min_fee, MiniStabbleError::FeeBelowAmpFloor);
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{Config, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct SetStableTokenSwapsDisabled<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Stable counterpart of `set_token_swaps_disabled`
+pub fn handler(
+    ctx: Context<SetStableTokenSwapsDisabled>,
+    token_index: u8,
+    swaps_disabled: bool,
+) -> Result<()> {
+    ctx.accounts
+        .pool
+        .tokens
+        .get_mut(token_index as usize)
+        .ok_or(MiniStabbleError::InvalidAccount)?
+        .swaps_disabled = swaps_disabled;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, POOL_PARAMS, STABLE_POOL},
+    errors::MiniStabbleError,
+    instructions::set_withdraw_queue::validate_withdraw_queue,
+    state::{Config, PoolParams, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct SetStableWithdrawQueue<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(
+        mut,
+        seeds = [POOL_PARAMS, pool.key().as_ref()],
+        bump = pool_params.bump,
+        has_one = pool,
+    )]
+    pub pool_params: Account<'info, PoolParams>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SetStableWithdrawQueue>,
+    threshold_bps: u16,
+    duration: u64,
+) -> Result<()> {
+    validate_withdraw_queue(threshold_bps, duration)?;
+
+    let pool_params = &mut ctx.accounts.pool_params;
+    pool_params.withdraw_queue_threshold_bps = threshold_bps;
+    pool_params.withdraw_queue_duration = duration;
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    state::{Config, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct SetTokenSwapsDisabled<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Withdraw-only mode for one token: blocks swaps into and out of `tokens[token_index]` while
+/// the rest of the pool keeps trading and withdrawals stay open
+pub fn handler(
+    ctx: Context<SetTokenSwapsDisabled>,
+    token_index: u8,
+    swaps_disabled: bool,
+) -> Result<()> {
+    ctx.accounts
+        .pool
+        .tokens
+        .get_mut(token_index as usize)
+        .ok_or(MiniStabbleError::InvalidAccount)?
+        .swaps_disabled = swaps_disabled;
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::CONFIG, errors::MiniStabbleError, state::Config};
+
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    #[account(mut, seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Sets the key allowed to seed protocol-owned liquidity; `Pubkey::default()` disables seeding
+pub fn handler(ctx: Context<SetTreasury>, treasury: Pubkey) -> Result<()> {
+    ctx.accounts.config.treasury = treasury;
+
+    Ok(())
+}
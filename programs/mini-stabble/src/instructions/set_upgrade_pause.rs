@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::CONFIG,
+    errors::MiniStabbleError,
+    instructions::rebalance::{LegPool, PoolKind},
+    state::{Config, FEATURE_PAUSE_ON_UNACKNOWLEDGED_UPGRADE},
+};
+
+#[derive(Accounts)]
+pub struct SetUpgradePause<'info> {
+    /// Pool passed as the only remaining account
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Opts the pool in or out of being paused by anyone after a deploy the upgrade guard hasn't
+/// acknowledged
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SetUpgradePause<'info>>,
+    pool_kind: PoolKind,
+    enabled: bool,
+) -> Result<()> {
+    let [pool_info] = ctx.remaining_accounts else {
+        return err!(MiniStabbleError::InvalidAccount);
+    };
+    require!(pool_info.is_writable, MiniStabbleError::InvalidAccount);
+
+    let mut pool = LegPool::load(pool_kind, pool_info)?;
+    pool.set_feature(FEATURE_PAUSE_ON_UNACKNOWLEDGED_UPGRADE, enabled);
+    pool.persist()
+}
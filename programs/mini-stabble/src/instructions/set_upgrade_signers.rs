@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::UPGRADE_GUARD,
+    state::{signed_keys, UpgradeGuard},
+};
+
+#[derive(Accounts)]
+pub struct SetUpgradeSigners<'info> {
+    #[account(mut, seeds = [UPGRADE_GUARD], bump = upgrade_guard.bump)]
+    pub upgrade_guard: Account<'info, UpgradeGuard>,
+}
+
+/// Replaces the upgrade signer set with `threshold` of `signers`. Remaining accounts are the
+/// approving signers of the current set, at least its threshold of them.
+pub fn handler(ctx: Context<SetUpgradeSigners>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+    let upgrade_guard = &mut ctx.accounts.upgrade_guard;
+    upgrade_guard.require_quorum(&signed_keys(ctx.remaining_accounts))?;
+    upgrade_guard.set_signers(signers, threshold)
+}
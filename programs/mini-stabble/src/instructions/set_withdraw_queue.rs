@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{BPS_DENOMINATOR, CONFIG, POOL_PARAMS, WEIGHT_POOL},
+    errors::MiniStabbleError,
+    state::{Config, PoolParams, WeightedPool},
+};
+
+#[derive(Accounts)]
+pub struct SetWithdrawQueue<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(
+        mut,
+        seeds = [POOL_PARAMS, pool.key().as_ref()],
+        bump = pool_params.bump,
+        has_one = pool,
+    )]
+    pub pool_params: Account<'info, PoolParams>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetWithdrawQueue>, threshold_bps: u16, duration: u64) -> Result<()> {
+    validate_withdraw_queue(threshold_bps, duration)?;
+
+    let pool_params = &mut ctx.accounts.pool_params;
+    pool_params.withdraw_queue_threshold_bps = threshold_bps;
+    pool_params.withdraw_queue_duration = duration;
+
+    Ok(())
+}
+
+/// A threshold of 0 disables the queue, otherwise it must be a valid share and unlock over at
+/// least one slot
+pub fn validate_withdraw_queue(threshold_bps: u16, duration: u64) -> Result<()> {
+    require!(
+        threshold_bps as u64 <= BPS_DENOMINATOR,
+        MiniStabbleError::InvalidWithdrawQueueConfig
+    );
+    require!(
+        threshold_bps == 0 || duration > 0,
+        MiniStabbleError::InvalidWithdrawQueueConfig
+    );
+
+    Ok(())
+}
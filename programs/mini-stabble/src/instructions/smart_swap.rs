@@ -0,0 +1,226 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Mint, Token},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    errors::MiniStabbleError,
+    instructions::{
+        compound_operation::swap_exact_in,
+        rebalance::{PairAccounts, PairPool, PoolKind},
+    },
+    transfers::transfer_fee,
+};
+
+/// Number of equal parts `smart_swap` considers splitting the input into
+pub const SMART_SWAP_SPLIT_PARTS: u64 = 4;
+
+/// Returned by `smart_swap`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SmartSwapResult {
+    /// Raw input routed through the weighted and the stable pool
+    pub weighted_amount_in: u64,
+    pub stable_amount_in: u64,
+
+    /// Raw amount the user received across both pools
+    pub amount_out: u64,
+}
+
+#[derive(Accounts)]
+pub struct SmartSwap<'info> {
+    #[account(constraint = mint_a.key() != mint_b.key(), mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::mint = mint_a, token::token_program = token_program_a, token::authority = user)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::mint = mint_b, token::token_program = token_program_b, token::authority = user)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+/// Swaps `amount_in` of token A (`a_to_b`) or token B through the pair's weighted and stable
+/// pools, given as remaining accounts `[pool, pool_authority, lp_mint, vault_a, vault_b]` for the
+/// weighted pool then the stable pool. Quotes every split of the input in quarters between the
+/// two and executes the one with the best total output.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SmartSwap<'info>>,
+    a_to_b: bool,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<SmartSwapResult> {
+    require!(amount_in > 0, MiniStabbleError::InvalidAmount);
+    require!(
+        ctx.remaining_accounts.len() == 10,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let clock = Clock::get()?;
+    let pair = PairAccounts {
+        token_program: &ctx.accounts.token_program,
+        token_programs: [&ctx.accounts.token_program_a, &ctx.accounts.token_program_b],
+        user: &ctx.accounts.user,
+        mints: [&ctx.accounts.mint_a, &ctx.accounts.mint_b],
+        user_tokens: [&ctx.accounts.user_token_a, &ctx.accounts.user_token_b],
+    };
+    let (side_in, side_out) = if a_to_b { (0, 1) } else { (1, 0) };
+
+    let mut pools = Vec::with_capacity(2);
+    for (pool_kind, accounts) in [PoolKind::Weighted, PoolKind::Stable]
+        .into_iter()
+        .zip(ctx.remaining_accounts.chunks(5))
+    {
+        let pool = PairPool::load(
+            pool_kind,
+            &accounts[0],
+            &accounts[1],
+            &accounts[3],
+            &accounts[4],
+            &pair,
+        )?;
+        let lp_mint = Account::<Mint>::try_from(&accounts[2])?;
+        require_keys_eq!(
+            lp_mint.key(),
+            pool.pool.lp_mint(),
+            MiniStabbleError::InvalidAccount
+        );
+        pools.push((pool, lp_mint.supply));
+    }
+
+    // Raw amount out of `pool` for `amount` raw in, or None if it can't take the trade
+    let quote = |(pool, _): &(PairPool, u64), amount: u64| -> Option<u64> {
+        if amount == 0 {
+            return Some(0);
+        }
+        if !pool.pool.is_active() || pool.pool.trader_allowlist() {
+            return None;
+        }
+
+        let token_in = &pool.pool.tokens()[pool.indices[side_in]];
+        let token_out = &pool.pool.tokens()[pool.indices[side_out]];
+        let amount_net = amount.checked_sub(transfer_fee(pair.mints[side_in], amount).ok()?)?;
+        let (scaled_amount_out, _) = pool
+            .pool
+            .quote_swap(
+                pool.indices[side_in],
+                pool.indices[side_out],
+                token_in.scale_amount_up(amount_net),
+                clock.unix_timestamp,
+            )
+            .ok()?;
+
+        Some(token_out.scale_amount_down(scaled_amount_out)).filter(|&out| out > 0)
+    };
+
+    let weighted_amount_in = best_split(amount_in, |weighted_amount, stable_amount| {
+        quote(&pools[0], weighted_amount)?.checked_add(quote(&pools[1], stable_amount)?)
+    })?;
+    let stable_amount_in = amount_in - weighted_amount_in;
+
+    let mut amount_out = 0u64;
+    for ((pool, lp_supply), leg_amount_in) in
+        pools.iter_mut().zip([weighted_amount_in, stable_amount_in])
+    {
+        if leg_amount_in == 0 {
+            continue;
+        }
+
+        let received = swap_exact_in(
+            &pair,
+            pool,
+            side_in,
+            side_out,
+            leg_amount_in,
+            *lp_supply,
+            clock.slot,
+            clock.unix_timestamp,
+        )?;
+        amount_out = amount_out
+            .checked_add(received)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        pool.pool.persist()?;
+    }
+
+    require!(
+        amount_out >= min_amount_out,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    Ok(SmartSwapResult {
+        weighted_amount_in,
+        stable_amount_in,
+        amount_out,
+    })
+}
+
+/// Part of `amount_in` to route through the weighted pool, the rest going to the stable pool:
+/// the split in `SMART_SWAP_SPLIT_PARTS` parts with the best total output. `quote` gives the
+/// total output of a `(weighted, stable)` split, or None if either pool can't take its part.
+fn best_split(amount_in: u64, quote: impl Fn(u64, u64) -> Option<u64>) -> Result<u64> {
+    let (weighted_amount_in, _) = (0..=SMART_SWAP_SPLIT_PARTS)
+        .filter_map(|parts| {
+            let weighted_amount =
+                u64::try_from(amount_in as u128 * parts as u128 / SMART_SWAP_SPLIT_PARTS as u128)
+                    .ok()?;
+            let amount_out = quote(weighted_amount, amount_in - weighted_amount)?;
+            Some((weighted_amount, amount_out))
+        })
+        .max_by_key(|&(_, amount_out)| amount_out)
+        .ok_or(MiniStabbleError::InvalidAmount)?;
+
+    Ok(weighted_amount_in)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Constant-product output of `amount_in` into `reserve`-deep pools
+    fn constant_product(reserve: u64, amount_in: u64) -> u64 {
+        (reserve as u128 * amount_in as u128 / (reserve as u128 + amount_in as u128)) as u64
+    }
+
+    #[test]
+    fn test_best_split_routes_everything_to_the_better_pool() {
+        let split = best_split(1_000, |weighted, stable| Some(weighted / 2 + stable));
+        assert_eq!(split.unwrap(), 0);
+
+        let split = best_split(1_000, |weighted, stable| Some(weighted + stable / 2));
+        assert_eq!(split.unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_best_split_splits_between_equal_depth_pools() {
+        // Price impact makes halving the trade across two identical pools the best split
+        let split = best_split(1_000_000, |weighted, stable| {
+            Some(constant_product(1_000_000, weighted) + constant_product(1_000_000, stable))
+        });
+        assert_eq!(split.unwrap(), 500_000);
+    }
+
+    #[test]
+    fn test_best_split_skips_a_pool_that_cant_trade() {
+        // The stable pool is paused: only the all-weighted split quotes
+        let split = best_split(1_000, |weighted, stable| (stable == 0).then_some(weighted));
+        assert_eq!(split.unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_best_split_rejects_when_no_split_quotes() {
+        assert_eq!(
+            best_split(1_000, |_, _| None),
+            Err(MiniStabbleError::InvalidAmount.into())
+        );
+    }
+}
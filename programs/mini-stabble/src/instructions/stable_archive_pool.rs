@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    constants::{CONFIG, POOL_PARAMS, STABLE_POOL},
+    errors::MiniStabbleError,
+    instructions::archive_pool::archive,
+    state::{Config, PoolParams, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct StableArchivePool<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Stable pool, deserialized in the handler since it is rewritten as a tombstone
+    #[account(mut, owner = crate::ID, seeds = [STABLE_POOL, lp_mint.key().as_ref()], bump)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, close = rent_recipient)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    /// CHECK: Receives the reclaimed rent
+    #[account(mut)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Archives a paused stable pool with no LP left: emits its final state and shrinks the
+/// account to an `ArchivedPool` tombstone, sending the freed rent to `rent_recipient`
+pub fn handler(ctx: Context<StableArchivePool>) -> Result<()> {
+    let pool = StablePool::try_deserialize(&mut &ctx.accounts.pool.try_borrow_data()?[..])?;
+    require!(!pool.is_active, MiniStabbleError::PoolStillActive);
+    require!(
+        ctx.accounts.lp_mint.supply == 0,
+        MiniStabbleError::PoolNotEmpty
+    );
+    // Internal balances, queued withdrawals and referral rewards are still paid out through
+    // the pool
+    require!(
+        pool.tokens.iter().all(|token| token.internal_balances == 0
+            && token.queued_withdrawals == 0
+            && token.referral_rewards == 0),
+        MiniStabbleError::PoolNotEmpty
+    );
+
+    archive(
+        &ctx.accounts.pool,
+        &ctx.accounts.rent_recipient,
+        ctx.accounts.lp_mint.key(),
+    )
+}
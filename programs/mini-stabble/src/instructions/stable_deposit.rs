@@ -1,19 +1,26 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, MintTo, Token, TokenAccount, Transfer},
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
 };
 
 use crate::{
-    constants::{AUTHORITY, POOL_VAULT, STABLE_POOL},
-    errors::MiniStabbleError,
-    state::StablePool,
+    constants::{AUTHORITY, CREATOR_LOCK, LP_POSITION, POOL_PARAMS, POOL_VAULT, STABLE_POOL},
+    errors::{end_dry_run, MiniStabbleError},
+    events::{CreatorLiquidityLockedEvent, DepositEvent},
+    math::common::calc_tokens_in_proportional,
+    pool_authority_signer_seeds,
+    state::{LpPosition, PoolParams, StablePool},
+    transfers::{gross_amount_for_net, mint_lp, transfer_fee, transfer_from_user},
 };
 
 #[derive(Accounts)]
 pub struct StableDeposit<'info> {
     /// CHECK: Unchecked
-    #[account(seeds = [AUTHORITY], bump)]
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
     pub authority: UncheckedAccount<'info>,
 
     /// Pool - derived from LP mint
@@ -24,38 +31,67 @@ pub struct StableDeposit<'info> {
     )]
     pub pool: Account<'info, StablePool>,
 
-    #[account(constraint = mint_a.key() != mint_b.key())]
-    pub mint_a: Account<'info, Mint>,
-    pub mint_b: Account<'info, Mint>,
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(constraint = mint_a.key() != mint_b.key(), mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, InterfaceMint>,
 
     #[account(mut, constraint = lp_mint.key() == pool.lp_mint)]
     pub lp_mint: Account<'info, Mint>,
 
-    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_a.key().as_ref()], bump, token::mint = mint_a, token::authority = authority)]
-    pub vault_token_a: Account<'info, TokenAccount>,
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_a.key().as_ref()], bump, token::mint = mint_a, token::token_program = token_program_a, token::authority = authority)]
+    pub vault_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
 
-    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_b.key().as_ref()], bump, token::mint = mint_b, token::authority = authority)]
-    pub vault_token_b: Account<'info, TokenAccount>,
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_b.key().as_ref()], bump, token::mint = mint_b, token::token_program = token_program_b, token::authority = authority)]
+    pub vault_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
 
-    #[account(mut, token::mint = mint_a, token::authority = user)]
-    pub user_token_a: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint_a, token::token_program = token_program_a, token::authority = user)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
 
-    #[account(mut, token::mint = mint_b, token::authority = user)]
-    pub user_token_b: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint_b, token::token_program = token_program_b, token::authority = user)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
 
     #[account(init_if_needed, associated_token::mint = lp_mint, associated_token::authority = user, payer = user)]
     pub user_lp: Account<'info, TokenAccount>,
 
+    /// Escrow for the first deposit's LP, required when the pool has a creator lock
+    #[account(init, seeds = [CREATOR_LOCK, pool.key().as_ref()], bump, payer = user, token::mint = lp_mint, token::authority = authority)]
+    pub creator_lock_escrow: Option<Account<'info, TokenAccount>>,
+
+    /// Attributes the LP minted to the position's referrer when passed
+    #[account(mut, seeds = [LP_POSITION, pool.key().as_ref(), user.key().as_ref()], bump = lp_position.bump)]
+    pub lp_position: Option<Account<'info, LpPosition>>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 pub fn handler(
-    ctx: Context<StableDeposit>,
+    mut ctx: Context<StableDeposit>,
+    max_amount_a: u64,
+    max_amount_b: u64,
+    lp_amount: u64,
+    dry_run: bool,
+) -> Result<()> {
+    execute(&mut ctx, max_amount_a, max_amount_b, lp_amount)?;
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
+
+/// The deposit itself, for instructions that do more in the same transaction
+pub(crate) fn execute(
+    ctx: &mut Context<StableDeposit>,
     max_amount_a: u64,
     max_amount_b: u64,
     lp_amount: u64,
@@ -65,95 +101,144 @@ pub fn handler(
 
     let pool = &mut ctx.accounts.pool;
     require!(pool.is_active, MiniStabbleError::PoolInActive);
+    // Pools of more than two constituents join through `stable_deposit_multi`
+    require!(pool.tokens.len() == 2, MiniStabbleError::InvalidMint);
 
     let lp_mint = &ctx.accounts.lp_mint;
+
+    #[cfg(feature = "strict-checks")]
+    let now = Clock::get()?.unix_timestamp;
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_mint.supply, now);
     let token_a_mint = &ctx.accounts.mint_a;
     let token_b_mint = &ctx.accounts.mint_b;
-    let token_a_index = pool
-        .get_token_index(&token_a_mint.key())
+    let [token_a_index, token_b_index] = pool
+        .get_token_pair_indices(&token_a_mint.key(), &token_b_mint.key())
         .ok_or(MiniStabbleError::InvalidMint)?;
-    let token_b_index = pool
-        .get_token_index(&token_b_mint.key())
-        .ok_or(MiniStabbleError::InvalidMint)?;
-    let scaled_max_amount_a = pool.tokens[token_a_index].scale_amount_up(max_amount_a);
-    let scaled_max_amount_b = pool.tokens[token_b_index].scale_amount_up(max_amount_b);
 
     let (lp_to_mint, actual_amount_a_to_deposit, actual_amount_b_to_deposit) =
         if lp_mint.supply == 0 {
-            let lp = ((scaled_max_amount_a as u128)
-                .checked_mul(scaled_max_amount_b as u128)
+            ctx.accounts
+                .pool_params
+                .check_first_depositor(&ctx.accounts.user.key())?;
+            // Credit only what reaches the vaults after any transfer fee
+            let scaled_amount_a = pool.tokens[token_a_index]
+                .scale_amount_up(max_amount_a - transfer_fee(token_a_mint, max_amount_a)?);
+            let scaled_amount_b = pool.tokens[token_b_index]
+                .scale_amount_up(max_amount_b - transfer_fee(token_b_mint, max_amount_b)?);
+
+            let lp = ((scaled_amount_a as u128)
+                .checked_mul(scaled_amount_b as u128)
                 .ok_or(MiniStabbleError::MathOverflow)?)
             .isqrt();
-            (u64::try_from(lp)?, scaled_max_amount_a, scaled_max_amount_b)
+            (u64::try_from(lp)?, scaled_amount_a, scaled_amount_b)
         } else {
             require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
             let token_a_balance_scaled = pool.tokens[token_a_index].balance;
             let token_b_balance_scaled = pool.tokens[token_b_index].balance;
 
-            let amount_a_to_deposit = (token_a_balance_scaled
-                .checked_mul(lp_amount)
-                .ok_or(MiniStabbleError::MathOverflow)?)
-            .checked_div(lp_mint.supply)
-            .ok_or(MiniStabbleError::MathOverflow)?;
-
-            let amount_b_to_deposit = (token_b_balance_scaled
-                .checked_mul(lp_amount)
-                .ok_or(MiniStabbleError::MathOverflow)?)
-            .checked_div(lp_mint.supply)
+            let amounts_in = calc_tokens_in_proportional(
+                &[token_a_balance_scaled, token_b_balance_scaled],
+                lp_amount,
+                lp_mint.supply,
+            )
             .ok_or(MiniStabbleError::MathOverflow)?;
+            let (amount_a_to_deposit, amount_b_to_deposit) = (amounts_in[0], amounts_in[1]);
 
             (lp_amount, amount_a_to_deposit, amount_b_to_deposit)
         };
 
+    // Gross up for transfer fees so the vaults receive the deposited amounts
+    let transfer_amount_a = gross_amount_for_net(
+        token_a_mint,
+        pool.tokens[token_a_index].scale_amount_down(actual_amount_a_to_deposit),
+    )?;
+    let transfer_amount_b = gross_amount_for_net(
+        token_b_mint,
+        pool.tokens[token_b_index].scale_amount_down(actual_amount_b_to_deposit),
+    )?;
+
     require!(
-        actual_amount_a_to_deposit <= scaled_max_amount_a,
+        transfer_amount_a <= max_amount_a,
         MiniStabbleError::SlippageExceeded
     );
     require!(
-        actual_amount_b_to_deposit <= scaled_max_amount_b,
+        transfer_amount_b <= max_amount_b,
         MiniStabbleError::SlippageExceeded
     );
 
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_token_a.to_account_info(),
-                to: ctx.accounts.vault_token_a.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        ),
-        pool.tokens[token_a_index].scale_amount_down(actual_amount_a_to_deposit),
+    transfer_from_user(
+        &ctx.accounts.token_program,
+        token_a_mint,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.vault_token_a,
+        &ctx.accounts.user,
+        transfer_amount_a,
     )?;
 
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_token_b.to_account_info(),
-                to: ctx.accounts.vault_token_b.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        ),
-        pool.tokens[token_b_index].scale_amount_down(actual_amount_b_to_deposit),
+    transfer_from_user(
+        &ctx.accounts.token_program,
+        token_b_mint,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.vault_token_b,
+        &ctx.accounts.user,
+        transfer_amount_b,
     )?;
 
-    let seeds = &[AUTHORITY, &[ctx.bumps.authority]];
-    let signer_seeds = &[&seeds[..]];
-
-    token::mint_to(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.lp_mint.to_account_info(),
-                to: ctx.accounts.user_lp.to_account_info(),
-                authority: ctx.accounts.authority.to_account_info(),
-            },
-            signer_seeds,
-        ),
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+
+    // A locked pool's first LP goes to the creator lock escrow
+    let pool_params = &mut ctx.accounts.pool_params;
+    let lock_lp = ctx.accounts.lp_mint.supply == 0 && pool_params.creator_lock.is_enabled();
+    let lp_destination = if lock_lp {
+        ctx.accounts
+            .creator_lock_escrow
+            .as_ref()
+            .ok_or(MiniStabbleError::InvalidAccount)?
+    } else {
+        &ctx.accounts.user_lp
+    };
+
+    mint_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        lp_destination,
+        &ctx.accounts.authority,
+        signer_seeds,
         lp_to_mint,
     )?;
 
+    if lock_lp {
+        pool_params.creator_lock.lock(
+            ctx.accounts.user.key(),
+            lp_to_mint,
+            Clock::get()?.unix_timestamp,
+        )?;
+        emit!(CreatorLiquidityLockedEvent {
+            pool: pool.key(),
+            owner: ctx.accounts.user.key(),
+            lp_amount: lp_to_mint,
+            unlock_ts: pool_params.creator_lock.unlock_ts,
+        });
+    }
+
+    if let Some(lp_position) = ctx.accounts.lp_position.as_mut() {
+        // Settle what the referred LP earned so far before it grows
+        lp_position.accrue(
+            &pool.fee_growth,
+            ctx.accounts.user_lp.amount,
+            pool_params.referral_fee_share_bps,
+        )?;
+        if lp_position.has_referrer() && !lock_lp {
+            lp_position.referred_lp = lp_position
+                .referred_lp
+                .checked_add(lp_to_mint)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+        }
+    }
+
+    pool.invalidate_invariant();
     pool.tokens[token_a_index].balance = pool.tokens[token_a_index]
         .balance
         .checked_add(actual_amount_a_to_deposit)
@@ -163,5 +248,20 @@ pub fn handler(
         .balance
         .checked_add(actual_amount_b_to_deposit)
         .ok_or(MiniStabbleError::MathOverflow)?;
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(ctx.accounts.lp_mint.supply + lp_to_mint, now),
+    );
+
+    emit!(DepositEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amount_a: transfer_amount_a,
+        amount_b: transfer_amount_b,
+        lp_amount: lp_to_mint,
+    });
+
     Ok(())
 }
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+    token_interface::{Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount},
+};
+
+use crate::{
+    constants::{AUTHORITY, CREATOR_LOCK, POOL_PARAMS, STABLE_POOL},
+    errors::MiniStabbleError,
+    events::{CreatorLiquidityLockedEvent, MultiTokenDepositEvent},
+    math::{common::calc_tokens_in_proportional, stable::calc_invariant},
+    pool_authority_signer_seeds,
+    state::{PoolParams, StablePool},
+    transfers::{
+        gross_amount_for_net, load_token_program, mint_lp, transfer_fee, transfer_from_user,
+    },
+};
+
+/// Remaining accounts per pool token: `[mint, user_token, vault, token_program]`
+pub const ACCOUNTS_PER_JOINED_TOKEN: usize = 4;
+
+#[derive(Accounts)]
+pub struct StableDepositMulti<'info> {
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(mut, address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(init_if_needed, associated_token::mint = lp_mint, associated_token::authority = user, payer = user)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    /// Escrow for the first deposit's LP, required when the pool has a creator lock
+    #[account(init, seeds = [CREATOR_LOCK, pool.key().as_ref()], bump, payer = user, token::mint = lp_mint, token::authority = authority)]
+    pub creator_lock_escrow: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Proportional join of every constituent, for pools of any size. The first deposit sends
+/// exactly `max_amounts_in` and mints the invariant it creates; later ones mint `lp_amount` for
+/// at most `max_amounts_in` (raw, in pool token order). Remaining accounts are
+/// `[mint, user_token, vault, token_program]` for each pool token, in pool token order.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, StableDepositMulti<'info>>,
+    max_amounts_in: Vec<u64>,
+    lp_amount: u64,
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    require!(pool.is_active, MiniStabbleError::PoolInActive);
+    require!(
+        max_amounts_in.len() == pool.tokens.len()
+            && max_amounts_in.iter().all(|&amount| amount > 0),
+        MiniStabbleError::InvalidAmount
+    );
+    require!(
+        ctx.remaining_accounts.len() == pool.tokens.len() * ACCOUNTS_PER_JOINED_TOKEN,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let mut mints = Vec::with_capacity(pool.tokens.len());
+    for (token, accounts) in pool
+        .tokens
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(ACCOUNTS_PER_JOINED_TOKEN))
+    {
+        let [mint_info, user_token_info, vault_info, token_program_info] = accounts else {
+            return err!(MiniStabbleError::InvalidAccount);
+        };
+        let mint = InterfaceAccount::<InterfaceMint>::try_from(mint_info)?;
+        let user_token = InterfaceAccount::<InterfaceTokenAccount>::try_from(user_token_info)?;
+        let token_program = load_token_program(token_program_info, &mint)?;
+        require_keys_eq!(mint.key(), token.mint, MiniStabbleError::InvalidMint);
+        require_keys_eq!(
+            vault_info.key(),
+            token.token_account,
+            MiniStabbleError::InvalidAccount
+        );
+        require_keys_eq!(user_token.mint, token.mint, MiniStabbleError::InvalidMint);
+        require_keys_eq!(
+            user_token.owner,
+            ctx.accounts.user.key(),
+            MiniStabbleError::InvalidAccount
+        );
+        mints.push((mint, token_program));
+    }
+
+    let lp_supply = ctx.accounts.lp_mint.supply;
+    let now = Clock::get()?.unix_timestamp;
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_supply, now);
+
+    let (lp_to_mint, scaled_amounts_in) = if lp_supply == 0 {
+        ctx.accounts
+            .pool_params
+            .check_first_depositor(&ctx.accounts.user.key())?;
+        // Credit only what reaches the vaults after any transfer fee
+        let scaled_amounts_in = pool
+            .tokens
+            .iter()
+            .zip(&mints)
+            .zip(&max_amounts_in)
+            .map(|((token, (mint, _)), &amount)| {
+                Ok(token.scale_amount_up(amount - transfer_fee(mint, amount)?))
+            })
+            .collect::<Result<Vec<u64>>>()?;
+
+        let amp = pool
+            .get_current_amp(now)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        let invariant =
+            calc_invariant(amp, &scaled_amounts_in).ok_or(MiniStabbleError::MathOverflow)?;
+        (invariant, scaled_amounts_in)
+    } else {
+        require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+        let amounts_in = calc_tokens_in_proportional(&pool.get_balances(), lp_amount, lp_supply)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        (lp_amount, amounts_in)
+    };
+    require!(lp_to_mint > 0, MiniStabbleError::InvalidAmount);
+
+    // Gross up for transfer fees so the vaults receive the deposited amounts
+    let mut amounts_in = Vec::with_capacity(mints.len());
+    for (index, (mint, token_program)) in mints.iter().enumerate() {
+        let raw_amount = pool.tokens[index].scale_amount_down(scaled_amounts_in[index]);
+        let transfer_amount = gross_amount_for_net(mint, raw_amount)?;
+        require!(
+            transfer_amount <= max_amounts_in[index],
+            MiniStabbleError::SlippageExceeded
+        );
+
+        let accounts = &ctx.remaining_accounts[index * ACCOUNTS_PER_JOINED_TOKEN..];
+        transfer_from_user(
+            token_program,
+            mint,
+            &accounts[1],
+            &accounts[2],
+            &ctx.accounts.user,
+            transfer_amount,
+        )?;
+        amounts_in.push(transfer_amount);
+    }
+
+    let pool_key = ctx.accounts.pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, ctx.accounts.pool.authority_bump);
+
+    // A locked pool's first LP goes to the creator lock escrow
+    let pool_params = &mut ctx.accounts.pool_params;
+    let lock_lp = lp_supply == 0 && pool_params.creator_lock.is_enabled();
+    let lp_destination = if lock_lp {
+        ctx.accounts
+            .creator_lock_escrow
+            .as_ref()
+            .ok_or(MiniStabbleError::InvalidAccount)?
+    } else {
+        &ctx.accounts.user_lp
+    };
+
+    mint_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        lp_destination,
+        &ctx.accounts.authority,
+        signer_seeds,
+        lp_to_mint,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    if lock_lp {
+        pool_params
+            .creator_lock
+            .lock(ctx.accounts.user.key(), lp_to_mint, now)?;
+        emit!(CreatorLiquidityLockedEvent {
+            pool: pool.key(),
+            owner: ctx.accounts.user.key(),
+            lp_amount: lp_to_mint,
+            unlock_ts: pool_params.creator_lock.unlock_ts,
+        });
+    }
+
+    pool.invalidate_invariant();
+    for (token, scaled_amount) in pool.tokens.iter_mut().zip(&scaled_amounts_in) {
+        token.balance = token
+            .balance
+            .checked_add(*scaled_amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+    }
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(lp_supply + lp_to_mint, now),
+    );
+
+    emit!(MultiTokenDepositEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amounts_in,
+        lp_amount: lp_to_mint,
+    });
+
+    Ok(())
+}
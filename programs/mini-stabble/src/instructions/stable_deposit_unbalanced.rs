@@ -0,0 +1,187 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    constants::{AUTHORITY, POOL_VAULT, STABLE_POOL},
+    errors::{end_dry_run, MiniStabbleError},
+    events::DepositEvent,
+    math::stable::{calc_invariant, calc_lp_tokens_for_deposit_with_fee},
+    pool_authority_signer_seeds,
+    state::StablePool,
+    transfers::{mint_lp, transfer_fee, transfer_from_user},
+};
+
+#[derive(Accounts)]
+pub struct StableDepositUnbalanced<'info> {
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // Mint Accounts
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(constraint = token_a_mint.key() != token_b_mint.key(), mint::token_program = token_program_a)]
+    pub token_a_mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub token_b_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::authority = user, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::authority = user, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    // Vault Tokens
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_a_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub vault_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_b_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub vault_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(init_if_needed, associated_token::mint = lp_mint, associated_token::authority = user, payer = user)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Deposits any mix of `input_amount_a` and `input_amount_b` (either may be 0) into a stable
+/// pool, charging the swap fee on whatever goes beyond the pool's current ratio, and mints the
+/// LP the resulting invariant growth is worth. Fails below `min_lp_amount`.
+pub fn handler(
+    ctx: Context<StableDepositUnbalanced>,
+    min_lp_amount: u64,
+    input_amount_a: u64,
+    input_amount_b: u64,
+    dry_run: bool,
+) -> Result<()> {
+    require!(
+        min_lp_amount > 0 && (input_amount_a > 0 || input_amount_b > 0),
+        MiniStabbleError::InvalidAmount
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    require!(pool.is_active, MiniStabbleError::PoolInActive);
+
+    let lp_supply = ctx.accounts.lp_mint.supply;
+    // The first deposit sets the pool's ratio and goes through `stable_deposit`
+    require!(lp_supply > 0, MiniStabbleError::InvalidAmount);
+
+    let token_a_mint = &ctx.accounts.token_a_mint;
+    let token_b_mint = &ctx.accounts.token_b_mint;
+
+    let [token_a_index, token_b_index] = pool
+        .get_token_pair_indices(&token_a_mint.key(), &token_b_mint.key())
+        .ok_or(MiniStabbleError::InvalidMint)?;
+    // Only the pair's vaults are passed, so the pair has to be the whole pool
+    require!(pool.tokens.len() == 2, MiniStabbleError::InvalidMint);
+
+    let now = Clock::get()?.unix_timestamp;
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_supply, now);
+
+    // Only what reaches the vaults after any transfer fee is deposited
+    let mut amounts_in = vec![0; pool.tokens.len()];
+    amounts_in[token_a_index] = pool.tokens[token_a_index]
+        .scale_amount_up(input_amount_a - transfer_fee(token_a_mint, input_amount_a)?);
+    amounts_in[token_b_index] = pool.tokens[token_b_index]
+        .scale_amount_up(input_amount_b - transfer_fee(token_b_mint, input_amount_b)?);
+
+    let amp = pool
+        .get_current_amp(now)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let balances = pool.get_balances();
+    let invariant = calc_invariant(amp, &balances).ok_or(MiniStabbleError::MathOverflow)?;
+    let lp_to_mint = calc_lp_tokens_for_deposit_with_fee(
+        amp,
+        &balances,
+        &amounts_in,
+        lp_supply,
+        invariant,
+        pool.swap_fee,
+    )
+    .ok_or(MiniStabbleError::MathOverflow)?;
+
+    require!(
+        lp_to_mint >= min_lp_amount,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    for (mint, user_token, vault, amount) in [
+        (
+            token_a_mint,
+            &ctx.accounts.user_token_a,
+            &ctx.accounts.vault_token_a,
+            input_amount_a,
+        ),
+        (
+            token_b_mint,
+            &ctx.accounts.user_token_b,
+            &ctx.accounts.vault_token_b,
+            input_amount_b,
+        ),
+    ] {
+        if amount > 0 {
+            transfer_from_user(
+                &ctx.accounts.token_program,
+                mint,
+                user_token,
+                vault,
+                &ctx.accounts.user,
+                amount,
+            )?;
+        }
+    }
+
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+    mint_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.authority,
+        signer_seeds,
+        lp_to_mint,
+    )?;
+
+    pool.invalidate_invariant();
+    for (token, amount_in) in pool.tokens.iter_mut().zip(amounts_in) {
+        token.balance = token
+            .balance
+            .checked_add(amount_in)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+    }
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(lp_supply + lp_to_mint, now),
+    );
+
+    emit!(DepositEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amount_a: input_amount_a,
+        amount_b: input_amount_b,
+        lp_amount: lp_to_mint,
+    });
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::end_dry_run,
+    instructions::{stable_deposit, StableDeposit},
+    transfers::close_native_account,
+};
+
+/// `stable_deposit` for zaps that fund it from temporary wrapped SOL accounts: afterwards each
+/// input account of the native mint is closed, so the SOL the deposit didn't use and the
+/// account's rent go back to the user instead of staying stranded in it
+pub fn handler(
+    mut ctx: Context<StableDeposit>,
+    max_amount_a: u64,
+    max_amount_b: u64,
+    lp_amount: u64,
+    dry_run: bool,
+) -> Result<()> {
+    stable_deposit::execute(&mut ctx, max_amount_a, max_amount_b, lp_amount)?;
+
+    let accounts = &ctx.accounts;
+    for (token_program, user_token) in [
+        (&accounts.token_program_a, &accounts.user_token_a),
+        (&accounts.token_program_b, &accounts.user_token_b),
+    ] {
+        close_native_account(token_program, user_token, &accounts.user)?;
+    }
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
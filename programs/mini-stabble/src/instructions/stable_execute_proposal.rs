@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    events::ProposalExecutedEvent,
+    instructions::{
+        execute_proposal::require_approved, ramp_amp::start_amp_ramp,
+        set_stable_swap_fee::apply_stable_swap_fee,
+    },
+    state::{Config, Proposal, ProposedChange, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct StableExecuteProposal<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+}
+
+/// Permissionless: applies an approved proposal to a stable pool, under the same checks as the
+/// admin's `set_stable_swap_fee` and `ramp_amp`
+pub fn handler(ctx: Context<StableExecuteProposal>) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    require_approved(proposal)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let pool = &mut ctx.accounts.pool;
+    match proposal.change {
+        ProposedChange::SwapFee { swap_fee } => {
+            apply_stable_swap_fee(pool, &ctx.accounts.config, swap_fee, now)?
+        }
+        ProposedChange::Amp {
+            target_amp,
+            ramp_duration,
+        } => start_amp_ramp(
+            pool,
+            &ctx.accounts.config,
+            target_amp,
+            now.checked_add(ramp_duration)
+                .ok_or(MiniStabbleError::MathOverflow)?,
+            now,
+        )?,
+    }
+    proposal.executed = true;
+
+    emit!(ProposalExecutedEvent {
+        pool: pool.key(),
+        proposal: proposal.key(),
+        change: proposal.change,
+        votes: proposal.votes,
+    });
+
+    Ok(())
+}
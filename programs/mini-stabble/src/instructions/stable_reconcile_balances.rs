@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{constants::STABLE_POOL, events::BalanceLossEvent, state::StablePool};
+
+#[derive(Accounts)]
+pub struct StableReconcileBalances<'info> {
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(address = pool.tokens[0].token_account)]
+    pub vault_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = pool.tokens[1].token_account)]
+    pub vault_token_b: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Permissionless: writes the cached balances down to what the vaults hold, for tokens whose
+/// balance can shrink on its own (negative rebase, fee-deducting wrappers)
+pub fn handler(ctx: Context<StableReconcileBalances>) -> Result<()> {
+    let vault_amounts = [
+        ctx.accounts.vault_token_a.amount,
+        ctx.accounts.vault_token_b.amount,
+    ];
+    for (index, vault_amount) in vault_amounts.into_iter().enumerate() {
+        reconcile_vault(&mut ctx.accounts.pool, index, vault_amount)?;
+    }
+
+    Ok(())
+}
+
+/// Socializes any loss on `tokens[index]` against its vault's `vault_amount`, emitting a
+/// `BalanceLossEvent` when there was one
+pub fn reconcile_vault(
+    pool: &mut Account<StablePool>,
+    index: usize,
+    vault_amount: u64,
+) -> Result<()> {
+    let balance_before = pool.tokens[index].balance;
+    if pool.reconcile_balance(index, vault_amount)? > 0 {
+        emit!(BalanceLossEvent {
+            pool: pool.key(),
+            mint: pool.tokens[index].mint,
+            balance_before,
+            balance_after: pool.tokens[index].balance,
+        });
+    }
+
+    Ok(())
+}
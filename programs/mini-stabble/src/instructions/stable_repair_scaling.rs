@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    constants::{CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    instructions::repair_scaling::repair_token_scaling,
+    state::{Config, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct StableRepairScaling<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, has_one = admin @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    /// Mint of the token being repaired, read for its current decimals
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault of the token being repaired
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<StableRepairScaling>, token_index: u8, new_factor: u64) -> Result<()> {
+    let pool_key = ctx.accounts.pool.key();
+    let pool = &mut ctx.accounts.pool;
+    require!(!pool.is_active, MiniStabbleError::PoolNotPaused);
+
+    pool.invalidate_invariant();
+    pool.drawdown_guard.reset();
+    let token = pool
+        .tokens
+        .get_mut(token_index as usize)
+        .ok_or(MiniStabbleError::InvalidAccount)?;
+
+    emit!(repair_token_scaling(
+        pool_key,
+        token_index,
+        token,
+        &ctx.accounts.mint,
+        &ctx.accounts.vault,
+        new_factor,
+    )?);
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    constants::{LP_POSITION, POOL_PARAMS, STABLE_POOL, WITHDRAW_REQUEST},
+    errors::{end_dry_run, MiniStabbleError},
+    events::WithdrawRequestEvent,
+    math::common::calc_tokens_out_proportional,
+    state::{settle_referral, LpPosition, PoolParams, QueuedToken, StablePool, WithdrawRequest},
+    transfers::{burn_lp, require_lp_burn_authority},
+};
+
+#[derive(Accounts)]
+pub struct StableRequestWithdraw<'info> {
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(
+        init,
+        seeds = [WITHDRAW_REQUEST, pool.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        space = WithdrawRequest::LEN
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+
+    /// LP source, owned by `user` or delegated to it
+    #[account(mut, token::mint = lp_mint)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Takes what the position's referrer is owed out of the payout when passed
+    #[account(
+        mut,
+        seeds = [LP_POSITION, pool.key().as_ref(), user.key().as_ref()],
+        bump = lp_position.bump,
+        constraint = user_lp.owner == user.key() @ MiniStabbleError::Unauthorized,
+    )]
+    pub lp_position: Option<Account<'info, LpPosition>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Burns `lp_amount` for a proportional share of the pool that unlocks over the queue duration.
+/// With an `lp_position`, the referrer's accrued rewards are taken out of the queued amounts.
+pub fn handler(ctx: Context<StableRequestWithdraw>, lp_amount: u64, dry_run: bool) -> Result<()> {
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    let pool = &mut ctx.accounts.pool;
+    require!(
+        ctx.accounts.pool_params.withdraw_queue_threshold_bps > 0,
+        MiniStabbleError::WithdrawQueueDisabled
+    );
+
+    #[cfg(feature = "strict-checks")]
+    let now = Clock::get()?.unix_timestamp;
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(ctx.accounts.lp_mint.supply, now);
+
+    // Lock in the proportional share at today's balances
+    let amounts_out =
+        calc_tokens_out_proportional(&pool.get_balances(), lp_amount, ctx.accounts.lp_mint.supply)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+    let raw_amounts_out = pool
+        .tokens
+        .iter()
+        .zip(amounts_out.iter())
+        .map(|(token, &amount_out)| token.scale_amount_down(amount_out))
+        .collect::<Vec<u64>>();
+    let taken = settle_referral(
+        pool,
+        ctx.accounts.lp_position.as_deref_mut(),
+        ctx.accounts.pool_params.referral_fee_share_bps,
+        ctx.accounts.user_lp.amount,
+        &raw_amounts_out
+            .iter()
+            .copied()
+            .enumerate()
+            .collect::<Vec<_>>(),
+    )?;
+
+    require_lp_burn_authority(&ctx.accounts.user_lp, &ctx.accounts.user.key(), lp_amount)?;
+    burn_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.user,
+        lp_amount,
+    )?;
+
+    // The owed tokens stay in the vaults but no longer back the pool
+    pool.invalidate_invariant();
+    let mut queued_tokens = Vec::with_capacity(pool.tokens.len());
+    for (index, token) in pool.tokens.iter_mut().enumerate() {
+        token.debit_payout(amounts_out[index], raw_amounts_out[index])?;
+        let amount = raw_amounts_out[index] - taken[index];
+        token.queued_withdrawals = token
+            .queued_withdrawals
+            .checked_add(amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        queued_tokens.push(QueuedToken {
+            mint: token.mint,
+            amount,
+            claimed: 0,
+        });
+    }
+
+    let start_slot = Clock::get()?.slot;
+
+    let withdraw_request = &mut ctx.accounts.withdraw_request;
+    withdraw_request.owner = ctx.accounts.user.key();
+    withdraw_request.pool = pool.key();
+    withdraw_request.tokens = queued_tokens;
+    withdraw_request.start_slot = start_slot;
+    withdraw_request.end_slot = start_slot
+        .checked_add(ctx.accounts.pool_params.withdraw_queue_duration)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    withdraw_request.bump = ctx.bumps.withdraw_request;
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(ctx.accounts.lp_mint.supply - lp_amount, now),
+    );
+
+    emit!(WithdrawRequestEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        lp_amount,
+        amounts_out: withdraw_request
+            .tokens
+            .iter()
+            .map(|token| token.amount)
+            .collect(),
+        end_slot: withdraw_request.end_slot,
+    });
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
@@ -1,20 +1,39 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::{
+    token::Mint,
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
 
 use crate::{
-    constants::{AUTHORITY, POOL_VAULT, STABLE_POOL},
-    errors::MiniStabbleError,
+    constants::{
+        AUTHORITY, BPS_DENOMINATOR, POOL_VAULT, STABLE_POOL, SWAP_CHECKPOINT, TRADER_ALLOWLIST,
+        USER_REBATE_STATS, USER_STATS,
+    },
+    errors::{end_dry_run, MiniStabbleError},
+    events::{StablePoolStressedEvent, SwapEvent, SwapResult},
+    instructions::{
+        initialize_swap_checkpoint::record_checkpoint_swap,
+        stable_reconcile_balances::reconcile_vault, SlippageLimit, SwapAmount,
+    },
     math::{
         fixed::{FixedMul, SCALE},
-        stable::calc_out_given_in,
+        stable::{stressed_token, MIN_SWAP_AMOUNT},
+        swap_context::SwapQuote,
     },
-    state::StablePool,
+    pool_authority_signer_seeds,
+    state::{
+        check_swaps_enabled, check_trader_allowed, StablePool, SwapCheckpoint,
+        TraderAllowlistEntry, UserRebateStats, UserStats,
+    },
+    transfers::{gross_amount_for_net, transfer_fee, transfer_from_user, transfer_from_vault},
 };
 
 #[derive(Accounts)]
 pub struct StableSwap<'info> {
     /// CHECK: Unchecked
-    #[account(seeds = [AUTHORITY], bump)]
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
     pub authority: UncheckedAccount<'info>,
 
     /// Pool - derived from LP mint
@@ -25,102 +44,300 @@ pub struct StableSwap<'info> {
     )]
     pub pool: Account<'info, StablePool>,
 
-    #[account(constraint = mint_in.key() != mint_out.key())]
-    pub mint_in: Account<'info, Mint>,
-    pub mint_out: Account<'info, Mint>,
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(constraint = mint_in.key() != mint_out.key(), mint::token_program = token_program_in)]
+    pub mint_in: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_out)]
+    pub mint_out: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_in.key().as_ref()], bump, token::mint = mint_in, token::token_program = token_program_in, token::authority = authority)]
+    pub vault_token_in: InterfaceAccount<'info, InterfaceTokenAccount>,
 
-    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_in.key().as_ref()], bump, token::mint = mint_in, token::authority = authority)]
-    pub vault_token_in: Account<'info, TokenAccount>,
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_out.key().as_ref()], bump, token::mint = mint_out, token::token_program = token_program_out, token::authority = authority)]
+    pub vault_token_out: InterfaceAccount<'info, InterfaceTokenAccount>,
 
-    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_out.key().as_ref()], bump, token::mint = mint_out, token::authority = authority)]
-    pub vault_token_out: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint_in, token::token_program = token_program_in, token::authority = user)]
+    pub user_token_in: InterfaceAccount<'info, InterfaceTokenAccount>,
 
-    #[account(mut, token::mint = mint_in, token::authority = user)]
-    pub user_token_in: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint_out, token::token_program = token_program_out, token::authority = user)]
+    pub user_token_out: InterfaceAccount<'info, InterfaceTokenAccount>,
 
-    #[account(mut, token::mint = mint_out, token::authority = user)]
-    pub user_token_out: Account<'info, TokenAccount>,
+    /// Receives `split_bps` of the output in `stable_swap_split`; unused otherwise
+    #[account(mut, token::mint = mint_out, token::token_program = token_program_out)]
+    pub secondary_recipient: Option<InterfaceAccount<'info, InterfaceTokenAccount>>,
+
+    /// Counts this swap towards the user's volume when passed
+    #[account(mut, seeds = [USER_STATS, user.key().as_ref()], bump = user_stats.bump)]
+    pub user_stats: Option<Account<'info, UserStats>>,
+
+    /// Counts this swap towards the user's fee rebate in this pool when passed
+    #[account(mut, seeds = [USER_REBATE_STATS, pool.key().as_ref(), user.key().as_ref()], bump = user_rebate_stats.bump)]
+    pub user_rebate_stats: Option<Account<'info, UserRebateStats>>,
+
+    /// Required when the pool only lets allowlisted traders swap
+    #[account(seeds = [TRADER_ALLOWLIST, pool.key().as_ref(), user.key().as_ref()], bump = trader_allowlist_entry.bump)]
+    pub trader_allowlist_entry: Option<Account<'info, TraderAllowlistEntry>>,
+
+    /// Aggregates this swap into the pool's checkpoint instead of emitting a `SwapEvent`
+    #[account(mut, seeds = [SWAP_CHECKPOINT, pool.key().as_ref()], bump = swap_checkpoint.bump)]
+    pub swap_checkpoint: Option<Account<'info, SwapCheckpoint>>,
 
     #[account(mut)]
     pub user: Signer<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program_in: Interface<'info, TokenInterface>,
+    pub token_program_out: Interface<'info, TokenInterface>,
+}
+
+pub fn handler(
+    ctx: Context<StableSwap>,
+    amount_in: u64,
+    min_amount_out: u64,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    execute(
+        ctx,
+        amount_in,
+        SlippageLimit::MinAmountOut(min_amount_out),
+        0,
+        dry_run,
+    )
+}
+
+/// Part of `amount_out` paid to the secondary recipient, rounded down in the user's favour
+fn secondary_amount(amount_out: u64, split_bps: u16, has_recipient: bool) -> Result<u64> {
+    if split_bps == 0 {
+        return Ok(0);
+    }
+    require!(
+        has_recipient && u64::from(split_bps) <= BPS_DENOMINATOR,
+        MiniStabbleError::InvalidSplit
+    );
+
+    Ok(u64::try_from(
+        amount_out as u128 * split_bps as u128 / BPS_DENOMINATOR as u128,
+    )?)
+}
+
+/// Swaps `amount_in`, paying `split_bps` of the output to the secondary recipient and the rest
+/// to the user. `slippage` bounds the whole output, before it is split.
+pub fn execute(
+    ctx: Context<StableSwap>,
+    amount_in: u64,
+    slippage: SlippageLimit,
+    split_bps: u16,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    execute_amount(
+        ctx,
+        SwapAmount::ExactIn {
+            amount_in,
+            slippage,
+        },
+        split_bps,
+        dry_run,
+    )
 }
 
-pub fn handler(ctx: Context<StableSwap>, amount_in: u64, min_amount_out: u64) -> Result<()> {
+/// `execute` for either side of the trade fixed by `amount`
+pub fn execute_amount(
+    ctx: Context<StableSwap>,
+    amount: SwapAmount,
+    split_bps: u16,
+    dry_run: bool,
+) -> Result<SwapResult> {
     let pool = &mut ctx.accounts.pool;
     // Check if pool is active
     require!(pool.is_active, MiniStabbleError::PoolInActive);
-    require!(amount_in > 0, MiniStabbleError::InvalidAmount);
-    require!(min_amount_out > 0, MiniStabbleError::InvalidAmount);
+    check_trader_allowed(
+        pool.trader_allowlist,
+        ctx.accounts.trader_allowlist_entry.as_deref(),
+    )?;
+    match amount {
+        SwapAmount::ExactIn {
+            amount_in,
+            slippage,
+        } => {
+            require!(amount_in > 0, MiniStabbleError::InvalidAmount);
+            slippage.validate()?;
+        }
+        SwapAmount::ExactOut { amount_out, .. } => {
+            // The exact output goes to the user alone
+            require!(
+                amount_out > 0 && split_bps == 0,
+                MiniStabbleError::InvalidAmount
+            );
+        }
+    }
 
     let mint_in = &ctx.accounts.mint_in;
     let mint_out = &ctx.accounts.mint_out;
-    let token_in_index = pool
-        .get_token_index(&mint_in.key())
-        .ok_or(MiniStabbleError::InvalidMint)?;
-    let token_out_index = pool
-        .get_token_index(&mint_out.key())
+    let [token_in_index, token_out_index] = pool
+        .get_token_pair_indices(&mint_in.key(), &mint_out.key())
         .ok_or(MiniStabbleError::InvalidMint)?;
+    check_swaps_enabled(&pool.tokens, [token_in_index, token_out_index])?;
 
-    let scaled_amount_in = pool.tokens[token_in_index].scale_amount_up(amount_in);
+    // Price against what the vaults hold, not balances a negative rebase left overstated
+    reconcile_vault(pool, token_in_index, ctx.accounts.vault_token_in.amount)?;
+    reconcile_vault(pool, token_out_index, ctx.accounts.vault_token_out.amount)?;
 
-    let amp = pool.amp;
-
-    let amount_out_scaled = calc_out_given_in(
-        amp,
-        &pool.get_balances(),
+    let clock = Clock::get()?;
+    let context = pool.swap_context(
         token_in_index,
         token_out_index,
-        scaled_amount_in,
-    )
-    .ok_or(MiniStabbleError::InvalidAmount)? as u128;
-
-    // amount_out * (1 - fee/scale) -> amount_out * ((scale - fee)/scale)
-    let scaled_amount_out_after_fee = u64::try_from(
-        amount_out_scaled.mul_down(
-            SCALE
-                .checked_sub(pool.swap_fee as u128)
-                .ok_or(MiniStabbleError::MathOverflow)?,
-        )?,
+        clock.unix_timestamp,
+        clock.slot,
     )?;
 
+    // Quoted conservatively by `SwapContext`; flagged so monitoring sees the pool is stuck
+    if let Some((index, share_bps)) = stressed_token(&context.balances) {
+        emit!(StablePoolStressedEvent {
+            pool: pool.key(),
+            mint: pool.tokens[index].mint,
+            share_bps,
+        });
+    }
+
+    let (amount_in, scaled_amount_in, quote) = match amount {
+        SwapAmount::ExactIn { amount_in, .. } => {
+            // Only what reaches the vault after any transfer fee is swapped
+            let amount_in_net = amount_in
+                .checked_sub(transfer_fee(mint_in, amount_in)?)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+            let scaled_amount_in = pool.tokens[token_in_index].scale_amount_up(amount_in_net);
+            (
+                amount_in,
+                scaled_amount_in,
+                context.calc_out_given_in(scaled_amount_in)?,
+            )
+        }
+        SwapAmount::ExactOut {
+            amount_out,
+            max_amount_in,
+        } => {
+            // The vault sends enough that the user is credited `amount_out` after transfer fees
+            let amount_sent = gross_amount_for_net(mint_out, amount_out)?;
+            let scaled_amount_out = pool.tokens[token_out_index].scale_amount_up(amount_sent);
+            let in_quote = context.calc_in_given_out(scaled_amount_out)?;
+
+            // Rounded up to whole raw units, the remainder staying in the pool
+            let amount_in_net = in_quote
+                .amount_in
+                .div_ceil(pool.tokens[token_in_index].scaling_factor);
+            let amount_in = gross_amount_for_net(mint_in, amount_in_net)?;
+            require!(
+                amount_in <= max_amount_in,
+                MiniStabbleError::SlippageExceeded
+            );
+            (
+                amount_in,
+                pool.tokens[token_in_index].scale_amount_up(amount_in_net),
+                SwapQuote {
+                    amount_out: scaled_amount_out,
+                    fee: in_quote.fee,
+                },
+            )
+        }
+    };
+    require!(
+        scaled_amount_in >= MIN_SWAP_AMOUNT,
+        MiniStabbleError::AmountTooSmall
+    );
+
+    let scaled_amount_out_after_fee = quote.amount_out;
+    pool.check_max_out_ratio(token_out_index, scaled_amount_out_after_fee)?;
+
+    let amount_out = pool.tokens[token_out_index].scale_amount_down(scaled_amount_out_after_fee);
+    require!(amount_out > 0, MiniStabbleError::AmountTooSmall);
+    let secondary_amount_out = secondary_amount(
+        amount_out,
+        split_bps,
+        ctx.accounts.secondary_recipient.is_some(),
+    )?;
+    let primary_amount_out = amount_out - secondary_amount_out;
+
+    // Each leg of a split pays its own transfer fee
+    let mut amount_received = 0;
+    for part in [primary_amount_out, secondary_amount_out] {
+        amount_received += part
+            .checked_sub(transfer_fee(mint_out, part)?)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+    }
+    if let SwapAmount::ExactIn { slippage, .. } = amount {
+        slippage.check(amount_out, amount_received, || {
+            let price = pool
+                .spot_price(token_in_index, token_out_index, clock.unix_timestamp)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+            let quoted_scaled = (scaled_amount_in as u128)
+                .mul_down(price)?
+                .mul_down(SCALE - pool.swap_fee as u128)?;
+            Ok(pool.tokens[token_out_index].scale_amount_down(u64::try_from(quoted_scaled)?))
+        })?;
+    }
+
+    // Circuit breaker: a swap that drains value per LP is refused; `trip_breaker` pauses a pool
+    // that is already drawn down
+    let balances_after = context.balances_after(scaled_amount_in, scaled_amount_out_after_fee)?;
     require!(
-        min_amount_out
-            <= pool.tokens[token_out_index].scale_amount_down(scaled_amount_out_after_fee),
-        MiniStabbleError::SlippageExceeded
+        pool.trips_drawdown_guard(
+            &balances_after,
+            ctx.accounts.lp_mint.supply,
+            clock.unix_timestamp,
+        )
+        .is_none(),
+        MiniStabbleError::DrawdownGuardTripped
     );
 
+    pool.swap_rate_limit.record_swap(
+        clock.slot,
+        &context.balances,
+        token_in_index,
+        scaled_amount_in,
+        token_out_index,
+        scaled_amount_out_after_fee,
+    )?;
+
     // Amount In
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_token_in.to_account_info(),
-                to: ctx.accounts.vault_token_in.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        ),
+    transfer_from_user(
+        &ctx.accounts.token_program_in,
+        mint_in,
+        &ctx.accounts.user_token_in,
+        &ctx.accounts.vault_token_in,
+        &ctx.accounts.user,
         amount_in,
     )?;
 
-    let seeds = [AUTHORITY, &[ctx.bumps.authority]];
-    let signer_seeds = &[&seeds[..]];
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
 
     // Amount out
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_token_out.to_account_info(),
-                to: ctx.accounts.user_token_out.to_account_info(),
-                authority: ctx.accounts.authority.to_account_info(),
-            },
+    if primary_amount_out > 0 {
+        transfer_from_vault(
+            &ctx.accounts.token_program_out,
+            mint_out,
+            &ctx.accounts.vault_token_out,
+            &ctx.accounts.user_token_out,
+            &ctx.accounts.authority,
             signer_seeds,
-        ),
-        pool.tokens[token_out_index].scale_amount_down(scaled_amount_out_after_fee),
-    )?;
+            primary_amount_out,
+        )?;
+    }
+    if let Some(secondary_recipient) = ctx.accounts.secondary_recipient.as_ref() {
+        if secondary_amount_out > 0 {
+            transfer_from_vault(
+                &ctx.accounts.token_program_out,
+                mint_out,
+                &ctx.accounts.vault_token_out,
+                secondary_recipient,
+                &ctx.accounts.authority,
+                signer_seeds,
+                secondary_amount_out,
+            )?;
+        }
+    }
 
     // let amount_out_scaled = pool.tokens[token_out_index].scale_amount_down(scaled_amount)
     pool.tokens[token_in_index].balance = pool.tokens[token_in_index]
@@ -128,9 +345,68 @@ pub fn handler(ctx: Context<StableSwap>, amount_in: u64, min_amount_out: u64) ->
         .checked_add(scaled_amount_in)
         .ok_or(MiniStabbleError::MathOverflow)?;
 
-    pool.tokens[token_out_index].balance = pool.tokens[token_out_index]
-        .balance
-        .checked_sub(scaled_amount_out_after_fee)
-        .ok_or(MiniStabbleError::MathOverflow)?;
-    Ok(())
+    pool.tokens[token_out_index].debit_payout(scaled_amount_out_after_fee, amount_out)?;
+
+    let scaled_fee = quote.fee;
+
+    // The rebate share of the fee stays in the vault but leaves the pool's balance until claimed
+    pool.roll_rebate_epoch(clock.unix_timestamp)?;
+    let mut rebate_fee = 0;
+    if pool.rebate.is_enabled() {
+        rebate_fee = pool.rebate.accrue_fee(token_out_index, scaled_fee);
+        pool.tokens[token_out_index].balance -= rebate_fee;
+
+        if let Some(user_rebate_stats) = ctx.accounts.user_rebate_stats.as_mut() {
+            user_rebate_stats.roll(pool.rebate.epoch);
+            user_rebate_stats.epoch_volume += scaled_amount_in as u128;
+            pool.rebate.epoch_volume += scaled_amount_in as u128;
+        }
+    }
+    pool.accrue_swap_fee(
+        token_out_index,
+        scaled_fee - rebate_fee,
+        ctx.accounts.lp_mint.supply,
+    );
+
+    if let Some(user_stats) = ctx.accounts.user_stats.as_mut() {
+        user_stats.record_swap(scaled_amount_in, clock.unix_timestamp);
+    }
+
+    if let Some(swap_checkpoint) = ctx.accounts.swap_checkpoint.as_mut() {
+        record_checkpoint_swap(
+            swap_checkpoint,
+            clock.slot,
+            pool.tokens.len(),
+            token_in_index,
+            amount_in,
+            token_out_index,
+            amount_out,
+            pool.tokens[token_out_index].scale_amount_down(scaled_fee),
+        );
+    } else {
+        emit!(SwapEvent {
+            pool: pool.key(),
+            user: ctx.accounts.user.key(),
+            mint_in: mint_in.key(),
+            mint_out: mint_out.key(),
+            amount_in,
+            amount_out,
+        });
+    }
+
+    let token_out = &pool.tokens[token_out_index];
+    let result = SwapResult {
+        amount_in,
+        amount_out,
+        fee: token_out.scale_amount_down(scaled_fee),
+        protocol_fee: 0,
+        spot_price_after: pool
+            .spot_price(token_in_index, token_out_index, clock.unix_timestamp)
+            .unwrap_or(0),
+    };
+    result.log()?;
+
+    end_dry_run(dry_run)?;
+
+    Ok(result)
 }
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::SwapResult,
+    instructions::{stable_swap, StableSwap, SwapAmount},
+};
+
+/// `stable_swap` with the output fixed instead of the input: the user receives exactly
+/// `amount_out` of `mint_out`, after any transfer fee, and pays whatever input the curve and the
+/// swap fee require, failing above `max_amount_in`
+pub fn handler(
+    ctx: Context<StableSwap>,
+    amount_out: u64,
+    max_amount_in: u64,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    stable_swap::execute_amount(
+        ctx,
+        SwapAmount::ExactOut {
+            amount_out,
+            max_amount_in,
+        },
+        0,
+        dry_run,
+    )
+}
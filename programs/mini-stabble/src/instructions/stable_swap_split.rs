@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::SwapResult,
+    instructions::{stable_swap, SlippageLimit, StableSwap},
+};
+
+/// `stable_swap` that pays `split_bps` of the output to `secondary_recipient` and the rest to
+/// the user in the same instruction. `min_amount_out` applies to the combined output.
+pub fn handler(
+    ctx: Context<StableSwap>,
+    amount_in: u64,
+    min_amount_out: u64,
+    split_bps: u16,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    stable_swap::execute(
+        ctx,
+        amount_in,
+        SlippageLimit::MinAmountOut(min_amount_out),
+        split_bps,
+        dry_run,
+    )
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::SwapResult,
+    instructions::{stable_swap, swap_with_fee_reimbursement::FeeReimbursement, StableSwap},
+};
+
+/// `stable_swap` into wrapped SOL that repays a relayer `reimbursement_lamports` of the output,
+/// sent to the wrapped SOL account passed as the only remaining account. `min_amount_out`
+/// bounds what the user keeps after the reimbursement.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, StableSwap<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    reimbursement_lamports: u64,
+) -> Result<SwapResult> {
+    let reimbursement = FeeReimbursement::load(
+        &ctx.accounts.token_program_out,
+        &ctx.accounts.mint_out,
+        &ctx.accounts.user_token_out,
+        &ctx.accounts.user,
+        ctx.remaining_accounts,
+        reimbursement_lamports,
+    )?;
+    let slippage = reimbursement.slippage(min_amount_out)?;
+
+    let result = stable_swap::execute(ctx, amount_in, slippage, 0, false)?;
+    reimbursement.pay()?;
+
+    Ok(result)
+}
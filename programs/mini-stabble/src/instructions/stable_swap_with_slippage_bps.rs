@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::SwapResult,
+    instructions::{stable_swap, SlippageLimit, StableSwap},
+};
+
+/// `stable_swap` with the tolerance given as `slippage_bps` below the on-chain spot price quote,
+/// so clients don't have to compute an exact `min_amount_out`
+pub fn handler(
+    ctx: Context<StableSwap>,
+    amount_in: u64,
+    slippage_bps: u16,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    stable_swap::execute(ctx, amount_in, SlippageLimit::Bps(slippage_bps), 0, dry_run)
+}
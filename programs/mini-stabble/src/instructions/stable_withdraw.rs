@@ -0,0 +1,190 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    constants::{AUTHORITY, LP_POSITION, POOL_PARAMS, POOL_VAULT, STABLE_POOL},
+    errors::{end_dry_run, MiniStabbleError},
+    events::WithdrawEvent,
+    math::common::calc_tokens_out_proportional,
+    pool_authority_signer_seeds,
+    state::{settle_referral, LpPosition, PoolParams, StablePool},
+    transfers::{burn_lp, require_lp_burn_authority, transfer_from_vault},
+};
+
+#[derive(Accounts)]
+pub struct StableWithdraw<'info> {
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // Mint Accounts
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(constraint = token_a_mint.key() != token_b_mint.key(), mint::token_program = token_program_a)]
+    pub token_a_mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub token_b_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::authority = user, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::authority = user, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    // Vault Tokens
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_a_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub vault_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_b_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub vault_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    /// LP source, owned by `user` or delegated to it
+    #[account(mut, token::mint = lp_mint)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Takes what the position's referrer is owed out of the payout when passed
+    #[account(
+        mut,
+        seeds = [LP_POSITION, pool.key().as_ref(), user.key().as_ref()],
+        bump = lp_position.bump,
+        constraint = user_lp.owner == user.key() @ MiniStabbleError::Unauthorized,
+    )]
+    pub lp_position: Option<Account<'info, LpPosition>>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+/// Stable counterpart of `withdraw`: burns `lp_amount` for the proportional share of both pool
+/// tokens, bounded by `min_amount_a` and `min_amount_b` after any transfer fees. Exits above
+/// the pool's withdraw queue threshold have to go through `stable_request_withdraw`. With an
+/// `lp_position`, the referrer's accrued rewards are taken out of the payouts first.
+pub fn handler(
+    ctx: Context<StableWithdraw>,
+    lp_amount: u64,
+    min_amount_a: u64,
+    min_amount_b: u64,
+    dry_run: bool,
+) -> Result<()> {
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    let pool = &mut ctx.accounts.pool;
+    let lp_supply = ctx.accounts.lp_mint.supply;
+
+    let token_a_mint = &ctx.accounts.token_a_mint;
+    let token_b_mint = &ctx.accounts.token_b_mint;
+
+    let [token_a_index, token_b_index] = pool
+        .get_token_pair_indices(&token_a_mint.key(), &token_b_mint.key())
+        .ok_or(MiniStabbleError::InvalidMint)?;
+    // Only the pair's vaults are passed, so the pair has to be the whole pool
+    require!(pool.tokens.len() == 2, MiniStabbleError::InvalidMint);
+    require!(
+        !pool.record_instant_exit(
+            &ctx.accounts.pool_params,
+            lp_amount,
+            lp_supply,
+            Clock::get()?.slot
+        ),
+        MiniStabbleError::ExitRequiresWithdrawQueue
+    );
+
+    #[cfg(feature = "strict-checks")]
+    let now = Clock::get()?.unix_timestamp;
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_supply, now);
+
+    let amounts_out = calc_tokens_out_proportional(
+        &[
+            pool.tokens[token_a_index].balance,
+            pool.tokens[token_b_index].balance,
+        ],
+        lp_amount,
+        lp_supply,
+    )
+    .ok_or(MiniStabbleError::MathOverflow)?;
+    let raw_amount_a = pool.tokens[token_a_index].scale_amount_down(amounts_out[0]);
+    let raw_amount_b = pool.tokens[token_b_index].scale_amount_down(amounts_out[1]);
+    let taken = settle_referral(
+        pool,
+        ctx.accounts.lp_position.as_deref_mut(),
+        ctx.accounts.pool_params.referral_fee_share_bps,
+        ctx.accounts.user_lp.amount,
+        &[(token_a_index, raw_amount_a), (token_b_index, raw_amount_b)],
+    )?;
+
+    require_lp_burn_authority(&ctx.accounts.user_lp, &ctx.accounts.user.key(), lp_amount)?;
+    burn_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.user,
+        lp_amount,
+    )?;
+
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+
+    let received_a = transfer_from_vault(
+        &ctx.accounts.token_program_a,
+        token_a_mint,
+        &ctx.accounts.vault_token_a,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.authority,
+        signer_seeds,
+        raw_amount_a - taken[0],
+    )?;
+    let received_b = transfer_from_vault(
+        &ctx.accounts.token_program_b,
+        token_b_mint,
+        &ctx.accounts.vault_token_b,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.authority,
+        signer_seeds,
+        raw_amount_b - taken[1],
+    )?;
+
+    // Slippage check against what the user actually receives
+    require!(
+        received_a >= min_amount_a && received_b >= min_amount_b,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    // The whole share leaves the pool; what the raw payouts rounded off is booked as dust
+    pool.invalidate_invariant();
+    pool.tokens[token_a_index].debit_payout(amounts_out[0], raw_amount_a)?;
+    pool.tokens[token_b_index].debit_payout(amounts_out[1], raw_amount_b)?;
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(lp_supply - lp_amount, now),
+    );
+
+    emit!(WithdrawEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amount_a: received_a,
+        amount_b: received_b,
+        lp_amount,
+    });
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
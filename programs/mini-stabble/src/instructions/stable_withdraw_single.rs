@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    constants::{AUTHORITY, LP_POSITION, POOL_PARAMS, POOL_VAULT, STABLE_POOL},
+    errors::{end_dry_run, MiniStabbleError},
+    events::MultiTokenWithdrawEvent,
+    math::stable::{calc_invariant, calc_token_out_for_lp_burn},
+    pool_authority_signer_seeds,
+    state::{settle_referral, LpPosition, PoolParams, StablePool},
+    transfers::{burn_lp, require_lp_burn_authority, transfer_from_vault},
+};
+
+#[derive(Accounts)]
+pub struct StableWithdrawSingle<'info> {
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(mint::token_program = token_program_out)]
+    pub token_out_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::authority = user, token::mint = token_out_mint, token::token_program = token_program_out)]
+    pub user_token_out: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_out_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_out_mint, token::token_program = token_program_out)]
+    pub vault_token_out: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    /// LP source, owned by `user` or delegated to it
+    #[account(mut, token::mint = lp_mint)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Takes what the position's referrer is owed out of the payout when passed
+    #[account(
+        mut,
+        seeds = [LP_POSITION, pool.key().as_ref(), user.key().as_ref()],
+        bump = lp_position.bump,
+        constraint = user_lp.owner == user.key() @ MiniStabbleError::Unauthorized,
+    )]
+    pub lp_position: Option<Account<'info, LpPosition>>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_out: Interface<'info, TokenInterface>,
+}
+
+/// Stable counterpart of `withdraw_single`: burns exactly `lp_amount` LP for `token_out_mint`
+/// alone, with the swap fee charged on the share a proportional exit would have paid in the
+/// other tokens. With an `lp_position`, the referrer's accrued rewards are taken out of the
+/// payout first. Fails if the user would receive less than `min_amount_out` raw.
+pub fn handler(
+    ctx: Context<StableWithdrawSingle>,
+    lp_amount: u64,
+    min_amount_out: u64,
+    dry_run: bool,
+) -> Result<()> {
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    let pool = &mut ctx.accounts.pool;
+    let lp_supply = ctx.accounts.lp_mint.supply;
+    // Burning the whole supply has no single-token price; the last LP exits through
+    // `stable_withdraw`
+    require!(lp_amount < lp_supply, MiniStabbleError::InvalidAmount);
+    require!(
+        !pool.record_instant_exit(
+            &ctx.accounts.pool_params,
+            lp_amount,
+            lp_supply,
+            Clock::get()?.slot
+        ),
+        MiniStabbleError::ExitRequiresWithdrawQueue
+    );
+
+    let token_out_mint = &ctx.accounts.token_out_mint;
+    let token_index = pool
+        .tokens
+        .iter()
+        .position(|token| token.mint == token_out_mint.key())
+        .ok_or(MiniStabbleError::InvalidMint)?;
+    let token = &pool.tokens[token_index];
+
+    let now = Clock::get()?.unix_timestamp;
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_supply, now);
+
+    let amp = pool
+        .get_current_amp(now)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let balances = pool.get_balances();
+    let invariant = calc_invariant(amp, &balances).ok_or(MiniStabbleError::MathOverflow)?;
+    let scaled_amount_out = calc_token_out_for_lp_burn(
+        amp,
+        &balances,
+        token_index,
+        lp_amount,
+        lp_supply,
+        invariant,
+        pool.swap_fee,
+    )
+    .ok_or(MiniStabbleError::MathOverflow)?;
+
+    let amount_out = token.scale_amount_down(scaled_amount_out);
+    require!(amount_out > 0, MiniStabbleError::InvalidAmount);
+    let taken = settle_referral(
+        pool,
+        ctx.accounts.lp_position.as_deref_mut(),
+        ctx.accounts.pool_params.referral_fee_share_bps,
+        ctx.accounts.user_lp.amount,
+        &[(token_index, amount_out)],
+    )?;
+
+    require_lp_burn_authority(&ctx.accounts.user_lp, &ctx.accounts.user.key(), lp_amount)?;
+    burn_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.user,
+        lp_amount,
+    )?;
+
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+    let received = transfer_from_vault(
+        &ctx.accounts.token_program_out,
+        token_out_mint,
+        &ctx.accounts.vault_token_out,
+        &ctx.accounts.user_token_out,
+        &ctx.accounts.authority,
+        signer_seeds,
+        amount_out - taken[0],
+    )?;
+    require!(
+        received >= min_amount_out,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    // What the raw payout rounded off is booked as dust
+    pool.invalidate_invariant();
+    pool.tokens[token_index].debit_payout(scaled_amount_out, amount_out)?;
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(lp_supply - lp_amount, now),
+    );
+
+    let mut amounts_out = vec![0; pool.tokens.len()];
+    amounts_out[token_index] = received;
+    emit!(MultiTokenWithdrawEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amounts_out,
+        lp_amount,
+    });
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
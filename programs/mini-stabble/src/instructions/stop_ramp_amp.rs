@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG, STABLE_POOL},
+    errors::MiniStabbleError,
+    state::{Config, Role, StablePool},
+};
+
+#[derive(Accounts)]
+pub struct StopRampAmp<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump, constraint = config.has_role(Role::Keeper, keeper.key) @ MiniStabbleError::Unauthorized)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    /// Admin or keeper
+    pub keeper: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<StopRampAmp>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    let pool = &mut ctx.accounts.pool;
+    let current_amp = pool
+        .get_current_amp(now)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    // Freeze the amp where it currently is
+    pool.amp = current_amp;
+    pool.amp_target = current_amp;
+    pool.amp_start_ts = now;
+    pool.amp_end_ts = now;
+    pool.invalidate_invariant();
+    pool.drawdown_guard.reset();
+
+    Ok(())
+}
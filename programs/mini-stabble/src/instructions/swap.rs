@@ -1,14 +1,30 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::{
+    token::Mint,
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
 
 use crate::{
-    constants::{AUTHORITY, POOL_VAULT, WEIGHT_POOL},
-    errors::MiniStabbleError,
+    constants::{
+        AUTHORITY, BPS_DENOMINATOR, POOL_FEE_STATS, POOL_VAULT, SWAP_CHECKPOINT, TRADER_ALLOWLIST,
+        USER_STATS, WEIGHT_POOL,
+    },
+    errors::{end_dry_run, MiniStabbleError},
+    events::{SwapEvent, SwapResult},
+    instructions::initialize_swap_checkpoint::record_checkpoint_swap,
     math::{
+        common::calc_min_amount_out_for_slippage,
         fixed::{FixedComplement, FixedMul},
-        weighted::calc_out_given_in,
+        swap_context::{SwapContext, SwapQuote},
+    },
+    pool_authority_signer_seeds,
+    state::{
+        check_swaps_enabled, check_trader_allowed, PoolFeeStats, PoolToken, SwapCheckpoint,
+        TraderAllowlistEntry, UserStats, WeightedPool,
     },
-    state::WeightedPool,
+    transfers::{gross_amount_for_net, transfer_fee, transfer_from_user, transfer_from_vault},
 };
 
 #[derive(Accounts)]
@@ -20,112 +36,459 @@ pub struct Swap<'info> {
     )]
     pub pool: Account<'info, WeightedPool>,
 
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
     /// CHECK: Unchecked
-    #[account(seeds = [AUTHORITY], bump)]
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
     pub authority: UncheckedAccount<'info>,
 
-    #[account(constraint = mint_in.key() != mint_out.key())]
-    pub mint_in: Account<'info, Mint>,
-    pub mint_out: Account<'info, Mint>,
+    #[account(constraint = mint_in.key() != mint_out.key(), mint::token_program = token_program_in)]
+    pub mint_in: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_out)]
+    pub mint_out: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::mint = mint_in, token::token_program = token_program_in, token::authority = user)]
+    pub user_token_in: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::mint = mint_out, token::token_program = token_program_out, token::authority = user)]
+    pub user_token_out: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    /// Counts this swap towards the user's volume when passed
+    #[account(mut, seeds = [USER_STATS, user.key().as_ref()], bump = user_stats.bump)]
+    pub user_stats: Option<Account<'info, UserStats>>,
 
-    #[account(mut, token::mint = mint_in, token::authority = user)]
-    pub user_token_in: Account<'info, TokenAccount>,
+    /// Required when the pool only lets allowlisted traders swap
+    #[account(seeds = [TRADER_ALLOWLIST, pool.key().as_ref(), user.key().as_ref()], bump = trader_allowlist_entry.bump)]
+    pub trader_allowlist_entry: Option<Account<'info, TraderAllowlistEntry>>,
 
-    #[account(mut, token::mint = mint_out, token::authority = user)]
-    pub user_token_out: Account<'info, TokenAccount>,
+    /// Aggregates this swap into the pool's checkpoint instead of emitting a `SwapEvent`
+    #[account(mut, seeds = [SWAP_CHECKPOINT, pool.key().as_ref()], bump = swap_checkpoint.bump)]
+    pub swap_checkpoint: Option<Account<'info, SwapCheckpoint>>,
 
-    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_in.key().as_ref()], bump, constraint = vault_token_in.mint == mint_in.key(), token::authority = authority)]
-    pub vault_token_in: Account<'info, TokenAccount>,
+    /// Adds this swap to the pool's per-slot trade stats
+    #[account(mut, seeds = [POOL_FEE_STATS, pool.key().as_ref()], bump = pool_fee_stats.bump)]
+    pub pool_fee_stats: Option<Account<'info, PoolFeeStats>>,
 
-    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_out.key().as_ref()], bump, constraint = vault_token_out.mint == mint_out.key(), token::authority = authority)]
-    pub vault_token_out: Account<'info, TokenAccount>,
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_in.key().as_ref()], bump, token::mint = mint_in, token::token_program = token_program_in, token::authority = authority)]
+    pub vault_token_in: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), mint_out.key().as_ref()], bump, token::mint = mint_out, token::token_program = token_program_out, token::authority = authority)]
+    pub vault_token_out: InterfaceAccount<'info, InterfaceTokenAccount>,
 
     pub user: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program_in: Interface<'info, TokenInterface>,
+    pub token_program_out: Interface<'info, TokenInterface>,
+}
+
+/// How a swap bounds what the user gets back
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlippageLimit {
+    /// Minimum raw amount the user must receive, after any transfer fee
+    MinAmountOut(u64),
+
+    /// Largest shortfall, in bps, of the amount the pool sends against a quote at the pre-swap
+    /// spot price net of the swap fee. Only bounds price impact; transfer fees aren't slippage.
+    Bps(u16),
+}
+
+impl SlippageLimit {
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            SlippageLimit::MinAmountOut(min_amount_out) => {
+                require!(min_amount_out > 0, MiniStabbleError::InvalidAmount)
+            }
+            SlippageLimit::Bps(slippage_bps) => require!(
+                u64::from(slippage_bps) <= BPS_DENOMINATOR,
+                MiniStabbleError::InvalidSlippage
+            ),
+        }
+
+        Ok(())
+    }
+
+    /// Checks a swap in which the pool sends `amount_out` and the user receives
+    /// `amount_received`. `quoted_amount_out` is only evaluated for `Bps`.
+    pub fn check(
+        &self,
+        amount_out: u64,
+        amount_received: u64,
+        quoted_amount_out: impl FnOnce() -> Result<u64>,
+    ) -> Result<()> {
+        let (amount, min_amount) = match *self {
+            SlippageLimit::MinAmountOut(min_amount_out) => (amount_received, min_amount_out),
+            SlippageLimit::Bps(slippage_bps) => (
+                amount_out,
+                calc_min_amount_out_for_slippage(quoted_amount_out()?, slippage_bps)
+                    .ok_or(MiniStabbleError::InvalidSlippage)?,
+            ),
+        };
+        require!(amount >= min_amount, MiniStabbleError::SlippageExceeded);
+
+        Ok(())
+    }
+}
+
+/// Which side of a swap the trader fixes, and the bound on the other side
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapAmount {
+    /// Sell exactly `amount_in`, bounded by `slippage`
+    ExactIn {
+        amount_in: u64,
+        slippage: SlippageLimit,
+    },
+
+    /// Receive exactly `amount_out`, after any transfer fee, paying at most `max_amount_in`
+    ExactOut { amount_out: u64, max_amount_in: u64 },
+}
+
+pub fn handler(
+    ctx: Context<Swap>,
+    amount_in: u64,
+    min_amount_out: u64,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    execute(
+        ctx,
+        amount_in,
+        SlippageLimit::MinAmountOut(min_amount_out),
+        dry_run,
+    )
+}
+
+pub fn execute(
+    ctx: Context<Swap>,
+    amount_in: u64,
+    slippage: SlippageLimit,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    execute_amount(
+        ctx,
+        SwapAmount::ExactIn {
+            amount_in,
+            slippage,
+        },
+        dry_run,
+    )
 }
 
-pub fn handler(ctx: Context<Swap>, amount_in: u64, min_amount_out: u64) -> Result<()> {
+/// `execute` for either side of the trade fixed by `amount`. Both sides are priced by the pool's
+/// `SwapContext`; exact-in swaps take the fee out of the output, exact-out swaps charge it on
+/// the input.
+pub fn execute_amount(ctx: Context<Swap>, amount: SwapAmount, dry_run: bool) -> Result<SwapResult> {
     let pool = &mut ctx.accounts.pool;
     // Step 1 starts
     require!(pool.is_active, MiniStabbleError::PoolInActive);
+    check_trader_allowed(
+        pool.trader_allowlist,
+        ctx.accounts.trader_allowlist_entry.as_deref(),
+    )?;
 
     let mint_in = ctx.accounts.mint_in.key();
     let mint_out = ctx.accounts.mint_out.key();
 
-    let token_0_index = pool
-        .get_token_index(&mint_in)
-        .ok_or(MiniStabbleError::InvalidMint)?;
-    let token_1_index = pool
-        .get_token_index(&mint_out)
+    let [token_0_index, token_1_index] = pool
+        .get_token_pair_indices(&mint_in, &mint_out)
         .ok_or(MiniStabbleError::InvalidMint)?;
+    check_swaps_enabled(&pool.tokens, [token_0_index, token_1_index])?;
 
-    require!(amount_in > 0, MiniStabbleError::InvalidAmount);
-    require!(min_amount_out > 0, MiniStabbleError::InvalidAmount);
+    match amount {
+        SwapAmount::ExactIn {
+            amount_in,
+            slippage,
+        } => {
+            require!(amount_in > 0, MiniStabbleError::InvalidAmount);
+            slippage.validate()?;
+        }
+        SwapAmount::ExactOut { amount_out, .. } => {
+            require!(amount_out > 0, MiniStabbleError::InvalidAmount)
+        }
+    }
     // Step 1 ends
 
     // Step 2 starts
-    let token_in_balance = pool.tokens[token_0_index].balance;
-    let token_in_weight = pool.tokens[token_0_index].weight;
-    let token_out_balance = pool.tokens[token_1_index].balance;
-    let token_out_weight = pool.tokens[token_1_index].weight;
-
-    let swap_fee = pool.swap_fee;
+    let context = pool.swap_context(token_0_index, token_1_index)?;
+    let swap_fee = context.swap_fee;
     // Step 2 ends
 
-    // Step 3 starts - Calculate amount out
-    let amount_out_without_fee = calc_out_given_in(
-        token_in_balance.into(),
-        token_in_weight.into(),
-        token_out_balance.into(),
-        token_out_weight.into(),
-        amount_in.into(),
-    )?;
-    // Step 3 end - Calculate amount out
+    // Step 3 starts - Calculate amount out after the fee, or amount in including it
+    let (token_in, token_out) = (&pool.tokens[token_0_index], &pool.tokens[token_1_index]);
+    let (amount_in, amount_in_net, scaled_amount_in, quote) = match amount {
+        SwapAmount::ExactIn { amount_in, .. } => {
+            // Only what reaches the vault after any transfer fee is swapped
+            let amount_in_net = amount_in
+                .checked_sub(transfer_fee(&ctx.accounts.mint_in, amount_in)?)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+            let (scaled_amount_in, quote) = quote_exact_in(&context, token_in, amount_in_net)?;
+            (amount_in, amount_in_net, scaled_amount_in, quote)
+        }
+        SwapAmount::ExactOut {
+            amount_out,
+            max_amount_in,
+        } => {
+            // The vault sends enough that the user is credited `amount_out` after transfer fees
+            let amount_sent = gross_amount_for_net(&ctx.accounts.mint_out, amount_out)?;
+            let (amount_in_net, scaled_amount_in, quote) =
+                quote_exact_out(&context, token_in, token_out, amount_sent)?;
+            let amount_in = gross_amount_for_net(&ctx.accounts.mint_in, amount_in_net)?;
+            require!(
+                amount_in <= max_amount_in,
+                MiniStabbleError::SlippageExceeded
+            );
+            (amount_in, amount_in_net, scaled_amount_in, quote)
+        }
+    };
+    let scaled_amount_out = quote.amount_out;
+    let amount_out_u64 = token_out.scale_amount_down(scaled_amount_out);
+    require!(amount_out_u64 > 0, MiniStabbleError::AmountTooSmall);
+    // Step 3 ends - Calculate amount out after the fee, or amount in including it
 
-    // Step 4 starts - Apply fee
-    let amount_out_after_fee = amount_out_without_fee.mul_down(swap_fee.complement() as u128)?;
-    // Step 4 ends - Apply fee
+    // Step 4 starts - Slippage check against what the user actually receives
+    if let SwapAmount::ExactIn { slippage, .. } = amount {
+        let amount_received = amount_out_u64
+            .checked_sub(transfer_fee(&ctx.accounts.mint_out, amount_out_u64)?)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        slippage.check(amount_out_u64, amount_received, || {
+            let price = pool.spot_price(token_0_index, token_1_index)?;
+            let quoted_scaled = (scaled_amount_in as u128)
+                .mul_down(price)?
+                .mul_down(swap_fee.complement() as u128)?;
+            Ok(pool.tokens[token_1_index].scale_amount_down(u64::try_from(quoted_scaled)?))
+        })?;
+    }
+    // Step 4 ends - Slippage Check
+
+    let balances_after = context.balances_after(scaled_amount_in, scaled_amount_out)?;
+    pool.check_spot_price_move(
+        token_0_index,
+        token_1_index,
+        balances_after[token_0_index],
+        balances_after[token_1_index],
+    )?;
 
-    // Step 5 starts - Slippage check
+    // Circuit breaker: a swap that drains value per LP is refused; `trip_breaker` pauses a pool
+    // that is already drawn down
     require!(
-        amount_out_after_fee >= u128::from(min_amount_out),
-        MiniStabbleError::SlippageExceeded
+        pool.trips_drawdown_guard(&balances_after, ctx.accounts.lp_mint.supply)
+            .is_none(),
+        MiniStabbleError::DrawdownGuardTripped
     );
-    // Step 5 ends - Slippage Check
 
-    // Step 6 starts -  Transfer Tokens
-    let cpi_accounts_in = Transfer {
-        from: ctx.accounts.user_token_in.to_account_info(),
-        to: ctx.accounts.vault_token_in.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-    };
-    let cpi_ctx_in = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        cpi_accounts_in,
-    );
-    token::transfer(cpi_ctx_in, amount_in)?;
+    // Step 5 starts -  Transfer Tokens
+    transfer_from_user(
+        &ctx.accounts.token_program_in,
+        &ctx.accounts.mint_in,
+        &ctx.accounts.user_token_in,
+        &ctx.accounts.vault_token_in,
+        &ctx.accounts.user,
+        amount_in,
+    )?;
+
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+
+    transfer_from_vault(
+        &ctx.accounts.token_program_out,
+        &ctx.accounts.mint_out,
+        &ctx.accounts.vault_token_out,
+        &ctx.accounts.user_token_out,
+        &ctx.accounts.authority,
+        signer_seeds,
+        amount_out_u64,
+    )?;
+    // Step 5 ends
+
+    // Step 6 - Update pool state
+    let price_before = ctx
+        .accounts
+        .pool_fee_stats
+        .is_some()
+        .then(|| pool.spot_price(0, 1))
+        .transpose()?;
+    pool.tokens[token_0_index].balance = pool.tokens[token_0_index]
+        .balance
+        .checked_add(scaled_amount_in)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    pool.tokens[token_1_index].debit_payout(scaled_amount_out, amount_out_u64)?;
 
-    let cpi_accounts_out = Transfer {
-        from: ctx.accounts.vault_token_out.to_account_info(),
-        to: ctx.accounts.user_token_out.to_account_info(),
-        authority: ctx.accounts.authority.to_account_info(),
+    if let (Some(pool_fee_stats), Some(price_before)) =
+        (ctx.accounts.pool_fee_stats.as_mut(), price_before)
+    {
+        let (amount_0, amount_1) = if token_0_index == 0 {
+            (amount_in_net, amount_out_u64)
+        } else {
+            (amount_out_u64, amount_in_net)
+        };
+        pool_fee_stats.record_trade(
+            Clock::get()?.slot,
+            amount_0,
+            amount_1,
+            price_before,
+            pool.spot_price(0, 1)?,
+        );
+    }
+
+    if let Some(user_stats) = ctx.accounts.user_stats.as_mut() {
+        user_stats.record_swap(scaled_amount_in, Clock::get()?.unix_timestamp);
+    }
+
+    if let Some(swap_checkpoint) = ctx.accounts.swap_checkpoint.as_mut() {
+        record_checkpoint_swap(
+            swap_checkpoint,
+            Clock::get()?.slot,
+            pool.tokens.len(),
+            token_0_index,
+            amount_in,
+            token_1_index,
+            amount_out_u64,
+            pool.tokens[token_1_index].scale_amount_down(quote.fee),
+        );
+    } else {
+        emit!(SwapEvent {
+            pool: pool.key(),
+            user: ctx.accounts.user.key(),
+            mint_in,
+            mint_out,
+            amount_in,
+            amount_out: amount_out_u64,
+        });
+    }
+
+    let result = SwapResult {
+        amount_in,
+        amount_out: amount_out_u64,
+        fee: pool.tokens[token_1_index].scale_amount_down(quote.fee),
+        protocol_fee: 0,
+        spot_price_after: pool.spot_price(token_0_index, token_1_index).unwrap_or(0),
     };
+    result.log()?;
 
-    let seeds = [AUTHORITY, &[ctx.bumps.authority]];
-    let signer_seeds = &[&seeds[..]];
+    end_dry_run(dry_run)?;
 
-    let cpi_ctx_out = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        cpi_accounts_out,
-        signer_seeds,
-    );
+    Ok(result)
+}
+
+/// Scaled amount in and quote of an exact-in swap of `amount_in_net` raw `token_in`, what
+/// reached the vault after any transfer fee
+fn quote_exact_in(
+    context: &SwapContext,
+    token_in: &PoolToken,
+    amount_in_net: u64,
+) -> Result<(u64, SwapQuote)> {
+    let scaled_amount_in = token_in.scale_amount_up(amount_in_net);
+
+    Ok((
+        scaled_amount_in,
+        context.calc_out_given_in(scaled_amount_in)?,
+    ))
+}
+
+/// Raw amount in, rounded up to whole raw units, its scaled value and the quote of an exact-out
+/// swap sending `amount_sent` raw `token_out`. The rounding remainder stays in the pool.
+fn quote_exact_out(
+    context: &SwapContext,
+    token_in: &PoolToken,
+    token_out: &PoolToken,
+    amount_sent: u64,
+) -> Result<(u64, u64, SwapQuote)> {
+    let scaled_amount_out = token_out.scale_amount_up(amount_sent);
+    let in_quote = context.calc_in_given_out(scaled_amount_out)?;
+    let amount_in_net = in_quote.amount_in.div_ceil(token_in.scaling_factor);
+
+    Ok((
+        amount_in_net,
+        token_in.scale_amount_up(amount_in_net),
+        SwapQuote {
+            amount_out: scaled_amount_out,
+            fee: in_quote.fee,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::fixed::ONE_U64;
+
+    const FEE: u64 = 3_000_000;
+
+    fn token(decimals: u8) -> PoolToken {
+        PoolToken {
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            decimals,
+            scaling_factor: 10u64.pow(9 - decimals as u32),
+            balance: 1_000_000 * ONE_U64,
+            weight: ONE_U64 / 2,
+            swaps_disabled: false,
+            rounding_dust: 0,
+            internal_balances: 0,
+            queued_withdrawals: 0,
+            referral_rewards: 0,
+        }
+    }
+
+    fn context(tokens: &[PoolToken; 2], index_in: usize) -> SwapContext {
+        SwapContext::weighted(
+            tokens.iter().map(|token| token.balance).collect(),
+            tokens.iter().map(|token| token.weight).collect(),
+            None,
+            FEE,
+            index_in,
+            1 - index_in,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_exact_in_executes_the_quote_across_decimals() {
+        // 1M of a 6-decimal token against 1M of a 9-decimal one, priced 1:1
+        let tokens = [token(6), token(9)];
+
+        for index_in in [0, 1] {
+            let (token_in, token_out) = (&tokens[index_in], &tokens[1 - index_in]);
+            let context = context(&tokens, index_in);
+            let amount_in = 100 * 10u64.pow(token_in.decimals as u32);
+
+            // What `quote_route` quotes for the hop
+            let quoted = context
+                .calc_out_given_in(token_in.scale_amount_up(amount_in))
+                .unwrap();
+            let (scaled_amount_in, quote) = quote_exact_in(&context, token_in, amount_in).unwrap();
+            assert_eq!(quote, quoted);
+            assert_eq!(scaled_amount_in, 100 * ONE_U64);
+
+            // ~99.7 whole tokens out, in the out token's own decimals
+            let amount_out = token_out.scale_amount_down(quote.amount_out);
+            let unit_out = 10u64.pow(token_out.decimals as u32);
+            assert!(amount_out > 99 * unit_out && amount_out < 100 * unit_out);
+        }
+    }
+
+    #[test]
+    fn test_exact_out_pays_what_exact_in_quotes_across_decimals() {
+        let tokens = [token(6), token(9)];
+
+        for index_in in [0, 1] {
+            let (token_in, token_out) = (&tokens[index_in], &tokens[1 - index_in]);
+            let context = context(&tokens, index_in);
+            let amount_sent = 50 * 10u64.pow(token_out.decimals as u32);
+
+            let (amount_in_net, scaled_amount_in, quote) =
+                quote_exact_out(&context, token_in, token_out, amount_sent).unwrap();
+            assert_eq!(quote.amount_out, token_out.scale_amount_up(amount_sent));
+            assert_eq!(scaled_amount_in, token_in.scale_amount_up(amount_in_net));
+            assert!(quote.fee > 0);
 
-    let amount_out_u64 = amount_out_after_fee.try_into()?;
-    token::transfer(cpi_ctx_out, amount_out_u64)?;
-    // Step 6 ends
+            // ~50.15 whole tokens in, in the in token's own decimals
+            let unit_in = 10u64.pow(token_in.decimals as u32);
+            assert!(amount_in_net > 50 * unit_in && amount_in_net < 51 * unit_in);
 
-    // Step 7 - Update pool state
-    pool.tokens[token_0_index].balance += amount_in;
-    pool.tokens[token_1_index].balance -= amount_out_u64;
-    Ok(())
+            // Selling the charged input back through the context pays the requested output, up
+            // to the curve's rounding against the trader
+            let (_, paid) = quote_exact_in(&context, token_in, amount_in_net).unwrap();
+            let paid = token_out.scale_amount_down(paid.amount_out);
+            assert!(paid.abs_diff(amount_sent) * 100_000 < amount_sent);
+        }
+    }
 }
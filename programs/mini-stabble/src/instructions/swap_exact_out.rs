@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::SwapResult,
+    instructions::{swap, Swap, SwapAmount},
+};
+
+/// `swap` with the output fixed instead of the input: the user receives exactly `amount_out` of
+/// `mint_out`, after any transfer fee, and pays the input `calc_in_given_out` requires grossed
+/// up by the swap fee, failing above `max_amount_in`
+pub fn handler(
+    ctx: Context<Swap>,
+    amount_out: u64,
+    max_amount_in: u64,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    swap::execute_amount(
+        ctx,
+        SwapAmount::ExactOut {
+            amount_out,
+            max_amount_in,
+        },
+        dry_run,
+    )
+}
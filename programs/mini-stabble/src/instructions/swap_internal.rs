@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    constants::INTERNAL_BALANCE,
+    errors::MiniStabbleError,
+    events::SwapEvent,
+    instructions::{
+        compound_operation::apply_swap_exact_in,
+        rebalance::{LegPool, PoolKind},
+    },
+    state::{hold_internal_balance, release_internal_balance, InternalBalance},
+};
+
+#[derive(Accounts)]
+pub struct SwapInternal<'info> {
+    /// Checked against the pool, passed as the only remaining account
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [INTERNAL_BALANCE, balance_in.pool.as_ref(), user.key().as_ref(), balance_in.mint.as_ref()],
+        bump = balance_in.bump,
+    )]
+    pub balance_in: Account<'info, InternalBalance>,
+
+    #[account(
+        mut,
+        seeds = [INTERNAL_BALANCE, balance_in.pool.as_ref(), user.key().as_ref(), balance_out.mint.as_ref()],
+        bump = balance_out.bump,
+        constraint = balance_out.mint != balance_in.mint @ MiniStabbleError::InvalidMint,
+    )]
+    pub balance_out: Account<'info, InternalBalance>,
+
+    pub user: Signer<'info>,
+}
+
+/// Swaps `amount_in` raw of the user's internal balance in `balance_in.mint` for
+/// `balance_out.mint`, priced and guarded like a routed swap, without moving any tokens: the
+/// pool's balances and the two ledgers are updated in place. The pool is the only remaining
+/// account.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapInternal<'info>>,
+    pool_kind: PoolKind,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<u64> {
+    require!(amount_in > 0, MiniStabbleError::InvalidAmount);
+    let [pool_info] = ctx.remaining_accounts else {
+        return err!(MiniStabbleError::InvalidAccount);
+    };
+
+    let mut pool = LegPool::load(pool_kind, pool_info)?;
+    let balance_in = &mut ctx.accounts.balance_in;
+    let balance_out = &mut ctx.accounts.balance_out;
+    require_keys_eq!(
+        pool.key(),
+        balance_in.pool,
+        MiniStabbleError::InvalidAccount
+    );
+    require_keys_eq!(
+        pool.lp_mint(),
+        ctx.accounts.lp_mint.key(),
+        MiniStabbleError::InvalidAccount
+    );
+
+    let [index_in, index_out] = pool
+        .get_token_pair_indices(&balance_in.mint, &balance_out.mint)
+        .ok_or(MiniStabbleError::InvalidMint)?;
+
+    balance_in.debit(amount_in)?;
+
+    let clock = Clock::get()?;
+    let amount_out = apply_swap_exact_in(
+        &mut pool,
+        index_in,
+        index_out,
+        amount_in,
+        ctx.accounts.lp_mint.supply,
+        clock.slot,
+        clock.unix_timestamp,
+    )?;
+    require!(
+        amount_out >= min_amount_out,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    balance_out.credit(amount_out)?;
+    // The amount in now backs the pool, and the amount out is held for the user instead
+    release_internal_balance(pool.tokens_mut(), &balance_in.mint, amount_in)?;
+    hold_internal_balance(pool.tokens_mut(), &balance_out.mint, amount_out)?;
+    pool.persist()?;
+
+    emit!(SwapEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        mint_in: balance_in.mint,
+        mint_out: balance_out.mint,
+        amount_in,
+        amount_out,
+    });
+
+    Ok(amount_out)
+}
@@ -0,0 +1,303 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Mint,
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    errors::MiniStabbleError,
+    instructions::{
+        compound_operation::apply_swap_exact_in, quote_route::RouteHop, rebalance::LegPool,
+    },
+    pool_authority_signer_seeds,
+    state::PoolToken,
+    transfers::{load_token_program, transfer_fee, transfer_from_user, transfer_from_vault},
+};
+
+/// Number of remaining accounts consumed by each hop:
+/// `[pool, pool_authority, lp_mint, vault_in, vault_out, mint_out, token_program_out]`
+pub const ACCOUNTS_PER_HOP: usize = 7;
+
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    #[account(mint::token_program = token_program_in)]
+    pub mint_in: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::mint = mint_in, token::token_program = token_program_in, token::authority = user)]
+    pub user_token_in: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    /// Mint checked against the last hop's out token
+    #[account(mut, token::authority = user)]
+    pub user_token_out: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program_in: Interface<'info, TokenInterface>,
+}
+
+/// One loaded hop of a route
+struct RouteLeg<'info> {
+    pool: LegPool<'info>,
+    authority: &'info AccountInfo<'info>,
+    vault_in: InterfaceAccount<'info, InterfaceTokenAccount>,
+    vault_out: InterfaceAccount<'info, InterfaceTokenAccount>,
+    mint_out: InterfaceAccount<'info, InterfaceMint>,
+    token_program_out: Interface<'info, TokenInterface>,
+    /// Raw amount `vault_out` owes once every hop is booked
+    amount_out: u64,
+}
+
+/// Swaps `amount_in` along `hops` (the same route `quote_route` prices) with flash
+/// accounting: every hop is booked on its pool's balances first, then tokens move once per
+/// hop boundary, straight from each pool's out vault into the next pool's in vault. The user
+/// only sends the route's input and receives its output; intermediate tokens never pass
+/// through their accounts. A pool can appear at most once per route.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+    hops: Vec<RouteHop>,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<u64> {
+    require!(!hops.is_empty(), MiniStabbleError::InvalidAmount);
+    require!(amount_in > 0, MiniStabbleError::InvalidAmount);
+    require!(
+        ctx.remaining_accounts.len() == hops.len() * ACCOUNTS_PER_HOP,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let clock = Clock::get()?;
+
+    // Raw amount the next hop's in vault is credited with, after transfer fees
+    let mut amount = amount_in
+        .checked_sub(transfer_fee(&ctx.accounts.mint_in, amount_in)?)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let mut mint_in = ctx.accounts.mint_in.key();
+    let mut legs: Vec<RouteLeg> = Vec::with_capacity(hops.len());
+
+    for (hop, accounts) in hops
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(ACCOUNTS_PER_HOP))
+    {
+        require!(
+            legs.iter().all(|leg| leg.pool.key() != accounts[0].key()),
+            MiniStabbleError::InvalidAccount
+        );
+        let mut pool = LegPool::load(hop.pool_kind, &accounts[0])?;
+        pool.check_authority(&accounts[1])?;
+        let lp_mint = Account::<Mint>::try_from(&accounts[2])?;
+        let vault_in = InterfaceAccount::<InterfaceTokenAccount>::try_from(&accounts[3])?;
+        let vault_out = InterfaceAccount::<InterfaceTokenAccount>::try_from(&accounts[4])?;
+        let mint_out = InterfaceAccount::<InterfaceMint>::try_from(&accounts[5])?;
+        let token_program_out = load_token_program(&accounts[6], &mint_out)?;
+
+        let (index_in, index_out) = check_hop(
+            hop,
+            pool.tokens(),
+            [mint_in, mint_out.key()],
+            [vault_in.key(), vault_out.key()],
+        )?;
+        require_keys_eq!(
+            lp_mint.key(),
+            pool.lp_mint(),
+            MiniStabbleError::InvalidAccount
+        );
+
+        let amount_out = apply_swap_exact_in(
+            &mut pool,
+            index_in,
+            index_out,
+            amount,
+            lp_mint.supply,
+            clock.slot,
+            clock.unix_timestamp,
+        )?;
+
+        amount = amount_out
+            .checked_sub(transfer_fee(&mint_out, amount_out)?)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        mint_in = mint_out.key();
+        legs.push(RouteLeg {
+            pool,
+            authority: &accounts[1],
+            vault_in,
+            vault_out,
+            mint_out,
+            token_program_out,
+            amount_out,
+        });
+    }
+
+    require_keys_eq!(
+        ctx.accounts.user_token_out.mint,
+        mint_in,
+        MiniStabbleError::InvalidMint
+    );
+
+    transfer_from_user(
+        &ctx.accounts.token_program_in,
+        &ctx.accounts.mint_in,
+        &ctx.accounts.user_token_in,
+        &legs[0].vault_in,
+        &ctx.accounts.user,
+        amount_in,
+    )?;
+
+    let mut received = 0u64;
+    for (i, leg) in legs.iter().enumerate() {
+        let pool_key = leg.pool.key();
+        let signer_seeds = pool_authority_signer_seeds!(pool_key, leg.pool.authority_bump());
+        received = match legs.get(i + 1) {
+            Some(next) => transfer_from_vault(
+                &leg.token_program_out,
+                &leg.mint_out,
+                &leg.vault_out,
+                &next.vault_in,
+                leg.authority,
+                signer_seeds,
+                leg.amount_out,
+            )?,
+            None => transfer_from_vault(
+                &leg.token_program_out,
+                &leg.mint_out,
+                &leg.vault_out,
+                &ctx.accounts.user_token_out,
+                leg.authority,
+                signer_seeds,
+                leg.amount_out,
+            )?,
+        };
+        leg.pool.persist()?;
+    }
+
+    require!(
+        received >= min_amount_out,
+        MiniStabbleError::SlippageExceeded
+    );
+
+    Ok(received)
+}
+
+/// Checks `hop` swaps between two distinct tokens of a pool holding `tokens`, from `mints[0]`
+/// to `mints[1]` through `vaults` in the same order. Returns the hop's `(index_in, index_out)`.
+fn check_hop(
+    hop: &RouteHop,
+    tokens: &[PoolToken],
+    mints: [Pubkey; 2],
+    vaults: [Pubkey; 2],
+) -> Result<(usize, usize)> {
+    let indices = [hop.token_in_index as usize, hop.token_out_index as usize];
+    require!(
+        indices[0] != indices[1] && indices.iter().all(|&index| index < tokens.len()),
+        MiniStabbleError::InvalidMint
+    );
+
+    for i in 0..2 {
+        let token = &tokens[indices[i]];
+        require_keys_eq!(token.mint, mints[i], MiniStabbleError::InvalidMint);
+        require_keys_eq!(
+            token.token_account,
+            vaults[i],
+            MiniStabbleError::InvalidAccount
+        );
+    }
+
+    Ok((indices[0], indices[1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{instructions::rebalance::PoolKind, math::fixed::ONE_U64};
+
+    fn tokens() -> Vec<PoolToken> {
+        (0..3)
+            .map(|_| PoolToken {
+                mint: Pubkey::new_unique(),
+                token_account: Pubkey::new_unique(),
+                decimals: 9,
+                scaling_factor: 1,
+                balance: 1_000 * ONE_U64,
+                weight: 0,
+                swaps_disabled: false,
+                rounding_dust: 0,
+                internal_balances: 0,
+                queued_withdrawals: 0,
+                referral_rewards: 0,
+            })
+            .collect()
+    }
+
+    fn hop(token_in_index: u8, token_out_index: u8) -> RouteHop {
+        RouteHop {
+            pool_kind: PoolKind::Stable,
+            token_in_index,
+            token_out_index,
+        }
+    }
+
+    #[test]
+    fn test_check_hop_accepts_the_pool_tokens_and_vaults() {
+        let tokens = tokens();
+        let mints = [tokens[2].mint, tokens[0].mint];
+        let vaults = [tokens[2].token_account, tokens[0].token_account];
+
+        assert_eq!(
+            check_hop(&hop(2, 0), &tokens, mints, vaults).unwrap(),
+            (2, 0)
+        );
+    }
+
+    #[test]
+    fn test_check_hop_rejects_bad_indices() {
+        let tokens = tokens();
+        let mints = [tokens[0].mint, tokens[0].mint];
+        let vaults = [tokens[0].token_account, tokens[0].token_account];
+
+        assert_eq!(
+            check_hop(&hop(0, 0), &tokens, mints, vaults),
+            Err(MiniStabbleError::InvalidMint.into())
+        );
+        assert_eq!(
+            check_hop(&hop(0, 3), &tokens, mints, vaults),
+            Err(MiniStabbleError::InvalidMint.into())
+        );
+    }
+
+    #[test]
+    fn test_check_hop_rejects_a_broken_chain_or_foreign_vault() {
+        let tokens = tokens();
+        let vaults = [tokens[0].token_account, tokens[1].token_account];
+
+        // The previous hop's out token isn't this hop's in token
+        assert_eq!(
+            check_hop(
+                &hop(0, 1),
+                &tokens,
+                [Pubkey::new_unique(), tokens[1].mint],
+                vaults
+            ),
+            Err(MiniStabbleError::InvalidMint.into())
+        );
+        assert_eq!(
+            check_hop(
+                &hop(0, 1),
+                &tokens,
+                [tokens[0].mint, tokens[2].mint],
+                vaults
+            ),
+            Err(MiniStabbleError::InvalidMint.into())
+        );
+        assert_eq!(
+            check_hop(
+                &hop(0, 1),
+                &tokens,
+                [tokens[0].mint, tokens[1].mint],
+                [tokens[0].token_account, Pubkey::new_unique()]
+            ),
+            Err(MiniStabbleError::InvalidAccount.into())
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::spl_token::native_mint,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    errors::MiniStabbleError,
+    events::SwapResult,
+    instructions::{swap, SlippageLimit, Swap},
+    transfers::transfer_from_user,
+};
+
+/// Wrapped SOL account a relayer is repaid into, taken from the remaining accounts
+pub(crate) struct FeeReimbursement<'info> {
+    token_program: Interface<'info, TokenInterface>,
+    mint_out: InterfaceAccount<'info, Mint>,
+    user_token_out: InterfaceAccount<'info, TokenAccount>,
+    user: Signer<'info>,
+    to: InterfaceAccount<'info, TokenAccount>,
+    lamports: u64,
+}
+
+impl<'info> FeeReimbursement<'info> {
+    /// Checks the swap pays out wrapped SOL and `remaining_accounts` is the wrapped SOL
+    /// account to repay
+    pub(crate) fn load(
+        token_program: &Interface<'info, TokenInterface>,
+        mint_out: &InterfaceAccount<'info, Mint>,
+        user_token_out: &InterfaceAccount<'info, TokenAccount>,
+        user: &Signer<'info>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        lamports: u64,
+    ) -> Result<Self> {
+        let [to_info] = remaining_accounts else {
+            return err!(MiniStabbleError::InvalidAccount);
+        };
+        let to = InterfaceAccount::<TokenAccount>::try_from(to_info)?;
+        check_reimbursement(lamports, mint_out.key(), to.mint)?;
+
+        Ok(Self {
+            token_program: token_program.clone(),
+            mint_out: mint_out.clone(),
+            user_token_out: user_token_out.clone(),
+            user: user.clone(),
+            to,
+            lamports,
+        })
+    }
+
+    /// What the user has to receive from the swap for `min_amount_out` to be left over
+    pub(crate) fn slippage(&self, min_amount_out: u64) -> Result<SlippageLimit> {
+        reimbursed_slippage(min_amount_out, self.lamports)
+    }
+
+    /// Carves the reimbursement out of the swap's proceeds
+    pub(crate) fn pay(&self) -> Result<u64> {
+        transfer_from_user(
+            &self.token_program,
+            &self.mint_out,
+            &self.user_token_out,
+            &self.to,
+            &self.user,
+            self.lamports,
+        )
+    }
+}
+
+/// Checks a swap paying out `mint_out` can repay `lamports` of it into an account of `to_mint`:
+/// both must be wrapped SOL
+fn check_reimbursement(lamports: u64, mint_out: Pubkey, to_mint: Pubkey) -> Result<()> {
+    require!(lamports > 0, MiniStabbleError::InvalidAmount);
+    require_keys_eq!(mint_out, native_mint::ID, MiniStabbleError::InvalidMint);
+    require_keys_eq!(to_mint, native_mint::ID, MiniStabbleError::InvalidMint);
+
+    Ok(())
+}
+
+/// Swap slippage limit leaving the user `min_amount_out` once `lamports` are carved out
+fn reimbursed_slippage(min_amount_out: u64, lamports: u64) -> Result<SlippageLimit> {
+    Ok(SlippageLimit::MinAmountOut(
+        min_amount_out
+            .checked_add(lamports)
+            .ok_or(MiniStabbleError::MathOverflow)?,
+    ))
+}
+
+/// `swap` into wrapped SOL that repays a relayer `reimbursement_lamports` of the output, sent
+/// to the wrapped SOL account passed as the only remaining account. `min_amount_out` bounds
+/// what the user keeps after the reimbursement.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    reimbursement_lamports: u64,
+) -> Result<SwapResult> {
+    let reimbursement = FeeReimbursement::load(
+        &ctx.accounts.token_program_out,
+        &ctx.accounts.mint_out,
+        &ctx.accounts.user_token_out,
+        &ctx.accounts.user,
+        ctx.remaining_accounts,
+        reimbursement_lamports,
+    )?;
+    let slippage = reimbursement.slippage(min_amount_out)?;
+
+    let result = swap::execute(ctx, amount_in, slippage, false)?;
+    reimbursement.pay()?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reimbursement_requires_wrapped_sol_on_both_ends() {
+        assert!(check_reimbursement(5_000, native_mint::ID, native_mint::ID).is_ok());
+        assert_eq!(
+            check_reimbursement(5_000, Pubkey::new_unique(), native_mint::ID),
+            Err(MiniStabbleError::InvalidMint.into())
+        );
+        assert_eq!(
+            check_reimbursement(5_000, native_mint::ID, Pubkey::new_unique()),
+            Err(MiniStabbleError::InvalidMint.into())
+        );
+    }
+
+    #[test]
+    fn test_reimbursement_of_nothing_is_rejected() {
+        assert_eq!(
+            check_reimbursement(0, native_mint::ID, native_mint::ID),
+            Err(MiniStabbleError::InvalidAmount.into())
+        );
+    }
+
+    #[test]
+    fn test_slippage_covers_the_reimbursement() {
+        assert_eq!(
+            reimbursed_slippage(1_000_000, 5_000).unwrap(),
+            SlippageLimit::MinAmountOut(1_005_000)
+        );
+        assert_eq!(
+            reimbursed_slippage(u64::MAX, 1),
+            Err(MiniStabbleError::MathOverflow.into())
+        );
+    }
+}
@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::SwapResult,
+    instructions::{swap, SlippageLimit, Swap},
+};
+
+/// `swap` with the tolerance given as `slippage_bps` below the on-chain spot price quote, so
+/// clients don't have to compute an exact `min_amount_out`
+pub fn handler(
+    ctx: Context<Swap>,
+    amount_in: u64,
+    slippage_bps: u16,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    swap::execute(ctx, amount_in, SlippageLimit::Bps(slippage_bps), dry_run)
+}
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    constants::{AUTHORITY, CONFIG, POOL_PARAMS, POOL_VAULT},
+    errors::MiniStabbleError,
+    events::RoundingDustSweptEvent,
+    instructions::rebalance::{LegPool, PoolKind},
+    pool_authority_signer_seeds,
+    state::{Config, DustDestination, PoolParams},
+    transfers::transfer_from_vault,
+};
+
+#[derive(Accounts)]
+pub struct SweepRoundingDust<'info> {
+    #[account(seeds = [CONFIG], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// Pool passed as the only remaining account
+    #[account(seeds = [POOL_PARAMS, pool_params.pool.as_ref()], bump = pool_params.bump)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, seeds = [POOL_VAULT, pool_params.pool.as_ref(), mint.key().as_ref()], bump, token::mint = mint, token::token_program = token_program, token::authority = authority)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Required when the dust goes to the protocol
+    #[account(mut, token::mint = mint, token::token_program = token_program, token::authority = config.treasury)]
+    pub treasury_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool_params.pool.as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Permissionless: hands `mint`'s rounding dust to the pool's `DustDestination`, either back
+/// into its balance or, in whole raw units, to the treasury. Every scaled unit a vault holds is
+/// then owned by the pool's balance, its unswept dust, or a claim recorded elsewhere.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SweepRoundingDust<'info>>,
+    pool_kind: PoolKind,
+) -> Result<()> {
+    let [pool_info] = ctx.remaining_accounts else {
+        return err!(MiniStabbleError::InvalidAccount);
+    };
+
+    let mut pool = LegPool::load(pool_kind, pool_info)?;
+    require_keys_eq!(
+        pool.key(),
+        ctx.accounts.pool_params.pool,
+        MiniStabbleError::InvalidAccount
+    );
+
+    let index = pool
+        .tokens()
+        .iter()
+        .position(|token| token.mint == ctx.accounts.mint.key())
+        .ok_or(MiniStabbleError::InvalidMint)?;
+
+    let destination = ctx.accounts.pool_params.dust_destination;
+    let token = &mut pool.tokens_mut()[index];
+    let amount = match destination {
+        DustDestination::Lps => token.rounding_dust,
+        // Sub-unit remainders can't be transferred and wait for more dust
+        DustDestination::Protocol => {
+            token.scale_amount_up(token.scale_amount_down(token.rounding_dust))
+        }
+    };
+    if amount == 0 {
+        return Ok(());
+    }
+    token.rounding_dust -= amount;
+
+    match destination {
+        DustDestination::Lps => {
+            token.balance = token
+                .balance
+                .checked_add(amount)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+            pool.invalidate_invariant();
+        }
+        DustDestination::Protocol => {
+            let raw_amount = token.scale_amount_down(amount);
+            let treasury_token = ctx
+                .accounts
+                .treasury_token
+                .as_ref()
+                .ok_or(MiniStabbleError::InvalidAccount)?;
+            let pool_key = ctx.accounts.pool_params.pool;
+            let signer_seeds = pool_authority_signer_seeds!(pool_key, ctx.bumps.authority);
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                &ctx.accounts.vault,
+                treasury_token,
+                &ctx.accounts.authority,
+                signer_seeds,
+                raw_amount,
+            )?;
+        }
+    }
+
+    pool.persist()?;
+
+    emit!(RoundingDustSweptEvent {
+        pool: pool.key(),
+        mint: ctx.accounts.mint.key(),
+        destination,
+        amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    errors::MiniStabbleError,
+    events::DrawdownGuardTrippedEvent,
+    instructions::rebalance::{LegPool, PoolKind},
+};
+
+#[derive(Accounts)]
+pub struct TripBreaker<'info> {
+    /// Pool passed as the only remaining account
+    pub lp_mint: Account<'info, Mint>,
+}
+
+/// Permissionless: pauses a pool whose invariant per LP token already sits further below its
+/// drawdown guard's high-water mark than the guard allows, e.g. after a balance loss was
+/// reconciled. Swaps that would cause such a drop fail instead. Resuming stays with the admin.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TripBreaker<'info>>,
+    pool_kind: PoolKind,
+) -> Result<()> {
+    let [pool_info] = ctx.remaining_accounts else {
+        return err!(MiniStabbleError::InvalidAccount);
+    };
+    require!(pool_info.is_writable, MiniStabbleError::InvalidAccount);
+
+    let mut pool = LegPool::load(pool_kind, pool_info)?;
+    require_keys_eq!(
+        ctx.accounts.lp_mint.key(),
+        pool.lp_mint(),
+        MiniStabbleError::InvalidAccount
+    );
+    require!(pool.is_active(), MiniStabbleError::PoolInActive);
+
+    let virtual_price = pool
+        .trips_drawdown_guard(ctx.accounts.lp_mint.supply, Clock::get()?.unix_timestamp)
+        .ok_or(MiniStabbleError::DrawdownGuardNotTripped)?;
+    pool.set_active(false);
+    pool.persist()?;
+
+    emit!(DrawdownGuardTrippedEvent {
+        pool: pool.key(),
+        virtual_price,
+        high_water_mark: pool.drawdown_high_water_mark(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{POOL_PARAMS, WEIGHT_POOL},
+    events::FeeUpdatedEvent,
+    state::{
+        PoolParams, WeightedPool, FEE_AUTOPILOT_KEEPER_MIN_INTERVAL, FEE_AUTOPILOT_MIN_INTERVAL,
+    },
+};
+
+#[derive(Accounts)]
+pub struct UpdateFeeFromVolatility<'info> {
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(mut, seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    /// One of the pool's crank keepers, for the shorter sampling interval
+    pub keeper: Option<Signer<'info>>,
+}
+
+/// Permissionless crank: samples the pool's spot price and moves the swap fee to match the
+/// realized volatility. Callable at most once every `FEE_AUTOPILOT_MIN_INTERVAL` seconds, or
+/// every `FEE_AUTOPILOT_KEEPER_MIN_INTERVAL` seconds when signed by a registered crank keeper.
+pub fn handler(ctx: Context<UpdateFeeFromVolatility>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let pool_params = &mut ctx.accounts.pool_params;
+    let min_interval = if pool_params.is_crank_keeper(ctx.accounts.keeper.as_ref().map(|k| k.key)) {
+        FEE_AUTOPILOT_KEEPER_MIN_INTERVAL
+    } else {
+        FEE_AUTOPILOT_MIN_INTERVAL
+    };
+    let fee_autopilot = &mut pool_params.fee_autopilot;
+    let now = Clock::get()?.unix_timestamp;
+
+    let price = pool.spot_price(0, 1)?;
+    let swap_fee = fee_autopilot.sample(price, now, min_interval)?;
+    pool.swap_fee = swap_fee;
+
+    emit!(FeeUpdatedEvent {
+        pool: pool.key(),
+        volatility_bps: fee_autopilot.volatility_bps,
+        swap_fee,
+    });
+
+    Ok(())
+}
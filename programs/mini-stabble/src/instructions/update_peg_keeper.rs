@@ -0,0 +1,209 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount};
+
+use crate::{
+    constants::{AUTHORITY, PEG_KEEPER, POOL_VAULT, STABLE_POOL},
+    errors::MiniStabbleError,
+    events::PegKeeperActionEvent,
+    math::{
+        fixed::{FixedComplement, FixedDiv},
+        stable::{calc_invariant, calc_lp_tokens_for_deposit_with_fee},
+    },
+    pool_authority_signer_seeds,
+    state::{PegAction, PegKeeper, StablePool, PEG_KEEPER_ACTION_DELAY},
+    transfers::mint_lp,
+};
+
+#[derive(Accounts)]
+pub struct UpdatePegKeeper<'info> {
+    #[account(
+        mut,
+        seeds = [PEG_KEEPER, pool.key().as_ref()],
+        bump = peg_keeper.bump,
+        has_one = pool,
+        has_one = stable_mint,
+    )]
+    pub peg_keeper: Account<'info, PegKeeper>,
+
+    #[account(mut, seeds = [STABLE_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, StablePool>,
+
+    #[account(mut, address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub stable_mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [POOL_VAULT, pool.key().as_ref(), stable_mint.key().as_ref()], bump, token::mint = stable_mint, token::authority = authority)]
+    pub stable_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, associated_token::mint = lp_mint, associated_token::authority = peg_keeper)]
+    pub keeper_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Permissionless crank: while the stable trades above peg, mints stable into the pool against
+/// LP; while below, burns keeper LP for stable and burns it. Each action closes at most half
+/// the pool's imbalance and is bounded by the keeper's per-action limit and debt ceiling.
+pub fn handler(ctx: Context<UpdatePegKeeper>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let peg_keeper = &mut ctx.accounts.peg_keeper;
+    let pool = &mut ctx.accounts.pool;
+    require!(pool.is_active, MiniStabbleError::PoolInActive);
+    require!(
+        now >= peg_keeper
+            .last_action_ts
+            .saturating_add(PEG_KEEPER_ACTION_DELAY),
+        MiniStabbleError::PegKeeperCooldown
+    );
+
+    let stable_index = pool
+        .get_token_index(&peg_keeper.stable_mint)
+        .ok_or(MiniStabbleError::InvalidMint)?;
+    let other_index = 1 - stable_index;
+
+    let price = u64::try_from(
+        pool.spot_price(stable_index, other_index, now)
+            .ok_or(MiniStabbleError::MathOverflow)?,
+    )?;
+    let action = peg_keeper.action(price);
+    let limit = pool.tokens[stable_index].scale_amount_up(peg_keeper.action_limit(action));
+
+    let amp = pool
+        .get_current_amp(now)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let balances = pool.get_balances();
+    let invariant = calc_invariant(amp, &balances).ok_or(MiniStabbleError::MathOverflow)?;
+    let lp_supply = ctx.accounts.lp_mint.supply;
+
+    // Close half the imbalance, rounded to what the stable's decimals can represent
+    let imbalance = balances[stable_index].abs_diff(balances[other_index]) / 2;
+    let stable_token = &pool.tokens[stable_index];
+    let amount = stable_token.scale_amount_down(imbalance.min(limit));
+    let scaled_amount = stable_token.scale_amount_up(amount);
+    require!(amount > 0, MiniStabbleError::PegKeeperNoAction);
+
+    let pool_key = pool.key();
+    let keeper_seeds = [PEG_KEEPER, pool_key.as_ref(), &[peg_keeper.bump]];
+    let keeper_signer_seeds = &[&keeper_seeds[..]];
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+
+    let lp_amount = if action == PegAction::Provide {
+        let mut amounts_in = vec![0; balances.len()];
+        amounts_in[stable_index] = scaled_amount;
+        let lp_out = calc_lp_tokens_for_deposit_with_fee(
+            amp,
+            &balances,
+            &amounts_in,
+            lp_supply,
+            invariant,
+            pool.swap_fee,
+        )
+        .ok_or(MiniStabbleError::MathOverflow)?;
+        require!(lp_out > 0, MiniStabbleError::PegKeeperNoAction);
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.stable_mint.to_account_info(),
+                    to: ctx.accounts.stable_vault.to_account_info(),
+                    authority: peg_keeper.to_account_info(),
+                },
+                keeper_signer_seeds,
+            ),
+            amount,
+        )?;
+        mint_lp(
+            &ctx.accounts.token_program,
+            &ctx.accounts.lp_mint,
+            &ctx.accounts.keeper_lp,
+            &ctx.accounts.authority,
+            signer_seeds,
+            lp_out,
+        )?;
+
+        pool.tokens[stable_index].balance = pool.tokens[stable_index]
+            .balance
+            .checked_add(scaled_amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        peg_keeper.debt = peg_keeper
+            .debt
+            .checked_add(amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        lp_out
+    } else {
+        // Pay the swap fee on the whole withdrawal: burn LP for the invariant lost if the pool
+        // gave up the fee-grossed amount, and keep the difference in the pool
+        let gross_amount = scaled_amount.div_up(pool.swap_fee.complement())?;
+        let mut balances_after = balances.clone();
+        balances_after[stable_index] = balances[stable_index]
+            .checked_sub(gross_amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        let invariant_after =
+            calc_invariant(amp, &balances_after).ok_or(MiniStabbleError::MathOverflow)?;
+        let lp_in = u64::try_from(
+            (lp_supply as u128 * (invariant - invariant_after) as u128).div_ceil(invariant as u128),
+        )?;
+        require!(
+            lp_in <= ctx.accounts.keeper_lp.amount,
+            MiniStabbleError::InvalidAmount
+        );
+
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    from: ctx.accounts.keeper_lp.to_account_info(),
+                    authority: peg_keeper.to_account_info(),
+                },
+                keeper_signer_seeds,
+            ),
+            lp_in,
+        )?;
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.stable_mint.to_account_info(),
+                    from: ctx.accounts.stable_vault.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        pool.tokens[stable_index].balance = pool.tokens[stable_index]
+            .balance
+            .checked_sub(scaled_amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        peg_keeper.debt = peg_keeper
+            .debt
+            .checked_sub(amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        lp_in
+    };
+
+    pool.invalidate_invariant();
+    peg_keeper.last_action_ts = now;
+
+    emit!(PegKeeperActionEvent {
+        pool: pool_key,
+        price,
+        provided: action == PegAction::Provide,
+        amount,
+        lp_amount,
+        debt: peg_keeper.debt,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    constants::{PROPOSAL_ESCROW, VOTE_RECORD},
+    errors::MiniStabbleError,
+    state::{Proposal, VoteRecord},
+    transfers::transfer_from_user,
+};
+
+#[derive(Accounts)]
+pub struct VoteOnProposal<'info> {
+    #[account(has_one = lp_mint)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [PROPOSAL_ESCROW, proposal.key().as_ref()], bump)]
+    pub escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = lp_mint, token::authority = voter)]
+    pub voter_lp: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        seeds = [VOTE_RECORD, proposal.key().as_ref(), voter.key().as_ref()],
+        bump,
+        payer = voter,
+        space = VoteRecord::LEN
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Escrows `lp_amount` LP in the proposal until its vote is over, to vote with in `cast_vote`.
+/// Only possible before voting starts, so the votes are fixed by then.
+pub fn handler(ctx: Context<VoteOnProposal>, lp_amount: u64) -> Result<()> {
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    let proposal = &ctx.accounts.proposal;
+    require!(
+        proposal.is_escrow_open(Clock::get()?.unix_timestamp),
+        MiniStabbleError::ProposalClosed
+    );
+
+    transfer_from_user(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.voter_lp,
+        &ctx.accounts.escrow,
+        &ctx.accounts.voter,
+        lp_amount,
+    )?;
+
+    let vote_record = &mut ctx.accounts.vote_record;
+    if vote_record.voter == Pubkey::default() {
+        vote_record.proposal = proposal.key();
+        vote_record.voter = ctx.accounts.voter.key();
+        vote_record.bump = ctx.bumps.vote_record;
+    }
+    vote_record.lp_amount = vote_record
+        .lp_amount
+        .checked_add(lp_amount)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    Ok(())
+}
@@ -0,0 +1,265 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    constants::{AUTHORITY, POOL_PARAMS, POOL_VAULT, WEIGHT_POOL},
+    errors::{end_dry_run, MiniStabbleError},
+    events::WithdrawEvent,
+    math::common::calc_tokens_out_proportional,
+    pool_authority_signer_seeds,
+    state::{PoolParams, PoolToken, WeightedPool},
+    transfers::{burn_lp, require_lp_burn_authority, transfer_from_vault},
+};
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // Mint Accounts
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(constraint = token_a_mint.key() != token_b_mint.key(), mint::token_program = token_program_a)]
+    pub token_a_mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub token_b_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::authority = user, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::authority = user, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    // Vault Tokens
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_a_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub vault_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_b_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub vault_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    /// LP source, owned by `user` or delegated to it
+    #[account(mut, token::mint = lp_mint)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+/// Burns `lp_amount` and pays out the proportional share of both pool tokens. `min_amount_a`
+/// and `min_amount_b` bound what the user receives, after any transfer fees. Exits above the
+/// pool's withdraw queue threshold have to go through `request_withdraw`.
+pub fn handler(
+    ctx: Context<Withdraw>,
+    lp_amount: u64,
+    min_amount_a: u64,
+    min_amount_b: u64,
+    dry_run: bool,
+) -> Result<()> {
+    require!(lp_amount > 0, MiniStabbleError::InvalidAmount);
+
+    let pool = &mut ctx.accounts.pool;
+    let lp_supply = ctx.accounts.lp_mint.supply;
+
+    let token_a_mint = &ctx.accounts.token_a_mint;
+    let token_b_mint = &ctx.accounts.token_b_mint;
+
+    let [token_a_index, token_b_index] = pool
+        .get_token_pair_indices(&token_a_mint.key(), &token_b_mint.key())
+        .ok_or(MiniStabbleError::InvalidMint)?;
+    // Only the pair's vaults are passed, so the pair has to be the whole pool
+    require!(pool.tokens.len() == 2, MiniStabbleError::InvalidMint);
+    require!(
+        !pool.record_instant_exit(
+            &ctx.accounts.pool_params,
+            lp_amount,
+            lp_supply,
+            Clock::get()?.slot
+        ),
+        MiniStabbleError::ExitRequiresWithdrawQueue
+    );
+
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_supply);
+
+    let [raw_amount_a, raw_amount_b] = raw_amounts_out(
+        [&pool.tokens[token_a_index], &pool.tokens[token_b_index]],
+        lp_amount,
+        lp_supply,
+    )?;
+
+    require_lp_burn_authority(&ctx.accounts.user_lp, &ctx.accounts.user.key(), lp_amount)?;
+    burn_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.user,
+        lp_amount,
+    )?;
+
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+
+    let received_a = transfer_from_vault(
+        &ctx.accounts.token_program_a,
+        token_a_mint,
+        &ctx.accounts.vault_token_a,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.authority,
+        signer_seeds,
+        raw_amount_a,
+    )?;
+    let received_b = transfer_from_vault(
+        &ctx.accounts.token_program_b,
+        token_b_mint,
+        &ctx.accounts.vault_token_b,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.authority,
+        signer_seeds,
+        raw_amount_b,
+    )?;
+
+    // Slippage check against what the user actually receives
+    check_min_amounts_out([received_a, received_b], [min_amount_a, min_amount_b])?;
+
+    // Debit the pool with what left the vaults, rounded up from the raw amounts paid
+    for (index, raw_amount) in [(token_a_index, raw_amount_a), (token_b_index, raw_amount_b)] {
+        let token = &mut pool.tokens[index];
+        token.balance = token
+            .balance
+            .checked_sub(token.scale_amount_up(raw_amount))
+            .ok_or(MiniStabbleError::MathOverflow)?;
+    }
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(lp_supply - lp_amount),
+    );
+
+    emit!(WithdrawEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amount_a: received_a,
+        amount_b: received_b,
+        lp_amount,
+    });
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
+
+/// Raw amounts of `tokens` a proportional exit of `lp_amount` out of `lp_supply` pays, rounded
+/// down in the pool's favor
+fn raw_amounts_out(tokens: [&PoolToken; 2], lp_amount: u64, lp_supply: u64) -> Result<[u64; 2]> {
+    require!(lp_amount <= lp_supply, MiniStabbleError::InvalidAmount);
+    let amounts_out =
+        calc_tokens_out_proportional(&tokens.map(|token| token.balance), lp_amount, lp_supply)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+    Ok([0, 1].map(|i| tokens[i].scale_amount_down(amounts_out[i])))
+}
+
+/// Checks the user received at least `min_amounts` raw of each token
+fn check_min_amounts_out(received: [u64; 2], min_amounts: [u64; 2]) -> Result<()> {
+    require!(
+        received[0] >= min_amounts[0] && received[1] >= min_amounts[1],
+        MiniStabbleError::SlippageExceeded
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::fixed::ONE_U64;
+
+    fn token(decimals: u8, balance: u64) -> PoolToken {
+        PoolToken {
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            decimals,
+            scaling_factor: 10u64.pow(9 - decimals as u32),
+            balance,
+            weight: ONE_U64 / 2,
+            swaps_disabled: false,
+            rounding_dust: 0,
+            internal_balances: 0,
+            queued_withdrawals: 0,
+            referral_rewards: 0,
+        }
+    }
+
+    #[test]
+    fn test_raw_amounts_out_pay_the_lp_share_in_each_decimals() {
+        let tokens = [token(6, 1_000 * ONE_U64), token(9, 4_000 * ONE_U64)];
+
+        // 10% of the supply: 100 A with 6 decimals and 400 B with 9
+        let amounts = raw_amounts_out([&tokens[0], &tokens[1]], 100 * ONE_U64, 1_000 * ONE_U64);
+        assert_eq!(amounts.unwrap(), [100_000_000, 400 * ONE_U64]);
+
+        // The whole supply drains the pool
+        let amounts = raw_amounts_out([&tokens[0], &tokens[1]], 1_000 * ONE_U64, 1_000 * ONE_U64);
+        assert_eq!(amounts.unwrap(), [1_000_000_000, 4_000 * ONE_U64]);
+    }
+
+    #[test]
+    fn test_withdrawal_never_dilutes_the_remaining_lp() {
+        let mut tokens = [token(6, 1_000_000_007_123), token(9, 3_333_333_333_333)];
+        let lp_supply = 1_999_999_999_999u64;
+        let lp_amount = 123_456_789_123;
+
+        let amounts = raw_amounts_out([&tokens[0], &tokens[1]], lp_amount, lp_supply).unwrap();
+        for (token, raw_amount) in tokens.iter_mut().zip(amounts) {
+            let per_lp_before = token.balance as u128 * ONE_U64 as u128 / lp_supply as u128;
+            token.balance -= token.scale_amount_up(raw_amount);
+            let per_lp_after =
+                token.balance as u128 * ONE_U64 as u128 / (lp_supply - lp_amount) as u128;
+            assert!(per_lp_after >= per_lp_before);
+        }
+    }
+
+    #[test]
+    fn test_raw_amounts_out_reject_more_than_the_supply() {
+        let tokens = [token(9, 1_000 * ONE_U64), token(9, 1_000 * ONE_U64)];
+
+        assert_eq!(
+            raw_amounts_out([&tokens[0], &tokens[1]], 1_001, 1_000),
+            Err(MiniStabbleError::InvalidAmount.into())
+        );
+        assert_eq!(
+            raw_amounts_out([&tokens[0], &tokens[1]], 1, 0),
+            Err(MiniStabbleError::InvalidAmount.into())
+        );
+    }
+
+    #[test]
+    fn test_min_amounts_out_bound_each_token() {
+        assert!(check_min_amounts_out([100, 200], [100, 200]).is_ok());
+        assert_eq!(
+            check_min_amounts_out([99, 200], [100, 200]),
+            Err(MiniStabbleError::SlippageExceeded.into())
+        );
+        assert_eq!(
+            check_min_amounts_out([100, 199], [100, 200]),
+            Err(MiniStabbleError::SlippageExceeded.into())
+        );
+    }
+}
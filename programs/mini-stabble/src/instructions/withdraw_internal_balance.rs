@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    constants::{AUTHORITY, INTERNAL_BALANCE, POOL_VAULT},
+    errors::MiniStabbleError,
+    pool_authority_signer_seeds,
+    state::{release_internal_balance, update_pool_tokens, InternalBalance},
+    transfers::transfer_from_vault,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawInternalBalance<'info> {
+    /// CHECK: Pool the internal balance is held in, checked through the PDA seeds and decoded in
+    /// the handler to release it
+    #[account(mut)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, seeds = [POOL_VAULT, pool.key().as_ref(), mint.key().as_ref()], bump, token::mint = mint, token::token_program = token_program, token::authority = authority)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [INTERNAL_BALANCE, pool.key().as_ref(), user.key().as_ref(), mint.key().as_ref()],
+        bump = internal_balance.bump,
+    )]
+    pub internal_balance: Account<'info, InternalBalance>,
+
+    /// CHECK: Authority PDA owning the vaults
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Settles `amount` raw of the user's internal balance out of the pool's vault to any token
+/// account
+pub fn handler(ctx: Context<WithdrawInternalBalance>, amount: u64) -> Result<()> {
+    require!(amount > 0, MiniStabbleError::InvalidAmount);
+
+    ctx.accounts.internal_balance.debit(amount)?;
+    let mint = ctx.accounts.mint.key();
+    update_pool_tokens(&ctx.accounts.pool, |tokens| {
+        release_internal_balance(tokens, &mint, amount)
+    })?;
+
+    let pool_key = ctx.accounts.pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, ctx.bumps.authority);
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.mint,
+        &ctx.accounts.vault,
+        &ctx.accounts.user_token,
+        &ctx.accounts.authority,
+        signer_seeds,
+        amount,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Mint, Token, TokenAccount},
+    token_interface::{
+        Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    constants::{AUTHORITY, POOL_PARAMS, POOL_VAULT, WEIGHT_POOL},
+    errors::{end_dry_run, MiniStabbleError},
+    events::WithdrawEvent,
+    math::weighted::calc_lp_in_given_exact_tokens_out,
+    pool_authority_signer_seeds,
+    state::{PoolParams, WeightedPool},
+    transfers::{burn_lp, require_lp_burn_authority, transfer_from_vault},
+};
+
+#[derive(Accounts)]
+pub struct WithdrawUnbalanced<'info> {
+    #[account(mut, seeds = [WEIGHT_POOL, pool.lp_mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(seeds = [POOL_PARAMS, pool.key().as_ref()], bump = pool_params.bump, has_one = pool)]
+    pub pool_params: Account<'info, PoolParams>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // Mint Accounts
+    #[account(mut, address = pool.lp_mint.key())]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(constraint = token_a_mint.key() != token_b_mint.key(), mint::token_program = token_program_a)]
+    pub token_a_mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mint::token_program = token_program_b)]
+    pub token_b_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, token::authority = user, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub user_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, token::authority = user, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub user_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    // Vault Tokens
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_a_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_a_mint, token::token_program = token_program_a)]
+    pub vault_token_a: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(mut, seeds=[POOL_VAULT, pool.key().as_ref(), token_b_mint.key().as_ref()], bump, token::authority = authority, token::mint = token_b_mint, token::token_program = token_program_b)]
+    pub vault_token_b: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    /// LP source, owned by `user` or delegated to it
+    #[account(mut, token::mint = lp_mint)]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: Authority PDA used for signing
+    #[account(seeds = [AUTHORITY, pool.key().as_ref()], bump = pool.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+/// Withdraws exactly `amount_a_out` and `amount_b_out` raw from the vaults (either may be 0),
+/// burning the LP that's worth with the swap fee charged on whatever goes beyond a
+/// proportional exit. Fails if that takes more than `max_lp_amount`.
+pub fn handler(
+    ctx: Context<WithdrawUnbalanced>,
+    max_lp_amount: u64,
+    amount_a_out: u64,
+    amount_b_out: u64,
+    dry_run: bool,
+) -> Result<()> {
+    require!(
+        max_lp_amount > 0 && (amount_a_out > 0 || amount_b_out > 0),
+        MiniStabbleError::InvalidAmount
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    let lp_supply = ctx.accounts.lp_mint.supply;
+
+    let token_a_mint = &ctx.accounts.token_a_mint;
+    let token_b_mint = &ctx.accounts.token_b_mint;
+
+    let [token_a_index, token_b_index] = pool
+        .get_token_pair_indices(&token_a_mint.key(), &token_b_mint.key())
+        .ok_or(MiniStabbleError::InvalidMint)?;
+    // Only the pair's vaults are passed, so the pair has to be the whole pool
+    require!(pool.tokens.len() == 2, MiniStabbleError::InvalidMint);
+
+    #[cfg(feature = "strict-checks")]
+    let virtual_price_before = pool.virtual_price(lp_supply);
+
+    // Debit the pool with what leaves the vaults, rounded up from the raw amounts paid
+    let scaled_amount_a_out = pool.tokens[token_a_index].scale_amount_up(amount_a_out);
+    let scaled_amount_b_out = pool.tokens[token_b_index].scale_amount_up(amount_b_out);
+
+    let balances = [
+        pool.tokens[token_a_index].balance as u128,
+        pool.tokens[token_b_index].balance as u128,
+    ];
+    let weights = [
+        pool.tokens[token_a_index].weight as u128,
+        pool.tokens[token_b_index].weight as u128,
+    ];
+    let lp_to_burn = u64::try_from(calc_lp_in_given_exact_tokens_out(
+        &balances,
+        &weights,
+        &[scaled_amount_a_out as u128, scaled_amount_b_out as u128],
+        lp_supply as u128,
+        pool.swap_fee as u128,
+    )?)
+    .map_err(|_| MiniStabbleError::MathOverflow)?;
+
+    require!(
+        lp_to_burn <= max_lp_amount,
+        MiniStabbleError::SlippageExceeded
+    );
+    require!(
+        !pool.record_instant_exit(
+            &ctx.accounts.pool_params,
+            lp_to_burn,
+            lp_supply,
+            Clock::get()?.slot
+        ),
+        MiniStabbleError::ExitRequiresWithdrawQueue
+    );
+
+    require_lp_burn_authority(&ctx.accounts.user_lp, &ctx.accounts.user.key(), lp_to_burn)?;
+    burn_lp(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp,
+        &ctx.accounts.user,
+        lp_to_burn,
+    )?;
+
+    let pool_key = pool.key();
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);
+
+    let received_a = transfer_from_vault(
+        &ctx.accounts.token_program_a,
+        token_a_mint,
+        &ctx.accounts.vault_token_a,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.authority,
+        signer_seeds,
+        amount_a_out,
+    )?;
+    let received_b = transfer_from_vault(
+        &ctx.accounts.token_program_b,
+        token_b_mint,
+        &ctx.accounts.vault_token_b,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.authority,
+        signer_seeds,
+        amount_b_out,
+    )?;
+
+    pool.tokens[token_a_index].balance = pool.tokens[token_a_index]
+        .balance
+        .checked_sub(scaled_amount_a_out)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    pool.tokens[token_b_index].balance = pool.tokens[token_b_index]
+        .balance
+        .checked_sub(scaled_amount_b_out)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    #[cfg(feature = "strict-checks")]
+    crate::strict_checks::assert_virtual_price_not_decreased(
+        virtual_price_before,
+        pool.virtual_price(lp_supply - lp_to_burn),
+    );
+
+    emit!(WithdrawEvent {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        amount_a: received_a,
+        amount_b: received_b,
+        lp_amount: lp_to_burn,
+    });
+
+    end_dry_run(dry_run)?;
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    constants::{AUTHORITY, PROPOSAL_ESCROW, VOTE_RECORD},
+    errors::MiniStabbleError,
+    pool_authority_signer_seeds,
+    state::{Proposal, VoteRecord},
+    transfers::transfer_from_vault,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawVote<'info> {
+    #[account(has_one = lp_mint)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [PROPOSAL_ESCROW, proposal.key().as_ref()], bump)]
+    pub escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = lp_mint, token::authority = voter)]
+    pub voter_lp: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VOTE_RECORD, proposal.key().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump,
+        close = voter
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    /// CHECK: Authority PDA that owns the escrow
+    #[account(seeds = [AUTHORITY, proposal.pool.as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Returns the voter's escrowed LP once the proposal was executed or its vote has ended
+pub fn handler(ctx: Context<WithdrawVote>) -> Result<()> {
+    require!(
+        !ctx.accounts.proposal.is_open(Clock::get()?.unix_timestamp),
+        MiniStabbleError::ProposalOpen
+    );
+
+    let pool_key = ctx.accounts.proposal.pool;
+    let signer_seeds = pool_authority_signer_seeds!(pool_key, ctx.bumps.authority);
+
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.escrow,
+        &ctx.accounts.voter_lp,
+        &ctx.accounts.authority,
+        signer_seeds,
+        ctx.accounts.vote_record.lp_amount,
+    )?;
+
+    Ok(())
+}
@@ -1,11 +1,19 @@
 use anchor_lang::prelude::*;
+use events::SwapResult;
 use instructions::*;
+use state::{DustDestination, InvariantCachePolicy, ProposedChange, Role};
 
 pub mod constants;
+pub mod ed25519;
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod math;
+pub mod pda;
 pub mod state;
+#[cfg(feature = "strict-checks")]
+pub mod strict_checks;
+pub mod transfers;
 
 declare_id!("FURtuxyXWgpnETkNho8PL6mpuRh9mCnVsWgUY14JzusX");
 
@@ -17,13 +25,24 @@ pub mod mini_stabble {
         ctx: Context<InitializeWeightedPool>,
         swap_fee: u64,
         only_token_a_weight: u64,
+        creator_lock_days: u16,
     ) -> Result<()> {
-        instructions::initialize_weighted_pool::handler(ctx, swap_fee, only_token_a_weight)?;
+        instructions::initialize_weighted_pool::handler(
+            ctx,
+            swap_fee,
+            only_token_a_weight,
+            creator_lock_days,
+        )?;
         Ok(())
     }
 
-    pub fn swap(ctx: Context<Swap>, amount_in: u64, min_amount_out: u64) -> Result<()> {
-        instructions::swap::handler(ctx, amount_in, min_amount_out)
+    pub fn swap(
+        ctx: Context<Swap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        dry_run: bool,
+    ) -> Result<SwapResult> {
+        instructions::swap::handler(ctx, amount_in, min_amount_out, dry_run)
     }
 
     pub fn deposit(
@@ -31,8 +50,15 @@ pub mod mini_stabble {
         lp_amount: u64,
         input_token_a_amount: u64,
         input_token_b_amount: u64,
+        dry_run: bool,
     ) -> Result<()> {
-        instructions::deposit::handler(ctx, lp_amount, input_token_a_amount, input_token_b_amount)
+        instructions::deposit::handler(
+            ctx,
+            lp_amount,
+            input_token_a_amount,
+            input_token_b_amount,
+            dry_run,
+        )
     }
 
     pub fn deposit_unbalanced(
@@ -40,21 +66,31 @@ pub mod mini_stabble {
         min_lp_amount: u64,
         input_amount_a: u64,
         input_amount_b: u64,
+        dry_run: bool,
     ) -> Result<()> {
         instructions::deposit_unbalanced::handler(
             ctx,
             min_lp_amount,
             input_amount_a,
             input_amount_b,
+            dry_run,
         )
     }
 
-    pub fn initialize_stable_pool(
-        ctx: Context<InitializeStablePool>,
+    pub fn initialize_stable_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializeStablePool<'info>>,
         swap_fee: u64,
         amp: u64,
+        creator_lock_days: u16,
+        amp_ramp: Option<AmpRampSchedule>,
     ) -> Result<()> {
-        instructions::initialize_stable_pool::handler(ctx, swap_fee, amp)
+        instructions::initialize_stable_pool::handler(
+            ctx,
+            swap_fee,
+            amp,
+            creator_lock_days,
+            amp_ramp,
+        )
     }
 
     pub fn stable_deposit(
@@ -62,15 +98,759 @@ pub mod mini_stabble {
         max_amount_a: u64,
         max_amount_b: u64,
         lp_amount: u64,
+        dry_run: bool,
     ) -> Result<()> {
-        instructions::stable_deposit::handler(ctx, max_amount_a, max_amount_b, lp_amount)
+        instructions::stable_deposit::handler(ctx, max_amount_a, max_amount_b, lp_amount, dry_run)
     }
 
     pub fn stable_swap(
         ctx: Context<StableSwap>,
         amount_in: u64,
         min_amount_out: u64,
+        dry_run: bool,
+    ) -> Result<SwapResult> {
+        instructions::stable_swap::handler(ctx, amount_in, min_amount_out, dry_run)
+    }
+
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        instructions::initialize_config::handler(ctx)
+    }
+
+    pub fn set_withdraw_queue(
+        ctx: Context<SetWithdrawQueue>,
+        threshold_bps: u16,
+        duration: u64,
+    ) -> Result<()> {
+        instructions::set_withdraw_queue::handler(ctx, threshold_bps, duration)
+    }
+
+    pub fn set_stable_withdraw_queue(
+        ctx: Context<SetStableWithdrawQueue>,
+        threshold_bps: u16,
+        duration: u64,
+    ) -> Result<()> {
+        instructions::set_stable_withdraw_queue::handler(ctx, threshold_bps, duration)
+    }
+
+    pub fn request_withdraw(
+        ctx: Context<RequestWithdraw>,
+        lp_amount: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::request_withdraw::handler(ctx, lp_amount, dry_run)
+    }
+
+    pub fn stable_request_withdraw(
+        ctx: Context<StableRequestWithdraw>,
+        lp_amount: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::stable_request_withdraw::handler(ctx, lp_amount, dry_run)
+    }
+
+    pub fn claim_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimWithdraw<'info>>,
+    ) -> Result<()> {
+        instructions::claim_withdraw::handler(ctx)
+    }
+
+    pub fn set_amp_fee_floor(
+        ctx: Context<SetAmpFeeFloor>,
+        amp_fee_floor_coefficient: u64,
+    ) -> Result<()> {
+        instructions::set_amp_fee_floor::handler(ctx, amp_fee_floor_coefficient)
+    }
+
+    pub fn set_stable_swap_fee(ctx: Context<SetStableSwapFee>, swap_fee: u64) -> Result<()> {
+        instructions::set_stable_swap_fee::handler(ctx, swap_fee)
+    }
+
+    pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, end_ts: i64) -> Result<()> {
+        instructions::ramp_amp::handler(ctx, target_amp, end_ts)
+    }
+
+    pub fn stop_ramp_amp(ctx: Context<StopRampAmp>) -> Result<()> {
+        instructions::stop_ramp_amp::handler(ctx)
+    }
+
+    pub fn rebalance<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Rebalance<'info>>,
+        legs: Vec<RebalanceLeg>,
+        min_net_value: i64,
+    ) -> Result<()> {
+        instructions::rebalance::handler(ctx, legs, min_net_value)
+    }
+
+    pub fn initialize_token_badge(ctx: Context<InitializeTokenBadge>) -> Result<()> {
+        instructions::initialize_token_badge::handler(ctx)
+    }
+
+    pub fn delete_token_badge(ctx: Context<DeleteTokenBadge>) -> Result<()> {
+        instructions::delete_token_badge::handler(ctx)
+    }
+
+    pub fn set_pool_active(ctx: Context<SetPoolActive>, is_active: bool) -> Result<()> {
+        instructions::set_pool_active::handler(ctx, is_active)
+    }
+
+    pub fn set_stable_pool_active(
+        ctx: Context<SetStablePoolActive>,
+        is_active: bool,
+    ) -> Result<()> {
+        instructions::set_stable_pool_active::handler(ctx, is_active)
+    }
+
+    pub fn repair_scaling(
+        ctx: Context<RepairScaling>,
+        token_index: u8,
+        new_factor: u64,
+    ) -> Result<()> {
+        instructions::repair_scaling::handler(ctx, token_index, new_factor)
+    }
+
+    pub fn stable_repair_scaling(
+        ctx: Context<StableRepairScaling>,
+        token_index: u8,
+        new_factor: u64,
+    ) -> Result<()> {
+        instructions::stable_repair_scaling::handler(ctx, token_index, new_factor)
+    }
+
+    pub fn set_stable_rate_limit(
+        ctx: Context<SetStableRateLimit>,
+        window_slots: u64,
+        max_outflow_bps: u16,
+    ) -> Result<()> {
+        instructions::set_stable_rate_limit::handler(ctx, window_slots, max_outflow_bps)
+    }
+
+    pub fn compound_operation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CompoundOperation<'info>>,
+        steps: Vec<CompoundStep>,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::compound_operation::handler(ctx, steps, min_amount_out)
+    }
+
+    pub fn migrate_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateLiquidity<'info>>,
+        from_kind: PoolKind,
+        to_kind: PoolKind,
+        lp_amount: u64,
+        min_lp_out: u64,
+    ) -> Result<()> {
+        instructions::migrate_liquidity::handler(ctx, from_kind, to_kind, lp_amount, min_lp_out)
+    }
+
+    pub fn set_max_spot_price_move(
+        ctx: Context<SetMaxSpotPriceMove>,
+        max_spot_price_move_per_swap_bps: u16,
+    ) -> Result<()> {
+        instructions::set_max_spot_price_move::handler(ctx, max_spot_price_move_per_swap_bps)
+    }
+
+    pub fn quote_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, QuoteRoute<'info>>,
+        hops: Vec<RouteHop>,
+        amount_in: u64,
+    ) -> Result<RouteQuote> {
+        instructions::quote_route::handler(ctx, hops, amount_in)
+    }
+
+    pub fn set_stable_invariant_cache_policy(
+        ctx: Context<SetStableInvariantCachePolicy>,
+        policy: InvariantCachePolicy,
+    ) -> Result<()> {
+        instructions::set_stable_invariant_cache_policy::handler(ctx, policy)
+    }
+
+    pub fn swap_with_slippage_bps(
+        ctx: Context<Swap>,
+        amount_in: u64,
+        slippage_bps: u16,
+        dry_run: bool,
+    ) -> Result<SwapResult> {
+        instructions::swap_with_slippage_bps::handler(ctx, amount_in, slippage_bps, dry_run)
+    }
+
+    pub fn stable_swap_with_slippage_bps(
+        ctx: Context<StableSwap>,
+        amount_in: u64,
+        slippage_bps: u16,
+        dry_run: bool,
+    ) -> Result<SwapResult> {
+        instructions::stable_swap_with_slippage_bps::handler(ctx, amount_in, slippage_bps, dry_run)
+    }
+
+    pub fn set_treasury(ctx: Context<SetTreasury>, treasury: Pubkey) -> Result<()> {
+        instructions::set_treasury::handler(ctx, treasury)
+    }
+
+    pub fn seed_protocol_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SeedProtocolLiquidity<'info>>,
+        pool_kind: PoolKind,
+        lp_amount: u64,
+        max_amount_a: u64,
+        max_amount_b: u64,
+    ) -> Result<()> {
+        instructions::seed_protocol_liquidity::handler(
+            ctx,
+            pool_kind,
+            lp_amount,
+            max_amount_a,
+            max_amount_b,
+        )
+    }
+
+    pub fn release_protocol_liquidity(
+        ctx: Context<ReleaseProtocolLiquidity>,
+        lp_amount: u64,
+    ) -> Result<()> {
+        instructions::release_protocol_liquidity::handler(ctx, lp_amount)
+    }
+
+    pub fn stable_swap_split(
+        ctx: Context<StableSwap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        split_bps: u16,
+        dry_run: bool,
+    ) -> Result<SwapResult> {
+        instructions::stable_swap_split::handler(ctx, amount_in, min_amount_out, split_bps, dry_run)
+    }
+
+    pub fn set_drawdown_guard(ctx: Context<SetDrawdownGuard>, max_drawdown_bps: u16) -> Result<()> {
+        instructions::set_drawdown_guard::handler(ctx, max_drawdown_bps)
+    }
+
+    pub fn set_stable_drawdown_guard(
+        ctx: Context<SetStableDrawdownGuard>,
+        max_drawdown_bps: u16,
+    ) -> Result<()> {
+        instructions::set_stable_drawdown_guard::handler(ctx, max_drawdown_bps)
+    }
+
+    pub fn set_fee_autopilot(
+        ctx: Context<SetFeeAutopilot>,
+        min_fee: u64,
+        max_fee: u64,
+        max_volatility_bps: u64,
+    ) -> Result<()> {
+        instructions::set_fee_autopilot::handler(ctx, min_fee, max_fee, max_volatility_bps)
+    }
+
+    pub fn update_fee_from_volatility(ctx: Context<UpdateFeeFromVolatility>) -> Result<()> {
+        instructions::update_fee_from_volatility::handler(ctx)
+    }
+
+    pub fn initialize_user_stats(ctx: Context<InitializeUserStats>) -> Result<()> {
+        instructions::initialize_user_stats::handler(ctx)
+    }
+
+    pub fn deposit_with_intent(
+        ctx: Context<DepositWithIntent>,
+        intent: DepositIntent,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::deposit_with_intent::handler(ctx, intent, dry_run)
+    }
+
+    pub fn initialize_peg_keeper(
+        ctx: Context<InitializePegKeeper>,
+        debt_ceiling: u64,
+        deviation_threshold_bps: u16,
+        max_amount_per_action: u64,
+    ) -> Result<()> {
+        instructions::initialize_peg_keeper::handler(
+            ctx,
+            debt_ceiling,
+            deviation_threshold_bps,
+            max_amount_per_action,
+        )
+    }
+
+    pub fn set_peg_keeper(
+        ctx: Context<SetPegKeeper>,
+        debt_ceiling: u64,
+        deviation_threshold_bps: u16,
+        max_amount_per_action: u64,
+    ) -> Result<()> {
+        instructions::set_peg_keeper::handler(
+            ctx,
+            debt_ceiling,
+            deviation_threshold_bps,
+            max_amount_per_action,
+        )
+    }
+
+    pub fn update_peg_keeper(ctx: Context<UpdatePegKeeper>) -> Result<()> {
+        instructions::update_peg_keeper::handler(ctx)
+    }
+
+    pub fn initialize_user_rebate_stats(ctx: Context<InitializeUserRebateStats>) -> Result<()> {
+        instructions::initialize_user_rebate_stats::handler(ctx)
+    }
+
+    pub fn set_stable_rebate_fee_share(
+        ctx: Context<SetStableRebateFeeShare>,
+        fee_share_bps: u16,
+    ) -> Result<()> {
+        instructions::set_stable_rebate_fee_share::handler(ctx, fee_share_bps)
+    }
+
+    pub fn claim_rebate(ctx: Context<ClaimRebate>, epoch: u64) -> Result<()> {
+        instructions::claim_rebate::handler(ctx, epoch)
+    }
+
+    pub fn smart_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SmartSwap<'info>>,
+        a_to_b: bool,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<SmartSwapResult> {
+        instructions::smart_swap::handler(ctx, a_to_b, amount_in, min_amount_out)
+    }
+
+    pub fn release_creator_lock(ctx: Context<ReleaseCreatorLock>) -> Result<()> {
+        instructions::release_creator_lock::handler(ctx)
+    }
+
+    pub fn stable_release_creator_lock(ctx: Context<StableReleaseCreatorLock>) -> Result<()> {
+        instructions::stable_release_creator_lock::handler(ctx)
+    }
+
+    pub fn migrate_pool_params(ctx: Context<MigratePoolParams>) -> Result<()> {
+        instructions::migrate_pool_params::handler(ctx)
+    }
+
+    pub fn migrate_pool_authority<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigratePoolAuthority<'info>>,
+        pool_kind: PoolKind,
+    ) -> Result<()> {
+        instructions::migrate_pool_authority::handler(ctx, pool_kind)
+    }
+
+    pub fn archive_pool(ctx: Context<ArchivePool>) -> Result<()> {
+        instructions::archive_pool::handler(ctx)
+    }
+
+    pub fn stable_archive_pool(ctx: Context<StableArchivePool>) -> Result<()> {
+        instructions::stable_archive_pool::handler(ctx)
+    }
+
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        pool_kind: PoolKind,
+        change: ProposedChange,
+    ) -> Result<()> {
+        instructions::create_proposal::handler(ctx, pool_kind, change)
+    }
+
+    pub fn vote_on_proposal(ctx: Context<VoteOnProposal>, lp_amount: u64) -> Result<()> {
+        instructions::vote_on_proposal::handler(ctx, lp_amount)
+    }
+
+    pub fn cast_vote(ctx: Context<CastVote>, in_favour: bool) -> Result<()> {
+        instructions::cast_vote::handler(ctx, in_favour)
+    }
+
+    pub fn withdraw_vote(ctx: Context<WithdrawVote>) -> Result<()> {
+        instructions::withdraw_vote::handler(ctx)
+    }
+
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        instructions::execute_proposal::handler(ctx)
+    }
+
+    pub fn stable_execute_proposal(ctx: Context<StableExecuteProposal>) -> Result<()> {
+        instructions::stable_execute_proposal::handler(ctx)
+    }
+
+    pub fn initialize_pool_fee_stats(ctx: Context<InitializePoolFeeStats>) -> Result<()> {
+        instructions::initialize_pool_fee_stats::handler(ctx)
+    }
+
+    pub fn collect_pool_fee_stats(ctx: Context<CollectPoolFeeStats>) -> Result<()> {
+        instructions::collect_pool_fee_stats::handler(ctx)
+    }
+
+    pub fn stable_reconcile_balances(ctx: Context<StableReconcileBalances>) -> Result<()> {
+        instructions::stable_reconcile_balances::handler(ctx)
+    }
+
+    pub fn set_compliance_authority(
+        ctx: Context<SetComplianceAuthority>,
+        compliance_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::set_compliance_authority::handler(ctx, compliance_authority)
+    }
+
+    pub fn set_stable_compliance_authority(
+        ctx: Context<SetStableComplianceAuthority>,
+        compliance_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::set_stable_compliance_authority::handler(ctx, compliance_authority)
+    }
+
+    pub fn allowlist_trader(ctx: Context<AllowlistTrader>, trader: Pubkey) -> Result<()> {
+        instructions::allowlist_trader::handler(ctx, trader)
+    }
+
+    pub fn remove_allowlisted_trader(ctx: Context<RemoveAllowlistedTrader>) -> Result<()> {
+        instructions::remove_allowlisted_trader::handler(ctx)
+    }
+
+    pub fn deposit_exact_tokens_in<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositExactTokensIn<'info>>,
+        amounts_in: Vec<u64>,
+        min_lp_amount: u64,
+    ) -> Result<()> {
+        instructions::deposit_exact_tokens_in::handler(ctx, amounts_in, min_lp_amount)
+    }
+
+    pub fn set_role(ctx: Context<SetRole>, role: Role, holder: Pubkey) -> Result<()> {
+        instructions::set_role::handler(ctx, role, holder)
+    }
+
+    pub fn set_stable_max_out_ratio(
+        ctx: Context<SetStableMaxOutRatio>,
+        max_out_ratio_bps: u16,
+    ) -> Result<()> {
+        instructions::set_stable_max_out_ratio::handler(ctx, max_out_ratio_bps)
+    }
+
+    pub fn set_token_swaps_disabled(
+        ctx: Context<SetTokenSwapsDisabled>,
+        token_index: u8,
+        swaps_disabled: bool,
+    ) -> Result<()> {
+        instructions::set_token_swaps_disabled::handler(ctx, token_index, swaps_disabled)
+    }
+
+    pub fn set_stable_token_swaps_disabled(
+        ctx: Context<SetStableTokenSwapsDisabled>,
+        token_index: u8,
+        swaps_disabled: bool,
+    ) -> Result<()> {
+        instructions::set_stable_token_swaps_disabled::handler(ctx, token_index, swaps_disabled)
+    }
+
+    pub fn set_crank_keeper(
+        ctx: Context<SetCrankKeeper>,
+        keeper: Pubkey,
+        registered: bool,
+    ) -> Result<()> {
+        instructions::set_crank_keeper::handler(ctx, keeper, registered)
+    }
+
+    pub fn initialize_swap_checkpoint(
+        ctx: Context<InitializeSwapCheckpoint>,
+        interval_slots: u64,
+    ) -> Result<()> {
+        instructions::initialize_swap_checkpoint::handler(ctx, interval_slots)
+    }
+
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+        hops: Vec<RouteHop>,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<u64> {
+        instructions::swap_route::handler(ctx, hops, amount_in, min_amount_out)
+    }
+
+    pub fn checkpoint_stable_virtual_price(
+        ctx: Context<CheckpointStableVirtualPrice>,
+    ) -> Result<()> {
+        instructions::checkpoint_stable_virtual_price::handler(ctx)
+    }
+
+    pub fn report_invariant_decrease(ctx: Context<ReportInvariantDecrease>) -> Result<()> {
+        instructions::report_invariant_decrease::handler(ctx)
+    }
+
+    pub fn deposit_single_token_exact_lp_out(
+        ctx: Context<DepositSingleTokenExactLpOut>,
+        lp_amount: u64,
+        max_amount_in: u64,
+    ) -> Result<()> {
+        instructions::deposit_single_token_exact_lp_out::handler(ctx, lp_amount, max_amount_in)
+    }
+
+    pub fn swap_with_fee_reimbursement<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        reimbursement_lamports: u64,
+    ) -> Result<SwapResult> {
+        instructions::swap_with_fee_reimbursement::handler(
+            ctx,
+            amount_in,
+            min_amount_out,
+            reimbursement_lamports,
+        )
+    }
+
+    pub fn stable_swap_with_fee_reimbursement<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StableSwap<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        reimbursement_lamports: u64,
+    ) -> Result<SwapResult> {
+        instructions::stable_swap_with_fee_reimbursement::handler(
+            ctx,
+            amount_in,
+            min_amount_out,
+            reimbursement_lamports,
+        )
+    }
+
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        lp_amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::withdraw::handler(ctx, lp_amount, min_amount_a, min_amount_b, dry_run)
+    }
+
+    pub fn withdraw_unbalanced(
+        ctx: Context<WithdrawUnbalanced>,
+        max_lp_amount: u64,
+        amount_a_out: u64,
+        amount_b_out: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::withdraw_unbalanced::handler(
+            ctx,
+            max_lp_amount,
+            amount_a_out,
+            amount_b_out,
+            dry_run,
+        )
+    }
+
+    pub fn deposit_internal_balance(
+        ctx: Context<DepositInternalBalance>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::deposit_internal_balance::handler(ctx, amount)
+    }
+
+    pub fn withdraw_internal_balance(
+        ctx: Context<WithdrawInternalBalance>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_internal_balance::handler(ctx, amount)
+    }
+
+    pub fn swap_internal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapInternal<'info>>,
+        pool_kind: PoolKind,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<u64> {
+        instructions::swap_internal::handler(ctx, pool_kind, amount_in, min_amount_out)
+    }
+
+    pub fn deposit_single(
+        ctx: Context<DepositSingle>,
+        amount_in: u64,
+        min_lp_amount: u64,
+    ) -> Result<()> {
+        instructions::deposit_single::handler(ctx, amount_in, min_lp_amount)
+    }
+
+    pub fn withdraw_single(
+        ctx: Context<WithdrawSingle>,
+        lp_amount: u64,
+        min_amount_out: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::withdraw_single::handler(ctx, lp_amount, min_amount_out, dry_run)
+    }
+
+    pub fn set_dust_destination(
+        ctx: Context<SetDustDestination>,
+        destination: DustDestination,
+    ) -> Result<()> {
+        instructions::set_dust_destination::handler(ctx, destination)
+    }
+
+    pub fn sweep_rounding_dust<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepRoundingDust<'info>>,
+        pool_kind: PoolKind,
+    ) -> Result<()> {
+        instructions::sweep_rounding_dust::handler(ctx, pool_kind)
+    }
+
+    pub fn query_trade_stats(ctx: Context<QueryTradeStats>) -> Result<TradeStatsQuote> {
+        instructions::query_trade_stats::handler(ctx)
+    }
+
+    pub fn stable_withdraw(
+        ctx: Context<StableWithdraw>,
+        lp_amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::stable_withdraw::handler(ctx, lp_amount, min_amount_a, min_amount_b, dry_run)
+    }
+
+    pub fn stable_withdraw_single(
+        ctx: Context<StableWithdrawSingle>,
+        lp_amount: u64,
+        min_amount_out: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::stable_withdraw_single::handler(ctx, lp_amount, min_amount_out, dry_run)
+    }
+
+    pub fn initialize_lp_position(
+        ctx: Context<InitializeLpPosition>,
+        referrer: Pubkey,
+    ) -> Result<()> {
+        instructions::initialize_lp_position::handler(ctx, referrer)
+    }
+
+    pub fn set_referral_fee_share(
+        ctx: Context<SetReferralFeeShare>,
+        fee_share_bps: u16,
+    ) -> Result<()> {
+        instructions::set_referral_fee_share::handler(ctx, fee_share_bps)
+    }
+
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        instructions::claim_referral_rewards::handler(ctx)
+    }
+
+    pub fn stable_deposit_unbalanced(
+        ctx: Context<StableDepositUnbalanced>,
+        min_lp_amount: u64,
+        input_amount_a: u64,
+        input_amount_b: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::stable_deposit_unbalanced::handler(
+            ctx,
+            min_lp_amount,
+            input_amount_a,
+            input_amount_b,
+            dry_run,
+        )
+    }
+
+    pub fn stable_swap_exact_out(
+        ctx: Context<StableSwap>,
+        amount_out: u64,
+        max_amount_in: u64,
+        dry_run: bool,
+    ) -> Result<SwapResult> {
+        instructions::stable_swap_exact_out::handler(ctx, amount_out, max_amount_in, dry_run)
+    }
+
+    pub fn swap_exact_out(
+        ctx: Context<Swap>,
+        amount_out: u64,
+        max_amount_in: u64,
+        dry_run: bool,
+    ) -> Result<SwapResult> {
+        instructions::swap_exact_out::handler(ctx, amount_out, max_amount_in, dry_run)
+    }
+
+    pub fn audit_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AuditPool<'info>>,
+        pool_kind: PoolKind,
+    ) -> Result<()> {
+        instructions::audit_pool::handler(ctx, pool_kind)
+    }
+
+    pub fn deposit_with_refund(
+        ctx: Context<Deposit>,
+        lp_amount: u64,
+        input_token_a_amount: u64,
+        input_token_b_amount: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::deposit_with_refund::handler(
+            ctx,
+            lp_amount,
+            input_token_a_amount,
+            input_token_b_amount,
+            dry_run,
+        )
+    }
+
+    pub fn stable_deposit_with_refund(
+        ctx: Context<StableDeposit>,
+        max_amount_a: u64,
+        max_amount_b: u64,
+        lp_amount: u64,
+        dry_run: bool,
+    ) -> Result<()> {
+        instructions::stable_deposit_with_refund::handler(
+            ctx,
+            max_amount_a,
+            max_amount_b,
+            lp_amount,
+            dry_run,
+        )
+    }
+
+    pub fn initialize_upgrade_guard(
+        ctx: Context<InitializeUpgradeGuard>,
+        timelock: i64,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::initialize_upgrade_guard::handler(ctx, timelock, signers, threshold)
+    }
+
+    pub fn commit_upgrade(ctx: Context<CommitUpgrade>, hash: [u8; 32]) -> Result<()> {
+        instructions::commit_upgrade::handler(ctx, hash)
+    }
+
+    pub fn reveal_upgrade(ctx: Context<RevealUpgrade>, hash: [u8; 32]) -> Result<()> {
+        instructions::reveal_upgrade::handler(ctx, hash)
+    }
+
+    pub fn set_upgrade_signers(
+        ctx: Context<SetUpgradeSigners>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::set_upgrade_signers::handler(ctx, signers, threshold)
+    }
+
+    pub fn set_upgrade_pause<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SetUpgradePause<'info>>,
+        pool_kind: PoolKind,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::set_upgrade_pause::handler(ctx, pool_kind, enabled)
+    }
+
+    pub fn pause_on_unacknowledged_upgrade<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PauseOnUnacknowledgedUpgrade<'info>>,
+        pool_kind: PoolKind,
+    ) -> Result<()> {
+        instructions::pause_on_unacknowledged_upgrade::handler(ctx, pool_kind)
+    }
+
+    pub fn stable_deposit_multi<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StableDepositMulti<'info>>,
+        max_amounts_in: Vec<u64>,
+        lp_amount: u64,
+    ) -> Result<()> {
+        instructions::stable_deposit_multi::handler(ctx, max_amounts_in, lp_amount)
+    }
+
+    pub fn trip_breaker<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TripBreaker<'info>>,
+        pool_kind: PoolKind,
     ) -> Result<()> {
-        instructions::stable_swap::handler(ctx, amount_in, min_amount_out)
+        instructions::trip_breaker::handler(ctx, pool_kind)
     }
 }
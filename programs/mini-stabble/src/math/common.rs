@@ -0,0 +1,134 @@
+//! Math shared by weighted and stable pools
+
+use crate::constants::BPS_DENOMINATOR;
+
+/// Calculates proportional token amounts for a balanced withdraw
+/// Ring 2.11: Proportional liquidity math
+pub fn calc_tokens_out_proportional(
+    balances: &[u64],
+    lp_amount_in: u64,
+    lp_supply: u64,
+) -> Option<Vec<u64>> {
+    let mut amounts_out = Vec::with_capacity(balances.len());
+
+    for &balance in balances {
+        // amount_out = balance × lp_amount / lp_supply
+        let amount = (balance as u128)
+            .checked_mul(lp_amount_in as u128)?
+            .checked_div(lp_supply as u128)?;
+        amounts_out.push(u64::try_from(amount).ok()?);
+    }
+
+    Some(amounts_out)
+}
+
+/// Calculates the required token amounts for a proportional deposit
+/// Ring 2.11: Proportional liquidity math
+pub fn calc_tokens_in_proportional(
+    balances: &[u64],
+    lp_amount_out: u64,
+    lp_supply: u64,
+) -> Option<Vec<u64>> {
+    let mut amounts_in = Vec::with_capacity(balances.len());
+
+    for &balance in balances {
+        // amount_in = balance × lp_amount / lp_supply (round up to be safe)
+        let amount = (balance as u128)
+            .checked_mul(lp_amount_out as u128)?
+            .checked_add(lp_supply as u128 - 1)? // round up
+            .checked_div(lp_supply as u128)?;
+        amounts_in.push(u64::try_from(amount).ok()?);
+    }
+
+    Some(amounts_in)
+}
+
+/// Smallest output within `slippage_bps` of `quoted_amount_out`, rounded up so the tolerance is
+/// never looser than requested. `None` if `slippage_bps` exceeds 100%.
+pub fn calc_min_amount_out_for_slippage(quoted_amount_out: u64, slippage_bps: u16) -> Option<u64> {
+    let kept_bps = BPS_DENOMINATOR.checked_sub(slippage_bps.into())?;
+    let min_amount_out =
+        (quoted_amount_out as u128 * kept_bps as u128).div_ceil(BPS_DENOMINATOR as u128);
+
+    u64::try_from(min_amount_out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calc_proportional_withdraw() {
+        let balances = vec![1_000_000_000_000_u64, 2_000_000_000_000_u64];
+        let lp_supply = 3_000_000_000_000_u64;
+
+        // Withdraw 10% of LP
+        let lp_amount = 300_000_000_000_u64;
+
+        let amounts_out = calc_tokens_out_proportional(&balances, lp_amount, lp_supply).unwrap();
+
+        println!("Proportional withdraw: {} LP", lp_amount);
+        println!("Token 0 out: {}", amounts_out[0]);
+        println!("Token 1 out: {}", amounts_out[1]);
+
+        // Should get 10% of each token
+        assert_eq!(amounts_out[0], 100_000_000_000, "Should get 10% of token 0");
+        assert_eq!(amounts_out[1], 200_000_000_000, "Should get 10% of token 1");
+    }
+
+    #[test]
+    fn test_calc_proportional_deposit() {
+        let balances = vec![1_000_000_000_000_u64, 2_000_000_000_000_u64];
+        let lp_supply = 3_000_000_000_000_u64;
+
+        // Want 10% more LP tokens
+        let lp_amount = 300_000_000_000_u64;
+
+        let amounts_in = calc_tokens_in_proportional(&balances, lp_amount, lp_supply).unwrap();
+
+        println!("Proportional deposit for {} LP", lp_amount);
+        println!("Token 0 needed: {}", amounts_in[0]);
+        println!("Token 1 needed: {}", amounts_in[1]);
+
+        // Should need 10% of each token (rounded up)
+        assert!(
+            amounts_in[0] >= 100_000_000_000,
+            "Should need ~10% of token 0"
+        );
+        assert!(
+            amounts_in[1] >= 200_000_000_000,
+            "Should need ~10% of token 1"
+        );
+    }
+
+    #[test]
+    fn test_calc_proportional_rounding() {
+        // 1/3 of the supply does not divide the balances evenly
+        let balances = vec![1_000_u64, 2_000_u64];
+        let lp_supply = 3_u64;
+
+        let amounts_out = calc_tokens_out_proportional(&balances, 1, lp_supply).unwrap();
+        let amounts_in = calc_tokens_in_proportional(&balances, 1, lp_supply).unwrap();
+
+        // Exits round down and joins round up, both in the pool's favour
+        assert_eq!(amounts_out, vec![333, 666]);
+        assert_eq!(amounts_in, vec![334, 667]);
+    }
+
+    #[test]
+    fn test_calc_min_amount_out_for_slippage() {
+        assert_eq!(
+            calc_min_amount_out_for_slippage(1_000_000, 0),
+            Some(1_000_000)
+        );
+        assert_eq!(
+            calc_min_amount_out_for_slippage(1_000_000, 50),
+            Some(995_000)
+        );
+        assert_eq!(calc_min_amount_out_for_slippage(1_000_000, 10_000), Some(0));
+        assert_eq!(calc_min_amount_out_for_slippage(1_000_000, 10_001), None);
+
+        // 99.5% of 999 is 994.005, which rounds up to keep the bound tight
+        assert_eq!(calc_min_amount_out_for_slippage(999, 50), Some(995));
+    }
+}
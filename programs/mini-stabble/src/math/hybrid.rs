@@ -0,0 +1,175 @@
+use crate::{
+    constants::BPS_DENOMINATOR,
+    math::{
+        fixed::ONE_U64,
+        stable::{self, BALANCE_THRESHOLD},
+        weighted,
+    },
+};
+
+/// Widest band a hybrid curve may trade stable in, in bps below the peg
+pub const MAX_HYBRID_BAND_BPS: u64 = 5_000;
+
+/// Bisection steps spent locating the band edge; enough to pin any u64 amount
+const BAND_EDGE_SEARCH_STEPS: u32 = 64;
+
+/// Lowest price, in units of the token out (SCALE = 1e9), at which the token in still trades
+/// on the stable core
+pub fn calc_band_floor(band_bps: u64) -> Option<u64> {
+    if band_bps > MAX_HYBRID_BAND_BPS {
+        return None;
+    }
+
+    Some(ONE_U64 * (BPS_DENOMINATOR - band_bps) / BPS_DENOMINATOR)
+}
+
+/// Output of a swap on the hybrid curve: stableswap while the token sold trades at or above
+/// `calc_band_floor(band_bps)`, then a weighted wing whose weights are fixed so its price starts
+/// exactly at the band floor. The core gives stable-like depth near the peg; the wing never
+/// pays out the whole balance however far the pool is pushed.
+///
+/// Balances are scaled, like `stable::calc_out_given_in`, and every leg rounds in the pool's
+/// favor.
+pub fn calc_out_given_in(
+    amp: u64,
+    balances: &[u64],
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_in: u64,
+    band_bps: u64,
+) -> Option<u64> {
+    let floor = calc_band_floor(band_bps)?;
+    let invariant = stable::calc_invariant(amp, balances)?;
+
+    // Nothing in, nothing out; the curve itself can round an empty trade past the balance
+    let stable_out = |amount: u64| match amount {
+        0 => Some(0),
+        amount => stable::calc_out_given_in_with_invariant(
+            amp,
+            balances,
+            token_index_in,
+            token_index_out,
+            amount,
+            invariant,
+        ),
+    };
+    // Price of the token in after selling `amount` of it on the core
+    let price_after = |amount: u64| -> Option<u64> {
+        let mut balances_after = balances.to_vec();
+        balances_after[token_index_in] = balances_after[token_index_in].checked_add(amount)?;
+        balances_after[token_index_out] =
+            balances_after[token_index_out].checked_sub(stable_out(amount)?)?;
+        stable::calc_out_given_in_with_invariant(
+            amp,
+            &balances_after,
+            token_index_in,
+            token_index_out,
+            ONE_U64,
+            invariant,
+        )
+    };
+
+    // Largest part of the trade the core takes without pushing the price below the floor; a
+    // trade the core can't price at all is past it
+    let within_band = |amount: u64| price_after(amount).is_some_and(|price| price >= floor);
+    let core_amount_in = if within_band(amount_in) {
+        amount_in
+    } else if !within_band(0) {
+        0
+    } else {
+        let (mut low, mut high) = (0, amount_in);
+        for _ in 0..BAND_EDGE_SEARCH_STEPS {
+            if high - low <= BALANCE_THRESHOLD {
+                break;
+            }
+            let mid = low + (high - low) / 2;
+            if within_band(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    };
+
+    let core_amount_out = stable_out(core_amount_in)?;
+    let wing_amount_in = amount_in - core_amount_in;
+    if wing_amount_in == 0 {
+        return Some(core_amount_out);
+    }
+
+    // The wing is a weighted curve whose spot price, exponent × balance_out / balance_in, is
+    // the floor at the balances the core left behind
+    let balance_in = balances[token_index_in].checked_add(core_amount_in)? as u128;
+    let balance_out = balances[token_index_out].checked_sub(core_amount_out)? as u128;
+    let exponent = (floor as u128)
+        .checked_mul(balance_in)?
+        .checked_div(balance_out)?;
+    let wing_amount_out = weighted::calc_out_given_in_with_exponent(
+        balance_in,
+        balance_out,
+        exponent,
+        wing_amount_in as u128,
+    )
+    .ok()?;
+
+    core_amount_out.checked_add(u64::try_from(wing_amount_out).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::stable::AMP_PRECISION;
+
+    const AMP: u64 = 200 * AMP_PRECISION;
+    const BAND_BPS: u64 = 200;
+
+    #[test]
+    fn test_matches_stable_inside_band() {
+        let balances = [1_000_000 * ONE_U64, 1_000_000 * ONE_U64];
+        let amount_in = 10_000 * ONE_U64;
+
+        assert_eq!(
+            calc_out_given_in(AMP, &balances, 0, 1, amount_in, BAND_BPS),
+            stable::calc_out_given_in(AMP, &balances, 0, 1, amount_in)
+        );
+    }
+
+    #[test]
+    fn test_wing_keeps_pool_solvent_far_from_peg() {
+        let balances = [1_000_000 * ONE_U64, 1_000_000 * ONE_U64];
+        let amount_in = 5_000_000 * ONE_U64;
+
+        let hybrid_out = calc_out_given_in(AMP, &balances, 0, 1, amount_in, BAND_BPS).unwrap();
+        let stable_out = stable::calc_out_given_in(AMP, &balances, 0, 1, amount_in).unwrap();
+
+        assert!(hybrid_out < stable_out);
+        assert!(hybrid_out < balances[1]);
+        // Still at least what the core pays up to the band edge
+        assert!(hybrid_out > balances[1] / 2);
+    }
+
+    #[test]
+    fn test_output_grows_across_band_edge() {
+        let balances = [1_000_000 * ONE_U64, 1_200_000 * ONE_U64];
+
+        let mut previous = 0;
+        for step in 1..=40 {
+            let amount_in = step * 50_000 * ONE_U64;
+            let amount_out = calc_out_given_in(AMP, &balances, 0, 1, amount_in, BAND_BPS).unwrap();
+            assert!(amount_out > previous, "step {step}");
+            // Never better than the stable curve alone
+            assert!(
+                amount_out <= stable::calc_out_given_in(AMP, &balances, 0, 1, amount_in).unwrap()
+            );
+            previous = amount_out;
+        }
+    }
+
+    #[test]
+    fn test_band_bounds() {
+        assert_eq!(calc_band_floor(0), Some(ONE_U64));
+        assert_eq!(calc_band_floor(BAND_BPS), Some(980_000_000));
+        assert!(calc_band_floor(MAX_HYBRID_BAND_BPS + 1).is_none());
+    }
+}
@@ -1,3 +1,6 @@
+pub mod common;
 pub mod fixed;
 pub mod weighted;
-pub mod stable;
\ No newline at end of file
+pub mod stable;
+pub mod swap_context;
+pub mod hybrid;
@@ -1,4 +1,7 @@
-use crate::math::fixed::{FixedComplement, FixedDiv, FixedMul, ONE_U64};
+use crate::{
+    constants::BPS_DENOMINATOR,
+    math::fixed::{FixedComplement, FixedDiv, FixedMul, ONE_U64},
+};
 use bn::{
     safe_math::{CheckedDivCeil, CheckedMulDiv, Downcast},
     uint192, U192,
@@ -8,6 +11,26 @@ pub const AMP_PRECISION: u64 = 1_000;
 pub const MIN_AMP: u64 = 1;
 pub const MAX_AMP: u64 = 10_000;
 pub const MAX_LOOP_LIMIT: u64 = 256;
+pub const MAX_BALANCE_LOOP_LIMIT: u64 = 64;
+
+// Smallest scaled swap input; keeps the 1-unit rounding protection in `calc_out_given_in`
+// under 0.1% of the trade
+pub const MIN_SWAP_AMOUNT: u64 = 1_000;
+
+// Amp ramp bounds
+pub const MIN_RAMP_DURATION: i64 = 86_400;
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+// Token counts the invariant is validated for; `StablePool::tokens` holds at most MAX_TOKENS
+pub const MIN_TOKENS: usize = 2;
+pub const MAX_TOKENS: usize = 8;
+
+// Stress mode: below this share of the pool (bps) a token's side is thin enough that the
+// invariant's Newton iteration works at the edge of convergence
+pub const STRESS_BALANCE_SHARE_BPS: u64 = 200;
+
+// Taken off the par price stress-mode swaps are capped at (SCALE = 1e9, 5%)
+pub const STRESS_SURCHARGE: u64 = 50_000_000;
 
 // Convergence thresholds
 pub const DEFAULT_INV_THRESHOLD: u64 = 100;
@@ -28,14 +51,72 @@ pub fn calc_spot_price(
     calc_out_given_in(amp, balances, token_index_in, token_index_out, ref_amount)
 }
 
+/// Interpolates the amp linearly between `amp_start` at `start_ts` and `amp_target` at `end_ts`.
+pub fn calc_current_amp(
+    amp_start: u64,
+    amp_target: u64,
+    start_ts: i64,
+    end_ts: i64,
+    now: i64,
+) -> Option<u64> {
+    if now >= end_ts || end_ts <= start_ts {
+        return Some(amp_target);
+    }
+    if now <= start_ts {
+        return Some(amp_start);
+    }
+
+    let elapsed = (now - start_ts) as u128;
+    let duration = (end_ts - start_ts) as u128;
+
+    if amp_target > amp_start {
+        let delta = ((amp_target - amp_start) as u128)
+            .checked_mul(elapsed)?
+            .checked_div(duration)?;
+        amp_start.checked_add(u64::try_from(delta).ok()?)
+    } else {
+        let delta = ((amp_start - amp_target) as u128)
+            .checked_mul(elapsed)?
+            .checked_div(duration)?;
+        amp_start.checked_sub(u64::try_from(delta).ok()?)
+    }
+}
+
+/// Minimum swap fee allowed at `amp` (with AMP_PRECISION).
+/// Higher amp prices tighter around the peg, so the floor shrinks as `coefficient / amp`,
+/// rounded up so a non-zero coefficient never yields a zero floor.
+pub fn calc_min_fee_for_amp(amp: u64, fee_floor_coefficient: u64) -> Option<u64> {
+    if amp == 0 {
+        return None;
+    }
+
+    let floor = (fee_floor_coefficient as u128)
+        .checked_mul(AMP_PRECISION as u128)?
+        .checked_add(amp as u128 - 1)?
+        .checked_div(amp as u128)?;
+
+    u64::try_from(floor).ok()
+}
+
 /// Calculates the StableSwap invariant D using Newton-Raphson iteration.
 /// Matches reference: libraries/math/src/stable_math.rs calc_invariant
 pub fn calc_invariant(amp: u64, balances: &[u64]) -> Option<u64> {
+    calc_invariant_with_iterations(amp, balances).map(|(invariant, _)| invariant)
+}
+
+/// `calc_invariant`, also returning how many Newton iterations it took to converge
+fn calc_invariant_with_iterations(amp: u64, balances: &[u64]) -> Option<(u64, u64)> {
+    if !(MIN_TOKENS..=MAX_TOKENS).contains(&balances.len()) {
+        return None;
+    }
+
     let n = balances.len() as u64;
-    let sum: u64 = balances.iter().sum();
+    let sum = balances
+        .iter()
+        .try_fold(0u64, |sum, &balance| sum.checked_add(balance))?;
 
     if sum == 0 {
-        return Some(0);
+        return Some((0, 0));
     }
 
     let ann = uint192!(amp.checked_mul(n)?);
@@ -43,7 +124,7 @@ pub fn calc_invariant(amp: u64, balances: &[u64]) -> Option<u64> {
     let n_u192 = uint192!(n);
     let mut d = uint192!(sum);
 
-    for _ in 0..MAX_LOOP_LIMIT {
+    for iteration in 1..=MAX_LOOP_LIMIT {
         let mut dp = d;
         for &balance in balances.iter() {
             dp = dp.checked_mul_div_down(d, n_u192.checked_mul(uint192!(balance))?)?;
@@ -71,7 +152,7 @@ pub fn calc_invariant(amp: u64, balances: &[u64]) -> Option<u64> {
             d.checked_sub(d_new)?
         };
         if diff <= uint192!(DEFAULT_INV_THRESHOLD) {
-            return d_new.as_u64();
+            return Some((d_new.as_u64()?, iteration));
         }
         d = d_new;
     }
@@ -88,6 +169,18 @@ fn get_token_balance_given_invariant_and_others(
     invariant: u64,
     token_index: usize,
 ) -> Option<u64> {
+    get_token_balance_with_iterations(amp, balances, invariant, token_index)
+        .map(|(token_balance, _)| token_balance)
+}
+
+/// `get_token_balance_given_invariant_and_others`, also returning how many Newton iterations
+/// it took to converge
+fn get_token_balance_with_iterations(
+    amp: u64,
+    balances: &[u64],
+    invariant: u64,
+    token_index: usize,
+) -> Option<(u64, u64)> {
     let num_tokens = balances.len() as u64;
     let amp_times_total = uint192!(amp.checked_mul(num_tokens)?);
 
@@ -97,10 +190,10 @@ fn get_token_balance_given_invariant_and_others(
     let mut sum = balances[0];
     let mut p = uint192!(balances[0].checked_mul(num_tokens)?);
 
-    for i in 1..balances.len() {
-        let p_i = uint192!(balances[i].checked_mul(num_tokens)?);
+    for &balance in balances.iter().skip(1) {
+        let p_i = uint192!(balance.checked_mul(num_tokens)?);
         p = p.checked_mul_div_down(p_i, invariant)?;
-        sum = sum.checked_add(balances[i])?;
+        sum = sum.checked_add(balance)?;
     }
 
     // Remove the balance at token_index from sum
@@ -127,7 +220,7 @@ fn get_token_balance_given_invariant_and_others(
         .checked_div_up(invariant.checked_add(b)?)?;
 
     // Newton-Raphson iteration: y = (y² + c) / (2y + b - D)
-    for _ in 0..64 {
+    for iteration in 1..=MAX_BALANCE_LOOP_LIMIT {
         let prev_token_balance = token_balance;
 
         token_balance = token_balance
@@ -144,10 +237,10 @@ fn get_token_balance_given_invariant_and_others(
 
         if token_balance_u64 > prev_token_balance_u64 {
             if token_balance_u64.saturating_sub(prev_token_balance_u64) <= BALANCE_THRESHOLD {
-                return Some(token_balance_u64);
+                return Some((token_balance_u64, iteration));
             }
         } else if prev_token_balance_u64.saturating_sub(token_balance_u64) <= BALANCE_THRESHOLD {
-            return Some(token_balance_u64);
+            return Some((token_balance_u64, iteration));
         }
     }
 
@@ -162,9 +255,25 @@ pub fn calc_out_given_in(
     token_index_out: usize,
     amount_in: u64,
 ) -> Option<u64> {
-    // Calculate invariant first
-    let invariant = calc_invariant(amp, balances)?;
+    calc_out_given_in_with_invariant(
+        amp,
+        balances,
+        token_index_in,
+        token_index_out,
+        amount_in,
+        calc_invariant(amp, balances)?,
+    )
+}
 
+/// Same as `calc_out_given_in`, against an invariant the caller already has
+pub fn calc_out_given_in_with_invariant(
+    amp: u64,
+    balances: &[u64],
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_in: u64,
+    invariant: u64,
+) -> Option<u64> {
     // Create new balances with amount_in added
     let mut new_balances = balances.to_vec();
     new_balances[token_index_in] = new_balances[token_index_in].checked_add(amount_in)?;
@@ -180,7 +289,12 @@ pub fn calc_out_given_in(
     )?;
 
     // Output = current_balance - final_balance - 1 (for rounding protection)
-    balance_out.checked_sub(final_balance_out)?.checked_sub(1)
+    // Saturates so sub-dust trades quote zero instead of failing
+    Some(
+        balance_out
+            .checked_sub(final_balance_out)?
+            .saturating_sub(1),
+    )
 }
 
 /// Calculates how many tokens must be sent to get `amount_out`.
@@ -270,9 +384,7 @@ pub fn calc_lp_tokens_for_deposit_with_fee(
     let mut new_balances = Vec::with_capacity(balances.len());
 
     for i in 0..balances.len() {
-        let amount_in_without_fee;
-
-        if balance_ratios[i] > ideal_ratio {
+        let amount_in_without_fee = if balance_ratios[i] > ideal_ratio {
             // This token has excess deposit → taxable portion
             let non_taxable = balances[i]
                 .mul_down(ideal_ratio.saturating_sub(ONE_U64))
@@ -280,14 +392,14 @@ pub fn calc_lp_tokens_for_deposit_with_fee(
             let taxable = amounts_in[i].saturating_sub(non_taxable);
 
             // Apply fee: taxable * (1 - swap_fee) + non_taxable
-            amount_in_without_fee = taxable
+            taxable
                 .mul_down(swap_fee.complement())
                 .ok()?
-                .checked_add(non_taxable)?;
+                .checked_add(non_taxable)?
         } else {
             // Below ideal ratio → no fee
-            amount_in_without_fee = amounts_in[i];
-        }
+            amounts_in[i]
+        };
 
         new_balances.push(balances[i].checked_add(amount_in_without_fee)?);
     }
@@ -347,45 +459,87 @@ pub fn calc_token_out_for_lp_burn(
         .checked_add(non_taxable_amount)
 }
 
-/// Calculates proportional token amounts for a balanced withdraw
-/// Ring 2.11: Proportional liquidity math
-pub fn calc_tokens_out_proportional(
-    balances: &[u64],
-    lp_amount_in: u64,
-    lp_supply: u64,
-) -> Option<Vec<u64>> {
-    let mut amounts_out = Vec::with_capacity(balances.len());
-
-    for &balance in balances {
-        // amount_out = balance × lp_amount / lp_supply
-        let amount = (balance as u128)
-            .checked_mul(lp_amount_in as u128)?
-            .checked_div(lp_supply as u128)?;
-        amounts_out.push(u64::try_from(amount).ok()?);
+/// Token whose share of the pool is below `STRESS_BALANCE_SHARE_BPS`, and its share in bps;
+/// `None` while every side is deep enough to price on the curve alone
+pub fn stressed_token(balances: &[u64]) -> Option<(usize, u64)> {
+    let total = balances
+        .iter()
+        .map(|&balance| balance as u128)
+        .sum::<u128>();
+    if total == 0 {
+        return None;
     }
 
-    Some(amounts_out)
+    let (index, &balance) = balances
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, &balance)| balance)?;
+    let share_bps = (balance as u128 * BPS_DENOMINATOR as u128 / total) as u64;
+
+    (share_bps < STRESS_BALANCE_SHARE_BPS).then_some((index, share_bps))
 }
 
-/// Calculates the required token amounts for a proportional deposit
-/// Ring 2.11: Proportional liquidity math  
-pub fn calc_tokens_in_proportional(
+/// Stress-mode output for `amount_in`, before the swap fee. A trade taking the scarce token out
+/// is paid par less `STRESS_SURCHARGE`, and never more than the curve pays when it can price
+/// the trade at all; near the edge the curve is only trusted to lower that quote, so a trade it
+/// can't solve still gets a bounded one. Selling the scarce token moves the pool away from the
+/// edge and is priced by the curve alone.
+pub fn calc_stress_out_given_in(
+    amp: u64,
     balances: &[u64],
-    lp_amount_out: u64,
-    lp_supply: u64,
-) -> Option<Vec<u64>> {
-    let mut amounts_in = Vec::with_capacity(balances.len());
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_in: u64,
+    invariant: u64,
+) -> Option<u64> {
+    let curve_out = calc_out_given_in_with_invariant(
+        amp,
+        balances,
+        token_index_in,
+        token_index_out,
+        amount_in,
+        invariant,
+    );
+    if !is_stressed_out(balances, token_index_out) {
+        return curve_out;
+    }
+
+    let par_out = amount_in.mul_down(STRESS_SURCHARGE.complement()).ok()?;
+    let amount_out = match curve_out {
+        Some(curve_out) => curve_out.min(par_out),
+        None => par_out,
+    };
 
-    for &balance in balances {
-        // amount_in = balance × lp_amount / lp_supply (round up to be safe)
-        let amount = (balance as u128)
-            .checked_mul(lp_amount_out as u128)?
-            .checked_add(lp_supply as u128 - 1)? // round up
-            .checked_div(lp_supply as u128)?;
-        amounts_in.push(u64::try_from(amount).ok()?);
+    (amount_out < balances[token_index_out]).then_some(amount_out)
+}
+
+/// Whether `tokens[token_index_out]` is the token `stressed_token` flags
+fn is_stressed_out(balances: &[u64], token_index_out: usize) -> bool {
+    stressed_token(balances).is_some_and(|(index, _)| index == token_index_out)
+}
+
+/// Inverse of `calc_stress_out_given_in`: buying the scarce token costs par grossed up by
+/// `STRESS_SURCHARGE`, and never less than the curve asks for when it can price the trade
+pub fn calc_stress_in_given_out(
+    amp: u64,
+    balances: &[u64],
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_out: u64,
+) -> Option<u64> {
+    let curve_in = calc_in_given_out(amp, balances, token_index_in, token_index_out, amount_out);
+    if !is_stressed_out(balances, token_index_out) {
+        return curve_in;
+    }
+    if amount_out >= balances[token_index_out] {
+        return None;
     }
 
-    Some(amounts_in)
+    let par_in = amount_out.div_up(STRESS_SURCHARGE.complement()).ok()?;
+    Some(match curve_in {
+        Some(curve_in) => curve_in.max(par_in),
+        None => par_in,
+    })
 }
 
 #[cfg(test)]
@@ -460,6 +614,44 @@ mod tests {
         assert_eq!(amount_out, 999845, "1M swap should match reference");
     }
 
+    #[test]
+    fn test_calc_out_given_in_dust() {
+        let amp = 5_000_000;
+        let balances = vec![894_520_800_000_000_u64, 467_581_800_000_000_u64];
+
+        // Rounding protection eats the whole output instead of underflowing
+        assert_eq!(calc_out_given_in(amp, &balances, 0, 1, 1), Some(0));
+
+        // At the minimum trade size the output is non-zero
+        let amount_out = calc_out_given_in(amp, &balances, 0, 1, MIN_SWAP_AMOUNT).unwrap();
+        assert!(amount_out > 0 && amount_out < MIN_SWAP_AMOUNT);
+    }
+
+    #[test]
+    fn test_calc_out_given_in_with_stale_invariant() {
+        let amp = 5_000_000;
+        let balances = vec![894_520_800_000_000_u64, 467_581_800_000_000_u64];
+        let amount_in = 1_000_000_000_000;
+        let invariant = calc_invariant(amp, &balances).unwrap();
+
+        assert_eq!(
+            calc_out_given_in_with_invariant(amp, &balances, 0, 1, amount_in, invariant),
+            calc_out_given_in(amp, &balances, 0, 1, amount_in)
+        );
+
+        // A cached D that missed some fee growth quotes in the trader's favour
+        let stale = calc_out_given_in_with_invariant(
+            amp,
+            &balances,
+            0,
+            1,
+            amount_in,
+            invariant - 1_000_000,
+        )
+        .unwrap();
+        assert!(stale > calc_out_given_in(amp, &balances, 0, 1, amount_in).unwrap());
+    }
+
     #[test]
     fn test_calc_in_given_out() {
         let amp = 5_000_000;
@@ -523,6 +715,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_calc_invariant_five_to_eight_tokens() {
+        for n in 5..=MAX_TOKENS {
+            for amp in [1_000, 100_000, 10_000_000] {
+                // A balanced pool sits on the peg, so D is the plain sum
+                let balances = vec![1_000_000_000_000_000_u64; n];
+                let sum = balances.iter().sum::<u64>();
+                let invariant = calc_invariant(amp, &balances).unwrap();
+                assert!(
+                    invariant.abs_diff(sum) <= n as u64,
+                    "{n} tokens, amp {amp}: D {invariant} drifted from sum {sum}"
+                );
+
+                // Off the peg D stays below the sum but within the curve's range
+                let balances: Vec<u64> = (0..n as u64)
+                    .map(|i| 1_000_000_000_000_000 + i * 100_000_000_000_000)
+                    .collect();
+                let sum = balances.iter().sum::<u64>();
+                let invariant = calc_invariant(amp, &balances).unwrap();
+                assert!(invariant < sum && invariant > sum / 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_swap_round_trip_five_to_eight_tokens() {
+        let cases = [
+            (1_000_000_000_000_000_u64, 1_000_000_000_000_000_u64),
+            (1_000_000_000_000_000, 1_000_000_000),
+            (100_000_000_000_000_000, 1_000_000),
+            (1_000_000_000_000, 1_000),
+        ];
+
+        for n in 5..=MAX_TOKENS {
+            for (big, small) in cases {
+                for amp in [1_000, 100_000, 10_000_000] {
+                    let mut balances = vec![big; n];
+                    balances[0] = small;
+                    let amount_in = small / 10;
+
+                    let amount_out = calc_out_given_in(amp, &balances, 0, n - 1, amount_in)
+                        .unwrap_or_else(|| panic!("{n} tokens, {big}/{small}, amp {amp}"));
+                    balances[0] += amount_in;
+                    balances[n - 1] -= amount_out;
+
+                    // Swapping the output straight back never returns more than was sent
+                    let amount_back =
+                        calc_out_given_in(amp, &balances, n - 1, 0, amount_out).unwrap();
+                    assert!(
+                        amount_back <= amount_in,
+                        "{n} tokens, {big}/{small}, amp {amp}: {amount_in} in, {amount_back} back"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_calc_invariant_rejects_unsupported_pools() {
+        assert_eq!(calc_invariant(100_000, &[1_000_000_000]), None);
+        assert_eq!(
+            calc_invariant(100_000, &[1_000_000_000; MAX_TOKENS + 1]),
+            None
+        );
+
+        // Balances whose sum overflows fail instead of wrapping
+        assert_eq!(calc_invariant(100_000, &[u64::MAX / 2 + 1; 2]), None);
+    }
+
     #[test]
     fn test_calc_lp_tokens_for_deposit_simple() {
         let amp = 5_000_000;
@@ -550,6 +811,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_calc_token_out_for_lp_burn() {
+        let amp = 5_000_000;
+        let balances = vec![1_000_000_000_000_u64, 1_000_000_000_000_u64];
+        let lp_supply = 2_000_000_000_000_u64;
+        let invariant = calc_invariant(amp, &balances).unwrap();
+
+        // Burning 1% of supply is worth 10B of each token, 20B in all at the peg
+        let lp_in = 20_000_000_000_u64;
+        let amount_out =
+            calc_token_out_for_lp_burn(amp, &balances, 0, lp_in, lp_supply, invariant, 0).unwrap();
+        assert!(amount_out < 20_000_000_000 && amount_out > 19_900_000_000);
+
+        // The fee only applies to the half a proportional exit would have paid in token 1
+        let with_fee =
+            calc_token_out_for_lp_burn(amp, &balances, 0, lp_in, lp_supply, invariant, 3_000_000)
+                .unwrap();
+        let fee = amount_out - with_fee;
+        assert!(fee > 29_000_000 && fee <= 30_000_000);
+
+        assert!(calc_token_out_for_lp_burn(
+            amp,
+            &balances,
+            0,
+            lp_supply + 1,
+            lp_supply,
+            invariant,
+            0
+        )
+        .is_none());
+    }
+
     #[test]
     fn test_calc_lp_tokens_with_fee_imbalanced() {
         let amp = 5_000_000;
@@ -588,46 +881,143 @@ mod tests {
     }
 
     #[test]
-    fn test_calc_proportional_withdraw() {
-        let balances = vec![1_000_000_000_000_u64, 2_000_000_000_000_u64];
-        let lp_supply = 3_000_000_000_000_u64;
+    fn test_calc_current_amp_ramp() {
+        let start = 1_000;
+        let end = 1_000 + MIN_RAMP_DURATION;
+
+        // Ramp up from 100 to 1000 (with AMP_PRECISION)
+        let ramp_up = |now| calc_current_amp(100_000, 1_000_000, start, end, now);
+        assert_eq!(ramp_up(0), Some(100_000));
+        assert_eq!(ramp_up(start), Some(100_000));
+        assert_eq!(ramp_up(start + MIN_RAMP_DURATION / 2), Some(550_000));
+        assert_eq!(ramp_up(end), Some(1_000_000));
+        assert_eq!(ramp_up(end + 1), Some(1_000_000));
+
+        // Ramp down
+        let ramp_down = |now| calc_current_amp(1_000_000, 100_000, start, end, now);
+        assert_eq!(ramp_down(start + MIN_RAMP_DURATION / 2), Some(550_000));
+
+        // No ramp scheduled
+        assert_eq!(
+            calc_current_amp(100_000, 100_000, 0, 0, 12345),
+            Some(100_000)
+        );
+    }
+
+    #[test]
+    fn test_calc_min_fee_for_amp() {
+        // Coefficient of 0.1% at amp = 1
+        let coefficient = 1_000_000_u64;
 
-        // Withdraw 10% of LP
-        let lp_amount = 300_000_000_000_u64;
+        let floor_low_amp = calc_min_fee_for_amp(10 * AMP_PRECISION, coefficient).unwrap();
+        let floor_high_amp = calc_min_fee_for_amp(1_000 * AMP_PRECISION, coefficient).unwrap();
 
-        let amounts_out = calc_tokens_out_proportional(&balances, lp_amount, lp_supply).unwrap();
+        assert_eq!(floor_low_amp, 100_000);
+        assert_eq!(floor_high_amp, 1_000);
+        assert!(
+            floor_high_amp < floor_low_amp,
+            "Higher amp should lower the floor"
+        );
 
-        println!("Proportional withdraw: {} LP", lp_amount);
-        println!("Token 0 out: {}", amounts_out[0]);
-        println!("Token 1 out: {}", amounts_out[1]);
+        // Never rounds a non-zero coefficient down to a zero floor
+        assert_eq!(calc_min_fee_for_amp(MAX_AMP * AMP_PRECISION, 1), Some(1));
+        assert_eq!(calc_min_fee_for_amp(MAX_AMP * AMP_PRECISION, 0), Some(0));
+    }
 
-        // Should get 10% of each token
-        assert_eq!(amounts_out[0], 100_000_000_000, "Should get 10% of token 0");
-        assert_eq!(amounts_out[1], 200_000_000_000, "Should get 10% of token 1");
+    /// Newton iterations allowed across `convergence_matrix`, with a few to spare over the
+    /// current worst cases (12 and 15) so a math change that slows convergence fails here
+    /// before it shows up as an exhausted compute budget on-chain
+    const INVARIANT_ITERATION_BUDGET: u64 = 16;
+    const BALANCE_ITERATION_BUDGET: u64 = 20;
+
+    /// Amps and token counts spanning everything a pool can be configured with, against pools
+    /// from balanced to one token drained to `1 / imbalance` of the others
+    fn convergence_matrix() -> Vec<(u64, Vec<u64>)> {
+        let mut matrix = Vec::new();
+        for amp in [MIN_AMP, 10, 100, 1_000, MAX_AMP] {
+            for n in [2, 3, 5, MAX_TOKENS] {
+                for imbalance in [1, 10, 100, 1_000, 10_000] {
+                    let mut balances = vec![1_000_000 * ONE_U64; n];
+                    balances[0] /= imbalance;
+                    matrix.push((amp * AMP_PRECISION, balances));
+                }
+            }
+        }
+        matrix
     }
 
     #[test]
-    fn test_calc_proportional_deposit() {
-        let balances = vec![1_000_000_000_000_u64, 2_000_000_000_000_u64];
-        let lp_supply = 3_000_000_000_000_u64;
+    fn test_invariant_iterations_within_budget() {
+        for (amp, balances) in convergence_matrix() {
+            let (_, iterations) = calc_invariant_with_iterations(amp, &balances).unwrap();
+            assert!(
+                iterations <= INVARIANT_ITERATION_BUDGET,
+                "amp {amp}, balances {balances:?}: {iterations} iterations"
+            );
+        }
+    }
 
-        // Want 10% more LP tokens
-        let lp_amount = 300_000_000_000_u64;
+    #[test]
+    fn test_balance_iterations_within_budget() {
+        for (amp, balances) in convergence_matrix() {
+            let invariant = calc_invariant(amp, &balances).unwrap();
+            for token_index in [0, 1] {
+                let mut balances = balances.clone();
+                // Quote the balance a 1% trade into the other token leaves
+                balances[1 - token_index] += balances[1 - token_index] / 100;
+                let (_, iterations) =
+                    get_token_balance_with_iterations(amp, &balances, invariant, token_index)
+                        .unwrap();
+                assert!(
+                    iterations <= BALANCE_ITERATION_BUDGET,
+                    "amp {amp}, balances {balances:?}, token {token_index}: {iterations} iterations"
+                );
+            }
+        }
+    }
 
-        let amounts_in = calc_tokens_in_proportional(&balances, lp_amount, lp_supply).unwrap();
+    #[test]
+    fn test_stress_mode_starts_below_two_percent() {
+        assert_eq!(stressed_token(&[ONE_U64, ONE_U64]), None);
+        assert_eq!(stressed_token(&[98 * ONE_U64, 2 * ONE_U64]), None);
+        assert_eq!(stressed_token(&[999 * ONE_U64, ONE_U64]), Some((1, 10)));
+        assert_eq!(stressed_token(&[0, 0]), None);
+    }
 
-        println!("Proportional deposit for {} LP", lp_amount);
-        println!("Token 0 needed: {}", amounts_in[0]);
-        println!("Token 1 needed: {}", amounts_in[1]);
+    #[test]
+    fn test_stress_pricing_at_extreme_imbalance() {
+        let amp = 200 * AMP_PRECISION;
+        let balances = [999_000 * ONE_U64, 1_000 * ONE_U64];
+        assert!(stressed_token(&balances).is_some());
+        let invariant = calc_invariant(amp, &balances).unwrap();
+        let amount = 100 * ONE_U64;
 
-        // Should need 10% of each token (rounded up)
-        assert!(
-            amounts_in[0] >= 100_000_000_000,
-            "Should need ~10% of token 0"
-        );
-        assert!(
-            amounts_in[1] >= 200_000_000_000,
-            "Should need ~10% of token 1"
+        // Selling the scarce token: the curve's premium is paid in full
+        let sell_scarce =
+            calc_stress_out_given_in(amp, &balances, 1, 0, amount, invariant).unwrap();
+        assert_eq!(
+            sell_scarce,
+            calc_out_given_in_with_invariant(amp, &balances, 1, 0, amount, invariant).unwrap()
         );
+        assert!(sell_scarce > amount);
+
+        // Buying the scarce token: capped below par, never more than the curve, and never the
+        // whole side
+        let buy_scarce = calc_stress_out_given_in(amp, &balances, 0, 1, amount, invariant).unwrap();
+        assert!(buy_scarce <= amount.mul_down(STRESS_SURCHARGE.complement()).unwrap());
+        assert!(buy_scarce <= calc_out_given_in(amp, &balances, 0, 1, amount).unwrap());
+        let drain = calc_stress_out_given_in(amp, &balances, 0, 1, 900_000 * ONE_U64, invariant);
+        assert!(drain.is_none_or(|amount_out| amount_out < balances[1]));
+
+        // The inverse charges at least enough to buy the output back
+        for (index_in, index_out, amount_out) in [(1, 0, sell_scarce), (0, 1, buy_scarce)] {
+            let amount_in =
+                calc_stress_in_given_out(amp, &balances, index_in, index_out, amount_out).unwrap();
+            let bought =
+                calc_stress_out_given_in(amp, &balances, index_in, index_out, amount_in, invariant)
+                    .unwrap();
+            assert!(bought + 1 >= amount_out);
+        }
+        assert!(calc_stress_in_given_out(amp, &balances, 0, 1, balances[1]).is_none());
     }
 }
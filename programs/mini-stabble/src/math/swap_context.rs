@@ -0,0 +1,386 @@
+//! Everything a swap's math needs, read from the pool once per instruction
+
+use crate::{
+    errors::MiniStabbleError,
+    math::{
+        fixed::{FixedComplement, FixedDiv, FixedMul},
+        stable::{self, calc_invariant, calc_out_given_in_with_invariant},
+        weighted,
+    },
+};
+
+/// Curve parameters of the pool being swapped against
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SwapCurve {
+    /// Normalized weights (SCALE = 1e9), in pool token order, and the `weight_in / weight_out`
+    /// exponent of this swap direction
+    Weighted { weights: Vec<u64>, exponent: u64 },
+
+    /// Current amp and the invariant to quote against
+    Stable { amp: u64, invariant: u64 },
+}
+
+/// Snapshot of a pool's scaled balances, fee and curve for one swap direction, so a handler
+/// reads `pool.tokens` once and its math can be unit tested without accounts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapContext {
+    pub index_in: usize,
+    pub index_out: usize,
+
+    /// Scaled balances, in pool token order
+    pub balances: Vec<u64>,
+
+    /// Swap fee (SCALE = 1e9)
+    pub swap_fee: u64,
+
+    pub curve: SwapCurve,
+}
+
+/// Result of running a swap through a `SwapContext`, in the units of the amount in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// What the pool sends out
+    pub amount_out: u64,
+
+    /// Output kept by the pool as the swap fee
+    pub fee: u64,
+}
+
+/// Result of quoting an exact output through a `SwapContext`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapInQuote {
+    /// What the trader must send in
+    pub amount_in: u64,
+
+    /// Output the swap fee cost the trader, on top of the amount out
+    pub fee: u64,
+}
+
+impl SwapContext {
+    /// Weighted context swapping with the cached `exponent`, or with the exponent computed from
+    /// `weights` when `None`
+    pub fn weighted(
+        balances: Vec<u64>,
+        weights: Vec<u64>,
+        exponent: Option<u64>,
+        swap_fee: u64,
+        index_in: usize,
+        index_out: usize,
+    ) -> Result<Self, MiniStabbleError> {
+        let exponent = match exponent {
+            Some(exponent) => exponent,
+            None => u64::try_from(weighted::calc_swap_exponent(
+                weights[index_in].into(),
+                weights[index_out].into(),
+            )?)
+            .map_err(|_| MiniStabbleError::MathOverflow)?,
+        };
+
+        Ok(Self {
+            index_in,
+            index_out,
+            balances,
+            swap_fee,
+            curve: SwapCurve::Weighted { weights, exponent },
+        })
+    }
+
+    /// Stable context quoting against `invariant`, or against the invariant of `balances` when
+    /// `None`
+    pub fn stable(
+        balances: Vec<u64>,
+        amp: u64,
+        invariant: Option<u64>,
+        swap_fee: u64,
+        index_in: usize,
+        index_out: usize,
+    ) -> Result<Self, MiniStabbleError> {
+        let invariant = match invariant {
+            Some(invariant) => invariant,
+            None => calc_invariant(amp, &balances).ok_or(MiniStabbleError::MathOverflow)?,
+        };
+
+        Ok(Self {
+            index_in,
+            index_out,
+            balances,
+            swap_fee,
+            curve: SwapCurve::Stable { amp, invariant },
+        })
+    }
+
+    /// Output for `amount_in` before the swap fee
+    pub fn calc_out_before_fee(&self, amount_in: u64) -> Result<u64, MiniStabbleError> {
+        let amount_out = match &self.curve {
+            SwapCurve::Weighted { exponent, .. } => {
+                u64::try_from(weighted::calc_out_given_in_with_exponent(
+                    self.balances[self.index_in].into(),
+                    self.balances[self.index_out].into(),
+                    (*exponent).into(),
+                    amount_in.into(),
+                )?)
+            }
+            .map_err(|_| MiniStabbleError::MathOverflow)?,
+            SwapCurve::Stable { amp, invariant } => {
+                // A side thinner than the stress share is priced conservatively, not by the
+                // curve alone
+                let calc_out = match stable::stressed_token(&self.balances) {
+                    Some(_) => stable::calc_stress_out_given_in,
+                    None => calc_out_given_in_with_invariant,
+                };
+                calc_out(
+                    *amp,
+                    &self.balances,
+                    self.index_in,
+                    self.index_out,
+                    amount_in,
+                    *invariant,
+                )
+                .ok_or(MiniStabbleError::InvalidAmount)?
+            }
+        };
+
+        Ok(amount_out)
+    }
+
+    /// Output for `amount_in` after the swap fee, and the fee
+    pub fn calc_out_given_in(&self, amount_in: u64) -> Result<SwapQuote, MiniStabbleError> {
+        let amount_out_before_fee = self.calc_out_before_fee(amount_in)?;
+        let amount_out = u64::try_from(
+            (amount_out_before_fee as u128).mul_down(self.swap_fee.complement() as u128)?,
+        )
+        .map_err(|_| MiniStabbleError::MathOverflow)?;
+
+        Ok(SwapQuote {
+            amount_out,
+            fee: amount_out_before_fee - amount_out,
+        })
+    }
+
+    /// Input needed for `amount_out` after the swap fee, and the fee. Weighted quotes charge
+    /// the fee on the input: the curve's input is grossed up by it. Stable quotes gross the
+    /// output up by the fee the way `calc_out_given_in` takes it off, so quoting the returned
+    /// input back pays at least `amount_out`, and solve against the invariant of the balances,
+    /// not a cached one.
+    pub fn calc_in_given_out(&self, amount_out: u64) -> Result<SwapInQuote, MiniStabbleError> {
+        match &self.curve {
+            SwapCurve::Weighted { weights, .. } => {
+                let amount_in = u64::try_from(weighted::calc_in_given_out_with_fee(
+                    self.balances[self.index_in].into(),
+                    weights[self.index_in].into(),
+                    self.balances[self.index_out].into(),
+                    weights[self.index_out].into(),
+                    amount_out.into(),
+                    self.swap_fee.into(),
+                )?)
+                .map_err(|_| MiniStabbleError::MathOverflow)?;
+
+                // Reported in output units like any other swap: what the whole input would
+                // have bought without the fee, beyond the amount out
+                Ok(SwapInQuote {
+                    amount_in,
+                    fee: self
+                        .calc_out_before_fee(amount_in)?
+                        .saturating_sub(amount_out),
+                })
+            }
+            SwapCurve::Stable { amp, .. } => {
+                let amount_out_before_fee = amount_out.div_up(self.swap_fee.complement())?;
+                let calc_in = match stable::stressed_token(&self.balances) {
+                    Some(_) => stable::calc_stress_in_given_out,
+                    None => stable::calc_in_given_out,
+                };
+                let amount_in = calc_in(
+                    *amp,
+                    &self.balances,
+                    self.index_in,
+                    self.index_out,
+                    amount_out_before_fee,
+                )
+                .ok_or(MiniStabbleError::InvalidAmount)?;
+
+                Ok(SwapInQuote {
+                    amount_in,
+                    fee: amount_out_before_fee - amount_out,
+                })
+            }
+        }
+    }
+
+    /// Balances once `amount_in` came in and `amount_out` went out
+    pub fn balances_after(
+        &self,
+        amount_in: u64,
+        amount_out: u64,
+    ) -> Result<Vec<u64>, MiniStabbleError> {
+        let mut balances = self.balances.clone();
+        balances[self.index_in] = balances[self.index_in]
+            .checked_add(amount_in)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        balances[self.index_out] = balances[self.index_out]
+            .checked_sub(amount_out)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        Ok(balances)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::fixed::ONE_U64;
+
+    const FEE: u64 = 3_000_000;
+
+    #[test]
+    fn test_weighted_context_matches_weighted_math() {
+        let context = SwapContext::weighted(
+            vec![1_000 * ONE_U64, 4_000 * ONE_U64],
+            vec![ONE_U64 / 5, ONE_U64 * 4 / 5],
+            None,
+            FEE,
+            0,
+            1,
+        )
+        .unwrap();
+        let amount_in = 10 * ONE_U64;
+        let expected = weighted::calc_out_given_in(
+            (1_000 * ONE_U64).into(),
+            (ONE_U64 / 5).into(),
+            (4_000 * ONE_U64).into(),
+            (ONE_U64 * 4 / 5).into(),
+            amount_in.into(),
+        )
+        .unwrap() as u64;
+
+        let quote = context.calc_out_given_in(amount_in).unwrap();
+        assert_eq!(quote.amount_out + quote.fee, expected);
+        assert_eq!(
+            quote.amount_out,
+            (expected as u128)
+                .mul_down(FEE.complement() as u128)
+                .unwrap() as u64
+        );
+    }
+
+    #[test]
+    fn test_stable_context_defaults_to_balances_invariant() {
+        let balances = vec![1_000 * ONE_U64, 1_000 * ONE_U64];
+        let amp = 100_000;
+        let computed = SwapContext::stable(balances.clone(), amp, None, FEE, 1, 0).unwrap();
+        let invariant = calc_invariant(amp, &balances).unwrap();
+        let cached = SwapContext::stable(balances, amp, Some(invariant), FEE, 1, 0).unwrap();
+        assert_eq!(computed, cached);
+
+        let quote = computed.calc_out_given_in(ONE_U64).unwrap();
+        assert!(quote.amount_out < ONE_U64 && quote.amount_out > ONE_U64 * 99 / 100);
+        assert!(quote.fee > 0);
+    }
+
+    #[test]
+    fn test_balances_after() {
+        let context =
+            SwapContext::weighted(vec![100, 200], vec![ONE_U64 / 2; 2], None, FEE, 1, 0).unwrap();
+        assert_eq!(context.balances_after(5, 7).unwrap(), vec![93, 205]);
+        assert!(context.balances_after(5, 101).is_err());
+    }
+
+    #[test]
+    fn test_weighted_calc_in_given_out_charges_the_fee_on_the_input() {
+        let context = SwapContext::weighted(
+            vec![1_000 * ONE_U64, 4_000 * ONE_U64],
+            vec![ONE_U64 / 5, ONE_U64 * 4 / 5],
+            None,
+            FEE,
+            0,
+            1,
+        )
+        .unwrap();
+        let amount_out = 7 * ONE_U64;
+        let quote = context.calc_in_given_out(amount_out).unwrap();
+        assert!(quote.fee > 0);
+
+        // What is left of the input after the fee buys the output, and not much more
+        let net_in = quote.amount_in.mul_down(FEE.complement()).unwrap();
+        let bought = context.calc_out_before_fee(net_in).unwrap();
+        assert!(bought >= amount_out - amount_out / 1_000_000);
+        assert!(bought - amount_out < ONE_U64 / 100_000);
+        assert_eq!(
+            quote.fee,
+            context.calc_out_before_fee(quote.amount_in).unwrap() - amount_out
+        );
+    }
+
+    #[test]
+    fn test_stable_calc_in_given_out_round_trips() {
+        let context = SwapContext::stable(
+            vec![1_000 * ONE_U64, 1_200 * ONE_U64],
+            100_000,
+            None,
+            FEE,
+            0,
+            1,
+        )
+        .unwrap();
+        let amount_out = 7 * ONE_U64;
+        let quote = context.calc_in_given_out(amount_out).unwrap();
+        assert!(quote.fee > 0);
+
+        // Sending the quoted input pays out at least what was asked for, and not much more
+        let paid = context.calc_out_given_in(quote.amount_in).unwrap();
+        assert!(paid.amount_out >= amount_out);
+        assert!(paid.amount_out - amount_out < ONE_U64 / 100_000);
+    }
+
+    #[test]
+    fn test_stable_context_swaps_every_pair_of_three_tokens() {
+        let balances = vec![1_000 * ONE_U64, 1_100 * ONE_U64, 900 * ONE_U64];
+        let amp = 100_000;
+        let invariant = calc_invariant(amp, &balances).unwrap();
+
+        for index_in in 0..3 {
+            for index_out in (0..3).filter(|&index_out| index_out != index_in) {
+                let context =
+                    SwapContext::stable(balances.clone(), amp, None, FEE, index_in, index_out)
+                        .unwrap();
+                let amount_in = 10 * ONE_U64;
+                let quote = context.calc_out_given_in(amount_in).unwrap();
+                assert!(
+                    quote.amount_out > 0 && quote.fee > 0,
+                    "{index_in} -> {index_out}"
+                );
+
+                // Only the pair moves, and the fee keeps the invariant from shrinking
+                let balances_after = context.balances_after(amount_in, quote.amount_out).unwrap();
+                let untouched = 3 - index_in - index_out;
+                assert_eq!(balances_after[untouched], balances[untouched]);
+                assert!(calc_invariant(amp, &balances_after).unwrap() >= invariant);
+
+                let in_quote = context.calc_in_given_out(quote.amount_out).unwrap();
+                assert!(in_quote.amount_in.abs_diff(amount_in) < ONE_U64 / 100_000);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stressed_stable_context_caps_only_the_scarce_output() {
+        // 99.9% / 0.1%: the curve pays a premium for the scarce token
+        let balances = vec![999_000 * ONE_U64, 1_000 * ONE_U64];
+        let amount_in = 10 * ONE_U64;
+
+        // Buying the scarce token never pays above par
+        let buy_scarce = SwapContext::stable(balances.clone(), 200_000, None, FEE, 0, 1).unwrap();
+        let quote = buy_scarce.calc_out_given_in(amount_in).unwrap();
+        assert!(quote.amount_out + quote.fee < amount_in);
+
+        // Selling it gets the curve's premium
+        let sell_scarce = SwapContext::stable(balances, 200_000, None, FEE, 1, 0).unwrap();
+        let premium_quote = sell_scarce.calc_out_given_in(amount_in).unwrap();
+        assert!(premium_quote.amount_out + premium_quote.fee > amount_in);
+
+        for (context, quote) in [(buy_scarce, quote), (sell_scarce, premium_quote)] {
+            let in_quote = context.calc_in_given_out(quote.amount_out).unwrap();
+            assert!(in_quote.amount_in.abs_diff(amount_in) < ONE_U64 / 100_000);
+        }
+    }
+}
@@ -1,8 +1,108 @@
+use fixed::types::U1F63;
+use fixed_exp::FixedPowF;
+
 use crate::{
     errors::MiniStabbleError,
-    math::fixed::{FixedComplement, FixedDiv, FixedMul, FixedPow, ONE},
+    math::fixed::{FixedComplement, FixedDiv, FixedMul, FixedPow, FOUR, ONE, SCALE},
 };
 
+/// Smallest weight a pool token may have (1%); the power approximations lose precision below it
+pub const MIN_WEIGHT: u64 = 10_000_000;
+
+/// Fractional bits of the exponent `pow_up_bound` keeps: `powf` takes one square root per bit,
+/// and 2^-34 is well below the 1e-9 the exponent is given in
+const EXPONENT_FRAC_BITS: u32 = 34;
+
+/// Bound on how far under the exact power `pow_at_most_one` can land, in U1F63 units (2^-63):
+/// every square root and product truncates, which costs far less than 2^20 units (~1e-13)
+const POW_ERROR_BITS: u128 = 1 << 20;
+
+/// Largest allowed gap between an unbalanced deposit's ratio and the pool ratio, in bps of the
+/// pool ratio
+pub const MAX_DEPOSIT_IMBALANCE_BPS: u128 = 1_000;
+
+/// `base ^ exponent`, never below the exact power, for the power terms that have to round
+/// against the trader. `pow_up` is only an upper bound for the integer exponents it multiplies
+/// out: its U34F30 `powf` truncates at every step and can land several units under the exact
+/// power, which a swap's `1 - power` turns into a payout above the curve. Other exponents are
+/// raised in U1F63 instead and rounded up past that computation's error.
+fn pow_up_bound(base: u128, exponent: u128) -> Result<u128, MiniStabbleError> {
+    if exponent % ONE == 0 && exponent <= FOUR {
+        return base.pow_up(exponent);
+    }
+
+    if base <= ONE {
+        // A larger base only raises the power
+        let base = U1F63::from_bits(
+            u64::try_from((base << 63).div_ceil(ONE))
+                .map_err(|_| MiniStabbleError::MathOverflow)?,
+        );
+        let power = pow_at_most_one(base, exponent, false)?.to_bits() as u128 + POW_ERROR_BITS;
+
+        Ok((power * ONE).div_ceil(1 << 63))
+    } else {
+        // base ^ exponent = 1 / (1 / base) ^ exponent, with the reciprocal power rounded down
+        let reciprocal = u64::try_from((ONE << 63) / base)
+            .ok()
+            .filter(|&reciprocal| reciprocal > 0)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        let power =
+            pow_at_most_one(U1F63::from_bits(reciprocal), exponent, true)?.to_bits() as u128;
+        // Square roots of a small base can also round up, by about `2 / base` relative
+        let power = power
+            .checked_sub(POW_ERROR_BITS + 2 * power / reciprocal as u128)
+            .filter(|&power| power > 0)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        Ok((ONE << 63).div_ceil(power))
+    }
+}
+
+/// `base ^ exponent` for a `base` of at most one, with `exponent` cut to `EXPONENT_FRAC_BITS`
+/// fractional bits, rounded up or down as asked. Lands at most `POW_ERROR_BITS` under the exact
+/// power, and at most about `2 / base` relative over it.
+fn pow_at_most_one(
+    base: U1F63,
+    exponent: u128,
+    round_exponent_up: bool,
+) -> Result<U1F63, MiniStabbleError> {
+    let mut int = exponent / ONE;
+    let frac = (exponent % ONE) << EXPONENT_FRAC_BITS;
+    let mut frac_bits = if round_exponent_up {
+        frac.div_ceil(ONE)
+    } else {
+        frac / ONE
+    };
+    if frac_bits == 1 << EXPONENT_FRAC_BITS {
+        int += 1;
+        frac_bits = 0;
+    }
+
+    let mut power = if frac_bits == 0 {
+        U1F63::ONE
+    } else {
+        let frac = U1F63::from_bits((frac_bits as u64) << (63 - EXPONENT_FRAC_BITS));
+        base.powf(frac).ok_or(MiniStabbleError::MathOverflow)?
+    };
+
+    let mut square = base;
+    while int > 0 {
+        if int & 1 == 1 {
+            power = power
+                .checked_mul(square)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+        }
+        int >>= 1;
+        if int > 0 {
+            square = square
+                .checked_mul(square)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+        }
+    }
+
+    Ok(power)
+}
+
 pub fn calc_spot_price(
     balance_in: u128,
     weight_in: u128,
@@ -21,10 +121,34 @@ pub fn calc_spot_price(
     Ok(price)
 }
 
+/// Checks that every weight is at least `MIN_WEIGHT` and that they sum to exactly ONE. Must hold
+/// for any weights written to a pool, or every invariant and price computed from them is wrong.
+pub fn validate_weights(weights: &[u64]) -> Result<(), MiniStabbleError> {
+    if weights.len() < 2 || weights.iter().any(|&weight| weight < MIN_WEIGHT) {
+        return Err(MiniStabbleError::InvalidWeight);
+    }
+
+    let sum = weights
+        .iter()
+        .try_fold(0u128, |sum, &weight| sum.checked_add(weight as u128))
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    if sum != ONE {
+        return Err(MiniStabbleError::InvalidWeight);
+    }
+
+    Ok(())
+}
+
 pub fn calc_invariant(balances: &[u128], weights: &[u128]) -> Result<u128, MiniStabbleError> {
-    if balances.len() != weights.len() || balances.len() == 0 {
+    if balances.len() != weights.len() || balances.is_empty() {
         return Err(MiniStabbleError::InvalidAmount);
     }
+    debug_assert_eq!(
+        weights.iter().sum::<u128>(),
+        ONE,
+        "pool weights must be normalized"
+    );
 
     let mut invariant = ONE;
 
@@ -34,9 +158,9 @@ pub fn calc_invariant(balances: &[u128], weights: &[u128]) -> Result<u128, MiniS
     }
 
     if invariant > 0 {
-        return Ok(invariant);
+        Ok(invariant)
     } else {
-        return Err(MiniStabbleError::InvalidAmount);
+        Err(MiniStabbleError::InvalidAmount)
     }
 }
 
@@ -51,13 +175,50 @@ pub fn calc_invariant(balances: &[u128], weights: &[u128]) -> Result<u128, MiniS
 /// - To make power larger with base < 1:
 ///   - base should be LARGER → use div_up
 ///   - exponent should be SMALLER → use div_down
-/// - power itself → use pow_up
+/// - power itself → use pow_up_bound
 pub fn calc_out_given_in(
     balance_in: u128,
     weight_in: u128,
     balance_out: u128,
     weight_out: u128,
     amount_in: u128,
+) -> Result<u128, MiniStabbleError> {
+    calc_out_given_in_with_exponent(
+        balance_in,
+        balance_out,
+        calc_swap_exponent(weight_in, weight_out)?,
+        amount_in,
+    )
+}
+
+/// Exponent of a swap from a token of `weight_in` into one of `weight_out`, in the direction
+/// `calc_out_given_in` needs it.
+/// For base < 1: smaller exponent → larger power → smaller complement → less output, so the
+/// ratio rounds DOWN.
+pub fn calc_swap_exponent(weight_in: u128, weight_out: u128) -> Result<u128, MiniStabbleError> {
+    weight_in.div_down(weight_out)
+}
+
+/// `calc_swap_exponent` for every ordered pair of `weights`, row-major by the token in
+pub fn calc_swap_exponents(weights: &[u64]) -> Result<Vec<u64>, MiniStabbleError> {
+    let mut exponents = Vec::with_capacity(weights.len() * weights.len());
+    for &weight_in in weights {
+        for &weight_out in weights {
+            let exponent = calc_swap_exponent(weight_in.into(), weight_out.into())?;
+            exponents.push(u64::try_from(exponent).map_err(|_| MiniStabbleError::MathOverflow)?);
+        }
+    }
+
+    Ok(exponents)
+}
+
+/// `calc_out_given_in` with `exponent` = `calc_swap_exponent(weight_in, weight_out)` already
+/// computed
+pub fn calc_out_given_in_with_exponent(
+    balance_in: u128,
+    balance_out: u128,
+    exponent: u128,
+    amount_in: u128,
 ) -> Result<u128, MiniStabbleError> {
     // Step 1: base = balance_in / (balance_in + amount_in)
     // base < 1 always. Larger base → larger power → smaller complement → less output
@@ -68,15 +229,12 @@ pub fn calc_out_given_in(
             .ok_or(MiniStabbleError::MathOverflow)?,
     )?;
 
-    // Step 2: exponent = weight_in / weight_out
-    // For base < 1: smaller exponent → larger power → smaller complement → less output
-    // Round DOWN to get smaller exponent
-    let exponent = weight_in.div_down(weight_out)?;
+    // Step 2: exponent = weight_in / weight_out, rounded DOWN by `calc_swap_exponent`
 
     // Step 3: power = base ^ exponent
     // Larger power → smaller complement → less output
-    // Round UP to get larger power
-    let power = base.pow_up(exponent)?;
+    // Round UP, past the error of the power approximation, to get larger power
+    let power = pow_up_bound(base, exponent)?;
 
     // Step 4: complement = 1 - power
     // No rounding choice here, just subtraction
@@ -93,7 +251,7 @@ pub fn calc_out_given_in(
 // amount_in should be LARGER
 // amount_in will be larger when: mul_up AND complement larger
 // complement (power - 1) larger when: power larger
-// power larger when: pow_up, base larger, exponent larger
+// power larger when: pow_up_bound, base larger, exponent larger
 //   - For base > 1: larger exponent → larger power
 //   - exponent larger → div_up
 //   - base larger → div_up
@@ -112,7 +270,7 @@ pub fn calc_in_given_out(
 
     let exponent = weight_out.div_up(weight_in)?;
 
-    let power = base.pow_up(exponent)?;
+    let power = pow_up_bound(base, exponent)?;
 
     let complement = power
         .checked_sub(ONE)
@@ -120,7 +278,21 @@ pub fn calc_in_given_out(
 
     let amount_in = balance_in.mul_up(complement)?;
 
-    return Ok(amount_in);
+    Ok(amount_in)
+}
+
+/// `calc_in_given_out` with the swap fee charged on the input: the curve needs the returned
+/// amount net of `swap_fee`, rounded up so the fee never comes out short
+pub fn calc_in_given_out_with_fee(
+    balance_in: u128,
+    weight_in: u128,
+    balance_out: u128,
+    weight_out: u128,
+    amount_out: u128,
+    swap_fee: u128,
+) -> Result<u128, MiniStabbleError> {
+    calc_in_given_out(balance_in, weight_in, balance_out, weight_out, amount_out)?
+        .div_up(swap_fee.complement())
 }
 
 pub fn calc_lp_to_mint(
@@ -141,3 +313,1062 @@ pub fn calc_lp_to_mint(
 
     Ok(net_minted)
 }
+
+/// How far apart the deposit ratios `amount_in / balance` of a join are: the gap between the
+/// largest and the smallest, in bps of the smallest. 0 for a proportional join.
+pub fn calc_deposit_imbalance_bps(
+    balances: &[u128],
+    amounts_in: &[u128],
+) -> Result<u128, MiniStabbleError> {
+    let [min, max] = deposit_ratio_extremes(balances, amounts_in)?;
+
+    // Cross-multiplied so neither ratio is rounded: (a_max × B_min - a_min × B_max) / (a_min × B_max)
+    let max_side = amounts_in[max]
+        .checked_mul(balances[min])
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    let min_side = amounts_in[min]
+        .checked_mul(balances[max])
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    if min_side == 0 {
+        return Err(MiniStabbleError::DivideByZero);
+    }
+
+    (max_side - min_side)
+        .checked_mul(10_000)
+        .ok_or(MiniStabbleError::MathOverflow)?
+        .checked_div(min_side)
+        .ok_or(MiniStabbleError::MathOverflow)
+}
+
+/// Indices of the tokens with the smallest and the largest `amount_in / balance`
+fn deposit_ratio_extremes(
+    balances: &[u128],
+    amounts_in: &[u128],
+) -> Result<[usize; 2], MiniStabbleError> {
+    if balances.is_empty() || balances.len() != amounts_in.len() {
+        return Err(MiniStabbleError::InvalidAmount);
+    }
+
+    // a_i / B_i < a_j / B_j  <=>  a_i × B_j < a_j × B_i
+    let less = |i: usize, j: usize| -> Result<bool, MiniStabbleError> {
+        let left = amounts_in[i]
+            .checked_mul(balances[j])
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        let right = amounts_in[j]
+            .checked_mul(balances[i])
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        Ok(left < right)
+    };
+
+    let (mut min, mut max) = (0, 0);
+    for i in 1..balances.len() {
+        if less(i, min)? {
+            min = i;
+        }
+        if less(max, i)? {
+            max = i;
+        }
+    }
+
+    Ok([min, max])
+}
+
+/// LP minted for a deposit of every pool token at any ratio.
+///
+/// The largest proportional part of the deposit, set by the token with the smallest
+/// `amount_in / balance`, joins for free. The excess of every other token is charged
+/// `swap_fee`, as if it had been swapped in, before the invariant growth is turned into LP.
+pub fn calc_lp_out_for_unbalanced_deposit(
+    balances: &[u128],
+    weights: &[u128],
+    amounts_in: &[u128],
+    lp_supply: u128,
+    swap_fee: u128,
+) -> Result<u128, MiniStabbleError> {
+    let [min, _] = deposit_ratio_extremes(balances, amounts_in)?;
+
+    let mut effective_amounts_in = Vec::with_capacity(amounts_in.len());
+    for (i, &amount_in) in amounts_in.iter().enumerate() {
+        // Rounded down, so the taxed excess rounds up
+        let balanced = amounts_in[min]
+            .checked_mul(balances[i])
+            .ok_or(MiniStabbleError::MathOverflow)?
+            .checked_div(balances[min])
+            .ok_or(MiniStabbleError::DivideByZero)?
+            .min(amount_in);
+        let excess = amount_in - balanced;
+
+        effective_amounts_in.push(
+            balanced
+                .checked_add(excess.mul_down(swap_fee.complement())?)
+                .ok_or(MiniStabbleError::MathOverflow)?,
+        );
+    }
+
+    let invariant_ratio = calc_invariant_ratio(balances, weights, &effective_amounts_in)?;
+
+    lp_supply.mul_down(
+        invariant_ratio
+            .checked_sub(ONE)
+            .ok_or(MiniStabbleError::MathOverflow)?,
+    )
+}
+
+/// LP minted for joining with `amounts_in` of every pool token, for `deposit_unbalanced` and
+/// `deposit_exact_tokens_in` alike. The deposit must be within `MAX_DEPOSIT_IMBALANCE_BPS` of
+/// the pool ratio, so it can't stand in for a swap, is priced by
+/// `calc_lp_out_for_unbalanced_deposit`, and must pass `check_join_round_trip`.
+pub fn calc_lp_out_for_join(
+    balances: &[u128],
+    weights: &[u128],
+    amounts_in: &[u128],
+    lp_supply: u128,
+    swap_fee: u128,
+) -> Result<u128, MiniStabbleError> {
+    if calc_deposit_imbalance_bps(balances, amounts_in)? > MAX_DEPOSIT_IMBALANCE_BPS {
+        return Err(MiniStabbleError::DepositTooImbalanced);
+    }
+
+    let lp_out =
+        calc_lp_out_for_unbalanced_deposit(balances, weights, amounts_in, lp_supply, swap_fee)?;
+
+    // Exiting the minted LP straight away must not be worth more than the deposit
+    check_join_round_trip(balances, weights, amounts_in, lp_out, lp_supply)?;
+
+    Ok(lp_out)
+}
+
+/// Growth of the invariant from adding `amounts_in` to `balances`: the product of
+/// `((balance + amount_in) / balance) ^ weight`, rounded down. Equal to `new_k / old_k` without
+/// computing either invariant, and tokens with nothing added cost no `pow` at all.
+pub fn calc_invariant_ratio(
+    balances: &[u128],
+    weights: &[u128],
+    amounts_in: &[u128],
+) -> Result<u128, MiniStabbleError> {
+    if balances.len() != weights.len() || balances.len() != amounts_in.len() {
+        return Err(MiniStabbleError::InvalidAmount);
+    }
+
+    let mut invariant_ratio = ONE;
+    for i in 0..balances.len() {
+        if amounts_in[i] == 0 {
+            continue;
+        }
+
+        let balance_ratio = balances[i]
+            .checked_add(amounts_in[i])
+            .ok_or(MiniStabbleError::MathOverflow)?
+            .div_down(balances[i])?;
+        invariant_ratio = invariant_ratio.mul_down(balance_ratio.pow_down(weights[i])?)?;
+    }
+
+    Ok(invariant_ratio)
+}
+
+/// LP minted for depositing exactly `amounts_in` of any subset of a pool's tokens (0 for the
+/// skipped ones).
+///
+/// Each token's deposit up to the weighted average growth of all balances joins for free; the
+/// rest is charged `swap_fee`, as if it had been swapped in for the other tokens. The LP out is
+/// the resulting invariant growth applied to `lp_supply`.
+pub fn calc_lp_out_given_exact_tokens_in(
+    balances: &[u128],
+    weights: &[u128],
+    amounts_in: &[u128],
+    lp_supply: u128,
+    swap_fee: u128,
+) -> Result<u128, MiniStabbleError> {
+    if balances.len() != weights.len() || balances.len() != amounts_in.len() {
+        return Err(MiniStabbleError::InvalidAmount);
+    }
+
+    let mut balance_ratios = Vec::with_capacity(balances.len());
+    let mut weighted_balance_ratio = 0u128;
+    for i in 0..balances.len() {
+        let ratio = balances[i]
+            .checked_add(amounts_in[i])
+            .ok_or(MiniStabbleError::MathOverflow)?
+            .div_down(balances[i])?;
+        weighted_balance_ratio = weighted_balance_ratio
+            .checked_add(ratio.mul_down(weights[i])?)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        balance_ratios.push(ratio);
+    }
+
+    let mut invariant_ratio = ONE;
+    for i in 0..balances.len() {
+        if amounts_in[i] == 0 {
+            continue;
+        }
+
+        let amount_in_without_fee = if balance_ratios[i] > weighted_balance_ratio {
+            let non_taxable = balances[i].mul_down(weighted_balance_ratio - ONE)?;
+            let taxable = amounts_in[i] - non_taxable;
+            non_taxable
+                .checked_add(taxable.mul_down(swap_fee.complement())?)
+                .ok_or(MiniStabbleError::MathOverflow)?
+        } else {
+            amounts_in[i]
+        };
+
+        let balance_ratio = balances[i]
+            .checked_add(amount_in_without_fee)
+            .ok_or(MiniStabbleError::MathOverflow)?
+            .div_down(balances[i])?;
+        invariant_ratio = invariant_ratio.mul_down(balance_ratio.pow_down(weights[i])?)?;
+    }
+
+    lp_supply.mul_down(invariant_ratio.saturating_sub(ONE))
+}
+
+/// LP to burn for withdrawing exactly `amounts_out` of any subset of a pool's tokens (0 for the
+/// skipped ones).
+///
+/// Mirror of `calc_lp_out_given_exact_tokens_in`: each token's withdrawal up to the weighted
+/// average shrink of all balances exits for free; the rest is grossed up by `swap_fee`, as if
+/// the other tokens had been swapped for it. Everything rounds so the user burns more LP.
+pub fn calc_lp_in_given_exact_tokens_out(
+    balances: &[u128],
+    weights: &[u128],
+    amounts_out: &[u128],
+    lp_supply: u128,
+    swap_fee: u128,
+) -> Result<u128, MiniStabbleError> {
+    if balances.len() != weights.len() || balances.len() != amounts_out.len() {
+        return Err(MiniStabbleError::InvalidAmount);
+    }
+
+    let mut balance_ratios = Vec::with_capacity(balances.len());
+    let mut weighted_balance_ratio = 0u128;
+    for i in 0..balances.len() {
+        let ratio = balances[i]
+            .checked_sub(amounts_out[i])
+            .ok_or(MiniStabbleError::MathOverflow)?
+            .div_up(balances[i])?;
+        weighted_balance_ratio = weighted_balance_ratio
+            .checked_add(ratio.mul_up(weights[i])?)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        balance_ratios.push(ratio);
+    }
+
+    let mut invariant_ratio = ONE;
+    for i in 0..balances.len() {
+        if amounts_out[i] == 0 {
+            continue;
+        }
+
+        let amount_out_with_fee = if weighted_balance_ratio > balance_ratios[i] {
+            let non_taxable = balances[i].mul_down(weighted_balance_ratio.complement())?;
+            let taxable = amounts_out[i] - non_taxable;
+            non_taxable
+                .checked_add(taxable.div_up(swap_fee.complement())?)
+                .ok_or(MiniStabbleError::MathOverflow)?
+        } else {
+            amounts_out[i]
+        };
+
+        let balance_ratio = balances[i]
+            .checked_sub(amount_out_with_fee)
+            .ok_or(MiniStabbleError::MathOverflow)?
+            .div_down(balances[i])?;
+        invariant_ratio = invariant_ratio.mul_down(balance_ratio.pow_down(weights[i])?)?;
+    }
+
+    lp_supply.mul_up(invariant_ratio.complement())
+}
+
+/// LP minted for depositing exactly `amount_in` of a single token of `balance` and `weight`.
+///
+/// One-token case of `calc_lp_out_given_exact_tokens_in`, without needing the other balances:
+/// the `weight` share of the deposit joins for free and the rest, which a proportional join
+/// would have paid in the other tokens, is charged `swap_fee`.
+pub fn calc_lp_out_given_exact_token_in(
+    balance: u128,
+    weight: u128,
+    amount_in: u128,
+    lp_supply: u128,
+    swap_fee: u128,
+) -> Result<u128, MiniStabbleError> {
+    let taxable = amount_in.mul_up(weight.complement())?;
+    let non_taxable = amount_in - taxable;
+    let amount_in_without_fee = non_taxable
+        .checked_add(taxable.mul_down(swap_fee.complement())?)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    let balance_ratio = balance
+        .checked_add(amount_in_without_fee)
+        .ok_or(MiniStabbleError::MathOverflow)?
+        .div_down(balance)?;
+    let invariant_ratio = balance_ratio.pow_down(weight)?;
+
+    lp_supply.mul_down(invariant_ratio.saturating_sub(ONE))
+}
+
+/// Amount of a single token of `balance` and `weight` to deposit for exactly `lp_out` LP.
+///
+/// Inverse of a one-token `calc_lp_out_given_exact_tokens_in`: the balance must grow by
+/// `((lp_supply + lp_out) / lp_supply) ^ (1 / weight)`, and the share of that growth a
+/// proportional join would have paid in the other tokens is grossed up by `swap_fee`.
+/// Everything rounds up, so the user pays at least what the LP is worth.
+pub fn calc_token_in_given_exact_lp_out(
+    balance: u128,
+    weight: u128,
+    lp_out: u128,
+    lp_supply: u128,
+    swap_fee: u128,
+) -> Result<u128, MiniStabbleError> {
+    let invariant_ratio = lp_supply
+        .checked_add(lp_out)
+        .ok_or(MiniStabbleError::MathOverflow)?
+        .div_up(lp_supply)?;
+    let balance_ratio = pow_up_bound(invariant_ratio, ONE.div_up(weight)?)?;
+
+    let amount_in_without_fee = balance.mul_up(
+        balance_ratio
+            .checked_sub(ONE)
+            .ok_or(MiniStabbleError::MathOverflow)?,
+    )?;
+
+    // Only the part a proportional join would have paid in the other tokens is taxed
+    let taxable = amount_in_without_fee.mul_up(weight.complement())?;
+    let non_taxable = amount_in_without_fee - taxable;
+
+    non_taxable
+        .checked_add(taxable.div_up(swap_fee.complement())?)
+        .ok_or(MiniStabbleError::MathOverflow)
+}
+
+/// Amount of a single token of `balance` and `weight` paid out for burning exactly `lp_in` LP.
+///
+/// One-token exit: the balance shrinks by `((lp_supply - lp_in) / lp_supply) ^ (1 / weight)`,
+/// and the share of that a proportional exit would have paid in the other tokens is charged
+/// `swap_fee`. Everything rounds down, so the user never gets more than the LP is worth.
+pub fn calc_token_out_given_exact_lp_in(
+    balance: u128,
+    weight: u128,
+    lp_in: u128,
+    lp_supply: u128,
+    swap_fee: u128,
+) -> Result<u128, MiniStabbleError> {
+    let invariant_ratio = lp_supply
+        .checked_sub(lp_in)
+        .ok_or(MiniStabbleError::MathOverflow)?
+        .div_up(lp_supply)?;
+    let balance_ratio = pow_up_bound(invariant_ratio, ONE.div_down(weight)?)?;
+
+    let amount_out_without_fee = balance.mul_down(balance_ratio.complement())?;
+
+    // Only the part a proportional exit would have paid in the other tokens is taxed
+    let taxable = amount_out_without_fee.mul_up(weight.complement())?;
+    let non_taxable = amount_out_without_fee - taxable;
+
+    non_taxable
+        .checked_add(taxable.mul_down(swap_fee.complement())?)
+        .ok_or(MiniStabbleError::MathOverflow)
+}
+
+/// Checks that exiting `lp_out` right after a join of `amounts_in` can't return more value than
+/// was deposited, both legs valued in the last pool token at the pre-join spot prices. A join
+/// that fails this would let anyone extract value by joining and exiting in one transaction.
+pub fn check_join_round_trip(
+    balances: &[u128],
+    weights: &[u128],
+    amounts_in: &[u128],
+    lp_out: u128,
+    lp_supply: u128,
+) -> Result<(), MiniStabbleError> {
+    if balances.is_empty() || balances.len() != weights.len() || balances.len() != amounts_in.len()
+    {
+        return Err(MiniStabbleError::InvalidAmount);
+    }
+    let supply_after = lp_supply
+        .checked_add(lp_out)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+    if supply_after == 0 {
+        return Err(MiniStabbleError::DivideByZero);
+    }
+
+    let last = balances.len() - 1;
+    let (mut value_in, mut value_out) = (0u128, 0u128);
+    for i in 0..balances.len() {
+        // Proportional exit, rounded down like calc_tokens_out_proportional
+        let amount_out = balances[i]
+            .checked_add(amounts_in[i])
+            .ok_or(MiniStabbleError::MathOverflow)?
+            .checked_mul(lp_out)
+            .ok_or(MiniStabbleError::MathOverflow)?
+            / supply_after;
+
+        let price = if i == last {
+            SCALE
+        } else {
+            calc_spot_price(balances[i], weights[i], balances[last], weights[last])?
+        };
+        let value = |amount: u128| -> Result<u128, MiniStabbleError> {
+            amount
+                .checked_mul(price)
+                .ok_or(MiniStabbleError::MathOverflow)?
+                .checked_div(SCALE)
+                .ok_or(MiniStabbleError::MathOverflow)
+        };
+        value_in = value_in
+            .checked_add(value(amounts_in[i])?)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        value_out = value_out
+            .checked_add(value(amount_out)?)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+    }
+
+    if value_out > value_in {
+        return Err(MiniStabbleError::RoundTripProfit);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::common::calc_tokens_out_proportional;
+
+    const HALF: u128 = ONE / 2;
+    const SWAP_FEE: u128 = 3_000_000; // 0.3%
+
+    #[test]
+    fn test_validate_weights() {
+        let half = ONE as u64 / 2;
+        assert!(validate_weights(&[half, half]).is_ok());
+        assert!(validate_weights(&[800_000_000, 150_000_000, 50_000_000]).is_ok());
+
+        // Must sum to exactly ONE
+        assert!(validate_weights(&[half, half - 1]).is_err());
+        assert!(validate_weights(&[half, half + 1]).is_err());
+
+        // Each weight must clear the minimum
+        assert!(validate_weights(&[ONE as u64 - MIN_WEIGHT + 1, MIN_WEIGHT - 1]).is_err());
+        assert!(validate_weights(&[ONE as u64 - MIN_WEIGHT, MIN_WEIGHT]).is_ok());
+
+        // At least two tokens
+        assert!(validate_weights(&[ONE as u64]).is_err());
+        assert!(validate_weights(&[]).is_err());
+    }
+
+    #[test]
+    fn test_pow_up_bound_never_undershoots() {
+        let exponents = [
+            10_204_081,
+            20_408_163,
+            250_000_000,
+            333_333_333,
+            1_500_000_000,
+            5_000_000_000,
+            49_000_000_000,
+            99_000_000_000,
+        ];
+        for exponent in exponents {
+            for base in [1, 4_583, 10_000_000, 500_000_000, 990_099_009, 999_999_999] {
+                let exact = (base as f64 / 1e9).powf(exponent as f64 / 1e9) * 1e9;
+                let power = pow_up_bound(base, exponent).unwrap() as f64;
+                assert!(power >= exact && power <= exact + 3.0, "{base}^{exponent}");
+            }
+            for base in [1_000_000_001, 1_010_000_000, 1_500_000_000, 3_000_000_000] {
+                let exact = (base as f64 / 1e9).powf(exponent as f64 / 1e9) * 1e9;
+                let Ok(power) = pow_up_bound(base, exponent) else {
+                    continue;
+                };
+                let power = power as f64;
+                assert!(power >= exact, "{base}^{exponent}");
+                // The reciprocal's own error only loosens the bound for huge powers
+                if exact < 1e12 {
+                    assert!(power <= exact * (1.0 + 1e-9) + 3.0, "{base}^{exponent}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cached_swap_exponents_match_weights() {
+        let weights = [800_000_000_u64, 200_000_000];
+        let exponents = calc_swap_exponents(&weights).unwrap();
+        assert_eq!(
+            exponents,
+            vec![1_000_000_000, 4_000_000_000, 250_000_000, 1_000_000_000]
+        );
+
+        let (balance_in, balance_out, amount_in) = (1_000 * ONE, 4_000 * ONE, 10 * ONE);
+        assert_eq!(
+            calc_out_given_in_with_exponent(
+                balance_in,
+                balance_out,
+                exponents[1].into(),
+                amount_in
+            )
+            .unwrap(),
+            calc_out_given_in(
+                balance_in,
+                weights[0].into(),
+                balance_out,
+                weights[1].into(),
+                amount_in
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_in_given_out_with_fee_buys_the_output() {
+        let (balance_in, balance_out) = (1_000 * ONE, 4_000 * ONE);
+        let (weight_in, weight_out) = (800_000_000, 200_000_000);
+
+        for amount_out in [10 * ONE, 100 * ONE, 1_000 * ONE] {
+            let amount_in = calc_in_given_out_with_fee(
+                balance_in,
+                weight_in,
+                balance_out,
+                weight_out,
+                amount_out,
+                SWAP_FEE,
+            )
+            .unwrap();
+            let fee_free =
+                calc_in_given_out(balance_in, weight_in, balance_out, weight_out, amount_out)
+                    .unwrap();
+            assert!(amount_in > fee_free);
+
+            // What is left of the input after the fee buys the output, up to the power
+            // approximations rounding each direction against the trader
+            let net_in = amount_in.mul_down(SWAP_FEE.complement()).unwrap();
+            assert!(net_in >= fee_free);
+            let bought =
+                calc_out_given_in(balance_in, weight_in, balance_out, weight_out, net_in).unwrap();
+            assert!(
+                bought >= amount_out - amount_out / 1_000_000,
+                "amount_out {amount_out}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_calc_deposit_imbalance_bps() {
+        let balances = [1_000_000_000_000, 2_000_000_000_000];
+
+        // Exactly the pool ratio
+        assert_eq!(
+            calc_deposit_imbalance_bps(&balances, &[100_000, 200_000]).unwrap(),
+            0
+        );
+        // 10% too much token A, then 10% too little: measured against the smaller ratio
+        assert_eq!(
+            calc_deposit_imbalance_bps(&balances, &[110_000, 200_000]).unwrap(),
+            1_000
+        );
+        assert_eq!(
+            calc_deposit_imbalance_bps(&balances, &[90_000, 200_000]).unwrap(),
+            1_111
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_deposit_then_withdraw_extracts_no_value() {
+        let balances = [1_000_000_000_000_u128, 1_000_000_000_000_u128];
+        let lp_supply = 1_000_000_000_000_u128;
+
+        for amounts_in in [
+            [10_000_000_000_u128, 10_000_000_000],
+            [10_900_000_000, 10_000_000_000],
+            [10_000_000_000, 10_900_000_000],
+            [50_000_000_000, 46_000_000_000],
+        ] {
+            assert!(
+                calc_deposit_imbalance_bps(&balances, &amounts_in).unwrap()
+                    <= MAX_DEPOSIT_IMBALANCE_BPS
+            );
+
+            let lp_out = calc_lp_out_for_unbalanced_deposit(
+                &balances,
+                &[HALF, HALF],
+                &amounts_in,
+                lp_supply,
+                SWAP_FEE,
+            )
+            .unwrap();
+
+            // Exit straight away against the post-deposit pool
+            let new_balances = [
+                (balances[0] + amounts_in[0]) as u64,
+                (balances[1] + amounts_in[1]) as u64,
+            ];
+            let amounts_out = calc_tokens_out_proportional(
+                &new_balances,
+                lp_out as u64,
+                (lp_supply + lp_out) as u64,
+            )
+            .unwrap();
+
+            // Both tokens trade 1:1 in this pool, so compare total value
+            let value_in = amounts_in[0] + amounts_in[1];
+            let value_out = (amounts_out[0] + amounts_out[1]) as u128;
+            assert!(
+                value_out <= value_in,
+                "deposit {:?} round-tripped into {} > {}",
+                amounts_in,
+                value_out,
+                value_in
+            );
+        }
+    }
+
+    #[test]
+    fn test_join_round_trip_check_never_lets_a_profit_through() {
+        let lp_supply = 1_000_000_000_000_u128;
+
+        for weights in [
+            [HALF, HALF],
+            [800_000_000, 200_000_000],
+            [200_000_000, 800_000_000],
+        ] {
+            for balances in [
+                [1_000_000_000_000_u128, 1_000_000_000_000_u128],
+                [4_000_000_000_000, 250_000_000_000],
+                [300_000_000_000, 7_000_000_000_000],
+            ] {
+                let price =
+                    calc_spot_price(balances[0], weights[0], balances[1], weights[1]).unwrap();
+                let value = |amounts: [u128; 2]| amounts[0] * price / SCALE + amounts[1];
+
+                for swap_fee in [0, SWAP_FEE, 10_000_000] {
+                    // Sweep deposit sizes and ratios up to the imbalance bound
+                    for size_bps in [1_u128, 10, 100, 1_000] {
+                        for skew_bps in [-1_000_i128, -500, -1, 0, 1, 500, 1_000] {
+                            let amount_b = balances[1] * size_bps / 10_000;
+                            let amount_a = ((balances[0] * size_bps / 10_000) as i128
+                                * (10_000 + skew_bps)
+                                / 10_000) as u128;
+                            let amounts_in = [amount_a, amount_b];
+                            if calc_deposit_imbalance_bps(&balances, &amounts_in).unwrap()
+                                > MAX_DEPOSIT_IMBALANCE_BPS
+                            {
+                                continue;
+                            }
+
+                            // Only deposits the pricing accepts can be round-tripped
+                            let Ok(lp_out) = calc_lp_out_for_unbalanced_deposit(
+                                &balances,
+                                &weights,
+                                &amounts_in,
+                                lp_supply,
+                                swap_fee,
+                            ) else {
+                                continue;
+                            };
+
+                            let amounts_out = calc_tokens_out_proportional(
+                                &[
+                                    (balances[0] + amounts_in[0]) as u64,
+                                    (balances[1] + amounts_in[1]) as u64,
+                                ],
+                                lp_out as u64,
+                                (lp_supply + lp_out) as u64,
+                            )
+                            .unwrap();
+                            let profitable =
+                                value([amounts_out[0] as u128, amounts_out[1] as u128])
+                                    > value(amounts_in);
+
+                            assert_eq!(
+                                check_join_round_trip(
+                                    &balances,
+                                    &weights,
+                                    &amounts_in,
+                                    lp_out,
+                                    lp_supply
+                                )
+                                .is_err(),
+                                profitable,
+                                "{:?} {:?} {:?} fee {}",
+                                weights,
+                                balances,
+                                amounts_in,
+                                swap_fee
+                            );
+
+                            // Equal weights price joins exactly, so the fee only ever costs
+                            if weights == [HALF, HALF] {
+                                assert!(!profitable);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_check_rejects_overminting() {
+        let balances = [1_000_000_000_000_u128, 1_000_000_000_000_u128];
+        let lp_supply = 1_000_000_000_000_u128;
+        let amounts_in = [10_000_000_000_u128, 10_000_000_000];
+
+        // Balanced join at par mints 1% of supply; anything more is extractable
+        assert!(check_join_round_trip(
+            &balances,
+            &[HALF, HALF],
+            &amounts_in,
+            10_000_000_000,
+            lp_supply
+        )
+        .is_ok());
+        assert!(check_join_round_trip(
+            &balances,
+            &[HALF, HALF],
+            &amounts_in,
+            10_100_000_000,
+            lp_supply
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_invariant_ratio_matches_invariants() {
+        let balances = [1_000_000_000_000_u128, 4_000_000_000_000, 250_000_000_000];
+        let weights = [500_000_000_u128, 300_000_000, 200_000_000];
+        let amounts_in = [10_000_000_000_u128, 0, 7_000_000_000];
+
+        let balances_after: Vec<u128> = balances
+            .iter()
+            .zip(amounts_in)
+            .map(|(balance, amount)| balance + amount)
+            .collect();
+        let expected = calc_invariant(&balances_after, &weights)
+            .unwrap()
+            .div_down(calc_invariant(&balances, &weights).unwrap())
+            .unwrap();
+        let ratio = calc_invariant_ratio(&balances, &weights, &amounts_in).unwrap();
+        assert!(ratio.abs_diff(expected) <= expected / 1_000_000);
+
+        // Nothing added, no growth
+        assert_eq!(
+            calc_invariant_ratio(&balances, &weights, &[0, 0, 0]).unwrap(),
+            ONE
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_deposit_pays_fee_on_excess() {
+        let balances = [1_000_000_000_000_u128, 1_000_000_000_000_u128];
+        let lp_supply = 1_000_000_000_000_u128;
+
+        let lp_for = |amounts_in: [u128; 2], swap_fee| {
+            calc_lp_out_for_unbalanced_deposit(
+                &balances,
+                &[HALF, HALF],
+                &amounts_in,
+                lp_supply,
+                swap_fee,
+            )
+            .unwrap()
+        };
+
+        let imbalanced = [10_900_000_000_u128, 10_000_000_000];
+        assert!(lp_for(imbalanced, SWAP_FEE) < lp_for(imbalanced, 0));
+
+        // A balanced deposit pays nothing
+        let balanced = [10_000_000_000_u128, 10_000_000_000];
+        assert_eq!(lp_for(balanced, SWAP_FEE), lp_for(balanced, 0));
+    }
+
+    #[test]
+    fn test_join_never_mints_more_than_an_exact_tokens_in_quote() {
+        let lp_supply = 1_000 * ONE;
+        for (balances, weights) in [
+            (vec![1_000 * ONE, 1_000 * ONE], vec![HALF, HALF]),
+            (vec![1_000 * ONE, 250 * ONE], vec![ONE * 4 / 5, ONE / 5]),
+            (
+                vec![1_000 * ONE, 2_000 * ONE, 4_000 * ONE],
+                vec![ONE / 2, ONE * 3 / 10, ONE / 5],
+            ),
+        ] {
+            for skew_bps in [0, 100, 500, 1_000] {
+                // 1% of every balance, the first token `skew_bps` over
+                let mut amounts_in: Vec<u128> = balances.iter().map(|b| b / 100).collect();
+                amounts_in[0] += amounts_in[0] * skew_bps / 10_000;
+
+                let lp_out =
+                    calc_lp_out_for_join(&balances, &weights, &amounts_in, lp_supply, SWAP_FEE)
+                        .unwrap();
+                let weighted_average_quote = calc_lp_out_given_exact_tokens_in(
+                    &balances,
+                    &weights,
+                    &amounts_in,
+                    lp_supply,
+                    SWAP_FEE,
+                )
+                .unwrap();
+                assert!(lp_out <= weighted_average_quote, "{balances:?} {skew_bps}");
+
+                // Two-token joins price exactly like `deposit_unbalanced`
+                if balances.len() == 2 {
+                    assert_eq!(
+                        lp_out,
+                        calc_lp_out_for_unbalanced_deposit(
+                            &balances,
+                            &weights,
+                            &amounts_in,
+                            lp_supply,
+                            SWAP_FEE
+                        )
+                        .unwrap()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_join_rejects_imbalanced_deposits() {
+        let balances = [1_000 * ONE, 2_000 * ONE, 4_000 * ONE];
+        let weights = [ONE / 2, ONE * 3 / 10, ONE / 5];
+        let lp_supply = 1_000 * ONE;
+        let join = |amounts_in: &[u128]| {
+            calc_lp_out_for_join(&balances, &weights, amounts_in, lp_supply, SWAP_FEE)
+        };
+
+        // Measured against the smallest ratio, the middle token 10% short is past the bound, 5%
+        // short within it
+        assert_eq!(
+            calc_deposit_imbalance_bps(&balances, &[10 * ONE, 18 * ONE, 40 * ONE]).unwrap(),
+            1_111
+        );
+        assert!(join(&[10 * ONE, 19 * ONE, 40 * ONE]).is_ok());
+        assert!(matches!(
+            join(&[10 * ONE, 16 * ONE, 40 * ONE]),
+            Err(MiniStabbleError::DepositTooImbalanced)
+        ));
+
+        // Leaving a token out is a swap, not a join
+        assert!(join(&[10 * ONE, 0, 40 * ONE]).is_err());
+        assert!(matches!(
+            join(&[ONE, ONE]),
+            Err(MiniStabbleError::InvalidAmount)
+        ));
+    }
+
+    #[test]
+    fn test_exact_tokens_in_join_matches_proportional_deposit() {
+        let balances = [1_000 * ONE, 2_000 * ONE, 4_000 * ONE];
+        let weights = [ONE / 2, ONE * 3 / 10, ONE / 5];
+        let lp_supply = 1_000 * ONE;
+
+        // 1% of every balance mints ~1% of the supply, fee free
+        let proportional = calc_lp_out_given_exact_tokens_in(
+            &balances,
+            &weights,
+            &[10 * ONE, 20 * ONE, 40 * ONE],
+            lp_supply,
+            SWAP_FEE,
+        )
+        .unwrap();
+        assert!(proportional <= 10 * ONE && proportional > 10 * ONE * 9_999 / 10_000);
+    }
+
+    #[test]
+    fn test_exact_tokens_in_join_charges_fee_on_skewed_subsets() {
+        let balances = [1_000 * ONE, 2_000 * ONE, 4_000 * ONE];
+        let weights = [ONE / 2, ONE * 3 / 10, ONE / 5];
+        let lp_supply = 1_000 * ONE;
+        let lp_for = |amounts_in: &[u128], swap_fee| {
+            calc_lp_out_given_exact_tokens_in(&balances, &weights, amounts_in, lp_supply, swap_fee)
+                .unwrap()
+        };
+
+        // Skipping a token is allowed and pays the fee on what stands in for it
+        let subset = [10 * ONE, 0, 40 * ONE];
+        assert!(lp_for(&subset, SWAP_FEE) < lp_for(&subset, 0));
+        assert!(lp_for(&subset, 0) > 0);
+
+        // Nothing in, nothing out
+        assert_eq!(lp_for(&[0, 0, 0], SWAP_FEE), 0);
+
+        assert!(calc_lp_out_given_exact_tokens_in(
+            &balances,
+            &weights,
+            &[ONE, ONE],
+            lp_supply,
+            SWAP_FEE
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_token_in_given_exact_lp_out_inverts_exact_tokens_in() {
+        let balances = [2_000_000 * ONE, 500_000 * ONE];
+        let weights = [ONE * 8 / 10, ONE * 2 / 10];
+        let lp_supply = 1_000_000 * ONE;
+
+        for (index, lp_out) in [(0, 1_000 * ONE), (0, 50_000 * ONE), (1, 20_000 * ONE)] {
+            let amount_in = calc_token_in_given_exact_lp_out(
+                balances[index],
+                weights[index],
+                lp_out,
+                lp_supply,
+                SWAP_FEE,
+            )
+            .unwrap();
+
+            let mut amounts_in = [0; 2];
+            amounts_in[index] = amount_in;
+            let lp_for_amount_in = calc_lp_out_given_exact_tokens_in(
+                &balances,
+                &weights,
+                &amounts_in,
+                lp_supply,
+                SWAP_FEE,
+            )
+            .unwrap();
+
+            // The two directions use different power approximations, so they only agree to
+            // within those
+            assert!(lp_for_amount_in.abs_diff(lp_out) <= lp_out / 100_000);
+        }
+
+        // A single-token join costs more than its proportional share
+        let amount_in =
+            calc_token_in_given_exact_lp_out(balances[0], weights[0], 1_000 * ONE, lp_supply, 0)
+                .unwrap();
+        assert!(amount_in > balances[0] / 1_000);
+    }
+
+    #[test]
+    fn test_single_token_join_matches_exact_tokens_in() {
+        let balances = [2_000_000 * ONE, 500_000 * ONE];
+        let weights = [ONE * 8 / 10, ONE * 2 / 10];
+        let lp_supply = 1_000_000 * ONE;
+
+        for (index, amount_in) in [(0, 1_000 * ONE), (0, 400_000 * ONE), (1, 30_000 * ONE)] {
+            let lp_out = calc_lp_out_given_exact_token_in(
+                balances[index],
+                weights[index],
+                amount_in,
+                lp_supply,
+                SWAP_FEE,
+            )
+            .unwrap();
+
+            let mut amounts_in = [0; 2];
+            amounts_in[index] = amount_in;
+            let expected = calc_lp_out_given_exact_tokens_in(
+                &balances,
+                &weights,
+                &amounts_in,
+                lp_supply,
+                SWAP_FEE,
+            )
+            .unwrap();
+            assert!(lp_out.abs_diff(expected) <= expected / 1_000_000);
+
+            // Never more than the fee-free join
+            let fee_free = calc_lp_out_given_exact_token_in(
+                balances[index],
+                weights[index],
+                amount_in,
+                lp_supply,
+                0,
+            )
+            .unwrap();
+            assert!(lp_out < fee_free);
+        }
+    }
+
+    #[test]
+    fn test_token_out_given_exact_lp_in_inverts_exact_tokens_out() {
+        let balances = [2_000_000 * ONE, 500_000 * ONE];
+        let weights = [ONE * 8 / 10, ONE * 2 / 10];
+        let lp_supply = 1_000_000 * ONE;
+
+        for (index, lp_in) in [(0, 1_000 * ONE), (0, 50_000 * ONE), (1, 20_000 * ONE)] {
+            let amount_out = calc_token_out_given_exact_lp_in(
+                balances[index],
+                weights[index],
+                lp_in,
+                lp_supply,
+                SWAP_FEE,
+            )
+            .unwrap();
+
+            let mut amounts_out = [0; 2];
+            amounts_out[index] = amount_out;
+            let lp_for_amount_out = calc_lp_in_given_exact_tokens_out(
+                &balances,
+                &weights,
+                &amounts_out,
+                lp_supply,
+                SWAP_FEE,
+            )
+            .unwrap();
+
+            // Both directions round their powers against the user, so they only agree to
+            // within twice the approximation error
+            assert!(lp_for_amount_out.abs_diff(lp_in) <= lp_in / 50_000);
+        }
+
+        // Even fee-free, a single-token exit pays less than the proportional share is worth in
+        // that token, balance / weight at spot
+        let amount_out =
+            calc_token_out_given_exact_lp_in(balances[0], weights[0], 1_000 * ONE, lp_supply, 0)
+                .unwrap();
+        assert!(amount_out < balances[0] / 1_000 * 10 / 8);
+        assert!(calc_token_out_given_exact_lp_in(
+            balances[0],
+            weights[0],
+            lp_supply + 1,
+            lp_supply,
+            0
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_lp_in_given_exact_tokens_out_charges_fee_off_ratio() {
+        let balances = [1_000_000 * ONE, 1_000_000 * ONE];
+        let weights = [HALF, HALF];
+        let lp_supply = 1_000_000 * ONE;
+
+        // A proportional exit pays no fee and burns its share, rounded in the pool's favor
+        let proportional = calc_lp_in_given_exact_tokens_out(
+            &balances,
+            &weights,
+            &[10_000 * ONE, 10_000 * ONE],
+            lp_supply,
+            SWAP_FEE,
+        )
+        .unwrap();
+        assert!(proportional >= 10_000 * ONE);
+        assert!(proportional - 10_000 * ONE <= 10_000 * ONE / 100_000);
+
+        // The same value taken from one side costs more LP than without a fee
+        let one_sided = |swap_fee| {
+            calc_lp_in_given_exact_tokens_out(
+                &balances,
+                &weights,
+                &[20_000 * ONE, 0],
+                lp_supply,
+                swap_fee,
+            )
+            .unwrap()
+        };
+        assert!(one_sided(SWAP_FEE) > one_sided(0));
+        assert!(one_sided(0) > proportional);
+
+        // Can't take out a whole balance
+        assert!(calc_lp_in_given_exact_tokens_out(
+            &balances,
+            &weights,
+            &[balances[0] + 1, 0],
+            lp_supply,
+            SWAP_FEE
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! Addresses of the program's PDAs, shared by the instructions and by off-chain integrators so
+//! seeds are spelled out in one place. Every function returns the address and its bump.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{
+        AUTHORITY, CONFIG, CREATOR_LOCK, INTERNAL_BALANCE, LP_POSITION, POOL_FEE_STATS,
+        POOL_PARAMS, POOL_VAULT, STABLE_POOL, SWAP_CHECKPOINT, TOKEN_BADGE, UPGRADE_GUARD,
+        USER_REBATE_STATS, USER_STATS, VIRTUAL_PRICE_CHECKPOINT, WEIGHT_POOL, WITHDRAW_REQUEST,
+    },
+    instructions::rebalance::PoolKind,
+};
+
+pub fn authority_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY], &crate::ID)
+}
+
+pub fn pool_authority_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY, pool.as_ref()], &crate::ID)
+}
+
+pub fn config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG], &crate::ID)
+}
+
+pub fn pool_address(pool_kind: PoolKind, lp_mint: &Pubkey) -> (Pubkey, u8) {
+    let pool_seed = match pool_kind {
+        PoolKind::Weighted => WEIGHT_POOL,
+        PoolKind::Stable => STABLE_POOL,
+    };
+    Pubkey::find_program_address(&[pool_seed, lp_mint.as_ref()], &crate::ID)
+}
+
+pub fn vault_address(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_VAULT, pool.as_ref(), mint.as_ref()], &crate::ID)
+}
+
+pub fn pool_params_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_PARAMS, pool.as_ref()], &crate::ID)
+}
+
+pub fn creator_lock_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CREATOR_LOCK, pool.as_ref()], &crate::ID)
+}
+
+pub fn token_badge_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TOKEN_BADGE, mint.as_ref()], &crate::ID)
+}
+
+pub fn withdraw_request_address(pool: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[WITHDRAW_REQUEST, pool.as_ref(), user.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn user_stats_address(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[USER_STATS, user.as_ref()], &crate::ID)
+}
+
+pub fn user_rebate_stats_address(pool: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[USER_REBATE_STATS, pool.as_ref(), user.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn pool_fee_stats_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_FEE_STATS, pool.as_ref()], &crate::ID)
+}
+
+pub fn swap_checkpoint_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SWAP_CHECKPOINT, pool.as_ref()], &crate::ID)
+}
+
+pub fn virtual_price_checkpoint_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VIRTUAL_PRICE_CHECKPOINT, pool.as_ref()], &crate::ID)
+}
+
+pub fn internal_balance_address(pool: &Pubkey, owner: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            INTERNAL_BALANCE,
+            pool.as_ref(),
+            owner.as_ref(),
+            mint.as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+pub fn lp_position_address(pool: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LP_POSITION, pool.as_ref(), owner.as_ref()], &crate::ID)
+}
+
+pub fn upgrade_guard_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[UPGRADE_GUARD], &crate::ID)
+}
+
+/// The upgradeable loader's program data account of this program, whose `slot` is the last
+/// deploy
+pub fn program_data_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[crate::ID.as_ref()],
+        &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+    )
+}
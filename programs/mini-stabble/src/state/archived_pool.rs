@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+/// What's left of an archived weighted or stable pool: the pool address keeps this minimal
+/// account so it can never be initialized again with different parameters
+#[account]
+#[derive(InitSpace)]
+pub struct ArchivedPool {
+    pub lp_mint: Pubkey,
+
+    /// Unix timestamp of the archival
+    pub archived_ts: i64,
+}
+
+impl ArchivedPool {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+/// Operational key that can perform a subset of the admin's instructions. The admin holds every
+/// role, so an unassigned role (default key) falls back to the admin alone.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Tunes swap fees and fee policy
+    FeeManager,
+
+    /// Pauses pools; resuming them stays with the admin
+    Pauser,
+
+    /// Runs amp ramps and re-arms safety settings
+    Keeper,
+}
+
+/// Protocol-wide configuration, a singleton PDA at `[CONFIG]`
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    /// Key allowed to tune per-pool parameters
+    pub admin: Pubkey,
+
+    /// Stable pool fee floor is `amp_fee_floor_coefficient / amp` (fee at amp = 1, SCALE = 1e9)
+    pub amp_fee_floor_coefficient: u64,
+
+    /// Key allowed to seed pools with protocol-owned liquidity (default = disabled)
+    pub treasury: Pubkey,
+
+    /// Holders of each `Role` besides the admin (default = unassigned)
+    pub fee_manager: Pubkey,
+    pub pauser: Pubkey,
+    pub keeper: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Config {
+    /// Whether `signer` may act as `role`: the admin, or the key assigned to it
+    pub fn has_role(&self, role: Role, signer: &Pubkey) -> bool {
+        let holder = match role {
+            Role::FeeManager => self.fee_manager,
+            Role::Pauser => self.pauser,
+            Role::Keeper => self.keeper,
+        };
+
+        *signer == self.admin || (holder != Pubkey::default() && *signer == holder)
+    }
+
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_holds_every_role() {
+        let admin = Pubkey::new_unique();
+        let pauser = Pubkey::new_unique();
+        let config = Config {
+            admin,
+            amp_fee_floor_coefficient: 0,
+            treasury: Pubkey::default(),
+            fee_manager: Pubkey::default(),
+            pauser,
+            keeper: Pubkey::default(),
+            bump: 255,
+        };
+
+        for role in [Role::FeeManager, Role::Pauser, Role::Keeper] {
+            assert!(config.has_role(role, &admin));
+        }
+        assert!(config.has_role(Role::Pauser, &pauser));
+        assert!(!config.has_role(Role::Keeper, &pauser));
+        assert!(!config.has_role(Role::FeeManager, &Pubkey::default()));
+    }
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::MiniStabbleError;
+
+/// Longest lock a pool creator may commit to at initialization, in days
+pub const MAX_CREATOR_LOCK_DAYS: u16 = 3_650;
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Bootstrapping lock on the LP minted by a pool's first deposit. The LP sits in an escrow
+/// token account at `[CREATOR_LOCK, pool]` until `unlock_ts`, and only `owner` can release it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct CreatorLock {
+    /// Lock duration chosen at initialization, in days (0 = no lock)
+    pub lock_days: u16,
+
+    /// First depositor, the only one who can release the escrowed LP
+    pub owner: Pubkey,
+
+    /// LP held in escrow; 0 before the first deposit and after release
+    pub lp_amount: u64,
+
+    /// Unix timestamp from which the LP can be released
+    pub unlock_ts: i64,
+}
+
+impl CreatorLock {
+    pub fn new(lock_days: u16) -> Self {
+        Self {
+            lock_days,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.lock_days > 0
+    }
+
+    /// Records the first deposit's `lp_amount` as locked for `owner` from `now`
+    pub fn lock(&mut self, owner: Pubkey, lp_amount: u64, now: i64) -> Result<()> {
+        self.unlock_ts = now
+            .checked_add(self.lock_days as i64 * SECONDS_PER_DAY)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        self.owner = owner;
+        self.lp_amount = lp_amount;
+
+        Ok(())
+    }
+
+    pub fn is_unlocked(&self, now: i64) -> bool {
+        now >= self.unlock_ts
+    }
+}
+
+pub fn validate_creator_lock_days(lock_days: u16) -> Result<()> {
+    require!(
+        lock_days <= MAX_CREATOR_LOCK_DAYS,
+        MiniStabbleError::InvalidCreatorLock
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_unlocks_after_duration() {
+        let owner = Pubkey::new_unique();
+        let mut lock = CreatorLock::new(30);
+        assert!(lock.is_enabled());
+
+        lock.lock(owner, 1_000, 100).unwrap();
+        assert_eq!(lock.owner, owner);
+        assert_eq!(lock.lp_amount, 1_000);
+        assert_eq!(lock.unlock_ts, 100 + 30 * SECONDS_PER_DAY);
+        assert!(!lock.is_unlocked(100 + 30 * SECONDS_PER_DAY - 1));
+        assert!(lock.is_unlocked(100 + 30 * SECONDS_PER_DAY));
+    }
+
+    #[test]
+    fn test_validate_creator_lock_days() {
+        assert!(!CreatorLock::new(0).is_enabled());
+        assert!(validate_creator_lock_days(0).is_ok());
+        assert!(validate_creator_lock_days(MAX_CREATOR_LOCK_DAYS).is_ok());
+        assert!(validate_creator_lock_days(MAX_CREATOR_LOCK_DAYS + 1).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::BPS_DENOMINATOR, errors::MiniStabbleError};
+
+/// Circuit breaker on the pool's invariant per LP token. Swaps only ever add fees to it, so a
+/// swap that would leave it well below its high-water mark points at a math exploit and fails.
+/// A pool that ends up below it some other way can be paused by anyone with `trip_breaker`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct DrawdownGuard {
+    /// Largest drop below the high-water mark a swap may cause, in bps (0 = disabled)
+    pub max_drawdown_bps: u16,
+
+    /// Highest invariant per LP token (SCALE = 1e9) a swap has left behind; 0 until the first
+    /// swap after the guard is armed
+    pub high_water_mark: u128,
+}
+
+impl DrawdownGuard {
+    pub fn is_enabled(&self) -> bool {
+        self.max_drawdown_bps > 0
+    }
+
+    /// Records the invariant per LP token a swap would leave behind. Returns whether it falls
+    /// more than `max_drawdown_bps` below the high-water mark, in which case the mark is kept.
+    pub fn record(&mut self, virtual_price: u128) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+
+        let floor = self.high_water_mark * (BPS_DENOMINATOR - self.max_drawdown_bps as u64) as u128
+            / BPS_DENOMINATOR as u128;
+        if virtual_price < floor {
+            return true;
+        }
+
+        self.high_water_mark = self.high_water_mark.max(virtual_price);
+        false
+    }
+
+    /// Sets the guard to `max_drawdown_bps`. The admin can set any threshold, including 0 to
+    /// disable it, and always starts from a fresh high-water mark. Anyone else can only arm it at
+    /// the same or a tighter threshold and keeps the mark, so a drained pool can't re-arm from
+    /// its lower level.
+    pub fn set(&mut self, max_drawdown_bps: u16, is_admin: bool) -> Result<()> {
+        validate_drawdown_guard(max_drawdown_bps)?;
+
+        if is_admin {
+            *self = DrawdownGuard {
+                max_drawdown_bps,
+                ..Default::default()
+            };
+            return Ok(());
+        }
+
+        require!(
+            max_drawdown_bps > 0
+                && (!self.is_enabled() || max_drawdown_bps <= self.max_drawdown_bps),
+            MiniStabbleError::Unauthorized
+        );
+        self.max_drawdown_bps = max_drawdown_bps;
+
+        Ok(())
+    }
+
+    /// Forgets the high-water mark, for changes that legitimately move the invariant per LP
+    /// (amp ramps, scaling repairs) and for re-arming after a trip
+    pub fn reset(&mut self) {
+        self.high_water_mark = 0;
+    }
+}
+
+pub fn validate_drawdown_guard(max_drawdown_bps: u16) -> Result<()> {
+    require!(
+        max_drawdown_bps as u64 <= BPS_DENOMINATOR,
+        MiniStabbleError::InvalidDrawdownGuard
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_guard_never_trips() {
+        let mut guard = DrawdownGuard::default();
+        assert!(!guard.record(1_000_000_000));
+        assert!(!guard.record(1));
+        assert_eq!(guard.high_water_mark, 0);
+    }
+
+    #[test]
+    fn test_guard_trips_below_high_water_mark() {
+        let mut guard = DrawdownGuard {
+            max_drawdown_bps: 100,
+            ..Default::default()
+        };
+
+        assert!(!guard.record(1_000_000_000));
+        assert!(!guard.record(1_010_000_000));
+
+        // 1% below the mark is still allowed, anything further trips and keeps the mark
+        assert!(!guard.record(999_900_000));
+        assert!(guard.record(999_899_999));
+        assert_eq!(guard.high_water_mark, 1_010_000_000);
+
+        guard.reset();
+        assert!(!guard.record(500_000_000));
+    }
+
+    #[test]
+    fn test_keeper_can_only_tighten_the_guard() {
+        let unauthorized: Result<()> = err!(MiniStabbleError::Unauthorized);
+        let mut guard = DrawdownGuard {
+            max_drawdown_bps: 100,
+            high_water_mark: 1_010_000_000,
+        };
+
+        // Disabling or loosening stays with the admin
+        assert_eq!(guard.set(0, false), unauthorized);
+        assert_eq!(guard.set(101, false), unauthorized);
+        assert_eq!(guard.max_drawdown_bps, 100);
+
+        // Tightening keeps the high-water mark
+        assert!(guard.set(50, false).is_ok());
+        assert_eq!(guard.max_drawdown_bps, 50);
+        assert_eq!(guard.high_water_mark, 1_010_000_000);
+        assert!(guard.record(1_004_000_000));
+
+        // The admin can loosen and start from a fresh mark
+        assert!(guard.set(200, true).is_ok());
+        assert_eq!(guard.high_water_mark, 0);
+        assert!(guard.set(0, true).is_ok());
+        assert!(!guard.is_enabled());
+    }
+}
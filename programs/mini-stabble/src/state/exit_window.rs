@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+
+/// Instant exits from a pool within the current withdraw queue window. The queue threshold is
+/// applied to their running total rather than to each exit, so splitting a large exit into
+/// several smaller ones, in one transaction or across a few, still ends up in the queue.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct ExitWindow {
+    /// Slot the current window opened at
+    pub window_start_slot: u64,
+
+    /// LP supply when the window opened; the threshold is a share of it (0 = no window open)
+    pub window_start_supply: u64,
+
+    /// LP burned by instant exits since the window opened
+    pub exited_lp: u64,
+}
+
+impl ExitWindow {
+    /// Records an instant exit of `lp_amount` out of `lp_supply` at `slot`, opening a new window
+    /// of `window_slots` first if the current one has elapsed. Returns whether the window's exits,
+    /// this one included, go past `threshold_bps` of the supply it opened with; such an exit
+    /// isn't recorded and has to go through the withdraw queue.
+    pub fn record(
+        &mut self,
+        lp_amount: u64,
+        lp_supply: u64,
+        threshold_bps: u16,
+        window_slots: u64,
+        slot: u64,
+    ) -> bool {
+        if threshold_bps == 0 {
+            return false;
+        }
+
+        let window_end = self.window_start_slot.saturating_add(window_slots.max(1));
+        if self.window_start_supply == 0 || slot >= window_end {
+            *self = ExitWindow {
+                window_start_slot: slot,
+                window_start_supply: lp_supply,
+                exited_lp: 0,
+            };
+        }
+
+        let exited_lp = self.exited_lp.saturating_add(lp_amount);
+        if (exited_lp as u128) * (BPS_DENOMINATOR as u128)
+            > (self.window_start_supply as u128) * (threshold_bps as u128)
+        {
+            return true;
+        }
+
+        self.exited_lp = exited_lp;
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUPPLY: u64 = 1_000_000;
+    const THRESHOLD_BPS: u16 = 1_000;
+    const WINDOW_SLOTS: u64 = 100;
+
+    #[test]
+    fn test_split_exits_add_up_to_the_threshold() {
+        let mut window = ExitWindow::default();
+
+        // Four 3% exits in the same slot: the fourth takes the window past 10%
+        let mut supply = SUPPLY;
+        for _ in 0..3 {
+            assert!(!window.record(30_000, supply, THRESHOLD_BPS, WINDOW_SLOTS, 50));
+            supply -= 30_000;
+        }
+        assert!(window.record(30_000, supply, THRESHOLD_BPS, WINDOW_SLOTS, 50));
+        assert_eq!(window.exited_lp, 90_000);
+
+        // What is left of the allowance can still exit instantly, a few slots later too
+        assert!(!window.record(10_000, supply, THRESHOLD_BPS, WINDOW_SLOTS, 149));
+        assert!(window.record(1, supply, THRESHOLD_BPS, WINDOW_SLOTS, 149));
+    }
+
+    #[test]
+    fn test_new_window_resets_the_total() {
+        let mut window = ExitWindow::default();
+        assert!(!window.record(100_000, SUPPLY, THRESHOLD_BPS, WINDOW_SLOTS, 10));
+        assert!(window.record(1, SUPPLY, THRESHOLD_BPS, WINDOW_SLOTS, 109));
+
+        assert!(!window.record(90_000, 900_000, THRESHOLD_BPS, WINDOW_SLOTS, 110));
+        assert_eq!(
+            window,
+            ExitWindow {
+                window_start_slot: 110,
+                window_start_supply: 900_000,
+                exited_lp: 90_000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_single_exit_over_threshold_is_queued() {
+        let mut window = ExitWindow::default();
+        assert!(window.record(100_001, SUPPLY, THRESHOLD_BPS, WINDOW_SLOTS, 10));
+        assert_eq!(window.exited_lp, 0);
+
+        // Disabled queue: nothing is tracked
+        let mut window = ExitWindow::default();
+        assert!(!window.record(SUPPLY, SUPPLY, 0, WINDOW_SLOTS, 10));
+        assert_eq!(window, ExitWindow::default());
+    }
+}
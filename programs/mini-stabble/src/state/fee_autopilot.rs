@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{fees::MAX_SWAP_FEE, BPS_DENOMINATOR},
+    errors::MiniStabbleError,
+    math::fixed::ONE_U64,
+};
+
+/// Weight of the governance token in the 80/20 preset (SCALE = 1e9)
+pub const GOVERNANCE_TOKEN_WEIGHT: u64 = 800_000_000;
+
+/// Minimum time between two volatility samples, in seconds. Without it a crank could feed
+/// back-to-back samples of an unchanged price and talk the fee down ahead of a volatile move.
+pub const FEE_AUTOPILOT_MIN_INTERVAL: i64 = 300;
+
+/// Minimum time between two samples taken by one of the pool's registered crank keepers
+pub const FEE_AUTOPILOT_KEEPER_MIN_INTERVAL: i64 = 60;
+
+/// Number of samples the volatility estimate averages over (exponential moving average)
+pub const VOLATILITY_SMOOTHING: u64 = 4;
+
+/// Swap fee autopilot for 80/20 governance-token pools: the fee tracks the realized volatility
+/// of the pool's spot price between `min_fee` (calm) and `max_fee` (at or above
+/// `max_volatility_bps`), so LPs are paid more while they're taking more adverse selection
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct FeeAutopilot {
+    /// Fee bounds (SCALE = 1e9); `max_fee` = 0 disables the autopilot
+    pub min_fee: u64,
+    pub max_fee: u64,
+
+    /// Volatility at which the fee reaches `max_fee`, in bps per sample
+    pub max_volatility_bps: u64,
+
+    /// Spot price of `tokens[0]` in `tokens[1]` at the last sample (SCALE = 1e9)
+    pub last_price: u128,
+    pub last_update_ts: i64,
+
+    /// Moving average of the absolute price change between samples, in bps
+    pub volatility_bps: u64,
+}
+
+impl FeeAutopilot {
+    pub fn is_enabled(&self) -> bool {
+        self.max_fee > 0
+    }
+
+    /// Fee for the current volatility estimate, interpolated linearly between the bounds
+    pub fn fee(&self) -> u64 {
+        let volatility = self.volatility_bps.min(self.max_volatility_bps);
+        let fee_range = (self.max_fee - self.min_fee) as u128;
+
+        self.min_fee + (fee_range * volatility as u128 / self.max_volatility_bps as u128) as u64
+    }
+
+    /// Folds the spot price observed at `now` into the volatility estimate and returns the fee
+    /// the pool should charge from now on. Fails within `min_interval` of the last sample.
+    pub fn sample(&mut self, price: u128, now: i64, min_interval: i64) -> Result<u64> {
+        require!(self.is_enabled(), MiniStabbleError::FeeAutopilotDisabled);
+        require!(
+            now >= self.last_update_ts.saturating_add(min_interval),
+            MiniStabbleError::FeeUpdateTooEarly
+        );
+
+        if self.last_price > 0 {
+            let change_bps =
+                price.abs_diff(self.last_price) * BPS_DENOMINATOR as u128 / self.last_price;
+            let change_bps = u64::try_from(change_bps).unwrap_or(u64::MAX);
+
+            self.volatility_bps = ((self.volatility_bps as u128
+                * (VOLATILITY_SMOOTHING - 1) as u128
+                + change_bps as u128)
+                / VOLATILITY_SMOOTHING as u128) as u64;
+        }
+        self.last_price = price;
+        self.last_update_ts = now;
+
+        Ok(self.fee())
+    }
+}
+
+/// Whether `weights` are the 80/20 governance preset, in either token order
+pub fn is_governance_pool_weights(weights: &[u64]) -> bool {
+    matches!(
+        weights,
+        [a, b] if a + b == ONE_U64 && (*a == GOVERNANCE_TOKEN_WEIGHT || *b == GOVERNANCE_TOKEN_WEIGHT)
+    )
+}
+
+pub fn validate_fee_autopilot(min_fee: u64, max_fee: u64, max_volatility_bps: u64) -> Result<()> {
+    require!(
+        min_fee <= max_fee && max_fee <= MAX_SWAP_FEE && max_volatility_bps > 0,
+        MiniStabbleError::InvalidFeeAutopilot
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn autopilot() -> FeeAutopilot {
+        FeeAutopilot {
+            min_fee: 1_000_000,
+            max_fee: 10_000_000,
+            max_volatility_bps: 500,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fee_follows_volatility_within_bounds() {
+        let mut autopilot = autopilot();
+        assert_eq!(
+            autopilot
+                .sample(1_000_000_000, 300, FEE_AUTOPILOT_MIN_INTERVAL)
+                .unwrap(),
+            1_000_000
+        );
+
+        // A 10% move averages in as 250 bps, halfway to the cap
+        assert_eq!(
+            autopilot
+                .sample(1_100_000_000, 600, FEE_AUTOPILOT_MIN_INTERVAL)
+                .unwrap(),
+            5_500_000
+        );
+        assert_eq!(autopilot.volatility_bps, 250);
+
+        // Repeated large moves saturate at max_fee
+        for i in 0..8 {
+            let price = if i % 2 == 0 {
+                700_000_000
+            } else {
+                1_100_000_000
+            };
+            autopilot
+                .sample(price, 900 + i * 300, FEE_AUTOPILOT_MIN_INTERVAL)
+                .unwrap();
+        }
+        assert_eq!(autopilot.fee(), 10_000_000);
+
+        // A calm price decays the fee back towards min_fee
+        for i in 0..20 {
+            autopilot
+                .sample(1_100_000_000, 4_000 + i * 300, FEE_AUTOPILOT_MIN_INTERVAL)
+                .unwrap();
+        }
+        assert!(autopilot.fee() < 1_500_000);
+    }
+
+    #[test]
+    fn test_sample_rate_limited_and_requires_enabled() {
+        let mut autopilot = autopilot();
+        autopilot
+            .sample(1_000_000_000, 1_000, FEE_AUTOPILOT_MIN_INTERVAL)
+            .unwrap();
+        assert!(autopilot
+            .sample(1_000_000_000, 1_299, FEE_AUTOPILOT_MIN_INTERVAL)
+            .is_err());
+        assert!(autopilot
+            .sample(1_000_000_000, 1_300, FEE_AUTOPILOT_MIN_INTERVAL)
+            .is_ok());
+
+        // Registered keepers may sample sooner
+        assert!(autopilot
+            .sample(1_000_000_000, 1_360, FEE_AUTOPILOT_KEEPER_MIN_INTERVAL)
+            .is_ok());
+
+        assert!(FeeAutopilot::default()
+            .sample(1_000_000_000, 1_000, FEE_AUTOPILOT_MIN_INTERVAL)
+            .is_err());
+    }
+
+    #[test]
+    fn test_governance_pool_weights() {
+        assert!(is_governance_pool_weights(&[800_000_000, 200_000_000]));
+        assert!(is_governance_pool_weights(&[200_000_000, 800_000_000]));
+        assert!(!is_governance_pool_weights(&[500_000_000, 500_000_000]));
+        assert!(!is_governance_pool_weights(&[
+            800_000_000,
+            100_000_000,
+            100_000_000
+        ]));
+    }
+}
@@ -0,0 +1,243 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::BPS_DENOMINATOR, errors::MiniStabbleError};
+
+/// Length of a rebate epoch, in seconds
+pub const REBATE_EPOCH_DURATION: i64 = 7 * 86_400;
+
+/// Rebate epoch containing unix timestamp `now`
+pub fn rebate_epoch(now: i64) -> u64 {
+    (now.max(0) / REBATE_EPOCH_DURATION) as u64
+}
+
+/// Slice of a pool's swap fees set aside each epoch for the traders who generated them. Volume
+/// only counts when the trader passes their `UserRebateStats`, and an epoch's rebates are
+/// claimable during the following epoch only; whatever is left then goes back to the LPs.
+/// Amounts are in scaled units, per pool token.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct FeeRebate {
+    /// Share of each swap fee set aside for rebates, in bps (0 = disabled)
+    pub fee_share_bps: u16,
+
+    /// Epoch currently accruing
+    pub epoch: u64,
+    pub epoch_volume: u128,
+    #[max_len(8)]
+    pub epoch_fees: Vec<u64>,
+
+    /// Epoch open for claims (`epoch - 1`)
+    pub previous_epoch_volume: u128,
+    #[max_len(8)]
+    pub previous_epoch_fees: Vec<u64>,
+    #[max_len(8)]
+    pub previous_epoch_claimed: Vec<u64>,
+}
+
+impl FeeRebate {
+    pub fn is_enabled(&self) -> bool {
+        self.fee_share_bps > 0
+    }
+
+    /// Moves the accrual to `epoch`. Returns, per token, the rebates that can no longer be
+    /// claimed and should be handed back to the pool.
+    pub fn roll(&mut self, epoch: u64, token_count: usize) -> Vec<u64> {
+        let mut expired = vec![0; token_count];
+
+        if self.epoch_fees.len() != token_count {
+            *self = FeeRebate {
+                fee_share_bps: self.fee_share_bps,
+                epoch,
+                epoch_fees: vec![0; token_count],
+                previous_epoch_fees: vec![0; token_count],
+                previous_epoch_claimed: vec![0; token_count],
+                ..Default::default()
+            };
+            return expired;
+        }
+        if epoch <= self.epoch {
+            return expired;
+        }
+
+        for (i, amount) in expired.iter_mut().enumerate() {
+            *amount = self.previous_epoch_fees[i] - self.previous_epoch_claimed[i];
+        }
+
+        if epoch == self.epoch + 1 {
+            self.previous_epoch_volume = self.epoch_volume;
+            self.previous_epoch_fees =
+                std::mem::replace(&mut self.epoch_fees, vec![0; token_count]);
+        } else {
+            // Nobody can claim an epoch that was already over when this one started
+            for (amount, fee) in expired.iter_mut().zip(&self.epoch_fees) {
+                *amount += fee;
+            }
+            self.previous_epoch_volume = 0;
+            self.previous_epoch_fees = vec![0; token_count];
+            self.epoch_fees = vec![0; token_count];
+        }
+        self.previous_epoch_claimed = vec![0; token_count];
+        self.epoch_volume = 0;
+        self.epoch = epoch;
+
+        expired
+    }
+
+    /// Sets aside the rebate share of `scaled_fee` charged in `tokens[index]` and returns it
+    pub fn accrue_fee(&mut self, index: usize, scaled_fee: u64) -> u64 {
+        let share =
+            (scaled_fee as u128 * self.fee_share_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        self.epoch_fees[index] += share;
+        share
+    }
+
+    /// Scaled `tokens[index]` rebates held in the vault until claimed or expired
+    pub fn reserved(&self, index: usize) -> u64 {
+        let owed = |fees: &Vec<u64>| fees.get(index).copied().unwrap_or(0);
+
+        owed(&self.epoch_fees) + owed(&self.previous_epoch_fees)
+            - owed(&self.previous_epoch_claimed)
+    }
+
+    /// Pays out the share of the previous epoch's `tokens[index]` rebates earned by
+    /// `user_volume` of that epoch's counted volume
+    pub fn claim(&mut self, index: usize, user_volume: u128) -> Result<u64> {
+        if self.previous_epoch_volume == 0 {
+            return Ok(0);
+        }
+
+        let share = u64::try_from(
+            self.previous_epoch_fees[index] as u128 * user_volume / self.previous_epoch_volume,
+        )?;
+        self.previous_epoch_claimed[index] = self.previous_epoch_claimed[index]
+            .checked_add(share)
+            .filter(|&claimed| claimed <= self.previous_epoch_fees[index])
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        Ok(share)
+    }
+}
+
+/// A trader's rebate-eligible volume in one pool, a PDA at `[USER_REBATE_STATS, pool, user]`,
+/// the per-pool counterpart of `UserStats`
+#[account]
+#[derive(InitSpace)]
+pub struct UserRebateStats {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+
+    /// Epoch `epoch_volume` was recorded in
+    pub epoch: u64,
+    pub epoch_volume: u128,
+
+    /// Volume of `epoch - 1`, the epoch open for claims
+    pub previous_epoch_volume: u128,
+
+    /// Bit `i` set once the previous epoch's rebate in `tokens[i]` was claimed
+    pub claimed_tokens: u8,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl UserRebateStats {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn roll(&mut self, epoch: u64) {
+        if epoch <= self.epoch {
+            return;
+        }
+
+        self.previous_epoch_volume = if epoch == self.epoch + 1 {
+            self.epoch_volume
+        } else {
+            0
+        };
+        self.epoch_volume = 0;
+        self.claimed_tokens = 0;
+        self.epoch = epoch;
+    }
+}
+
+pub fn validate_rebate_fee_share(fee_share_bps: u16) -> Result<()> {
+    require!(
+        fee_share_bps as u64 <= BPS_DENOMINATOR,
+        MiniStabbleError::InvalidAmount
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebates_claimable_for_one_epoch_then_expire() {
+        let mut rebate = FeeRebate {
+            fee_share_bps: 2_000,
+            ..Default::default()
+        };
+        assert_eq!(rebate.roll(10, 2), vec![0, 0]);
+
+        assert_eq!(rebate.accrue_fee(1, 1_000), 200);
+        rebate.epoch_volume = 400;
+
+        assert_eq!(rebate.roll(11, 2), vec![0, 0]);
+        assert_eq!(rebate.epoch_fees, vec![0, 0]);
+        assert_eq!(rebate.claim(1, 100).unwrap(), 50);
+        assert_eq!(rebate.claim(0, 100).unwrap(), 0);
+
+        // Whatever wasn't claimed during epoch 11 goes back to the pool
+        assert_eq!(rebate.roll(12, 2), vec![0, 150]);
+        assert_eq!(rebate.previous_epoch_fees, vec![0, 0]);
+    }
+
+    #[test]
+    fn test_reserved_counts_unclaimed_rebates() {
+        let mut rebate = FeeRebate {
+            fee_share_bps: 10_000,
+            ..Default::default()
+        };
+        assert_eq!(rebate.reserved(0), 0);
+
+        rebate.roll(1, 2);
+        rebate.accrue_fee(0, 300);
+        rebate.epoch_volume = 3;
+        rebate.roll(2, 2);
+        rebate.accrue_fee(0, 50);
+        rebate.claim(0, 1).unwrap();
+        assert_eq!(rebate.reserved(0), 250);
+        assert_eq!(rebate.reserved(1), 0);
+    }
+
+    #[test]
+    fn test_skipped_epoch_expires_everything() {
+        let mut rebate = FeeRebate {
+            fee_share_bps: 10_000,
+            ..Default::default()
+        };
+        rebate.roll(1, 2);
+        rebate.accrue_fee(0, 300);
+        rebate.epoch_volume = 1;
+        rebate.roll(2, 2);
+        rebate.accrue_fee(1, 70);
+
+        assert_eq!(rebate.roll(5, 2), vec![300, 70]);
+        assert_eq!(rebate.previous_epoch_volume, 0);
+    }
+
+    #[test]
+    fn test_claims_cannot_exceed_accrued() {
+        let mut rebate = FeeRebate {
+            fee_share_bps: 10_000,
+            ..Default::default()
+        };
+        rebate.roll(1, 2);
+        rebate.accrue_fee(0, 100);
+        rebate.epoch_volume = 10;
+        rebate.roll(2, 2);
+
+        assert_eq!(rebate.claim(0, 10).unwrap(), 100);
+        assert!(rebate.claim(0, 1).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+/// Replay protection for a user's signed intents, a PDA at `[INTENT_NONCE, user]`. Each intent
+/// commits to the next nonce, so it can execute at most once and only in order.
+#[account]
+#[derive(InitSpace)]
+pub struct IntentNonce {
+    /// User whose intents this account sequences
+    pub user: Pubkey,
+
+    /// Nonce the next intent must carry
+    pub next_nonce: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl IntentNonce {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
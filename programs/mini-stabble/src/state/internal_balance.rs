@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+
+use super::PoolToken;
+use crate::errors::MiniStabbleError;
+
+/// Tokens a user keeps parked in a pool's vault to trade from without an SPL transfer per
+/// swap, a PDA at `[INTERNAL_BALANCE, pool, owner, mint]`. Like queued withdrawals, they sit in
+/// the vault without backing the pool, tracked per token in `PoolToken::internal_balances`.
+/// Amounts are raw.
+#[account]
+#[derive(InitSpace)]
+pub struct InternalBalance {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl InternalBalance {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn credit(&mut self, amount: u64) -> Result<()> {
+        self.amount = self
+            .amount
+            .checked_add(amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn debit(&mut self, amount: u64) -> Result<()> {
+        self.amount = self
+            .amount
+            .checked_sub(amount)
+            .ok_or(MiniStabbleError::InsufficientInternalBalance)?;
+
+        Ok(())
+    }
+}
+
+/// Books `amount` raw more of `mint`'s vault as internal balance on the pool's `tokens`
+pub fn hold_internal_balance(tokens: &mut [PoolToken], mint: &Pubkey, amount: u64) -> Result<()> {
+    let token = find_token_mut(tokens, mint)?;
+    token.internal_balances = token
+        .internal_balances
+        .checked_add(amount)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Releases `amount` raw of `mint`'s internal balance on the pool's `tokens`, once it has been
+/// paid out or swapped into the pool
+pub fn release_internal_balance(
+    tokens: &mut [PoolToken],
+    mint: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let token = find_token_mut(tokens, mint)?;
+    token.internal_balances = token
+        .internal_balances
+        .checked_sub(amount)
+        .ok_or(MiniStabbleError::InsufficientInternalBalance)?;
+
+    Ok(())
+}
+
+fn find_token_mut<'a>(tokens: &'a mut [PoolToken], mint: &Pubkey) -> Result<&'a mut PoolToken> {
+    tokens
+        .iter_mut()
+        .find(|token| token.mint == *mint)
+        .ok_or(MiniStabbleError::InvalidMint.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debit_never_overdraws() {
+        let mut balance = InternalBalance {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            amount: 0,
+            bump: 255,
+        };
+
+        balance.credit(1_000).unwrap();
+        balance.debit(400).unwrap();
+        assert_eq!(balance.amount, 600);
+
+        assert!(balance.debit(601).is_err());
+        assert_eq!(balance.amount, 600);
+        assert!(balance.credit(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_pool_tracks_internal_balances_per_mint() {
+        let mut tokens = vec![PoolToken::default(), PoolToken::default()];
+        tokens[0].mint = Pubkey::new_unique();
+        tokens[1].mint = Pubkey::new_unique();
+        let (other_mint, mint) = (tokens[0].mint, tokens[1].mint);
+
+        // Deposit 1_000, swap 300 of it into the other token, withdraw the rest
+        hold_internal_balance(&mut tokens, &mint, 1_000).unwrap();
+        release_internal_balance(&mut tokens, &mint, 300).unwrap();
+        hold_internal_balance(&mut tokens, &other_mint, 250).unwrap();
+        assert_eq!(tokens[1].internal_balances, 700);
+        assert_eq!(tokens[0].internal_balances, 250);
+
+        assert!(release_internal_balance(&mut tokens, &mint, 701).is_err());
+        release_internal_balance(&mut tokens, &mint, 700).unwrap();
+        assert_eq!(tokens[1].internal_balances, 0);
+
+        assert!(hold_internal_balance(&mut tokens, &Pubkey::new_unique(), 1).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use super::{
+    DrawdownGuard, ExitWindow, FeeRebate, InvariantCachePolicy, PoolToken, StablePool,
+    SwapRateLimit, WeightedPool,
+};
+use crate::{errors::MiniStabbleError, math::weighted::calc_swap_exponents};
+
+/// `PoolToken` as stored by the original program
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct LegacyPoolToken {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub decimals: u8,
+    pub scaling_factor: u64,
+    pub balance: u64,
+    pub weight: u64,
+}
+
+/// `WeightedPool` as stored by the original program
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct LegacyWeightedPool {
+    pub authority: Pubkey,
+    pub lp_mint: Pubkey,
+    pub is_active: bool,
+    pub invariant: u64,
+    pub swap_fee: u64,
+    #[max_len(8)]
+    pub tokens: Vec<LegacyPoolToken>,
+    pub bump: u8,
+}
+
+/// `StablePool` as stored by the original program
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct LegacyStablePool {
+    pub authority: Pubkey,
+    pub lp_mint: Pubkey,
+    pub is_active: bool,
+    pub invariant: u64,
+    pub swap_fee: u64,
+    pub amp: u64,
+    pub amp_target: u64,
+    pub amp_start_ts: i64,
+    pub amp_end_ts: i64,
+    #[max_len(8)]
+    pub tokens: Vec<LegacyPoolToken>,
+    pub bump: u8,
+}
+
+/// A pool rewritten in the current layout
+pub enum MigratedPool {
+    Weighted(Box<WeightedPool>),
+    Stable(Box<StablePool>),
+}
+
+impl MigratedPool {
+    /// Account size of the current layout
+    pub fn space(&self) -> usize {
+        match self {
+            MigratedPool::Weighted(_) => WeightedPool::LEN,
+            MigratedPool::Stable(_) => StablePool::LEN,
+        }
+    }
+
+    /// Writes the pool, discriminator first, over the start of `data`
+    pub fn write(&self, data: &mut [u8]) -> Result<()> {
+        let mut writer = data;
+        match self {
+            MigratedPool::Weighted(pool) => pool.try_serialize(&mut writer),
+            MigratedPool::Stable(pool) => pool.try_serialize(&mut writer),
+        }
+    }
+}
+
+impl From<LegacyPoolToken> for PoolToken {
+    fn from(token: LegacyPoolToken) -> Self {
+        PoolToken {
+            mint: token.mint,
+            token_account: token.token_account,
+            decimals: token.decimals,
+            scaling_factor: token.scaling_factor,
+            balance: token.balance,
+            weight: token.weight,
+            swaps_disabled: false,
+            rounding_dust: 0,
+            internal_balances: 0,
+            queued_withdrawals: 0,
+            referral_rewards: 0,
+        }
+    }
+}
+
+impl LegacyWeightedPool {
+    /// The pool in the current layout, every setting the original program didn't have off
+    pub fn migrate(self) -> Result<WeightedPool> {
+        let weights: Vec<u64> = self.tokens.iter().map(|token| token.weight).collect();
+
+        Ok(WeightedPool {
+            authority: self.authority,
+            lp_mint: self.lp_mint,
+            is_active: self.is_active,
+            invariant: self.invariant,
+            swap_fee: self.swap_fee,
+            max_spot_price_move_per_swap_bps: 0,
+            drawdown_guard: DrawdownGuard::default(),
+            trader_allowlist: false,
+            swap_exponents: calc_swap_exponents(&weights)?,
+            tokens: self.tokens.into_iter().map(PoolToken::from).collect(),
+            bump: self.bump,
+            feature_flags: 0,
+            exit_window: ExitWindow::default(),
+            authority_bump: 0,
+        })
+    }
+}
+
+impl LegacyStablePool {
+    /// The pool in the current layout, every setting the original program didn't have off.
+    /// The out-ratio cap stays off too, as it was for the pool until now.
+    pub fn migrate(self) -> StablePool {
+        StablePool {
+            authority: self.authority,
+            lp_mint: self.lp_mint,
+            is_active: self.is_active,
+            invariant: self.invariant,
+            swap_fee: self.swap_fee,
+            amp: self.amp,
+            amp_target: self.amp_target,
+            amp_start_ts: self.amp_start_ts,
+            amp_end_ts: self.amp_end_ts,
+            swap_rate_limit: SwapRateLimit::default(),
+            fee_growth: vec![0; self.tokens.len()],
+            invariant_cache_policy: InvariantCachePolicy::Strict,
+            invariant_last_updated_slot: 0,
+            drawdown_guard: DrawdownGuard::default(),
+            rebate: FeeRebate::default(),
+            trader_allowlist: false,
+            max_out_ratio_bps: 0,
+            tokens: self.tokens.into_iter().map(PoolToken::from).collect(),
+            bump: self.bump,
+            feature_flags: 0,
+            exit_window: ExitWindow::default(),
+            authority_bump: 0,
+        }
+    }
+}
+
+/// Decodes the data of a pool account still in the original program's layout and converts it
+/// to the current one
+pub fn migrate_legacy_pool(data: &[u8]) -> Result<MigratedPool> {
+    let (discriminator, mut body) = data
+        .split_at_checked(8)
+        .ok_or(MiniStabbleError::InvalidAccount)?;
+
+    if discriminator == WeightedPool::DISCRIMINATOR {
+        let pool = LegacyWeightedPool::deserialize(&mut body)
+            .map_err(|_| MiniStabbleError::InvalidAccount)?;
+        Ok(MigratedPool::Weighted(Box::new(pool.migrate()?)))
+    } else if discriminator == StablePool::DISCRIMINATOR {
+        let pool = LegacyStablePool::deserialize(&mut body)
+            .map_err(|_| MiniStabbleError::InvalidAccount)?;
+        Ok(MigratedPool::Stable(Box::new(pool.migrate())))
+    } else {
+        err!(MiniStabbleError::InvalidAccount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::PoolParams;
+
+    /// Size the original program allocated for each pool: `8 + INIT_SPACE` of its structs
+    const BASELINE_WEIGHTED_POOL_LEN: usize = 806;
+    const BASELINE_STABLE_POOL_LEN: usize = 838;
+
+    /// Writes a token field by field in the original `PoolToken` order
+    fn write_token(data: &mut Vec<u8>, mint: Pubkey, vault: Pubkey, balance: u64, weight: u64) {
+        data.extend_from_slice(mint.as_ref());
+        data.extend_from_slice(vault.as_ref());
+        data.push(6);
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&balance.to_le_bytes());
+        data.extend_from_slice(&weight.to_le_bytes());
+    }
+
+    /// Writes the fields the original pools start with
+    fn write_header(data: &mut Vec<u8>, authority: Pubkey, lp_mint: Pubkey, swap_fee: u64) {
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(lp_mint.as_ref());
+        data.push(1);
+        data.extend_from_slice(&77u64.to_le_bytes());
+        data.extend_from_slice(&swap_fee.to_le_bytes());
+    }
+
+    /// Rewrites the pool the way the migration does, into a zeroed account of the new size
+    fn rewrite(migrated: &MigratedPool) -> Vec<u8> {
+        let mut data = vec![0; migrated.space()];
+        migrated.write(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_legacy_structs_match_the_baseline_size() {
+        assert_eq!(
+            8 + LegacyWeightedPool::INIT_SPACE,
+            BASELINE_WEIGHTED_POOL_LEN
+        );
+        assert_eq!(8 + LegacyStablePool::INIT_SPACE, BASELINE_STABLE_POOL_LEN);
+    }
+
+    #[test]
+    fn test_migrates_baseline_weighted_pool() {
+        let (authority, lp_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let vaults = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let mut data = WeightedPool::DISCRIMINATOR.to_vec();
+        write_header(&mut data, authority, lp_mint, 3_000_000);
+        data.extend_from_slice(&2u32.to_le_bytes());
+        write_token(
+            &mut data,
+            mints[0],
+            vaults[0],
+            5_000_000_000_000,
+            800_000_000,
+        );
+        write_token(
+            &mut data,
+            mints[1],
+            vaults[1],
+            1_250_000_000_000,
+            200_000_000,
+        );
+        data.push(251);
+        data.resize(BASELINE_WEIGHTED_POOL_LEN, 0);
+
+        let migrated = migrate_legacy_pool(&data).unwrap();
+        assert_eq!(migrated.space(), WeightedPool::LEN);
+
+        let pool = WeightedPool::try_deserialize(&mut rewrite(&migrated).as_slice()).unwrap();
+        assert_eq!(pool.authority, authority);
+        assert_eq!(pool.lp_mint, lp_mint);
+        assert!(pool.is_active);
+        assert_eq!(pool.invariant, 77);
+        assert_eq!(pool.swap_fee, 3_000_000);
+        assert_eq!(pool.bump, 251);
+        assert_eq!(pool.tokens.len(), 2);
+        for i in 0..2 {
+            assert_eq!(pool.tokens[i].mint, mints[i]);
+            assert_eq!(pool.tokens[i].token_account, vaults[i]);
+            assert_eq!(pool.tokens[i].decimals, 6);
+            assert_eq!(pool.tokens[i].scaling_factor, 1_000);
+            assert!(!pool.tokens[i].swaps_disabled);
+            assert_eq!(pool.tokens[i].rounding_dust, 0);
+        }
+        assert_eq!(pool.tokens[1].balance, 1_250_000_000_000);
+        assert_eq!(pool.tokens[0].weight, 800_000_000);
+        assert_eq!(
+            pool.swap_exponents,
+            calc_swap_exponents(&pool.get_weights()).unwrap()
+        );
+        assert_eq!(pool.max_spot_price_move_per_swap_bps, 0);
+        assert!(!pool.trader_allowlist);
+        assert_eq!(pool.feature_flags, 0);
+        assert_eq!(pool.authority_bump, 0);
+    }
+
+    #[test]
+    fn test_migrates_baseline_stable_pool() {
+        let (authority, lp_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let vaults = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let mut data = StablePool::DISCRIMINATOR.to_vec();
+        write_header(&mut data, authority, lp_mint, 1_000_000);
+        for field in [100_000u64, 200_000, 10, 20] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for vault in vaults {
+            write_token(&mut data, Pubkey::new_unique(), vault, 7_000_000_000_000, 0);
+        }
+        data.push(250);
+        data.resize(BASELINE_STABLE_POOL_LEN, 0);
+
+        let migrated = migrate_legacy_pool(&data).unwrap();
+        assert_eq!(migrated.space(), StablePool::LEN);
+
+        let pool = StablePool::try_deserialize(&mut rewrite(&migrated).as_slice()).unwrap();
+        assert_eq!(pool.authority, authority);
+        assert_eq!(pool.lp_mint, lp_mint);
+        assert_eq!(pool.swap_fee, 1_000_000);
+        assert_eq!(pool.amp, 100_000);
+        assert_eq!(pool.amp_target, 200_000);
+        assert_eq!(pool.amp_start_ts, 10);
+        assert_eq!(pool.amp_end_ts, 20);
+        assert_eq!(pool.tokens[1].token_account, vaults[1]);
+        assert_eq!(pool.tokens[0].balance, 7_000_000_000_000);
+        assert_eq!(pool.bump, 250);
+        assert_eq!(pool.fee_growth, vec![0, 0]);
+        assert_eq!(pool.invariant_cache_policy, InvariantCachePolicy::Strict);
+        assert!(!pool.swap_rate_limit.is_enabled());
+        assert_eq!(pool.max_out_ratio_bps, 0);
+        assert_eq!(pool.authority_bump, 0);
+    }
+
+    #[test]
+    fn test_rejects_accounts_that_are_not_baseline_pools() {
+        let data = vec![0; PoolParams::LEN];
+        assert!(migrate_legacy_pool(&data).is_err());
+        assert!(migrate_legacy_pool(&WeightedPool::DISCRIMINATOR[..4]).is_err());
+
+        // Truncated before the token list
+        let mut data = StablePool::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[0; 16]);
+        assert!(migrate_legacy_pool(&data).is_err());
+    }
+}
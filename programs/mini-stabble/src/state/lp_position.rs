@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+
+use super::StablePool;
+use crate::{constants::BPS_DENOMINATOR, errors::MiniStabbleError, math::fixed::SCALE};
+
+/// An LP's position in a stable pool, a PDA at `[LP_POSITION, pool, owner]`, recording who
+/// referred it. LP minted by deposits made through the position earns the referrer
+/// `PoolParams::referral_fee_share_bps` of the fees it grows by. The referred LP pays that
+/// share itself: it is taken out of the owner's exits made through the position and becomes
+/// claimable with `claim_referral_rewards`, so the pool's other LPs never fund it. LP tokens
+/// are fungible, so the referred amount is capped at what the owner still holds whenever it
+/// is accrued.
+#[account]
+#[derive(InitSpace)]
+pub struct LpPosition {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+
+    /// Key rewarded for this LP's deposits (default = none)
+    pub referrer: Pubkey,
+
+    /// LP minted through referred deposits that still earns the referrer a share
+    pub referred_lp: u64,
+
+    /// Pool `fee_growth` at the last accrual, per token in pool token order
+    #[max_len(8)]
+    pub fee_growth_snapshot: Vec<u128>,
+
+    /// Scaled rewards the referrer has earned that haven't been taken out of an exit yet, per
+    /// token in pool token order
+    #[max_len(8)]
+    pub referrer_accrued: Vec<u64>,
+
+    /// Raw rewards taken out of the owner's exits for the referrer to claim, per token in pool
+    /// token order
+    #[max_len(8)]
+    pub referrer_claimable: Vec<u64>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl LpPosition {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn has_referrer(&self) -> bool {
+        self.referrer != Pubkey::default()
+    }
+
+    /// Credits the referrer `fee_share_bps` of the fees `referred_lp`, capped at the `held_lp`
+    /// the owner still has, earned since the last accrual
+    pub fn accrue(&mut self, fee_growth: &[u128], held_lp: u64, fee_share_bps: u16) -> Result<()> {
+        self.referred_lp = self.referred_lp.min(held_lp);
+        self.fee_growth_snapshot.resize(fee_growth.len(), 0);
+        self.referrer_accrued.resize(fee_growth.len(), 0);
+
+        for (i, &growth) in fee_growth.iter().enumerate() {
+            let earned = growth
+                .wrapping_sub(self.fee_growth_snapshot[i])
+                .saturating_mul(self.referred_lp as u128)
+                / SCALE;
+            let share = earned.saturating_mul(fee_share_bps as u128) / BPS_DENOMINATOR as u128;
+            self.referrer_accrued[i] = self.referrer_accrued[i]
+                .checked_add(u64::try_from(share).map_err(|_| MiniStabbleError::MathOverflow)?)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+            self.fee_growth_snapshot[i] = growth;
+        }
+
+        Ok(())
+    }
+
+    /// Accrues against the `held_lp` the owner has before an exit, then takes what the referrer
+    /// has accrued out of the exit's `raw_payouts` of `pool.tokens`, given as `(index, amount)`,
+    /// in whole raw units. What is taken moves to `referrer_claimable` and the pool's referral
+    /// rewards; sub-unit remainders stay accrued. Returns the raw amount taken from each payout.
+    pub fn settle_exit(
+        &mut self,
+        pool: &mut StablePool,
+        fee_share_bps: u16,
+        held_lp: u64,
+        raw_payouts: &[(usize, u64)],
+    ) -> Result<Vec<u64>> {
+        self.accrue(&pool.fee_growth, held_lp, fee_share_bps)?;
+        self.referrer_claimable
+            .resize(self.referrer_accrued.len(), 0);
+
+        raw_payouts
+            .iter()
+            .map(|&(index, raw_payout)| {
+                let token = &mut pool.tokens[index];
+                let Some(accrued) = self.referrer_accrued.get_mut(index) else {
+                    return Ok(0);
+                };
+                let taken = token.scale_amount_down(*accrued).min(raw_payout);
+                *accrued -= token.scale_amount_up(taken);
+                self.referrer_claimable[index] = self.referrer_claimable[index]
+                    .checked_add(taken)
+                    .ok_or(MiniStabbleError::MathOverflow)?;
+                token.referral_rewards = token
+                    .referral_rewards
+                    .checked_add(taken)
+                    .ok_or(MiniStabbleError::MathOverflow)?;
+
+                Ok(taken)
+            })
+            .collect()
+    }
+}
+
+/// Settles an exit for a referred LP's `lp_position`, taking nothing from the payouts of an
+/// exit made without one
+pub fn settle_referral(
+    pool: &mut StablePool,
+    lp_position: Option<&mut LpPosition>,
+    fee_share_bps: u16,
+    held_lp: u64,
+    raw_payouts: &[(usize, u64)],
+) -> Result<Vec<u64>> {
+    match lp_position {
+        Some(lp_position) => lp_position.settle_exit(pool, fee_share_bps, held_lp, raw_payouts),
+        None => Ok(vec![0; raw_payouts.len()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::pool::tests::{stable_pool, token};
+
+    #[test]
+    fn test_accrue_shares_growth_of_held_referred_lp() {
+        let mut position = LpPosition {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            referrer: Pubkey::new_unique(),
+            referred_lp: 1_000,
+            fee_growth_snapshot: vec![SCALE, 0],
+            referrer_accrued: vec![],
+            referrer_claimable: vec![],
+            bump: 255,
+        };
+
+        // 2 and 5 units per LP earned on 1_000 LP, 10% to the referrer
+        position
+            .accrue(&[3 * SCALE, 5 * SCALE], 1_000, 1_000)
+            .unwrap();
+        assert_eq!(position.referrer_accrued, vec![200, 500]);
+
+        // Only the 400 LP still held keeps earning, even once more is held again
+        position
+            .accrue(&[4 * SCALE, 5 * SCALE], 400, 1_000)
+            .unwrap();
+        position
+            .accrue(&[5 * SCALE, 5 * SCALE], 1_000, 1_000)
+            .unwrap();
+        assert_eq!(position.referred_lp, 400);
+        assert_eq!(position.referrer_accrued, vec![280, 500]);
+    }
+
+    #[test]
+    fn test_settle_exit_moves_accrued_rewards_out_of_the_payout() {
+        let mut pool = stable_pool(vec![token(1_000_000_000), token(1_000_000_000)]);
+        // Nothing new to accrue: 2_300 scaled (2.3 raw) is already owed in token 0
+        pool.fee_growth = vec![3 * SCALE, 0];
+        let mut position = LpPosition {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            referrer: Pubkey::new_unique(),
+            referred_lp: 1_000,
+            fee_growth_snapshot: vec![3 * SCALE, 0],
+            referrer_accrued: vec![2_300, 0],
+            referrer_claimable: vec![],
+            bump: 255,
+        };
+
+        let taken = position
+            .settle_exit(&mut pool, 100, 1_000, &[(0, 10), (1, 10)])
+            .unwrap();
+        // Whole raw units are taken; the sub-unit remainder stays accrued
+        assert_eq!(taken, vec![2, 0]);
+        assert_eq!(position.referrer_accrued, vec![300, 0]);
+        assert_eq!(position.referrer_claimable, vec![2, 0]);
+        assert_eq!(pool.tokens[0].referral_rewards, 2);
+
+        // Never more than the payout itself
+        position.referrer_accrued[0] = 50_000;
+        let taken = position
+            .settle_exit(&mut pool, 100, 1_000, &[(0, 10)])
+            .unwrap();
+        assert_eq!(taken, vec![10]);
+        assert_eq!(position.referrer_accrued, vec![40_000, 0]);
+        assert_eq!(pool.tokens[0].referral_rewards, 12);
+    }
+}
@@ -1,2 +1,81 @@
 pub mod pool;
-pub use pool::*;
\ No newline at end of file
+pub use pool::*;
+
+pub mod config;
+pub use config::*;
+
+pub mod withdraw_request;
+pub use withdraw_request::*;
+
+pub mod token_badge;
+pub use token_badge::*;
+
+pub mod rate_limit;
+pub use rate_limit::*;
+
+pub mod drawdown_guard;
+pub use drawdown_guard::*;
+
+pub mod fee_autopilot;
+pub use fee_autopilot::*;
+
+pub mod user_stats;
+pub use user_stats::*;
+
+pub mod intent_nonce;
+pub use intent_nonce::*;
+
+pub mod peg_keeper;
+pub use peg_keeper::*;
+
+pub mod fee_rebate;
+pub use fee_rebate::*;
+
+pub mod creator_lock;
+pub use creator_lock::*;
+
+pub mod pool_params;
+pub use pool_params::*;
+
+pub mod archived_pool;
+pub use archived_pool::*;
+
+pub mod proposal;
+pub use proposal::*;
+
+pub mod pool_fee_stats;
+pub use pool_fee_stats::*;
+
+pub mod trader_allowlist;
+pub use trader_allowlist::*;
+
+pub mod swap_checkpoint;
+pub use swap_checkpoint::*;
+
+pub mod virtual_price_checkpoint;
+pub use virtual_price_checkpoint::*;
+
+pub mod internal_balance;
+pub use internal_balance::*;
+
+pub mod lp_position;
+pub use lp_position::*;
+
+pub mod upgrade_guard;
+pub use upgrade_guard::*;
+
+pub mod exit_window;
+pub use exit_window::*;
+
+pub mod legacy_pool;
+pub use legacy_pool::*;
+
+#[cfg(feature = "serde")]
+pub mod snapshot;
+#[cfg(feature = "serde")]
+pub use snapshot::*;
+
+#[cfg(feature = "bytemuck")]
+pub mod views;
+#[cfg(feature = "bytemuck")]
+pub use views::*;
@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::BPS_DENOMINATOR, errors::MiniStabbleError, math::fixed::ONE_U64};
+
+/// Minimum time between two peg keeper actions, in seconds, so one update's effect on the price
+/// is visible (and arbitraged) before the next
+pub const PEG_KEEPER_ACTION_DELAY: i64 = 900;
+
+/// What the peg keeper should do about the current price of its stable
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PegAction {
+    /// Stable trades above peg: mint and deposit more of it
+    Provide,
+
+    /// Stable trades below peg: withdraw and burn what was provided earlier
+    Withdraw,
+
+    /// Price is within the threshold
+    None,
+}
+
+/// Curve-style peg keeper for a protocol-owned stable paired in a two-token stable pool, a PDA
+/// at `[PEG_KEEPER, pool]` that is also the stable's mint authority. It mints stable into the
+/// pool while the stable trades above peg and withdraws and burns it while below, never
+/// holding more than `debt_ceiling` of minted stable in the pool.
+#[account]
+#[derive(InitSpace)]
+pub struct PegKeeper {
+    pub pool: Pubkey,
+
+    /// Protocol stable the keeper mints and burns
+    pub stable_mint: Pubkey,
+
+    /// Raw amount of stable minted into the pool and not yet withdrawn and burned
+    pub debt: u64,
+
+    /// Upper bound on `debt`; lowering it below `debt` leaves the keeper withdraw-only
+    pub debt_ceiling: u64,
+
+    /// Price deviation from peg, in bps, past which the keeper acts
+    pub deviation_threshold_bps: u16,
+
+    /// Most raw stable a single action may mint or burn
+    pub max_amount_per_action: u64,
+
+    /// Unix timestamp of the last action
+    pub last_action_ts: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl PegKeeper {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Action for the stable trading at `price` (SCALE = 1e9) in units of the paired token
+    pub fn action(&self, price: u64) -> PegAction {
+        let deviation = ONE_U64 / BPS_DENOMINATOR * self.deviation_threshold_bps as u64;
+
+        if price > ONE_U64.saturating_add(deviation) {
+            PegAction::Provide
+        } else if price < ONE_U64.saturating_sub(deviation) && self.debt > 0 {
+            PegAction::Withdraw
+        } else {
+            PegAction::None
+        }
+    }
+
+    /// Most raw stable the next action may mint or burn
+    pub fn action_limit(&self, action: PegAction) -> u64 {
+        match action {
+            PegAction::Provide => self
+                .max_amount_per_action
+                .min(self.debt_ceiling.saturating_sub(self.debt)),
+            PegAction::Withdraw => self.max_amount_per_action.min(self.debt),
+            PegAction::None => 0,
+        }
+    }
+}
+
+pub fn validate_peg_keeper(deviation_threshold_bps: u16, max_amount_per_action: u64) -> Result<()> {
+    require!(
+        deviation_threshold_bps > 0
+            && deviation_threshold_bps as u64 <= BPS_DENOMINATOR
+            && max_amount_per_action > 0,
+        MiniStabbleError::InvalidPegKeeper
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keeper(debt: u64) -> PegKeeper {
+        PegKeeper {
+            pool: Pubkey::new_unique(),
+            stable_mint: Pubkey::new_unique(),
+            debt,
+            debt_ceiling: 1_000,
+            deviation_threshold_bps: 50,
+            max_amount_per_action: 300,
+            last_action_ts: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_action_follows_price_and_debt() {
+        assert_eq!(keeper(0).action(1_005_000_000), PegAction::None);
+        assert_eq!(keeper(0).action(1_005_000_001), PegAction::Provide);
+        assert_eq!(keeper(0).action(994_999_999), PegAction::None);
+        assert_eq!(keeper(1).action(994_999_999), PegAction::Withdraw);
+        assert_eq!(keeper(1).action(995_000_000), PegAction::None);
+    }
+
+    #[test]
+    fn test_action_limit_respects_ceiling_and_debt() {
+        assert_eq!(keeper(0).action_limit(PegAction::Provide), 300);
+        assert_eq!(keeper(900).action_limit(PegAction::Provide), 100);
+        assert_eq!(keeper(1_200).action_limit(PegAction::Provide), 0);
+        assert_eq!(keeper(200).action_limit(PegAction::Withdraw), 200);
+        assert_eq!(keeper(900).action_limit(PegAction::Withdraw), 300);
+        assert_eq!(keeper(900).action_limit(PegAction::None), 0);
+    }
+}
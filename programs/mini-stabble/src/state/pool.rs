@@ -1,4 +1,79 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
+
+use super::{rebate_epoch, DrawdownGuard, ExitWindow, FeeRebate, PoolParams, SwapRateLimit};
+use crate::{
+    constants::BPS_DENOMINATOR,
+    errors::MiniStabbleError,
+    math::{
+        fixed::{ONE_U64, SCALE},
+        stable::{self, calc_current_amp},
+        swap_context::SwapContext,
+        weighted,
+    },
+};
+
+/// Checks that neither token of a swap has swaps disabled
+pub fn check_swaps_enabled(tokens: &[PoolToken], indices: [usize; 2]) -> Result<()> {
+    require!(
+        indices.iter().all(|&index| !tokens[index].swaps_disabled),
+        MiniStabbleError::TokenSwapsDisabled
+    );
+
+    Ok(())
+}
+
+/// Indices of `mint_a` and `mint_b` among `mints`, found in a single pass.
+///
+/// A linear scan on purpose: pools hold at most 8 tokens, so at most 8 key comparisons, which
+/// costs less than keeping a mint-to-index map or a sorted token layout in the account.
+pub fn find_token_pair<'a>(
+    mints: impl IntoIterator<Item = &'a Pubkey>,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+) -> Option<[usize; 2]> {
+    let (mut index_a, mut index_b) = (None, None);
+    for (index, mint) in mints.into_iter().enumerate() {
+        if mint == mint_a {
+            index_a = Some(index);
+        } else if mint == mint_b {
+            index_b = Some(index);
+        }
+        if index_a.is_some() && index_b.is_some() {
+            break;
+        }
+    }
+
+    Some([index_a?, index_b?])
+}
+
+/// Applies `update` to the tokens of the weighted or stable pool stored in `pool_info`, for
+/// handlers that take either kind of pool as an unchecked account
+pub fn update_pool_tokens<T>(
+    pool_info: &AccountInfo,
+    update: impl FnOnce(&mut [PoolToken]) -> Result<T>,
+) -> Result<T> {
+    require_keys_eq!(
+        *pool_info.owner,
+        crate::ID,
+        MiniStabbleError::InvalidAccount
+    );
+    let mut data = pool_info.try_borrow_mut_data()?;
+    let discriminator = data.get(..8).ok_or(MiniStabbleError::InvalidAccount)?;
+
+    if discriminator == WeightedPool::DISCRIMINATOR {
+        let mut pool = Box::new(WeightedPool::try_deserialize(&mut &data[..])?);
+        let result = update(&mut pool.tokens)?;
+        pool.try_serialize(&mut &mut data[..])?;
+        Ok(result)
+    } else if discriminator == StablePool::DISCRIMINATOR {
+        let mut pool = Box::new(StablePool::try_deserialize(&mut &data[..])?);
+        let result = update(&mut pool.tokens)?;
+        pool.try_serialize(&mut &mut data[..])?;
+        Ok(result)
+    } else {
+        err!(MiniStabbleError::InvalidAccount)
+    }
+}
 
 /// Struct representing a single token in the pool
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
@@ -20,6 +95,24 @@ pub struct PoolToken {
 
     /// The weight of the token within the pool (for weighted pools)
     pub weight: u64,
+
+    /// Blocks swaps into and out of this token, e.g. while its bridge or issuer is compromised;
+    /// deposits and withdrawals stay open
+    pub swaps_disabled: bool,
+
+    /// Scaled remainders swap payouts rounded off to whole raw units, held in the vault but
+    /// outside `balance` until `sweep_rounding_dust` hands them to the pool's `DustDestination`
+    pub rounding_dust: u64,
+
+    /// Raw amount users keep in the vault as internal balance, held outside `balance`
+    pub internal_balances: u64,
+
+    /// Raw amount queued withdrawals have yet to claim from the vault, held outside `balance`
+    pub queued_withdrawals: u64,
+
+    /// Raw referral rewards taken out of referred LPs' exits that referrers have yet to claim,
+    /// held outside `balance`
+    pub referral_rewards: u64,
 }
 
 impl PoolToken {
@@ -30,12 +123,79 @@ impl PoolToken {
     pub fn scale_amount_down(&self, scaled_amount: u64) -> u64 {
         scaled_amount.checked_div(self.scaling_factor).unwrap()
     }
+
+    /// Takes `scaled_amount` out of `balance` for a payout of `raw_amount`, its value rounded
+    /// down to whole raw units, booking what the rounding kept back as rounding dust
+    pub fn debit_payout(&mut self, scaled_amount: u64, raw_amount: u64) -> Result<()> {
+        let dust = scaled_amount
+            .checked_sub(self.scale_amount_up(raw_amount))
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        self.balance = self
+            .balance
+            .checked_sub(scaled_amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        self.rounding_dust = self
+            .rounding_dust
+            .checked_add(dust)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Scaled amount of the vault that doesn't back the pool: rounding dust, internal balances,
+    /// queued withdrawals and unclaimed referral rewards
+    pub fn held_outside_balance(&self) -> Option<u64> {
+        self.internal_balances
+            .checked_add(self.queued_withdrawals)?
+            .checked_add(self.referral_rewards)?
+            .checked_mul(self.scaling_factor)?
+            .checked_add(self.rounding_dust)
+    }
+
+    /// Switches to `scaling_factor`, converting the cached balance and rounding dust so they keep
+    /// representing the same raw amounts. Returns `None` if either doesn't fit in a u64.
+    pub fn rescale(&mut self, decimals: u8, scaling_factor: u64) -> Option<()> {
+        let rescale = |amount: u64| -> Option<u64> {
+            (amount as u128)
+                .checked_mul(scaling_factor as u128)?
+                .checked_div(self.scaling_factor as u128)?
+                .try_into()
+                .ok()
+        };
+
+        self.balance = rescale(self.balance)?;
+        self.rounding_dust = rescale(self.rounding_dust)?;
+        self.decimals = decimals;
+        self.scaling_factor = scaling_factor;
+
+        Some(())
+    }
+}
+
+/// `feature_flags` bit: anyone may pause the pool while the program runs a deploy the
+/// `UpgradeGuard` hasn't acknowledged
+pub const FEATURE_PAUSE_ON_UNACKNOWLEDGED_UPGRADE: u64 = 1 << 0;
+
+/// Whether `flag` is set in a pool's `feature_flags`
+fn has_feature(feature_flags: u64, flag: u64) -> bool {
+    feature_flags & flag == flag
 }
 
+/// `feature_flags` with `flag` set or cleared
+fn with_feature(feature_flags: u64, flag: u64, enabled: bool) -> u64 {
+    if enabled {
+        feature_flags | flag
+    } else {
+        feature_flags & !flag
+    }
+}
+
+/// Pools created by the original program are in the layout of `LegacyWeightedPool`; they have
+/// to be rewritten by `migrate_pool_params` before any other instruction can load them.
 #[account]
 #[derive(InitSpace)]
 pub struct WeightedPool {
-    /// PDA that signs for token transfers
+    /// `[AUTHORITY, pool]` PDA that owns the pool's vaults and LP mint and signs for them
     pub authority: Pubkey,
 
     /// LP token mint for this pool
@@ -50,12 +210,36 @@ pub struct WeightedPool {
     /// Swap fee (e.g., 3_000_000 = 0.3% with SCALE = 1e9)
     pub swap_fee: u64,
 
+    /// Largest spot price move a single swap may cause, in bps (0 = unlimited)
+    pub max_spot_price_move_per_swap_bps: u16,
+
+    /// Pauses the pool instead of executing a swap that drops the invariant per LP token
+    pub drawdown_guard: DrawdownGuard,
+
+    /// Only traders on the pool's allowlist may swap, for pools of restricted assets
+    pub trader_allowlist: bool,
+
+    /// `weight_in / weight_out` of every ordered token pair, row-major by the token in
+    /// (SCALE = 1e9), computed once with the rounding swaps need
+    #[max_len(64)]
+    pub swap_exponents: Vec<u64>,
+
     /// Token metadata
     #[max_len(8)]
     pub tokens: Vec<PoolToken>,
 
     /// PDA bump seed
     pub bump: u8,
+
+    /// Opt-in behaviors, one bit each; every bit clear is the default behavior
+    pub feature_flags: u64,
+
+    /// Instant exits in the current withdraw queue window
+    pub exit_window: ExitWindow,
+
+    /// Bump of `authority` when it's the pool's own `[AUTHORITY, pool]` PDA; 0 while the pool is
+    /// still owned by the global authority, until `migrate_pool_authority` moves it
+    pub authority_bump: u8,
 }
 
 impl WeightedPool {
@@ -63,6 +247,11 @@ impl WeightedPool {
         self.tokens.iter().position(|t| t.mint == *mint)
     }
 
+    /// Indices of two distinct mints, for handlers that touch a pair of tokens
+    pub fn get_token_pair_indices(&self, mint_a: &Pubkey, mint_b: &Pubkey) -> Option<[usize; 2]> {
+        find_token_pair(self.tokens.iter().map(|t| &t.mint), mint_a, mint_b)
+    }
+
     pub fn get_balances(&self) -> Vec<u64> {
         self.tokens.iter().map(|t| t.balance).collect()
     }
@@ -71,9 +260,156 @@ impl WeightedPool {
         self.tokens.iter().map(|t| t.weight).collect()
     }
 
+    /// Balances, weights, cached exponent and fee for swapping `tokens[index_in]` into
+    /// `tokens[index_out]`
+    pub fn swap_context(&self, index_in: usize, index_out: usize) -> Result<SwapContext> {
+        Ok(SwapContext::weighted(
+            self.get_balances(),
+            self.get_weights(),
+            self.swap_exponent(index_in, index_out),
+            self.swap_fee,
+            index_in,
+            index_out,
+        )?)
+    }
+
+    /// Cached `weight_in / weight_out` of a swap from `tokens[index_in]` into
+    /// `tokens[index_out]`; `None` if it isn't cached, in which case swaps compute it
+    pub fn swap_exponent(&self, index_in: usize, index_out: usize) -> Option<u64> {
+        self.swap_exponents
+            .get(index_in * self.tokens.len() + index_out)
+            .copied()
+    }
+
+    /// Price of `tokens[index_in]` in units of `tokens[index_out]` (SCALE = 1e9)
+    pub fn spot_price(&self, index_in: usize, index_out: usize) -> Result<u128> {
+        let token_in = &self.tokens[index_in];
+        let token_out = &self.tokens[index_out];
+
+        Ok(weighted::calc_spot_price(
+            token_in.balance.into(),
+            token_in.weight.into(),
+            token_out.balance.into(),
+            token_out.weight.into(),
+        )?)
+    }
+
+    /// Checks that moving `tokens[index_in]` and `tokens[index_out]` to the given balances keeps
+    /// their spot price within `max_spot_price_move_per_swap_bps` of the current one
+    pub fn check_spot_price_move(
+        &self,
+        index_in: usize,
+        index_out: usize,
+        balance_in_after: u64,
+        balance_out_after: u64,
+    ) -> Result<()> {
+        if self.max_spot_price_move_per_swap_bps == 0 {
+            return Ok(());
+        }
+
+        let price_before = self.spot_price(index_in, index_out)?;
+        let price_after = weighted::calc_spot_price(
+            balance_in_after.into(),
+            self.tokens[index_in].weight.into(),
+            balance_out_after.into(),
+            self.tokens[index_out].weight.into(),
+        )?;
+
+        let move_bps = price_before
+            .abs_diff(price_after)
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .and_then(|v| v.checked_div(price_before))
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        require!(
+            move_bps <= self.max_spot_price_move_per_swap_bps as u128,
+            MiniStabbleError::PriceMoveTooLarge
+        );
+
+        Ok(())
+    }
+
+    /// Invariant per LP token (SCALE = 1e9); `None` while the pool has no LP outstanding
+    pub fn virtual_price(&self, lp_supply: u64) -> Option<u128> {
+        self.virtual_price_at(&self.get_balances(), lp_supply)
+    }
+
+    /// `virtual_price` with the pool holding `balances` instead of its current balances
+    fn virtual_price_at(&self, balances: &[u64], lp_supply: u64) -> Option<u128> {
+        if lp_supply == 0 {
+            return None;
+        }
+
+        let balances: Vec<u128> = balances.iter().map(|&b| b.into()).collect();
+        let weights: Vec<u128> = self.tokens.iter().map(|t| t.weight.into()).collect();
+        let invariant = weighted::calc_invariant(&balances, &weights).ok()?;
+
+        invariant.checked_mul(SCALE)?.checked_div(lp_supply.into())
+    }
+
+    /// Feeds the invariant per LP token of a swap leaving the pool at `balances_after` to the
+    /// drawdown guard. Returns that value if it trips the guard.
+    pub fn trips_drawdown_guard(&mut self, balances_after: &[u64], lp_supply: u64) -> Option<u128> {
+        if !self.drawdown_guard.is_enabled() {
+            return None;
+        }
+
+        let virtual_price = self.virtual_price_at(balances_after, lp_supply)?;
+        self.drawdown_guard
+            .record(virtual_price)
+            .then_some(virtual_price)
+    }
+
+    /// Records an instant exit burning `lp_amount` out of `lp_supply` at `slot`. Returns whether
+    /// it must go through the withdraw queue `pool_params` configures instead, counting every
+    /// instant exit in the window.
+    pub fn record_instant_exit(
+        &mut self,
+        pool_params: &PoolParams,
+        lp_amount: u64,
+        lp_supply: u64,
+        slot: u64,
+    ) -> bool {
+        self.exit_window.record(
+            lp_amount,
+            lp_supply,
+            pool_params.withdraw_queue_threshold_bps,
+            pool_params.withdraw_queue_duration,
+            slot,
+        )
+    }
+
+    pub fn has_feature(&self, flag: u64) -> bool {
+        has_feature(self.feature_flags, flag)
+    }
+
+    pub fn set_feature(&mut self, flag: u64, enabled: bool) {
+        self.feature_flags = with_feature(self.feature_flags, flag, enabled);
+    }
+
     pub const LEN: usize = 8 + Self::INIT_SPACE;
 }
 
+/// How stable swaps obtain the invariant D, whose Newton iteration dominates a swap's compute
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub enum InvariantCachePolicy {
+    /// Recompute D from the balances on every swap
+    #[default]
+    Strict,
+
+    /// Reuse the cached D for up to `max_age_slots` after it was computed. Swap fees grow the
+    /// true D, so a stale one quotes slightly in the trader's favour and fees earned within the
+    /// window can leak back out; the leak is bounded by the window.
+    Fast { max_age_slots: u64 },
+}
+
+/// Share of a token's balance a single stable swap may take out by default, in bps; draining a
+/// side to near zero leaves the invariant math unstable for every swap after it
+pub const DEFAULT_MAX_OUT_RATIO_BPS: u16 = 9_000;
+
+/// Pools created by the original program are in the layout of `LegacyStablePool`; they have to
+/// be rewritten by `migrate_pool_params` before any other instruction can load them.
 #[account]
 #[derive(InitSpace)]
 pub struct StablePool {
@@ -83,7 +419,7 @@ pub struct StablePool {
     pub invariant: u64,
     pub swap_fee: u64,
 
-    /// Current amplification factor
+    /// Amplification factor at the start of the current ramp
     pub amp: u64,
 
     /// Target amp (for ramping)
@@ -95,9 +431,46 @@ pub struct StablePool {
     /// Ramp end timestamp  
     pub amp_end_ts: i64,
 
+    /// Per-window cap on the net amount of each token swaps can take out
+    pub swap_rate_limit: SwapRateLimit,
+
+    /// Cumulative swap fees per LP unit, per token in pool token order (scaled amount × SCALE /
+    /// LP). Wraps on overflow; an LP's earnings are the growth between two snapshots times its
+    /// LP balance.
+    #[max_len(8)]
+    pub fee_growth: Vec<u128>,
+
+    /// Whether swaps may reuse `invariant` instead of recomputing it
+    pub invariant_cache_policy: InvariantCachePolicy,
+
+    /// Slot at which `invariant` was last computed from the balances
+    pub invariant_last_updated_slot: u64,
+
+    /// Pauses the pool instead of executing a swap that drops the invariant per LP token
+    pub drawdown_guard: DrawdownGuard,
+
+    /// Swap fees set aside for traders' epoch rebates
+    pub rebate: FeeRebate,
+
+    /// Only traders on the pool's allowlist may swap, for pools of restricted assets
+    pub trader_allowlist: bool,
+
+    /// Largest share of `tokens[i].balance` one swap may pay out, in bps (0 = no cap)
+    pub max_out_ratio_bps: u16,
+
     #[max_len(8)]
     pub tokens: Vec<PoolToken>,
     pub bump: u8,
+
+    /// Opt-in behaviors, one bit each; every bit clear is the default behavior
+    pub feature_flags: u64,
+
+    /// Instant exits in the current withdraw queue window
+    pub exit_window: ExitWindow,
+
+    /// Bump of `authority` when it's the pool's own `[AUTHORITY, pool]` PDA; 0 while the pool is
+    /// still owned by the global authority, until `migrate_pool_authority` moves it
+    pub authority_bump: u8,
 }
 
 impl StablePool {
@@ -105,13 +478,385 @@ impl StablePool {
         self.tokens.iter().position(|t| t.mint == *mint)
     }
 
+    /// Indices of two distinct mints, for handlers that touch a pair of tokens
+    pub fn get_token_pair_indices(&self, mint_a: &Pubkey, mint_b: &Pubkey) -> Option<[usize; 2]> {
+        find_token_pair(self.tokens.iter().map(|t| &t.mint), mint_a, mint_b)
+    }
+
     pub fn get_balances(&self) -> Vec<u64> {
         self.tokens.iter().map(|t| t.balance).collect()
     }
 
-    pub fn get_current_amp(&self) -> u64 {
-        self.amp
+    /// Amp at `now`, following any scheduled ramp
+    pub fn get_current_amp(&self, now: i64) -> Option<u64> {
+        calc_current_amp(
+            self.amp,
+            self.amp_target,
+            self.amp_start_ts,
+            self.amp_end_ts,
+            now,
+        )
+    }
+
+    /// Balances, amp, invariant (per `swap_invariant`) and fee for swapping `tokens[index_in]`
+    /// into `tokens[index_out]` at `now`/`slot`
+    pub fn swap_context(
+        &mut self,
+        index_in: usize,
+        index_out: usize,
+        now: i64,
+        slot: u64,
+    ) -> Result<SwapContext> {
+        let amp = self
+            .get_current_amp(now)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        let invariant = self
+            .swap_invariant(amp, now, slot)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        Ok(SwapContext::stable(
+            self.get_balances(),
+            amp,
+            Some(invariant),
+            self.swap_fee,
+            index_in,
+            index_out,
+        )?)
+    }
+
+    /// Price of `tokens[index_in]` in units of `tokens[index_out]` (SCALE = 1e9), quoted by
+    /// swapping one whole scaled unit at the current amp
+    pub fn spot_price(&self, index_in: usize, index_out: usize, now: i64) -> Option<u128> {
+        let amp = self.get_current_amp(now)?;
+        stable::calc_spot_price(amp, &self.get_balances(), index_in, index_out, ONE_U64)
+            .map(u128::from)
+    }
+
+    /// Invariant per LP token at the current amp (SCALE = 1e9); `None` while the pool has no LP
+    /// outstanding
+    pub fn virtual_price(&self, lp_supply: u64, now: i64) -> Option<u128> {
+        self.virtual_price_at(&self.get_balances(), lp_supply, now)
+    }
+
+    /// `virtual_price` with the pool holding `balances` instead of its current balances
+    fn virtual_price_at(&self, balances: &[u64], lp_supply: u64, now: i64) -> Option<u128> {
+        if lp_supply == 0 {
+            return None;
+        }
+
+        let amp = self.get_current_amp(now)?;
+        let invariant = stable::calc_invariant(amp, balances)?;
+
+        (invariant as u128)
+            .checked_mul(SCALE)?
+            .checked_div(lp_supply.into())
+    }
+
+    /// Feeds the invariant per LP token of a swap leaving the pool at `balances_after` to the
+    /// drawdown guard. Returns that value if it trips the guard.
+    pub fn trips_drawdown_guard(
+        &mut self,
+        balances_after: &[u64],
+        lp_supply: u64,
+        now: i64,
+    ) -> Option<u128> {
+        if !self.drawdown_guard.is_enabled() {
+            return None;
+        }
+
+        let virtual_price = self.virtual_price_at(balances_after, lp_supply, now)?;
+        self.drawdown_guard
+            .record(virtual_price)
+            .then_some(virtual_price)
+    }
+
+    /// Records an instant exit burning `lp_amount` out of `lp_supply` at `slot`. Returns whether
+    /// it must go through the withdraw queue `pool_params` configures instead, counting every
+    /// instant exit in the window.
+    pub fn record_instant_exit(
+        &mut self,
+        pool_params: &PoolParams,
+        lp_amount: u64,
+        lp_supply: u64,
+        slot: u64,
+    ) -> bool {
+        self.exit_window.record(
+            lp_amount,
+            lp_supply,
+            pool_params.withdraw_queue_threshold_bps,
+            pool_params.withdraw_queue_duration,
+            slot,
+        )
+    }
+
+    /// Checks that paying out `scaled_amount_out` of `tokens[index_out]` stays within
+    /// `max_out_ratio_bps` of its balance
+    pub fn check_max_out_ratio(&self, index_out: usize, scaled_amount_out: u64) -> Result<()> {
+        if self.max_out_ratio_bps == 0 {
+            return Ok(());
+        }
+
+        require!(
+            (scaled_amount_out as u128) * (BPS_DENOMINATOR as u128)
+                <= (self.tokens[index_out].balance as u128) * (self.max_out_ratio_bps as u128),
+            MiniStabbleError::MaxOutRatioExceeded
+        );
+
+        Ok(())
+    }
+
+    /// Adds a swap fee of `scaled_fee` in `tokens[index]` to that token's fee growth
+    pub fn accrue_swap_fee(&mut self, index: usize, scaled_fee: u64, lp_supply: u64) {
+        if lp_supply == 0 {
+            return;
+        }
+        self.fee_growth.resize(self.tokens.len(), 0);
+
+        let growth = scaled_fee as u128 * SCALE / lp_supply as u128;
+        self.fee_growth[index] = self.fee_growth[index].wrapping_add(growth);
+    }
+
+    /// Invariant a swap at `now`/`slot` should quote against. Under `Fast` the cached value is
+    /// reused while it is younger than `max_age_slots` and no amp ramp is running; otherwise it
+    /// is recomputed from the balances and, outside a ramp, cached for later swaps.
+    pub fn swap_invariant(&mut self, amp: u64, now: i64, slot: u64) -> Option<u64> {
+        let ramping = now < self.amp_end_ts;
+        if let InvariantCachePolicy::Fast { max_age_slots } = self.invariant_cache_policy {
+            if !ramping
+                && self.invariant > 0
+                && slot
+                    <= self
+                        .invariant_last_updated_slot
+                        .saturating_add(max_age_slots)
+            {
+                return Some(self.invariant);
+            }
+        }
+
+        let invariant = stable::calc_invariant(amp, &self.get_balances())?;
+        if !ramping {
+            self.invariant = invariant;
+            self.invariant_last_updated_slot = slot;
+        }
+        Some(invariant)
+    }
+
+    /// Drops the cached invariant after anything other than a swap changes the balances or amp
+    pub fn invalidate_invariant(&mut self) {
+        self.invariant = 0;
+    }
+
+    /// Writes `tokens[index]`'s cached balance down to what a vault holding `vault_amount` (raw)
+    /// backs once unclaimed rebates and everything else held outside the balance are set aside,
+    /// e.g. after a negative rebase. The loss is spread over every LP token in proportion, since
+    /// the LP supply doesn't change, while internal balances and queued withdrawals stay whole.
+    /// Returns the scaled loss, 0 when the vault covers the cached balance.
+    pub fn reconcile_balance(&mut self, index: usize, vault_amount: u64) -> Result<u64> {
+        let reserved = self.rebate.reserved(index);
+        let token = &mut self.tokens[index];
+        let held = token
+            .held_outside_balance()
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        let available = vault_amount
+            .checked_mul(token.scaling_factor)
+            .ok_or(MiniStabbleError::MathOverflow)?
+            .saturating_sub(reserved)
+            .saturating_sub(held);
+        let loss = token.balance.saturating_sub(available);
+        if loss == 0 {
+            return Ok(0);
+        }
+
+        token.balance = available;
+        self.invalidate_invariant();
+        // The loss isn't a swap draining the pool; measure later drawdowns from here
+        self.drawdown_guard.reset();
+
+        Ok(loss)
+    }
+
+    /// Moves rebate accrual to the epoch containing `now`, handing rebates nobody can claim
+    /// anymore back to the LPs
+    pub fn roll_rebate_epoch(&mut self, now: i64) -> Result<()> {
+        let expired = self.rebate.roll(rebate_epoch(now), self.tokens.len());
+        if expired.iter().all(|&amount| amount == 0) {
+            return Ok(());
+        }
+
+        for (token, amount) in self.tokens.iter_mut().zip(expired) {
+            token.balance = token
+                .balance
+                .checked_add(amount)
+                .ok_or(MiniStabbleError::MathOverflow)?;
+        }
+        self.invalidate_invariant();
+
+        Ok(())
+    }
+
+    pub fn has_feature(&self, flag: u64) -> bool {
+        has_feature(self.feature_flags, flag)
+    }
+
+    pub fn set_feature(&mut self, flag: u64, enabled: bool) {
+        self.feature_flags = with_feature(self.feature_flags, flag, enabled);
     }
 
     pub const LEN: usize = 8 + Self::INIT_SPACE;
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::state::hold_internal_balance;
+
+    pub(crate) fn token(balance: u64) -> PoolToken {
+        PoolToken {
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            decimals: 6,
+            scaling_factor: 1_000,
+            balance,
+            weight: 0,
+            swaps_disabled: false,
+            rounding_dust: 0,
+            internal_balances: 0,
+            queued_withdrawals: 0,
+            referral_rewards: 0,
+        }
+    }
+
+    pub(crate) fn stable_pool(tokens: Vec<PoolToken>) -> StablePool {
+        StablePool {
+            authority: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            is_active: true,
+            invariant: 0,
+            swap_fee: 1_000_000,
+            amp: 100_000,
+            amp_target: 100_000,
+            amp_start_ts: 0,
+            amp_end_ts: 0,
+            swap_rate_limit: Default::default(),
+            fee_growth: vec![0; tokens.len()],
+            invariant_cache_policy: Default::default(),
+            invariant_last_updated_slot: 0,
+            drawdown_guard: Default::default(),
+            rebate: Default::default(),
+            trader_allowlist: false,
+            max_out_ratio_bps: 0,
+            tokens,
+            bump: 255,
+            feature_flags: 0,
+            exit_window: Default::default(),
+            authority_bump: 254,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_leaves_tokens_held_outside_the_balance_whole() {
+        let mut pool = stable_pool(vec![token(1_000_000_000), token(1_000_000_000)]);
+        let held = &mut pool.tokens[0];
+        held.internal_balances = 200_000;
+        held.queued_withdrawals = 100_000;
+        held.rounding_dust = 1_000;
+
+        // The vault covers everything: nothing to write down
+        assert_eq!(pool.reconcile_balance(0, 1_300_001).unwrap(), 0);
+        assert_eq!(pool.tokens[0].balance, 1_000_000_000);
+
+        // A 5% negative rebase is taken by the LPs alone
+        let vault_amount = 1_300_001 * 95 / 100;
+        let loss = pool.reconcile_balance(0, vault_amount).unwrap();
+        let held = 300_000 * 1_000 + 1_000;
+        assert_eq!(pool.tokens[0].balance, vault_amount * 1_000 - held);
+        assert_eq!(loss, 1_000_000_000 - pool.tokens[0].balance);
+        assert_eq!(pool.tokens[0].internal_balances, 200_000);
+        assert_eq!(pool.tokens[0].queued_withdrawals, 100_000);
+        assert_eq!(pool.tokens[1].balance, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_update_pool_tokens_rewrites_the_account() {
+        let pool = stable_pool(vec![token(1_000_000_000), token(2_000_000_000)]);
+        let mint = pool.tokens[1].mint;
+        let mut data = Vec::new();
+        pool.try_serialize(&mut data).unwrap();
+        data.resize(StablePool::LEN, 0);
+
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let owner = crate::ID;
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        update_pool_tokens(&info, |tokens| hold_internal_balance(tokens, &mint, 500)).unwrap();
+        assert!(update_pool_tokens(&info, |tokens| hold_internal_balance(
+            tokens,
+            &Pubkey::new_unique(),
+            500
+        ))
+        .is_err());
+        drop(info);
+
+        let updated = StablePool::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(updated.tokens[1].internal_balances, 500);
+        assert_eq!(updated.tokens[1].balance, 2_000_000_000);
+        assert_eq!(updated.tokens[0].internal_balances, 0);
+    }
+
+    #[test]
+    fn test_update_pool_tokens_rejects_other_accounts() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![0; StablePool::LEN];
+        let owner = crate::ID;
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(update_pool_tokens(&info, |_| Ok(())).is_err());
+
+        let pool = stable_pool(vec![token(1), token(1)]);
+        let mut data = Vec::new();
+        pool.try_serialize(&mut data).unwrap();
+        let mut lamports = 0;
+        let not_owner = Pubkey::new_unique();
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &not_owner,
+            false,
+            0,
+        );
+        assert!(update_pool_tokens(&info, |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn test_feature_flags_toggle_independently() {
+        let (fee_on_input, hooks) = (1 << 0, 1 << 2);
+        let mut flags = with_feature(0, fee_on_input, true);
+        flags = with_feature(flags, hooks, true);
+        assert!(has_feature(flags, fee_on_input) && has_feature(flags, hooks));
+
+        flags = with_feature(flags, fee_on_input, false);
+        assert!(!has_feature(flags, fee_on_input));
+        assert!(has_feature(flags, hooks));
+    }
+}
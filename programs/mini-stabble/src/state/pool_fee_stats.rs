@@ -0,0 +1,223 @@
+use anchor_lang::prelude::*;
+
+use super::SECONDS_PER_DAY;
+use crate::{constants::BPS_DENOMINATOR, math::fixed::SCALE};
+
+pub const SECONDS_PER_YEAR: i64 = 365 * SECONDS_PER_DAY;
+
+/// Swaps a pool executed within one slot. A sandwich shows up as a wide price excursion that
+/// comes back before the slot ends, with the VWAP far from where the price closed.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct SlotTradeStats {
+    pub slot: u64,
+    pub trade_count: u32,
+
+    /// Raw amounts of `tokens[0]` and `tokens[1]` traded in the slot, in either direction
+    pub volume_0: u128,
+    pub volume_1: u128,
+
+    /// Spot price of `tokens[0]` in `tokens[1]` (SCALE = 1e9) before the slot's first swap, and
+    /// the lowest and highest it reached after any swap of the slot
+    pub open_price: u128,
+    pub low_price: u128,
+    pub high_price: u128,
+}
+
+impl SlotTradeStats {
+    /// Volume-weighted average price of `tokens[0]` in `tokens[1]` (SCALE = 1e9), 0 without
+    /// trades
+    pub fn vwap(&self) -> u128 {
+        self.volume_1
+            .saturating_mul(SCALE)
+            .checked_div(self.volume_0)
+            .unwrap_or(0)
+    }
+
+    /// Furthest the spot price moved away from `open_price` during the slot, in bps
+    pub fn max_move_bps(&self) -> u64 {
+        let max_move = self
+            .high_price
+            .saturating_sub(self.open_price)
+            .max(self.open_price.saturating_sub(self.low_price));
+
+        u64::try_from(max_move.saturating_mul(BPS_DENOMINATOR as u128) / self.open_price.max(1))
+            .unwrap_or(u64::MAX)
+    }
+}
+
+/// Swap fee earnings of a weighted pool's LPs, a PDA at `[POOL_FEE_STATS, pool]`. Fees stay in
+/// the pool and grow its invariant per LP token, so each collection compares that value with the
+/// one stored at the previous collection. Front-ends read the APR and lifetime earnings here
+/// instead of replaying swap history.
+#[account]
+#[derive(InitSpace)]
+pub struct PoolFeeStats {
+    pub pool: Pubkey,
+
+    /// Unix timestamp of the first collection
+    pub inception_ts: i64,
+
+    /// Invariant per LP token at the first collection (SCALE = 1e9)
+    pub inception_invariant_per_lp: u128,
+
+    /// Unix timestamp of the last collection
+    pub last_collection_ts: i64,
+
+    /// Invariant per LP token at the last collection (SCALE = 1e9)
+    pub last_invariant_per_lp: u128,
+
+    /// Annualized fee yield between the last two collections (SCALE = 1e9, 0.05e9 = 5%)
+    pub fee_apr: u64,
+
+    /// Growth of an LP token's value from fees since inception (SCALE = 1e9)
+    pub earned_since_inception: u64,
+
+    /// Swaps of the latest slot that traded, and of the one before it
+    pub current_slot_trades: SlotTradeStats,
+    pub previous_slot_trades: SlotTradeStats,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl PoolFeeStats {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Records the pool's current `invariant_per_lp` at `now`, updating the APR over the time
+    /// since the last collection. The first call only sets the inception point.
+    pub fn collect(&mut self, invariant_per_lp: u128, now: i64) {
+        if self.inception_invariant_per_lp == 0 {
+            self.inception_ts = now;
+            self.inception_invariant_per_lp = invariant_per_lp;
+            self.last_collection_ts = now;
+            self.last_invariant_per_lp = invariant_per_lp;
+            return;
+        }
+
+        let elapsed = now.saturating_sub(self.last_collection_ts);
+        if elapsed <= 0 {
+            return;
+        }
+
+        let growth = growth_since(self.last_invariant_per_lp, invariant_per_lp);
+        self.fee_apr =
+            u64::try_from(growth.saturating_mul(SECONDS_PER_YEAR as u128) / elapsed as u128)
+                .unwrap_or(u64::MAX);
+        self.earned_since_inception = u64::try_from(growth_since(
+            self.inception_invariant_per_lp,
+            invariant_per_lp,
+        ))
+        .unwrap_or(u64::MAX);
+        self.last_collection_ts = now;
+        self.last_invariant_per_lp = invariant_per_lp;
+    }
+
+    /// Adds a swap of `amount_0` of `tokens[0]` against `amount_1` of `tokens[1]` (raw) to the
+    /// stats of `slot`, which moved the spot price from `price_before` to `price_after`
+    pub fn record_trade(
+        &mut self,
+        slot: u64,
+        amount_0: u64,
+        amount_1: u64,
+        price_before: u128,
+        price_after: u128,
+    ) {
+        if self.current_slot_trades.slot != slot || self.current_slot_trades.trade_count == 0 {
+            if self.current_slot_trades.trade_count > 0 {
+                self.previous_slot_trades = self.current_slot_trades;
+            }
+            self.current_slot_trades = SlotTradeStats {
+                slot,
+                open_price: price_before,
+                low_price: price_before,
+                high_price: price_before,
+                ..Default::default()
+            };
+        }
+
+        let trades = &mut self.current_slot_trades;
+        trades.trade_count = trades.trade_count.saturating_add(1);
+        trades.volume_0 = trades.volume_0.saturating_add(amount_0 as u128);
+        trades.volume_1 = trades.volume_1.saturating_add(amount_1 as u128);
+        trades.low_price = trades.low_price.min(price_after);
+        trades.high_price = trades.high_price.max(price_after);
+    }
+}
+
+/// Relative growth from `before` to `after` (SCALE = 1e9), 0 if it shrank
+fn growth_since(before: u128, after: u128) -> u128 {
+    after
+        .saturating_sub(before)
+        .saturating_mul(SCALE)
+        .checked_div(before)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_annualizes_growth() {
+        let mut stats = PoolFeeStats {
+            pool: Pubkey::new_unique(),
+            inception_ts: 0,
+            inception_invariant_per_lp: 0,
+            last_collection_ts: 0,
+            last_invariant_per_lp: 0,
+            fee_apr: 0,
+            earned_since_inception: 0,
+            current_slot_trades: SlotTradeStats::default(),
+            previous_slot_trades: SlotTradeStats::default(),
+            bump: 0,
+        };
+        stats.collect(SCALE, 1_000);
+        assert_eq!(stats.inception_ts, 1_000);
+        assert_eq!(stats.fee_apr, 0);
+
+        // 1% over a tenth of a year
+        stats.collect(SCALE * 101 / 100, 1_000 + SECONDS_PER_YEAR / 10);
+        assert_eq!(stats.fee_apr, 100_000_000);
+        assert_eq!(stats.earned_since_inception, 10_000_000);
+
+        // Same timestamp is ignored, a lower value counts as no yield
+        stats.collect(SCALE * 2, 1_000 + SECONDS_PER_YEAR / 10);
+        assert_eq!(stats.last_invariant_per_lp, SCALE * 101 / 100);
+        stats.collect(SCALE, 1_000 + SECONDS_PER_YEAR / 5);
+        assert_eq!(stats.fee_apr, 0);
+        assert_eq!(stats.earned_since_inception, 0);
+    }
+
+    #[test]
+    fn test_record_trade_tracks_slot_excursion() {
+        let mut stats = PoolFeeStats {
+            pool: Pubkey::new_unique(),
+            inception_ts: 0,
+            inception_invariant_per_lp: 0,
+            last_collection_ts: 0,
+            last_invariant_per_lp: 0,
+            fee_apr: 0,
+            earned_since_inception: 0,
+            current_slot_trades: SlotTradeStats::default(),
+            previous_slot_trades: SlotTradeStats::default(),
+            bump: 0,
+        };
+
+        // Front-run, victim and back-run in one slot: the price spikes 5% and comes back
+        stats.record_trade(10, 100, 100, SCALE, SCALE * 103 / 100);
+        stats.record_trade(10, 100, 104, SCALE * 103 / 100, SCALE * 105 / 100);
+        stats.record_trade(10, 200, 206, SCALE * 105 / 100, SCALE);
+        let trades = stats.current_slot_trades;
+        assert_eq!(trades.trade_count, 3);
+        assert_eq!(trades.vwap(), SCALE * 410 / 400);
+        assert_eq!(trades.max_move_bps(), 500);
+
+        // A later slot starts fresh and keeps the finished one
+        stats.record_trade(12, 50, 49, SCALE, SCALE * 99 / 100);
+        assert_eq!(stats.previous_slot_trades, trades);
+        assert_eq!(stats.current_slot_trades.trade_count, 1);
+        assert_eq!(stats.current_slot_trades.max_move_bps(), 100);
+    }
+}
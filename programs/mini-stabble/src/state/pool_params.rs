@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use super::{CreatorLock, FeeAutopilot};
+use crate::errors::MiniStabbleError;
+
+/// Most crank keepers a pool can register
+pub const MAX_CRANK_KEEPERS: usize = 4;
+
+/// Who receives the pool's rounding dust when it's swept
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub enum DustDestination {
+    /// Added back to the pool's balances
+    #[default]
+    Lps,
+
+    /// Paid out in whole raw units to the treasury
+    Protocol,
+}
+
+/// Rarely-changing configuration of a weighted or stable pool, a PDA at `[POOL_PARAMS, pool]`.
+/// Kept out of the pool account so swaps don't deserialize and rewrite it; only the
+/// instructions that need a setting take this account.
+#[account]
+#[derive(InitSpace)]
+pub struct PoolParams {
+    pub pool: Pubkey,
+
+    /// Only key allowed to make the first deposit, so nobody can front-run the creator's
+    /// funding at a different ratio (default = anyone, for migrated pools)
+    pub initial_depositor: Pubkey,
+
+    /// Volatility-driven swap fee for 80/20 governance pools (weighted only, disabled by default)
+    pub fee_autopilot: FeeAutopilot,
+
+    /// Founder's initial LP held in escrow for a minimum duration
+    pub creator_lock: CreatorLock,
+
+    /// Number of proposals created for the pool, and the id of the next one
+    pub proposal_count: u64,
+
+    /// Key managing the pool's trader allowlist (default = none)
+    pub compliance_authority: Pubkey,
+
+    /// Keys allowed to run the pool's maintenance cranks more often than anyone else
+    #[max_len(MAX_CRANK_KEEPERS)]
+    pub crank_keepers: Vec<Pubkey>,
+
+    /// Who the rounding dust swaps leave in the vaults goes to (default = LPs)
+    pub dust_destination: DustDestination,
+
+    /// Share of referred LPs' fee earnings paid to their referrers, in bps (stable only)
+    pub referral_fee_share_bps: u16,
+
+    /// Exits above this share of LP supply (bps) must go through the withdraw queue (0 = disabled)
+    pub withdraw_queue_threshold_bps: u16,
+
+    /// Number of slots over which a queued withdraw unlocks
+    pub withdraw_queue_duration: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl PoolParams {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Whether `signer` is present and a registered crank keeper
+    pub fn is_crank_keeper(&self, signer: Option<&Pubkey>) -> bool {
+        signer.is_some_and(|signer| self.crank_keepers.contains(signer))
+    }
+
+    pub fn check_first_depositor(&self, depositor: &Pubkey) -> Result<()> {
+        require!(
+            self.initial_depositor == Pubkey::default() || self.initial_depositor == *depositor,
+            MiniStabbleError::InitialDepositorOnly
+        );
+
+        Ok(())
+    }
+}
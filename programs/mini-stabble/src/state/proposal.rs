@@ -0,0 +1,201 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+
+/// How long after creation LP can be escrowed in a proposal, in seconds. Only LP escrowed by
+/// then carries a vote.
+pub const PROPOSAL_ESCROW_PERIOD: i64 = 86_400;
+
+/// How long escrowed LP can vote once escrowing closed, in seconds
+pub const PROPOSAL_VOTING_PERIOD: i64 = 3 * 86_400;
+
+/// Wait between the end of the vote and execution, so LPs who disagree can exit first
+pub const PROPOSAL_EXECUTION_DELAY: i64 = 2 * 86_400;
+
+/// How long an approved proposal stays executable once its delay passed, in seconds
+pub const PROPOSAL_EXECUTION_WINDOW: i64 = 3 * 86_400;
+
+/// Share of the LP supply at voting start that has to vote for a result to count, in bps
+pub const PROPOSAL_QUORUM_BPS: u64 = 2_000;
+
+/// Parameter change a proposal applies when executed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ProposedChange {
+    /// New swap fee (SCALE = 1e9)
+    SwapFee { swap_fee: u64 },
+
+    /// Amp ramp to `target_amp` over `ramp_duration` seconds from execution (stable pools only)
+    Amp { target_amp: u64, ramp_duration: i64 },
+}
+
+/// Parameter change anyone can propose for a pool, a PDA at `[PROPOSAL, pool, id]`. LP holders
+/// escrow LP in the proposal before voting starts and vote with it during the voting period, so
+/// each LP token counts once and LP bought after the snapshot can't swing the vote. It passes
+/// when votes reach `PROPOSAL_QUORUM_BPS` of the LP supply at voting start and most are in
+/// favour, and can be executed once `PROPOSAL_EXECUTION_DELAY` has passed after the vote.
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+
+    /// Index of the proposal among the pool's proposals
+    pub id: u64,
+
+    pub proposer: Pubkey,
+    pub change: ProposedChange,
+
+    /// LP supply when voting started, taken by the first vote cast (0 until then)
+    pub lp_supply_snapshot: u64,
+
+    /// Escrowed LP voted in favour
+    pub votes: u64,
+
+    /// Escrowed LP voted against
+    pub votes_against: u64,
+
+    /// Unix timestamp at which escrowing closes and voting opens
+    pub voting_starts_ts: i64,
+
+    /// Unix timestamp at which voting closes
+    pub voting_ends_ts: i64,
+
+    pub executed: bool,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Whether the votes cast reach the quorum and most are in favour
+    pub fn is_approved(&self) -> bool {
+        let cast = self.votes as u128 + self.votes_against as u128;
+        self.lp_supply_snapshot > 0
+            && cast * BPS_DENOMINATOR as u128
+                >= self.lp_supply_snapshot as u128 * PROPOSAL_QUORUM_BPS as u128
+            && self.votes > self.votes_against
+    }
+
+    /// Whether LP can still be escrowed to vote
+    pub fn is_escrow_open(&self, now: i64) -> bool {
+        !self.executed && now < self.voting_starts_ts
+    }
+
+    pub fn is_voting(&self, now: i64) -> bool {
+        !self.executed && self.voting_starts_ts <= now && now < self.voting_ends_ts
+    }
+
+    /// Whether the vote isn't over yet, keeping escrowed LP locked
+    pub fn is_open(&self, now: i64) -> bool {
+        !self.executed && now < self.voting_ends_ts
+    }
+
+    /// Earliest time the proposal can be executed
+    pub fn executable_ts(&self) -> i64 {
+        self.voting_ends_ts.saturating_add(PROPOSAL_EXECUTION_DELAY)
+    }
+
+    /// Whether an approved proposal can be executed at `now`
+    pub fn is_executable(&self, now: i64) -> bool {
+        let executable_ts = self.executable_ts();
+        !self.executed
+            && executable_ts <= now
+            && now < executable_ts.saturating_add(PROPOSAL_EXECUTION_WINDOW)
+    }
+}
+
+/// LP a voter escrowed in a proposal, a PDA at `[VOTE_RECORD, proposal, voter]`
+#[account]
+#[derive(InitSpace)]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub lp_amount: u64,
+
+    /// Whether the escrowed LP has voted
+    pub voted: bool,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STARTS: i64 = 100;
+    const ENDS: i64 = STARTS + PROPOSAL_VOTING_PERIOD;
+
+    fn proposal(votes: u64, votes_against: u64) -> Proposal {
+        Proposal {
+            pool: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            id: 0,
+            proposer: Pubkey::new_unique(),
+            change: ProposedChange::SwapFee {
+                swap_fee: 1_000_000,
+            },
+            lp_supply_snapshot: 1_000,
+            votes,
+            votes_against,
+            voting_starts_ts: STARTS,
+            voting_ends_ts: ENDS,
+            executed: false,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_approval_needs_quorum_and_a_majority_of_votes() {
+        // Under the 20% quorum, however one-sided
+        assert!(!proposal(0, 0).is_approved());
+        assert!(!proposal(199, 0).is_approved());
+        assert!(proposal(200, 0).is_approved());
+
+        // Against votes count towards the quorum but have to be outnumbered
+        assert!(!proposal(100, 100).is_approved());
+        assert!(proposal(101, 100).is_approved());
+        assert!(!proposal(300, 700).is_approved());
+
+        // No vote cast yet, no snapshot
+        let mut proposal = proposal(200, 0);
+        proposal.lp_supply_snapshot = 0;
+        assert!(!proposal.is_approved());
+    }
+
+    #[test]
+    fn test_phases_follow_each_other() {
+        let mut proposal = proposal(0, 0);
+        assert!(proposal.is_escrow_open(STARTS - 1));
+        assert!(!proposal.is_voting(STARTS - 1));
+
+        assert!(!proposal.is_escrow_open(STARTS));
+        assert!(proposal.is_voting(STARTS) && proposal.is_voting(ENDS - 1));
+        assert!(proposal.is_open(ENDS - 1));
+        assert!(!proposal.is_voting(ENDS) && !proposal.is_open(ENDS));
+
+        proposal.executed = true;
+        assert!(!proposal.is_escrow_open(0) && !proposal.is_open(0));
+    }
+
+    #[test]
+    fn test_execution_waits_for_the_delay_then_expires() {
+        let mut proposal = proposal(600, 0);
+        let executable_ts = ENDS + PROPOSAL_EXECUTION_DELAY;
+        assert_eq!(proposal.executable_ts(), executable_ts);
+
+        assert!(!proposal.is_executable(ENDS));
+        assert!(!proposal.is_executable(executable_ts - 1));
+        assert!(proposal.is_executable(executable_ts));
+        assert!(!proposal.is_executable(executable_ts + PROPOSAL_EXECUTION_WINDOW));
+
+        proposal.executed = true;
+        assert!(!proposal.is_executable(executable_ts));
+    }
+}
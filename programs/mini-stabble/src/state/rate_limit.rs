@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::BPS_DENOMINATOR, errors::MiniStabbleError};
+
+/// Balance of a single token when the current window opened, and how much swaps have taken
+/// out of it since (scaled units, negative when swaps added more than they removed)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
+pub struct TokenFlow {
+    pub start_balance: u64,
+    pub net_outflow: i128,
+}
+
+/// Caps the net amount of each token swaps can take out of a pool per window of slots, bounding
+/// how much toxic flow (e.g. during a depeg or ahead of a stale oracle) one burst can extract
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
+pub struct SwapRateLimit {
+    /// Window length in slots (0 = disabled)
+    pub window_slots: u64,
+
+    /// Max net outflow of each token per window, in bps of its balance when the window opened
+    pub max_outflow_bps: u16,
+
+    /// Slot the current window opened at
+    pub window_start_slot: u64,
+
+    /// Per-token flows in the current window, in pool token order
+    #[max_len(8)]
+    pub flows: Vec<TokenFlow>,
+}
+
+impl SwapRateLimit {
+    pub fn is_enabled(&self) -> bool {
+        self.window_slots > 0
+    }
+
+    /// Records a swap of `amount_in` of `tokens[index_in]` for `amount_out` of
+    /// `tokens[index_out]` at `slot`, given the pool `balances` before the swap. Opens a new
+    /// window first if the current one has elapsed, and fails with `RateLimitExceeded` if the
+    /// swap would push the net outflow of the out token over the limit.
+    pub fn record_swap(
+        &mut self,
+        slot: u64,
+        balances: &[u64],
+        index_in: usize,
+        amount_in: u64,
+        index_out: usize,
+        amount_out: u64,
+    ) -> Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        if self.flows.len() != balances.len()
+            || slot >= self.window_start_slot.saturating_add(self.window_slots)
+        {
+            self.window_start_slot = slot;
+            self.flows = balances
+                .iter()
+                .map(|&start_balance| TokenFlow {
+                    start_balance,
+                    net_outflow: 0,
+                })
+                .collect();
+        }
+
+        let flow_out = &self.flows[index_out];
+        let net_outflow = flow_out.net_outflow + amount_out as i128;
+        let max_outflow =
+            flow_out.start_balance as i128 * self.max_outflow_bps as i128 / BPS_DENOMINATOR as i128;
+        require!(
+            net_outflow <= max_outflow,
+            MiniStabbleError::RateLimitExceeded
+        );
+
+        self.flows[index_out].net_outflow = net_outflow;
+        self.flows[index_in].net_outflow -= amount_in as i128;
+
+        Ok(())
+    }
+}
+
+/// A window of 0 disables the limit, otherwise the limit must be a non-zero share of the balance
+pub fn validate_swap_rate_limit(window_slots: u64, max_outflow_bps: u16) -> Result<()> {
+    require!(
+        window_slots == 0 || (max_outflow_bps > 0 && max_outflow_bps as u64 <= BPS_DENOMINATOR),
+        MiniStabbleError::InvalidRateLimitConfig
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BALANCES: [u64; 2] = [1_000_000, 1_000_000];
+
+    fn limit() -> SwapRateLimit {
+        SwapRateLimit {
+            window_slots: 10,
+            max_outflow_bps: 1_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_disabled_limit_allows_anything() {
+        let mut limit = SwapRateLimit::default();
+        assert!(limit.record_swap(0, &BALANCES, 0, 0, 1, 1_000_000).is_ok());
+        assert!(limit.flows.is_empty());
+    }
+
+    #[test]
+    fn test_outflow_capped_per_window() {
+        let mut limit = limit();
+        assert!(limit
+            .record_swap(5, &BALANCES, 0, 60_000, 1, 60_000)
+            .is_ok());
+        assert!(limit
+            .record_swap(6, &[1_060_000, 940_000], 0, 50_000, 1, 50_000)
+            .is_err());
+
+        // Flow back in frees up room within the same window
+        assert!(limit
+            .record_swap(7, &[1_060_000, 940_000], 1, 30_000, 0, 30_000)
+            .is_ok());
+        assert!(limit
+            .record_swap(8, &[1_030_000, 970_000], 0, 70_000, 1, 70_000)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_window_resets_after_elapsing() {
+        let mut limit = limit();
+        assert!(limit
+            .record_swap(5, &BALANCES, 0, 100_000, 1, 100_000)
+            .is_ok());
+        assert!(limit
+            .record_swap(14, &[1_100_000, 900_000], 0, 1, 1, 1)
+            .is_err());
+
+        assert!(limit
+            .record_swap(15, &[1_100_000, 900_000], 0, 90_000, 1, 90_000)
+            .is_ok());
+        assert_eq!(limit.window_start_slot, 15);
+        assert_eq!(limit.flows[1].start_balance, 900_000);
+    }
+}
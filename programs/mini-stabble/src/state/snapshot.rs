@@ -0,0 +1,339 @@
+//! JSON-friendly views of pool accounts for indexers and the SDK, built from the on-chain types
+//! so both sides share one representation. Pubkeys are base58 and amounts are decimal strings,
+//! since u64/u128 values don't survive a round-trip through JavaScript numbers.
+
+use anchor_lang::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{PoolToken, StablePool, WeightedPool};
+
+/// (De)serializes any `Display + FromStr` value as a string
+mod display_from_str {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Same as `display_from_str`, with `None` as `null`
+mod option_display_from_str {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| value.parse().map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolTokenSnapshot {
+    #[serde(with = "display_from_str")]
+    pub mint: Pubkey,
+    #[serde(with = "display_from_str")]
+    pub token_account: Pubkey,
+    pub decimals: u8,
+    #[serde(with = "display_from_str")]
+    pub scaling_factor: u64,
+
+    /// Balance in scaled (1e9) units, as stored on-chain
+    #[serde(with = "display_from_str")]
+    pub balance: u64,
+
+    /// Balance in the mint's own units
+    #[serde(with = "display_from_str")]
+    pub raw_balance: u64,
+
+    #[serde(with = "display_from_str")]
+    pub weight: u64,
+
+    pub swaps_disabled: bool,
+
+    /// Scaled payout remainders not yet swept
+    #[serde(with = "display_from_str")]
+    pub rounding_dust: u64,
+
+    /// Raw amount held in the vault for internal balances
+    #[serde(with = "display_from_str")]
+    pub internal_balances: u64,
+
+    /// Raw amount held in the vault for queued withdrawals
+    #[serde(with = "display_from_str")]
+    pub queued_withdrawals: u64,
+
+    /// Raw referral rewards held in the vault for referrers to claim
+    #[serde(with = "display_from_str")]
+    pub referral_rewards: u64,
+}
+
+/// Price of `tokens[index_in]` in units of `tokens[index_out]` (SCALE = 1e9)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpotPriceSnapshot {
+    pub index_in: u8,
+    pub index_out: u8,
+    #[serde(with = "display_from_str")]
+    pub price: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightedPoolSnapshot {
+    #[serde(with = "display_from_str")]
+    pub address: Pubkey,
+    #[serde(with = "display_from_str")]
+    pub authority: Pubkey,
+    #[serde(with = "display_from_str")]
+    pub lp_mint: Pubkey,
+    #[serde(with = "display_from_str")]
+    pub lp_supply: u64,
+    pub is_active: bool,
+    #[serde(with = "display_from_str")]
+    pub swap_fee: u64,
+    pub max_spot_price_move_per_swap_bps: u16,
+    pub tokens: Vec<PoolTokenSnapshot>,
+    pub spot_prices: Vec<SpotPriceSnapshot>,
+    #[serde(with = "option_display_from_str")]
+    pub virtual_price: Option<u128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StablePoolSnapshot {
+    #[serde(with = "display_from_str")]
+    pub address: Pubkey,
+    #[serde(with = "display_from_str")]
+    pub authority: Pubkey,
+    #[serde(with = "display_from_str")]
+    pub lp_mint: Pubkey,
+    #[serde(with = "display_from_str")]
+    pub lp_supply: u64,
+    pub is_active: bool,
+    #[serde(with = "display_from_str")]
+    pub swap_fee: u64,
+
+    /// Amp at the snapshot time, following any ramp (AMP_PRECISION = 1e3)
+    #[serde(with = "display_from_str")]
+    pub current_amp: u64,
+    #[serde(with = "display_from_str")]
+    pub amp_target: u64,
+    pub amp_start_ts: i64,
+    pub amp_end_ts: i64,
+
+    /// Swap rate limit window in slots (0 = disabled)
+    #[serde(with = "display_from_str")]
+    pub swap_rate_limit_window_slots: u64,
+    pub swap_rate_limit_max_outflow_bps: u16,
+    pub tokens: Vec<PoolTokenSnapshot>,
+    pub spot_prices: Vec<SpotPriceSnapshot>,
+    #[serde(with = "option_display_from_str")]
+    pub virtual_price: Option<u128>,
+}
+
+impl From<&PoolToken> for PoolTokenSnapshot {
+    fn from(token: &PoolToken) -> Self {
+        Self {
+            mint: token.mint,
+            token_account: token.token_account,
+            decimals: token.decimals,
+            scaling_factor: token.scaling_factor,
+            balance: token.balance,
+            raw_balance: token.scale_amount_down(token.balance),
+            weight: token.weight,
+            swaps_disabled: token.swaps_disabled,
+            rounding_dust: token.rounding_dust,
+            internal_balances: token.internal_balances,
+            queued_withdrawals: token.queued_withdrawals,
+            referral_rewards: token.referral_rewards,
+        }
+    }
+}
+
+/// Every ordered pair of token indices whose price `price_of` can quote
+fn spot_prices(
+    token_count: usize,
+    price_of: impl Fn(usize, usize) -> Option<u128>,
+) -> Vec<SpotPriceSnapshot> {
+    (0..token_count)
+        .flat_map(|index_in| (0..token_count).map(move |index_out| (index_in, index_out)))
+        .filter(|(index_in, index_out)| index_in != index_out)
+        .filter_map(|(index_in, index_out)| {
+            Some(SpotPriceSnapshot {
+                index_in: index_in as u8,
+                index_out: index_out as u8,
+                price: price_of(index_in, index_out)?,
+            })
+        })
+        .collect()
+}
+
+impl WeightedPool {
+    /// Snapshot of the pool at `address`, with derived prices for the given LP supply
+    pub fn snapshot(&self, address: Pubkey, lp_supply: u64) -> WeightedPoolSnapshot {
+        WeightedPoolSnapshot {
+            address,
+            authority: self.authority,
+            lp_mint: self.lp_mint,
+            lp_supply,
+            is_active: self.is_active,
+            swap_fee: self.swap_fee,
+            max_spot_price_move_per_swap_bps: self.max_spot_price_move_per_swap_bps,
+            tokens: self.tokens.iter().map(PoolTokenSnapshot::from).collect(),
+            spot_prices: spot_prices(self.tokens.len(), |index_in, index_out| {
+                self.spot_price(index_in, index_out).ok()
+            }),
+            virtual_price: self.virtual_price(lp_supply),
+        }
+    }
+}
+
+impl StablePool {
+    /// Snapshot of the pool at `address` as of `now`, with derived prices for the given LP supply
+    pub fn snapshot(&self, address: Pubkey, lp_supply: u64, now: i64) -> StablePoolSnapshot {
+        StablePoolSnapshot {
+            address,
+            authority: self.authority,
+            lp_mint: self.lp_mint,
+            lp_supply,
+            is_active: self.is_active,
+            swap_fee: self.swap_fee,
+            current_amp: self.get_current_amp(now).unwrap_or(self.amp_target),
+            amp_target: self.amp_target,
+            amp_start_ts: self.amp_start_ts,
+            amp_end_ts: self.amp_end_ts,
+            swap_rate_limit_window_slots: self.swap_rate_limit.window_slots,
+            swap_rate_limit_max_outflow_bps: self.swap_rate_limit.max_outflow_bps,
+            tokens: self.tokens.iter().map(PoolTokenSnapshot::from).collect(),
+            spot_prices: spot_prices(self.tokens.len(), |index_in, index_out| {
+                self.spot_price(index_in, index_out, now)
+            }),
+            virtual_price: self.virtual_price(lp_supply, now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(weight: u64) -> PoolToken {
+        PoolToken {
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            decimals: 6,
+            scaling_factor: 1_000,
+            balance: 1_000_000_000_000,
+            weight,
+            swaps_disabled: false,
+            rounding_dust: 0,
+            internal_balances: 0,
+            queued_withdrawals: 0,
+            referral_rewards: 0,
+        }
+    }
+
+    #[test]
+    fn test_weighted_pool_snapshot_round_trip() {
+        let pool = WeightedPool {
+            authority: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            is_active: true,
+            invariant: 0,
+            swap_fee: 3_000_000,
+            max_spot_price_move_per_swap_bps: 0,
+            drawdown_guard: Default::default(),
+            trader_allowlist: false,
+            swap_exponents: Vec::new(),
+            tokens: vec![token(500_000_000), token(500_000_000)],
+            bump: 255,
+            feature_flags: 0,
+            exit_window: Default::default(),
+            authority_bump: 0,
+        };
+
+        let snapshot = pool.snapshot(Pubkey::new_unique(), 1_000_000_000_000);
+        assert_eq!(snapshot.tokens[0].raw_balance, 1_000_000_000);
+        assert_eq!(snapshot.spot_prices.len(), 2);
+        assert!(snapshot.virtual_price.is_some());
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["lpMint"], pool.lp_mint.to_string());
+        assert_eq!(json["tokens"][0]["balance"], "1000000000000");
+
+        let decoded: WeightedPoolSnapshot = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, snapshot);
+    }
+
+    #[test]
+    fn test_empty_pool_has_no_virtual_price() {
+        let pool = StablePool {
+            authority: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            is_active: true,
+            invariant: 0,
+            swap_fee: 1_000_000,
+            amp: 100_000,
+            amp_target: 100_000,
+            amp_start_ts: 0,
+            amp_end_ts: 0,
+            swap_rate_limit: Default::default(),
+            fee_growth: vec![0, 0],
+            invariant_cache_policy: Default::default(),
+            invariant_last_updated_slot: 0,
+            drawdown_guard: Default::default(),
+            rebate: Default::default(),
+            trader_allowlist: false,
+            max_out_ratio_bps: 0,
+            tokens: vec![token(0), token(0)],
+            bump: 255,
+            feature_flags: 0,
+            exit_window: Default::default(),
+            authority_bump: 0,
+        };
+
+        let snapshot = pool.snapshot(Pubkey::new_unique(), 0, 0);
+        assert_eq!(snapshot.virtual_price, None);
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert!(json["virtualPrice"].is_null());
+        assert_eq!(
+            serde_json::from_value::<StablePoolSnapshot>(json).unwrap(),
+            snapshot
+        );
+    }
+}
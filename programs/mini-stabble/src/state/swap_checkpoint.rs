@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+/// Swap activity of one pool aggregated over a window of slots, a PDA at
+/// `[SWAP_CHECKPOINT, pool]`. Swaps that pass it add to the open window instead of emitting a
+/// `SwapEvent` each, and the first swap after the window closes emits it as one
+/// `SwapCheckpointEvent`, so busy pools stay observable without a log line per trade.
+/// Amounts are raw, per pool token.
+#[account]
+#[derive(InitSpace)]
+pub struct SwapCheckpoint {
+    pub pool: Pubkey,
+
+    /// Length of a window, in slots
+    pub interval_slots: u64,
+
+    /// Slot the open window started at
+    pub window_start_slot: u64,
+
+    pub swap_count: u64,
+
+    /// Amounts sent in by traders
+    #[max_len(8)]
+    pub volume_in: Vec<u128>,
+
+    /// Amounts sent out of the vaults
+    #[max_len(8)]
+    pub volume_out: Vec<u128>,
+
+    /// Swap fees charged, in the token paid out
+    #[max_len(8)]
+    pub fees: Vec<u128>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SwapCheckpoint {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Whether the open window holds swaps and has run its course by `slot`
+    pub fn is_due(&self, slot: u64) -> bool {
+        self.swap_count > 0 && slot >= self.window_start_slot.saturating_add(self.interval_slots)
+    }
+
+    /// Opens an empty window at `slot`
+    pub fn reset(&mut self, slot: u64, token_count: usize) {
+        self.window_start_slot = slot;
+        self.swap_count = 0;
+        self.volume_in = vec![0; token_count];
+        self.volume_out = vec![0; token_count];
+        self.fees = vec![0; token_count];
+    }
+
+    /// Adds one swap to the open window
+    pub fn record(
+        &mut self,
+        index_in: usize,
+        amount_in: u64,
+        index_out: usize,
+        amount_out: u64,
+        fee: u64,
+    ) {
+        self.swap_count = self.swap_count.saturating_add(1);
+        self.volume_in[index_in] = self.volume_in[index_in].saturating_add(amount_in.into());
+        self.volume_out[index_out] = self.volume_out[index_out].saturating_add(amount_out.into());
+        self.fees[index_out] = self.fees[index_out].saturating_add(fee.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_aggregates_until_due() {
+        let mut checkpoint = SwapCheckpoint {
+            pool: Pubkey::new_unique(),
+            interval_slots: 100,
+            window_start_slot: 0,
+            swap_count: 0,
+            volume_in: Vec::new(),
+            volume_out: Vec::new(),
+            fees: Vec::new(),
+            bump: 255,
+        };
+        checkpoint.reset(1_000, 2);
+        assert!(!checkpoint.is_due(2_000));
+
+        checkpoint.record(0, 500, 1, 480, 3);
+        checkpoint.record(1, 200, 0, 190, 1);
+        checkpoint.record(0, 100, 1, 95, 1);
+        assert!(!checkpoint.is_due(1_099));
+        assert!(checkpoint.is_due(1_100));
+
+        assert_eq!(checkpoint.swap_count, 3);
+        assert_eq!(checkpoint.volume_in, vec![600, 200]);
+        assert_eq!(checkpoint.volume_out, vec![190, 575]);
+        assert_eq!(checkpoint.fees, vec![1, 4]);
+
+        checkpoint.reset(1_100, 2);
+        assert_eq!(checkpoint.swap_count, 0);
+        assert_eq!(checkpoint.fees, vec![0, 0]);
+    }
+}
@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::spl_token,
+    token_2022::spl_token_2022::{
+        extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+        state::Mint as Token2022Mint,
+    },
+    token_interface::Mint,
+};
+
+use crate::errors::MiniStabbleError;
+
+/// Admin-issued permission to create pools for a Token-2022 mint whose extensions are
+/// otherwise considered too risky, a PDA at `[TOKEN_BADGE, mint]`
+#[account]
+#[derive(InitSpace)]
+pub struct TokenBadge {
+    /// Mint this badge was issued for
+    pub mint: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl TokenBadge {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// Checks that `mint` can back a pool vault.
+///
+/// Classic SPL mints are always accepted. Token-2022 mints with only metadata-style extensions
+/// are fine, extensions that let a third party move, freeze, tax or reprice vault funds need a
+/// badge, and non-transferable mints are rejected outright.
+pub fn validate_pool_mint(mint: &InterfaceAccount<Mint>, has_badge: bool) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    let data = mint_info.try_borrow_data()?;
+
+    check_pool_mint(mint_info.owner, &data, has_badge)
+}
+
+/// `validate_pool_mint` on the raw mint account
+fn check_pool_mint(owner: &Pubkey, data: &[u8], has_badge: bool) -> Result<()> {
+    if *owner == spl_token::ID {
+        return Ok(());
+    }
+
+    let state = StateWithExtensions::<Token2022Mint>::unpack(data)?;
+    let mut requires_badge = state.base.freeze_authority.is_some();
+
+    for extension in state.get_extension_types()? {
+        match extension {
+            ExtensionType::MetadataPointer
+            | ExtensionType::TokenMetadata
+            | ExtensionType::GroupPointer
+            | ExtensionType::TokenGroup
+            | ExtensionType::GroupMemberPointer
+            | ExtensionType::TokenGroupMember
+            | ExtensionType::InterestBearingConfig => {}
+            ExtensionType::NonTransferable => {
+                return err!(MiniStabbleError::UnsupportedMint);
+            }
+            _ => requires_badge = true,
+        }
+    }
+
+    require!(
+        !requires_badge || has_badge,
+        MiniStabbleError::TokenBadgeRequired
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token_2022::{
+        self,
+        spl_token_2022::extension::{
+            non_transferable::NonTransferable, transfer_fee::TransferFeeConfig,
+            BaseStateWithExtensionsMut, StateWithExtensionsMut,
+        },
+    };
+
+    use super::*;
+
+    /// Data of an initialized Token-2022 mint with `extensions`
+    fn token_2022_mint(extensions: &[ExtensionType], freeze_authority: bool) -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Token2022Mint>(extensions).unwrap();
+        let mut data = vec![0; len];
+        let mut state =
+            StateWithExtensionsMut::<Token2022Mint>::unpack_uninitialized(&mut data).unwrap();
+        for extension in extensions {
+            match extension {
+                ExtensionType::TransferFeeConfig => {
+                    state.init_extension::<TransferFeeConfig>(true).unwrap();
+                }
+                ExtensionType::NonTransferable => {
+                    state.init_extension::<NonTransferable>(true).unwrap();
+                }
+                _ => unreachable!(),
+            }
+        }
+        state.base.decimals = 6;
+        state.base.is_initialized = true;
+        state.base.freeze_authority = if freeze_authority {
+            Some(Pubkey::new_unique()).into()
+        } else {
+            None.into()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    fn validate(owner: Pubkey, mut data: Vec<u8>, has_badge: bool) -> Result<()> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1_000_000_000;
+        let info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let mint = InterfaceAccount::<Mint>::try_from(&info)?;
+        validate_pool_mint(&mint, has_badge)
+    }
+
+    #[test]
+    fn test_classic_mint_is_accepted() {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+
+        assert!(validate(spl_token::ID, data, false).is_ok());
+    }
+
+    #[test]
+    fn test_token_2022_mints_follow_the_badge_rules() {
+        let unsupported: Result<()> = err!(MiniStabbleError::UnsupportedMint);
+        let badge_required: Result<()> = err!(MiniStabbleError::TokenBadgeRequired);
+
+        // A plain Token-2022 mint needs no badge, one with a transfer fee does
+        let plain = token_2022_mint(&[], false);
+        assert!(validate(token_2022::ID, plain, false).is_ok());
+        let taxed = token_2022_mint(&[ExtensionType::TransferFeeConfig], false);
+        assert!(validate(token_2022::ID, taxed.clone(), true).is_ok());
+        assert_eq!(validate(token_2022::ID, taxed, false), badge_required);
+        let frozen = token_2022_mint(&[], true);
+        assert_eq!(validate(token_2022::ID, frozen, false), badge_required);
+        let soulbound = token_2022_mint(&[ExtensionType::NonTransferable], false);
+        assert_eq!(validate(token_2022::ID, soulbound, true), unsupported);
+    }
+}
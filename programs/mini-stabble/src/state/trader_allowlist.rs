@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::MiniStabbleError;
+
+/// A trader cleared to swap in a permissioned pool, a PDA at `[TRADER_ALLOWLIST, pool, trader]`.
+/// Created and closed by the pool's compliance authority; independent of who may provide
+/// liquidity.
+#[account]
+#[derive(InitSpace)]
+pub struct TraderAllowlistEntry {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl TraderAllowlistEntry {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// Fails unless the pool is open to everyone (`!trader_allowlist`) or the trader passed their
+/// allowlist entry
+pub fn check_trader_allowed(
+    trader_allowlist: bool,
+    entry: Option<&TraderAllowlistEntry>,
+) -> Result<()> {
+    require!(
+        !trader_allowlist || entry.is_some(),
+        MiniStabbleError::TraderNotAllowlisted
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_trader_allowed() {
+        let entry = TraderAllowlistEntry {
+            pool: Pubkey::new_unique(),
+            trader: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert!(check_trader_allowed(false, None).is_ok());
+        assert!(check_trader_allowed(true, Some(&entry)).is_ok());
+        assert!(check_trader_allowed(true, None).is_err());
+    }
+}
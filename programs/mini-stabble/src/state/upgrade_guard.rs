@@ -0,0 +1,187 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::MiniStabbleError;
+
+/// Most keys an upgrade guard's signer set can hold
+pub const MAX_UPGRADE_SIGNERS: usize = 8;
+
+/// Upgrade transparency for LPs, a singleton PDA at `[UPGRADE_GUARD]`. `threshold` of its
+/// `signers` together commit to the hash of the next program binary and can reveal it as
+/// deployed only once `timelock` has passed. Until then, and after any deploy nobody committed
+/// to, the program data's deploy slot runs ahead of `acknowledged_slot`, and pools that opted
+/// in can be paused by anyone.
+#[account]
+#[derive(InitSpace)]
+pub struct UpgradeGuard {
+    /// Keys that approve upgrade commitments and reveals
+    #[max_len(MAX_UPGRADE_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+
+    /// Number of distinct `signers` each commitment or reveal needs
+    pub threshold: u8,
+
+    /// Seconds between a commitment and the earliest reveal
+    pub timelock: i64,
+
+    /// Hash of the committed upgrade's program binary (all zero = none pending)
+    pub pending_hash: [u8; 32],
+
+    /// Earliest time the pending upgrade can be revealed
+    pub ready_ts: i64,
+
+    /// Hash of the last revealed upgrade, for keepers to check the deployed binary against
+    pub last_hash: [u8; 32],
+
+    /// Program data deploy slot of the last acknowledged deploy
+    pub acknowledged_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl UpgradeGuard {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Replaces the signer set with `threshold` of `signers`, which must be distinct
+    pub fn set_signers(&mut self, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        let mut distinct = signers.clone();
+        distinct.sort();
+        distinct.dedup();
+        require!(
+            distinct.len() == signers.len()
+                && signers.len() <= MAX_UPGRADE_SIGNERS
+                && threshold > 0
+                && threshold as usize <= signers.len(),
+            MiniStabbleError::InvalidUpgradeSigners
+        );
+
+        self.signers = signers;
+        self.threshold = threshold;
+
+        Ok(())
+    }
+
+    /// Requires `threshold` distinct keys of the signer set among `signed`, the keys that signed
+    /// the transaction
+    pub fn require_quorum(&self, signed: &[Pubkey]) -> Result<()> {
+        let mut approvals = signed
+            .iter()
+            .filter(|key| self.signers.contains(key))
+            .collect::<Vec<_>>();
+        approvals.sort();
+        approvals.dedup();
+        require!(
+            approvals.len() >= self.threshold as usize,
+            MiniStabbleError::UpgradeQuorumNotMet
+        );
+
+        Ok(())
+    }
+
+    /// Commits to `hash` as the next upgrade, replacing any pending commitment and restarting
+    /// the timelock
+    pub fn commit(&mut self, hash: [u8; 32], now: i64) -> Result<i64> {
+        require!(hash != [0; 32], MiniStabbleError::UpgradeNotCommitted);
+
+        self.pending_hash = hash;
+        self.ready_ts = now
+            .checked_add(self.timelock)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+
+        Ok(self.ready_ts)
+    }
+
+    /// Acknowledges the deploy at `deploy_slot` as the committed upgrade `hash`
+    pub fn reveal(&mut self, hash: [u8; 32], now: i64, deploy_slot: u64) -> Result<()> {
+        require!(
+            self.pending_hash != [0; 32] && self.pending_hash == hash,
+            MiniStabbleError::UpgradeNotCommitted
+        );
+        require!(now >= self.ready_ts, MiniStabbleError::UpgradeTimelocked);
+        require!(
+            deploy_slot > self.acknowledged_slot,
+            MiniStabbleError::UpgradeNotDeployed
+        );
+
+        self.last_hash = hash;
+        self.pending_hash = [0; 32];
+        self.acknowledged_slot = deploy_slot;
+
+        Ok(())
+    }
+
+    /// Whether the program was deployed since the last acknowledged upgrade
+    pub fn is_unacknowledged(&self, deploy_slot: u64) -> bool {
+        deploy_slot != self.acknowledged_slot
+    }
+}
+
+/// Keys of the `accounts` that signed the transaction, the approvals passed to
+/// `UpgradeGuard::require_quorum` as remaining accounts
+pub fn signed_keys(accounts: &[AccountInfo]) -> Vec<Pubkey> {
+    accounts
+        .iter()
+        .filter(|account| account.is_signer)
+        .map(|account| *account.key)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard() -> UpgradeGuard {
+        UpgradeGuard {
+            signers: vec![],
+            threshold: 0,
+            timelock: 86_400,
+            pending_hash: [0; 32],
+            ready_ts: 0,
+            last_hash: [0; 32],
+            acknowledged_slot: 100,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_reveal_needs_commitment_timelock_and_deploy() {
+        let mut guard = guard();
+        let hash = [7; 32];
+        assert!(guard.reveal(hash, 0, 200).is_err());
+        assert!(guard.commit([0; 32], 0).is_err());
+
+        assert_eq!(guard.commit(hash, 1_000).unwrap(), 87_400);
+        assert!(guard.reveal([8; 32], 87_400, 200).is_err());
+        assert!(guard.reveal(hash, 87_399, 200).is_err());
+        assert!(guard.reveal(hash, 87_400, 100).is_err());
+
+        // Deployed early: pausable until the timelock passes and it is revealed
+        assert!(guard.is_unacknowledged(200));
+        guard.reveal(hash, 87_400, 200).unwrap();
+        assert!(!guard.is_unacknowledged(200));
+        assert_eq!(guard.last_hash, hash);
+        assert_eq!(guard.pending_hash, [0; 32]);
+
+        // The commitment is spent
+        assert!(guard.reveal(hash, 90_000, 300).is_err());
+    }
+
+    #[test]
+    fn test_quorum_needs_threshold_distinct_signers() {
+        let mut guard = guard();
+        let [a, b, c] = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        assert!(guard.set_signers(vec![a, b, a], 2).is_err());
+        assert!(guard.set_signers(vec![a, b], 3).is_err());
+        assert!(guard.set_signers(vec![a, b], 0).is_err());
+        guard.set_signers(vec![a, b, c], 2).unwrap();
+
+        assert!(guard.require_quorum(&[a]).is_err());
+        assert!(guard.require_quorum(&[a, a]).is_err());
+        assert!(guard.require_quorum(&[a, Pubkey::new_unique()]).is_err());
+        assert!(guard.require_quorum(&[c, a]).is_ok());
+    }
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+/// Cumulative swap activity of one user across all pools, a PDA at `[USER_STATS, user]`.
+/// Opt-in: the user creates it and passes it to swaps they want counted, so fee tiers and
+/// reward programs can read volume on-chain instead of trusting an indexer.
+#[account]
+#[derive(InitSpace)]
+pub struct UserStats {
+    /// User whose swaps are counted
+    pub user: Pubkey,
+
+    pub swap_count: u64,
+
+    /// Sum of the amounts swapped in, in scaled (1e9, decimal-normalized) units of whichever
+    /// token was sold
+    pub volume: u128,
+
+    /// Unix timestamp of the last counted swap
+    pub last_swap_ts: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl UserStats {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn record_swap(&mut self, scaled_amount_in: u64, now: i64) {
+        self.swap_count = self.swap_count.saturating_add(1);
+        self.volume = self.volume.saturating_add(scaled_amount_in.into());
+        self.last_swap_ts = now;
+    }
+}
@@ -0,0 +1,376 @@
+//! Read-only views of pool accounts for indexers consuming raw account updates (e.g. Geyser
+//! streams), cast straight from the account data without Borsh. Pools are Borsh-encoded with
+//! variable-length vectors, so each view is a set of fixed-layout `bytemuck` segments located by
+//! walking the length prefixes; nothing is copied or allocated.
+//!
+//! Every segment is `repr(C, packed)` and mirrors the on-chain field order exactly. Read fields
+//! by value: references into packed structs aren't allowed. `bool` fields are `u8` (0 or 1).
+
+use std::mem::size_of;
+
+use anchor_lang::{prelude::*, Discriminator};
+use bytemuck::{Pod, Zeroable};
+
+use super::{StablePool, WeightedPool};
+
+/// Unaligned little-endian u64, as stored in a Borsh vector
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct PodU64(pub u64);
+
+impl PodU64 {
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+/// Unaligned little-endian u128, as stored in a Borsh vector
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct PodU128(pub u128);
+
+impl PodU128 {
+    pub fn get(self) -> u128 {
+        self.0
+    }
+}
+
+/// Layout of a `PoolToken`
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct PoolTokenView {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub decimals: u8,
+    pub scaling_factor: u64,
+    pub balance: u64,
+    pub weight: u64,
+    pub swaps_disabled: u8,
+    pub rounding_dust: u64,
+    pub internal_balances: u64,
+    pub queued_withdrawals: u64,
+    pub referral_rewards: u64,
+}
+
+/// Layout of a `DrawdownGuard`
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct DrawdownGuardView {
+    pub max_drawdown_bps: u16,
+    pub high_water_mark: u128,
+}
+
+/// Layout of a `TokenFlow`
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct TokenFlowView {
+    pub start_balance: u64,
+    pub net_outflow: i128,
+}
+
+/// Layout of an `ExitWindow`
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct ExitWindowView {
+    pub window_start_slot: u64,
+    pub window_start_supply: u64,
+    pub exited_lp: u64,
+}
+
+/// `WeightedPool` fields up to `swap_exponents`
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct WeightedPoolHeader {
+    pub authority: Pubkey,
+    pub lp_mint: Pubkey,
+    pub is_active: u8,
+    pub invariant: u64,
+    pub swap_fee: u64,
+    pub max_spot_price_move_per_swap_bps: u16,
+    pub drawdown_guard: DrawdownGuardView,
+    pub trader_allowlist: u8,
+}
+
+/// `StablePool` fields up to `swap_rate_limit.flows`, with the rate limit's fixed fields inlined
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct StablePoolHeader {
+    pub authority: Pubkey,
+    pub lp_mint: Pubkey,
+    pub is_active: u8,
+    pub invariant: u64,
+    pub swap_fee: u64,
+    pub amp: u64,
+    pub amp_target: u64,
+    pub amp_start_ts: i64,
+    pub amp_end_ts: i64,
+    pub rate_limit_window_slots: u64,
+    pub rate_limit_max_outflow_bps: u16,
+    pub rate_limit_window_start_slot: u64,
+}
+
+/// `FeeRebate` fields up to `epoch_fees`
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct FeeRebateHeader {
+    pub fee_share_bps: u16,
+    pub epoch: u64,
+    pub epoch_volume: u128,
+}
+
+pub struct WeightedPoolView<'a> {
+    pub header: &'a WeightedPoolHeader,
+    pub swap_exponents: &'a [PodU64],
+    pub tokens: &'a [PoolTokenView],
+    pub bump: u8,
+    pub feature_flags: u64,
+    pub exit_window: &'a ExitWindowView,
+    pub authority_bump: u8,
+}
+
+impl<'a> WeightedPoolView<'a> {
+    /// Views the data of a `WeightedPool` account, discriminator included. Returns `None` for
+    /// any other account or truncated data.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let mut cursor = Cursor::new(data, WeightedPool::DISCRIMINATOR)?;
+
+        Some(Self {
+            header: cursor.read()?,
+            swap_exponents: cursor.read_vec()?,
+            tokens: cursor.read_vec()?,
+            bump: *cursor.read::<u8>()?,
+            feature_flags: cursor.read::<PodU64>()?.get(),
+            exit_window: cursor.read()?,
+            authority_bump: *cursor.read::<u8>()?,
+        })
+    }
+}
+
+pub struct FeeRebateView<'a> {
+    pub header: &'a FeeRebateHeader,
+    pub epoch_fees: &'a [PodU64],
+    pub previous_epoch_volume: u128,
+    pub previous_epoch_fees: &'a [PodU64],
+    pub previous_epoch_claimed: &'a [PodU64],
+}
+
+pub struct StablePoolView<'a> {
+    pub header: &'a StablePoolHeader,
+    pub rate_limit_flows: &'a [TokenFlowView],
+    pub fee_growth: &'a [PodU128],
+
+    /// `max_age_slots` of an `InvariantCachePolicy::Fast` pool; `None` for `Strict`
+    pub invariant_cache_max_age_slots: Option<u64>,
+    pub invariant_last_updated_slot: u64,
+    pub drawdown_guard: &'a DrawdownGuardView,
+    pub rebate: FeeRebateView<'a>,
+    pub trader_allowlist: bool,
+    pub max_out_ratio_bps: u16,
+    pub tokens: &'a [PoolTokenView],
+    pub bump: u8,
+    pub feature_flags: u64,
+    pub exit_window: &'a ExitWindowView,
+    pub authority_bump: u8,
+}
+
+impl<'a> StablePoolView<'a> {
+    /// Views the data of a `StablePool` account, discriminator included. Returns `None` for any
+    /// other account or truncated data.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let mut cursor = Cursor::new(data, StablePool::DISCRIMINATOR)?;
+
+        let header = cursor.read()?;
+        let rate_limit_flows = cursor.read_vec()?;
+        let fee_growth = cursor.read_vec()?;
+        let invariant_cache_max_age_slots = match cursor.read::<u8>()? {
+            0 => None,
+            1 => Some(cursor.read::<PodU64>()?.get()),
+            _ => return None,
+        };
+
+        Some(Self {
+            header,
+            rate_limit_flows,
+            fee_growth,
+            invariant_cache_max_age_slots,
+            invariant_last_updated_slot: cursor.read::<PodU64>()?.get(),
+            drawdown_guard: cursor.read()?,
+            rebate: FeeRebateView {
+                header: cursor.read()?,
+                epoch_fees: cursor.read_vec()?,
+                previous_epoch_volume: cursor.read::<PodU128>()?.get(),
+                previous_epoch_fees: cursor.read_vec()?,
+                previous_epoch_claimed: cursor.read_vec()?,
+            },
+            trader_allowlist: *cursor.read::<u8>()? != 0,
+            max_out_ratio_bps: u16::from_le_bytes(*cursor.read::<[u8; 2]>()?),
+            tokens: cursor.read_vec()?,
+            bump: *cursor.read::<u8>()?,
+            feature_flags: cursor.read::<PodU64>()?.get(),
+            exit_window: cursor.read()?,
+            authority_bump: *cursor.read::<u8>()?,
+        })
+    }
+}
+
+/// Walks Borsh-encoded account data in field order
+struct Cursor<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    /// Starts after `discriminator`, if the data begins with it
+    fn new(data: &'a [u8], discriminator: &[u8]) -> Option<Self> {
+        Some(Self {
+            data: data.strip_prefix(discriminator)?,
+        })
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let (taken, rest) = self.data.split_at_checked(len)?;
+        self.data = rest;
+        Some(taken)
+    }
+
+    fn read<T: Pod>(&mut self) -> Option<&'a T> {
+        bytemuck::try_from_bytes(self.take(size_of::<T>())?).ok()
+    }
+
+    /// A Borsh vector: a u32 length, then the elements
+    fn read_vec<T: Pod>(&mut self) -> Option<&'a [T]> {
+        let len = u32::from_le_bytes(*self.read::<[u8; 4]>()?) as usize;
+        bytemuck::try_cast_slice(self.take(len.checked_mul(size_of::<T>())?)?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{InvariantCachePolicy, PoolToken, TokenFlow};
+
+    fn token(weight: u64) -> PoolToken {
+        PoolToken {
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            decimals: 6,
+            scaling_factor: 1_000,
+            balance: 1_000_000_000_000,
+            weight,
+            swaps_disabled: true,
+            rounding_dust: 7,
+            internal_balances: 0,
+            queued_withdrawals: 3,
+            referral_rewards: 0,
+        }
+    }
+
+    /// Account data as allocated on-chain: serialized, then zero-padded to the full size
+    fn account_data<T: AccountSerialize>(account: &T, len: usize) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data.resize(len, 0);
+        data
+    }
+
+    #[test]
+    fn test_weighted_pool_view_matches_account() {
+        let pool = WeightedPool {
+            authority: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            is_active: true,
+            invariant: 42,
+            swap_fee: 3_000_000,
+            max_spot_price_move_per_swap_bps: 0,
+            drawdown_guard: Default::default(),
+            trader_allowlist: false,
+            swap_exponents: vec![1_000_000_000; 4],
+            tokens: vec![token(500_000_000), token(500_000_000)],
+            bump: 254,
+            feature_flags: 1,
+            exit_window: Default::default(),
+            authority_bump: 253,
+        };
+
+        let data = account_data(&pool, WeightedPool::LEN);
+        let view = WeightedPoolView::parse(&data).unwrap();
+
+        assert_eq!({ view.header.lp_mint }, pool.lp_mint);
+        assert_eq!(view.header.is_active, 1);
+        assert_eq!({ view.header.swap_fee }, 3_000_000);
+        assert_eq!(view.swap_exponents.len(), 4);
+        assert_eq!(view.swap_exponents[3].get(), 1_000_000_000);
+        assert_eq!(view.tokens.len(), 2);
+        assert_eq!({ view.tokens[1].mint }, pool.tokens[1].mint);
+        assert_eq!({ view.tokens[1].rounding_dust }, 7);
+        assert_eq!({ view.tokens[1].queued_withdrawals }, 3);
+        assert_eq!(view.bump, 254);
+        assert_eq!(view.feature_flags, 1);
+        assert_eq!(view.authority_bump, 253);
+
+        // Not a weighted pool
+        assert!(StablePoolView::parse(&data).is_none());
+        assert!(WeightedPoolView::parse(&data[..100]).is_none());
+    }
+
+    #[test]
+    fn test_stable_pool_view_matches_account() {
+        let mut pool = StablePool {
+            authority: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            is_active: true,
+            invariant: 2_000_000_000_000,
+            swap_fee: 1_000_000,
+            amp: 100_000,
+            amp_target: 200_000,
+            amp_start_ts: 10,
+            amp_end_ts: -20,
+            swap_rate_limit: Default::default(),
+            fee_growth: vec![3, u128::MAX, 5],
+            invariant_cache_policy: InvariantCachePolicy::Fast { max_age_slots: 9 },
+            invariant_last_updated_slot: 11,
+            drawdown_guard: Default::default(),
+            rebate: Default::default(),
+            trader_allowlist: true,
+            max_out_ratio_bps: 9_000,
+            tokens: vec![token(0), token(0), token(0)],
+            bump: 253,
+            feature_flags: 0,
+            exit_window: Default::default(),
+            authority_bump: 0,
+        };
+        pool.swap_rate_limit.max_outflow_bps = 1_000;
+        pool.swap_rate_limit.flows = vec![TokenFlow {
+            start_balance: 8,
+            net_outflow: -8,
+        }];
+        pool.drawdown_guard.high_water_mark = 1 << 100;
+        pool.rebate.epoch_fees = vec![1, 2, 3];
+        pool.rebate.previous_epoch_volume = 77;
+        pool.rebate.previous_epoch_claimed = vec![4, 5, 6];
+
+        let data = account_data(&pool, StablePool::LEN);
+        let view = StablePoolView::parse(&data).unwrap();
+
+        assert_eq!({ view.header.amp_end_ts }, -20);
+        assert_eq!({ view.header.rate_limit_max_outflow_bps }, 1_000);
+        assert_eq!({ view.rate_limit_flows[0].net_outflow }, -8);
+        assert_eq!(view.fee_growth[1].get(), u128::MAX);
+        assert_eq!(view.invariant_cache_max_age_slots, Some(9));
+        assert_eq!(view.invariant_last_updated_slot, 11);
+        assert_eq!({ view.drawdown_guard.high_water_mark }, 1 << 100);
+        assert_eq!(view.rebate.epoch_fees[2].get(), 3);
+        assert_eq!(view.rebate.previous_epoch_volume, 77);
+        assert!(view.rebate.previous_epoch_fees.is_empty());
+        assert_eq!(view.rebate.previous_epoch_claimed[0].get(), 4);
+        assert!(view.trader_allowlist);
+        assert_eq!(view.max_out_ratio_bps, 9_000);
+        assert_eq!(view.tokens.len(), 3);
+        assert_eq!(
+            { view.tokens[2].token_account },
+            pool.tokens[2].token_account
+        );
+        assert_eq!(view.tokens[0].swaps_disabled, 1);
+        assert_eq!(view.bump, 253);
+        assert_eq!({ view.exit_window.exited_lp }, 0);
+    }
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+
+/// How old a checkpoint may be, in slots (about an hour), for a report against it
+pub const VIRTUAL_PRICE_CHECKPOINT_MAX_AGE_SLOTS: u64 = 9_000;
+
+/// Drop below the checkpoint, in bps, that counts as proof the invariant decreased. Nothing
+/// but a swap fee or rounding in the pool's favor moves the virtual price at a fixed amp, so
+/// any real drop points at a math exploit.
+pub const INVARIANT_DECREASE_TOLERANCE_BPS: u64 = 10;
+
+/// A stable pool's invariant per LP token at a recent slot, a PDA at
+/// `[VIRTUAL_PRICE_CHECKPOINT, pool]`. Anyone can move it forward and anyone can report a
+/// virtual price below it, which pauses the pool until the admin resumes it.
+#[account]
+#[derive(InitSpace)]
+pub struct VirtualPriceCheckpoint {
+    pub pool: Pubkey,
+
+    /// Slot the checkpoint was taken at
+    pub slot: u64,
+
+    /// Amp the virtual price was taken at; a report needs the pool at the same amp
+    pub amp: u64,
+
+    /// Invariant per LP token (SCALE = 1e9)
+    pub virtual_price: u128,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl VirtualPriceCheckpoint {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Whether a report at `slot` may still rely on this checkpoint
+    pub fn is_recent(&self, slot: u64) -> bool {
+        slot.saturating_sub(self.slot) <= VIRTUAL_PRICE_CHECKPOINT_MAX_AGE_SLOTS
+    }
+
+    /// Whether `virtual_price` at `amp` is more than the tolerance below this checkpoint
+    pub fn proves_decrease(&self, amp: u64, virtual_price: u128) -> bool {
+        let floor = self.virtual_price
+            * (BPS_DENOMINATOR - INVARIANT_DECREASE_TOLERANCE_BPS) as u128
+            / BPS_DENOMINATOR as u128;
+        amp == self.amp && virtual_price < floor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decrease_needs_same_amp_and_drop_past_tolerance() {
+        let checkpoint = VirtualPriceCheckpoint {
+            pool: Pubkey::new_unique(),
+            slot: 1_000,
+            amp: 100_000,
+            virtual_price: 1_000_000_000,
+            bump: 255,
+        };
+
+        assert!(checkpoint.is_recent(1_000 + VIRTUAL_PRICE_CHECKPOINT_MAX_AGE_SLOTS));
+        assert!(!checkpoint.is_recent(1_001 + VIRTUAL_PRICE_CHECKPOINT_MAX_AGE_SLOTS));
+
+        assert!(!checkpoint.proves_decrease(100_000, 999_000_000));
+        assert!(checkpoint.proves_decrease(100_000, 998_999_999));
+        assert!(!checkpoint.proves_decrease(200_000, 900_000_000));
+        assert!(!checkpoint.proves_decrease(100_000, 1_100_000_000));
+    }
+}
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+
+use super::PoolToken;
+use crate::errors::MiniStabbleError;
+
+/// Amount of a single token owed to a queued exit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
+pub struct QueuedToken {
+    /// Mint address of the token
+    pub mint: Pubkey,
+
+    /// Total amount owed (on-chain units)
+    pub amount: u64,
+
+    /// Amount already transferred to the owner (on-chain units)
+    pub claimed: u64,
+}
+
+/// A queued exit. The LP is burned when the request is created, so the owner's share is
+/// locked in, and the owed tokens unlock linearly between `start_slot` and `end_slot`. Until
+/// claimed they sit in the vault outside the pool's balance, tracked per token in
+/// `PoolToken::queued_withdrawals`.
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawRequest {
+    /// Owner who receives the queued tokens
+    pub owner: Pubkey,
+
+    /// Pool the LP was burned from
+    pub pool: Pubkey,
+
+    /// Per-token amounts owed
+    #[max_len(8)]
+    pub tokens: Vec<QueuedToken>,
+
+    /// Slot the request was created at
+    pub start_slot: u64,
+
+    /// Slot at which the full amount is claimable
+    pub end_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl WithdrawRequest {
+    pub fn get_token_index(&self, mint: &Pubkey) -> Option<usize> {
+        self.tokens.iter().position(|t| t.mint == *mint)
+    }
+
+    /// Amount of `tokens[index]` unlocked at `slot` that has not been claimed yet
+    pub fn claimable(&self, index: usize, slot: u64) -> Option<u64> {
+        let token = &self.tokens[index];
+
+        let unlocked = if slot >= self.end_slot {
+            token.amount
+        } else {
+            let elapsed = slot.saturating_sub(self.start_slot);
+            let duration = self.end_slot.checked_sub(self.start_slot)?;
+            u64::try_from(
+                (token.amount as u128)
+                    .checked_mul(elapsed as u128)?
+                    .checked_div(duration as u128)?,
+            )
+            .ok()?
+        };
+
+        unlocked.checked_sub(token.claimed)
+    }
+
+    pub fn is_fully_claimed(&self) -> bool {
+        self.tokens.iter().all(|t| t.claimed == t.amount)
+    }
+
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// Releases `claimed` raw of each queued token, in request order, from the pool's `tokens`
+/// once it has been paid out, and returns the pool vault each one is paid from
+pub fn release_queued_withdrawals(
+    tokens: &mut [PoolToken],
+    queued: &[QueuedToken],
+    claimed: &[u64],
+) -> Result<Vec<Pubkey>> {
+    let mut vaults = Vec::with_capacity(queued.len());
+    for (queued, &amount) in queued.iter().zip(claimed) {
+        let token = tokens
+            .iter_mut()
+            .find(|token| token.mint == queued.mint)
+            .ok_or(MiniStabbleError::InvalidMint)?;
+        token.queued_withdrawals = token
+            .queued_withdrawals
+            .checked_sub(amount)
+            .ok_or(MiniStabbleError::MathOverflow)?;
+        vaults.push(token.token_account);
+    }
+
+    Ok(vaults)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claimable_unlocks_linearly() {
+        let mut request = WithdrawRequest {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            tokens: vec![QueuedToken {
+                mint: Pubkey::new_unique(),
+                amount: 1_000,
+                claimed: 0,
+            }],
+            start_slot: 100,
+            end_slot: 200,
+            bump: 255,
+        };
+
+        assert_eq!(request.claimable(0, 50), Some(0));
+        assert_eq!(request.claimable(0, 125), Some(250));
+
+        request.tokens[0].claimed = 250;
+        assert_eq!(request.claimable(0, 150), Some(250));
+        assert_eq!(request.claimable(0, 500), Some(750));
+        assert!(!request.is_fully_claimed());
+
+        request.tokens[0].claimed = 1_000;
+        assert_eq!(request.claimable(0, 500), Some(0));
+        assert!(request.is_fully_claimed());
+    }
+
+    #[test]
+    fn test_claims_release_what_the_pool_holds_for_the_queue() {
+        let mut tokens = vec![PoolToken::default(), PoolToken::default()];
+        tokens[0].mint = Pubkey::new_unique();
+        tokens[1].mint = Pubkey::new_unique();
+        tokens[0].token_account = Pubkey::new_unique();
+        tokens[1].token_account = Pubkey::new_unique();
+        tokens[0].queued_withdrawals = 1_000;
+        tokens[1].queued_withdrawals = 40;
+        let queued = tokens
+            .iter()
+            .map(|token| QueuedToken {
+                mint: token.mint,
+                amount: token.queued_withdrawals,
+                claimed: 0,
+            })
+            .collect::<Vec<_>>();
+
+        let vaults = release_queued_withdrawals(&mut tokens, &queued, &[250, 10]).unwrap();
+        assert_eq!(vaults, [tokens[0].token_account, tokens[1].token_account]);
+        assert_eq!(tokens[0].queued_withdrawals, 750);
+        assert_eq!(tokens[1].queued_withdrawals, 30);
+
+        assert!(release_queued_withdrawals(&mut tokens, &queued, &[0, 31]).is_err());
+    }
+}
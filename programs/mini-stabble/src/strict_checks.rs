@@ -0,0 +1,21 @@
+//! Development-time sanity checks, compiled in with the `strict-checks` feature for devnet
+//! builds and left out of mainnet builds.
+
+/// Relative slack (1 = 1e-9) for rounding inside the invariant approximations
+const VIRTUAL_PRICE_TOLERANCE: u128 = 1_000;
+
+/// Panics if LP virtual price dropped across a deposit or withdraw, which means fees or rounding
+/// leaked value to the caller. Skipped when the pool was or became empty.
+pub fn assert_virtual_price_not_decreased(before: Option<u128>, after: Option<u128>) {
+    let (Some(before), Some(after)) = (before, after) else {
+        return;
+    };
+
+    let slack = before * VIRTUAL_PRICE_TOLERANCE / 1_000_000_000;
+    assert!(
+        after + slack >= before,
+        "LP virtual price decreased: {} -> {}",
+        before,
+        after
+    );
+}
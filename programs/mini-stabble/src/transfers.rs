@@ -0,0 +1,481 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{self, spl_token::native_mint, Burn, MintTo, TokenAccount},
+    token_2022::spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::Mint as Token2022Mint,
+    },
+    token_interface::{
+        self, CloseAccount, TokenAccount as InterfaceTokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::errors::MiniStabbleError;
+
+/// Signer seeds of the global `[AUTHORITY]` PDA, which owned every pool's vaults and LP mint
+/// before pools had their own, for the `signer_seeds` of a CPI:
+/// `let signer_seeds = authority_signer_seeds!(ctx.bumps.authority);`
+#[macro_export]
+macro_rules! authority_signer_seeds {
+    ($bump:expr) => {
+        &[&[$crate::constants::AUTHORITY, &[$bump]] as &[&[u8]]]
+    };
+}
+
+/// Signer seeds of the `[AUTHORITY, pool]` PDA that owns a pool's vaults, LP mint and LP
+/// escrows. `$pool` must be a `Pubkey` binding that outlives the seeds:
+/// `let signer_seeds = pool_authority_signer_seeds!(pool_key, pool.authority_bump);`
+#[macro_export]
+macro_rules! pool_authority_signer_seeds {
+    ($pool:expr, $bump:expr) => {
+        &[&[$crate::constants::AUTHORITY, $pool.as_ref(), &[$bump]] as &[&[u8]]]
+    };
+}
+
+/// Loads the token program passed as `info` for a remaining-accounts token, checking it is the
+/// one that owns `mint`
+pub fn load_token_program<'info>(
+    info: &'info AccountInfo<'info>,
+    mint: &impl ToAccountInfo<'info>,
+) -> Result<Interface<'info, TokenInterface>> {
+    let token_program = Interface::<TokenInterface>::try_from(info)?;
+    require_keys_eq!(
+        *mint.to_account_info().owner,
+        token_program.key(),
+        MiniStabbleError::InvalidAccount
+    );
+
+    Ok(token_program)
+}
+
+/// Decimals and transfer fee config of a classic or Token-2022 mint
+fn read_mint(mint: &AccountInfo) -> Result<(u8, Option<TransferFeeConfig>)> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+    let transfer_fee_config = state.get_extension::<TransferFeeConfig>().ok().copied();
+
+    Ok((state.base.decimals, transfer_fee_config))
+}
+
+/// Fee `config` withholds on a transfer of `amount` in `epoch`
+fn epoch_fee(config: &TransferFeeConfig, epoch: u64, amount: u64) -> Result<u64> {
+    Ok(config
+        .calculate_epoch_fee(epoch, amount)
+        .ok_or(MiniStabbleError::MathOverflow)?)
+}
+
+/// Amount that has to be sent in `epoch` so the receiver is credited exactly `net_amount`
+fn epoch_gross_amount(config: &TransferFeeConfig, epoch: u64, net_amount: u64) -> Result<u64> {
+    if net_amount == 0 {
+        return Ok(0);
+    }
+
+    let fee = config
+        .calculate_inverse_epoch_fee(epoch, net_amount)
+        .ok_or(MiniStabbleError::MathOverflow)?;
+
+    Ok(net_amount
+        .checked_add(fee)
+        .ok_or(MiniStabbleError::MathOverflow)?)
+}
+
+/// Fee Token-2022 withholds when `amount` of `mint` is transferred; zero for mints without a
+/// transfer fee
+pub fn transfer_fee<'info>(mint: &impl ToAccountInfo<'info>, amount: u64) -> Result<u64> {
+    let Some(config) = read_mint(&mint.to_account_info())?.1 else {
+        return Ok(0);
+    };
+
+    epoch_fee(&config, Clock::get()?.epoch, amount)
+}
+
+/// Amount that has to be sent so the receiver is credited exactly `net_amount` of `mint`
+pub fn gross_amount_for_net<'info>(
+    mint: &impl ToAccountInfo<'info>,
+    net_amount: u64,
+) -> Result<u64> {
+    let Some(config) = read_mint(&mint.to_account_info())?.1 else {
+        return Ok(net_amount);
+    };
+
+    epoch_gross_amount(&config, Clock::get()?.epoch, net_amount)
+}
+
+/// `transfer_checked` of `amount` raw tokens, returning what `to` receives after transfer fees
+fn transfer<'info>(
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<u64> {
+    let (decimals, transfer_fee_config) = read_mint(&mint)?;
+    let fee = match transfer_fee_config {
+        Some(config) => epoch_fee(&config, Clock::get()?.epoch, amount)?,
+        None => 0,
+    };
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program,
+            TransferChecked {
+                from,
+                mint,
+                to,
+                authority,
+            },
+            signer_seeds,
+        ),
+        amount,
+        decimals,
+    )?;
+
+    Ok(amount - fee)
+}
+
+/// Moves `amount` from a user-owned token account into a pool vault. Returns the amount the
+/// vault actually received.
+pub fn transfer_from_user<'info>(
+    token_program: &impl ToAccountInfo<'info>,
+    mint: &impl ToAccountInfo<'info>,
+    from: &impl ToAccountInfo<'info>,
+    vault: &impl ToAccountInfo<'info>,
+    user: &impl ToAccountInfo<'info>,
+    amount: u64,
+) -> Result<u64> {
+    transfer(
+        token_program.to_account_info(),
+        mint.to_account_info(),
+        from.to_account_info(),
+        vault.to_account_info(),
+        user.to_account_info(),
+        &[],
+        amount,
+    )
+}
+
+/// Moves `amount` from a user-owned token account into a pool vault, signed by a program PDA the
+/// user approved as delegate of `from`. Returns the amount the vault actually received.
+pub fn transfer_from_delegated_user<'info>(
+    token_program: &impl ToAccountInfo<'info>,
+    mint: &impl ToAccountInfo<'info>,
+    from: &impl ToAccountInfo<'info>,
+    vault: &impl ToAccountInfo<'info>,
+    delegate: &impl ToAccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<u64> {
+    transfer(
+        token_program.to_account_info(),
+        mint.to_account_info(),
+        from.to_account_info(),
+        vault.to_account_info(),
+        delegate.to_account_info(),
+        signer_seeds,
+        amount,
+    )
+}
+
+/// Moves `amount` out of a pool vault, signed by the pool authority PDA. Returns the amount the
+/// recipient actually received.
+pub fn transfer_from_vault<'info>(
+    token_program: &impl ToAccountInfo<'info>,
+    mint: &impl ToAccountInfo<'info>,
+    vault: &impl ToAccountInfo<'info>,
+    to: &impl ToAccountInfo<'info>,
+    authority: &impl ToAccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<u64> {
+    transfer(
+        token_program.to_account_info(),
+        mint.to_account_info(),
+        vault.to_account_info(),
+        to.to_account_info(),
+        authority.to_account_info(),
+        signer_seeds,
+        amount,
+    )
+}
+
+/// Mints `amount` LP tokens, signed by the pool authority PDA
+pub fn mint_lp<'info>(
+    token_program: &impl ToAccountInfo<'info>,
+    lp_mint: &impl ToAccountInfo<'info>,
+    to: &impl ToAccountInfo<'info>,
+    authority: &impl ToAccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    token::mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                mint: lp_mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+/// Checks that `authority` may burn `amount` from `lp_account`, either as its owner or as a
+/// delegate approved for at least `amount`
+pub fn require_lp_burn_authority(
+    lp_account: &TokenAccount,
+    authority: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    if lp_account.owner == *authority {
+        return Ok(());
+    }
+
+    require!(
+        lp_account.delegate.contains(authority) && lp_account.delegated_amount >= amount,
+        MiniStabbleError::LpBurnNotAuthorized
+    );
+
+    Ok(())
+}
+
+/// Burns `amount` LP tokens, signed by the account owner or an approved delegate. Callers check
+/// the signer with `require_lp_burn_authority` first.
+pub fn burn_lp<'info>(
+    token_program: &impl ToAccountInfo<'info>,
+    lp_mint: &impl ToAccountInfo<'info>,
+    from: &impl ToAccountInfo<'info>,
+    authority: &impl ToAccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    token::burn(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Burn {
+                mint: lp_mint.to_account_info(),
+                from: from.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Closes `account` if it holds wrapped SOL, unwrapping what is left in it and its rent to
+/// `owner`, who must sign. Accounts of any other mint are left alone. Returns whether it closed.
+pub fn close_native_account<'info>(
+    token_program: &impl ToAccountInfo<'info>,
+    account: &InterfaceAccount<'info, InterfaceTokenAccount>,
+    owner: &impl ToAccountInfo<'info>,
+) -> Result<bool> {
+    if account.mint != native_mint::ID {
+        return Ok(false);
+    }
+
+    token_interface::close_account(CpiContext::new(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: account.to_account_info(),
+            destination: owner.to_account_info(),
+            authority: owner.to_account_info(),
+        },
+    ))?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{
+        solana_program::{program_option::COption, program_pack::Pack},
+        AccountDeserialize,
+    };
+    use anchor_spl::{
+        token::spl_token,
+        token_2022::{
+            self,
+            spl_token_2022::extension::{
+                transfer_fee::TransferFee, BaseStateWithExtensionsMut, ExtensionType,
+                StateWithExtensionsMut,
+            },
+        },
+        token_interface::Mint as InterfaceMint,
+    };
+
+    use super::*;
+
+    /// 1% fee capped at 50 raw tokens, raised to 2% uncapped from epoch 10
+    fn fee_config() -> TransferFeeConfig {
+        TransferFeeConfig {
+            older_transfer_fee: TransferFee {
+                epoch: 0.into(),
+                maximum_fee: 50.into(),
+                transfer_fee_basis_points: 100.into(),
+            },
+            newer_transfer_fee: TransferFee {
+                epoch: 10.into(),
+                maximum_fee: u64::MAX.into(),
+                transfer_fee_basis_points: 200.into(),
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Account info that lives for the rest of the test run
+    fn account_info(
+        key: Pubkey,
+        owner: Pubkey,
+        data: Vec<u8>,
+        executable: bool,
+    ) -> &'static AccountInfo<'static> {
+        Box::leak(Box::new(AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            true,
+            Box::leak(Box::new(1_000_000_000)),
+            data.leak(),
+            Box::leak(Box::new(owner)),
+            executable,
+            0,
+        )))
+    }
+
+    /// Data of an initialized Token-2022 mint charging `fee_config`
+    fn transfer_fee_mint() -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Token2022Mint>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .unwrap();
+        let mut data = vec![0; len];
+        let mut state =
+            StateWithExtensionsMut::<Token2022Mint>::unpack_uninitialized(&mut data).unwrap();
+        *state.init_extension::<TransferFeeConfig>(true).unwrap() = fee_config();
+        state.base.decimals = 6;
+        state.base.is_initialized = true;
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    fn lp_account(owner: Pubkey, delegate: Option<Pubkey>, delegated_amount: u64) -> TokenAccount {
+        let account = spl_token::state::Account {
+            mint: Pubkey::new_unique(),
+            owner,
+            amount: 1_000,
+            delegate: delegate.into(),
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount,
+            close_authority: COption::None,
+        };
+        let mut data = [0u8; spl_token::state::Account::LEN];
+        account.pack_into_slice(&mut data);
+
+        TokenAccount::try_deserialize_unchecked(&mut data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_epoch_fee_follows_the_active_schedule() {
+        let config = fee_config();
+
+        // 1%, rounded up, then capped
+        assert_eq!(epoch_fee(&config, 0, 1_000).unwrap(), 10);
+        assert_eq!(epoch_fee(&config, 0, 1_001).unwrap(), 11);
+        assert_eq!(epoch_fee(&config, 9, 1_000_000).unwrap(), 50);
+        assert_eq!(epoch_fee(&config, 10, 1_000_000).unwrap(), 20_000);
+        assert_eq!(epoch_fee(&config, 0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_gross_amount_nets_out_to_the_requested_amount() {
+        let config = fee_config();
+
+        for epoch in [0, 10] {
+            for net_amount in [1, 99, 1_000, 123_456, 10_000_000] {
+                let gross = epoch_gross_amount(&config, epoch, net_amount).unwrap();
+                assert_eq!(
+                    gross - epoch_fee(&config, epoch, gross).unwrap(),
+                    net_amount
+                );
+            }
+        }
+
+        assert_eq!(epoch_gross_amount(&config, 0, 0).unwrap(), 0);
+        // The fee cap bounds the gross-up of large amounts
+        assert_eq!(
+            epoch_gross_amount(&config, 0, 1_000_000).unwrap(),
+            1_000_050
+        );
+    }
+
+    #[test]
+    fn test_gross_amount_overflow_is_rejected() {
+        assert_eq!(
+            epoch_gross_amount(&fee_config(), 10, u64::MAX),
+            Err(MiniStabbleError::MathOverflow.into())
+        );
+    }
+
+    #[test]
+    fn test_transfer_fee_mint_loads_through_the_token_interface() {
+        let mint_info = account_info(
+            Pubkey::new_unique(),
+            token_2022::ID,
+            transfer_fee_mint(),
+            false,
+        );
+        let mint = InterfaceAccount::<InterfaceMint>::try_from(mint_info).unwrap();
+
+        let (decimals, config) = read_mint(&mint.to_account_info()).unwrap();
+        assert_eq!(decimals, 6);
+        let config = config.unwrap();
+        assert_eq!(epoch_fee(&config, 0, 1_000).unwrap(), 10);
+        assert_eq!(epoch_fee(&config, 10, 1_000_000).unwrap(), 20_000);
+
+        // The mint's own program is accepted, the classic one is not
+        let token_2022_program = account_info(token_2022::ID, Pubkey::default(), vec![], true);
+        assert!(load_token_program(token_2022_program, &mint).is_ok());
+        let classic_program = account_info(spl_token::ID, Pubkey::default(), vec![], true);
+        assert_eq!(
+            load_token_program(classic_program, &mint).map(|_| ()),
+            Err(MiniStabbleError::InvalidAccount.into())
+        );
+    }
+
+    #[test]
+    fn test_lp_burn_authority_owner_or_approved_delegate() {
+        let owner = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+
+        assert!(require_lp_burn_authority(&lp_account(owner, None, 0), &owner, 1_000).is_ok());
+        assert!(
+            require_lp_burn_authority(&lp_account(owner, Some(delegate), 500), &delegate, 500)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_lp_burn_authority_rejects_strangers_and_overdrawn_delegates() {
+        let owner = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let stranger = Pubkey::new_unique();
+
+        assert_eq!(
+            require_lp_burn_authority(&lp_account(owner, Some(delegate), 500), &stranger, 1),
+            Err(MiniStabbleError::LpBurnNotAuthorized.into())
+        );
+        assert_eq!(
+            require_lp_burn_authority(&lp_account(owner, Some(delegate), 500), &delegate, 501),
+            Err(MiniStabbleError::LpBurnNotAuthorized.into())
+        );
+        assert_eq!(
+            require_lp_burn_authority(&lp_account(owner, None, 0), &delegate, 1),
+            Err(MiniStabbleError::LpBurnNotAuthorized.into())
+        );
+    }
+}
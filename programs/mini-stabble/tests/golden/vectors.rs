@@ -0,0 +1,144 @@
+// Generated by generate.py; do not edit by hand.
+
+pub struct StableVector {
+    pub name: &'static str,
+    pub amp: u64,
+    pub balances: &'static [u64],
+    pub index_in: usize,
+    pub index_out: usize,
+    pub amount_in: u64,
+
+    /// Exact output, rounded down
+    pub amount_out: u64,
+}
+
+pub struct WeightedVector {
+    pub name: &'static str,
+    pub balance_in: u128,
+    pub weight_in: u128,
+    pub balance_out: u128,
+    pub weight_out: u128,
+    pub amount_in: u128,
+
+    /// Exact output, rounded down
+    pub amount_out: u128,
+}
+
+pub const STABLE: &[StableVector] = &[
+    StableVector {
+        name: "usdc_usdt_balanced",
+        amp: 100000,
+        balances: &[50000000000000000, 50000000000000000],
+        index_in: 0,
+        index_out: 1,
+        amount_in: 250000000000000,
+        amount_out: 249987624071759,
+    },
+    StableVector {
+        name: "three_pool",
+        amp: 2000000,
+        balances: &[160000000000000000, 170000000000000000, 60000000000000000],
+        index_in: 2,
+        index_out: 0,
+        amount_in: 1000000000000000,
+        amount_out: 1000892472575237,
+    },
+    StableVector {
+        name: "steth_eth_imbalanced",
+        amp: 50000,
+        balances: &[300000000000000, 120000000000000],
+        index_in: 0,
+        index_out: 1,
+        amount_in: 10000000000000,
+        amount_out: 9728494944645,
+    },
+    StableVector {
+        name: "depeg_large_trade",
+        amp: 200000,
+        balances: &[10000000000000000, 10000000000000000],
+        index_in: 1,
+        index_out: 0,
+        amount_in: 8000000000000000,
+        amount_out: 7916666666666666,
+    },
+    StableVector {
+        name: "min_swap_amount",
+        amp: 100000,
+        balances: &[1000000000000000, 1000000000000000],
+        index_in: 0,
+        index_out: 1,
+        amount_in: 1000,
+        amount_out: 999,
+    },
+    StableVector {
+        name: "four_token_skewed",
+        amp: 500000,
+        balances: &[
+            4000000000000000,
+            1500000000000000,
+            2500000000000000,
+            900000000000000,
+        ],
+        index_in: 3,
+        index_out: 1,
+        amount_in: 300000000000000,
+        amount_out: 300500744582691,
+    },
+];
+
+pub const WEIGHTED: &[WeightedVector] = &[
+    WeightedVector {
+        name: "bal_weth_80_20_sell_bal",
+        balance_in: 2000000000000000,
+        weight_in: 800000000,
+        balance_out: 1500000000000,
+        weight_out: 200000000,
+        amount_in: 10000000000000,
+        amount_out: 29628717448,
+    },
+    WeightedVector {
+        name: "bal_weth_80_20_sell_weth",
+        balance_in: 1500000000000,
+        weight_in: 200000000,
+        balance_out: 2000000000000000,
+        weight_out: 800000000,
+        amount_in: 10000000000,
+        amount_out: 3319513514850,
+    },
+    WeightedVector {
+        name: "wbtc_weth_50_50",
+        balance_in: 500000000000,
+        weight_in: 500000000,
+        balance_out: 8000000000000,
+        weight_out: 500000000,
+        amount_in: 5000000000,
+        amount_out: 79207920792,
+    },
+    WeightedVector {
+        name: "sixty_twenty_twenty",
+        balance_in: 3000000000000000,
+        weight_in: 200000000,
+        balance_out: 9000000000000000,
+        weight_out: 600000000,
+        amount_in: 45000000000000,
+        amount_out: 44555185216902,
+    },
+    WeightedVector {
+        name: "ninety_eight_two_into_heavy",
+        balance_in: 40000000000000,
+        weight_in: 20000000,
+        balance_out: 1000000000000000,
+        weight_out: 980000000,
+        amount_in: 400000000000,
+        amount_out: 203047359689,
+    },
+    WeightedVector {
+        name: "fifty_fifty_half_balance_trade",
+        balance_in: 1000000000000000,
+        weight_in: 500000000,
+        balance_out: 1000000000000000,
+        weight_out: 500000000,
+        amount_in: 500000000000000,
+        amount_out: 333333333333333,
+    },
+];
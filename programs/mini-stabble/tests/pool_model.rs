@@ -0,0 +1,403 @@
+//! Drives random join / swap / exit sequences through the program's pool math in lockstep with
+//! a plain `f64` reference model of the same pool, asserting balances, LP supply and invariant
+//! stay together within rounding and that LPs never lose value per LP token.
+//!
+//! This is the math half of the lockstep model test only: it calls the pool math directly
+//! instead of running the program's instructions under LiteSVM, so the accounts, token
+//! transfers and raw/scaled conversions of the handlers are not covered here.
+
+use mini_stabble::math::{
+    common::{calc_tokens_in_proportional, calc_tokens_out_proportional},
+    fixed::ONE_U64,
+    stable::{calc_invariant, AMP_PRECISION, STRESS_BALANCE_SHARE_BPS, STRESS_SURCHARGE},
+    swap_context::SwapContext,
+};
+
+const SEEDS: [u64; 4] = [1, 42, 0xdead_beef, 0x5eed_5eed_5eed];
+const OPS_PER_SEQUENCE: usize = 300;
+
+/// Largest relative gap between the program and the model, on any balance, the LP supply or
+/// the invariant, anywhere in a sequence. Stable math stays within a few units per swap;
+/// weighted swaps round their power term up by a unit or two, so each pays out a little under
+/// the exact curve (asserted per swap), and what that leaves in the pool compounds over a
+/// sequence.
+const STABLE_MAX_RELATIVE_DRIFT: f64 = 1e-9;
+const WEIGHTED_MAX_RELATIVE_DRIFT: f64 = 1e-6;
+
+/// Fee charged on every swap (SCALE = 1e9)
+const SWAP_FEE: u64 = 3_000_000;
+
+/// Deterministic xorshift64 so every failure replays from its seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in `[low, high)`
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next() % (high - low)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Curve {
+    Weighted { weights: Vec<u64> },
+    Stable { amp: u64 },
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    /// Join for this many LP tokens
+    Join(u64),
+    /// Swap this much of `tokens[in]` for `tokens[out]`
+    Swap(usize, usize, u64),
+    /// Exit with this many LP tokens
+    Exit(u64),
+}
+
+/// The pool as the program keeps it: scaled `u64` balances run through `mini_stabble::math`
+struct ProgramPool {
+    curve: Curve,
+    balances: Vec<u64>,
+    lp_supply: u64,
+}
+
+impl ProgramPool {
+    fn apply(&mut self, op: Op) -> Option<Vec<u64>> {
+        match op {
+            Op::Join(lp_amount) => {
+                let amounts_in =
+                    calc_tokens_in_proportional(&self.balances, lp_amount, self.lp_supply)?;
+                for (balance, amount) in self.balances.iter_mut().zip(&amounts_in) {
+                    *balance += amount;
+                }
+                self.lp_supply += lp_amount;
+                Some(amounts_in)
+            }
+            Op::Swap(index_in, index_out, amount_in) => {
+                let context = match &self.curve {
+                    Curve::Weighted { weights } => SwapContext::weighted(
+                        self.balances.clone(),
+                        weights.clone(),
+                        None,
+                        SWAP_FEE,
+                        index_in,
+                        index_out,
+                    )
+                    .ok()?,
+                    Curve::Stable { amp } => SwapContext::stable(
+                        self.balances.clone(),
+                        *amp,
+                        None,
+                        SWAP_FEE,
+                        index_in,
+                        index_out,
+                    )
+                    .ok()?,
+                };
+                let quote = context.calc_out_given_in(amount_in).ok()?;
+                self.balances = context.balances_after(amount_in, quote.amount_out).ok()?;
+                Some(vec![quote.amount_out])
+            }
+            Op::Exit(lp_amount) => {
+                let amounts_out =
+                    calc_tokens_out_proportional(&self.balances, lp_amount, self.lp_supply)?;
+                for (balance, amount) in self.balances.iter_mut().zip(&amounts_out) {
+                    *balance -= amount;
+                }
+                self.lp_supply -= lp_amount;
+                Some(amounts_out)
+            }
+        }
+    }
+
+    fn invariant(&self) -> f64 {
+        match &self.curve {
+            Curve::Weighted { weights } => weighted_invariant(
+                &self.balances.iter().map(|&b| b as f64).collect::<Vec<_>>(),
+                weights,
+            ),
+            Curve::Stable { amp } => calc_invariant(*amp, &self.balances).unwrap() as f64,
+        }
+    }
+}
+
+/// The reference: the same pool in exact-ish `f64`, with no rounding rules of its own
+struct ModelPool {
+    curve: Curve,
+    balances: Vec<f64>,
+    lp_supply: f64,
+}
+
+impl ModelPool {
+    fn apply(&mut self, op: Op) {
+        match op {
+            Op::Join(lp_amount) => {
+                let ratio = lp_amount as f64 / self.lp_supply;
+                for balance in &mut self.balances {
+                    *balance += *balance * ratio;
+                }
+                self.lp_supply += lp_amount as f64;
+            }
+            Op::Swap(index_in, index_out, amount_in) => {
+                let amount_in = amount_in as f64;
+                let amount_out = self.swap_out(index_in, index_out, amount_in);
+                self.balances[index_in] += amount_in;
+                self.balances[index_out] -= amount_out;
+            }
+            Op::Exit(lp_amount) => {
+                let ratio = lp_amount as f64 / self.lp_supply;
+                for balance in &mut self.balances {
+                    *balance -= *balance * ratio;
+                }
+                self.lp_supply -= lp_amount as f64;
+            }
+        }
+    }
+
+    /// What a swap of `amount_in` pays out after the fee
+    fn swap_out(&self, index_in: usize, index_out: usize, amount_in: f64) -> f64 {
+        let fee = SWAP_FEE as f64 / ONE_U64 as f64;
+        self.out_given_in(index_in, index_out, amount_in) * (1.0 - fee)
+    }
+
+    fn out_given_in(&self, index_in: usize, index_out: usize, amount_in: f64) -> f64 {
+        let balance_out = self.balances[index_out];
+        match &self.curve {
+            Curve::Weighted { weights } => {
+                let balance_in = self.balances[index_in];
+                let exponent = weights[index_in] as f64 / weights[index_out] as f64;
+                // 1 - (balance_in / (balance_in + amount_in)) ^ exponent, without cancellation
+                -balance_out * (-exponent * (amount_in / balance_in).ln_1p()).exp_m1()
+            }
+            Curve::Stable { amp } => {
+                let invariant = stable_invariant(*amp, &self.balances);
+                let mut balances = self.balances.clone();
+                balances[index_in] += amount_in;
+                let amount_out = balance_out
+                    - stable_balance_given_invariant(*amp, &balances, index_out, invariant);
+
+                // Stress mode: buying a side under its minimum share caps the price at par
+                // less the surcharge
+                let total: f64 = self.balances.iter().sum();
+                let min_share = self.balances[index_out] / total;
+                let is_scarcest = self.balances.iter().all(|&b| b >= self.balances[index_out]);
+                if is_scarcest && min_share < STRESS_BALANCE_SHARE_BPS as f64 / 10_000.0 {
+                    let surcharge = STRESS_SURCHARGE as f64 / ONE_U64 as f64;
+                    amount_out.min(amount_in * (1.0 - surcharge))
+                } else {
+                    amount_out
+                }
+            }
+        }
+    }
+
+    fn invariant(&self) -> f64 {
+        match &self.curve {
+            Curve::Weighted { weights } => weighted_invariant(&self.balances, weights),
+            Curve::Stable { amp } => stable_invariant(*amp, &self.balances),
+        }
+    }
+}
+
+fn weighted_invariant(balances: &[f64], weights: &[u64]) -> f64 {
+    balances
+        .iter()
+        .zip(weights)
+        .map(|(&balance, &weight)| balance.powf(weight as f64 / ONE_U64 as f64))
+        .product()
+}
+
+/// D solving Ann*S + D = Ann*D + D^(n+1) / (n^n * P), with Ann = amp * n / AMP_PRECISION
+fn stable_invariant(amp: u64, balances: &[f64]) -> f64 {
+    let n = balances.len() as f64;
+    let ann = amp as f64 * n / AMP_PRECISION as f64;
+    let sum: f64 = balances.iter().sum();
+
+    let mut invariant = sum;
+    for _ in 0..256 {
+        let d_p = balances
+            .iter()
+            .fold(invariant, |d_p, &balance| d_p * invariant / (balance * n));
+        let next = (ann * sum + d_p * n) * invariant / ((ann - 1.0) * invariant + (n + 1.0) * d_p);
+        if (next - invariant).abs() <= invariant * 1e-15 {
+            return next;
+        }
+        invariant = next;
+    }
+
+    invariant
+}
+
+/// Balance of `tokens[index]` that keeps `invariant` given the other balances
+fn stable_balance_given_invariant(amp: u64, balances: &[f64], index: usize, invariant: f64) -> f64 {
+    let n = balances.len() as f64;
+    let ann = amp as f64 * n / AMP_PRECISION as f64;
+
+    let (sum, c) = balances
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != index)
+        .fold((0.0, invariant), |(sum, c), (_, &balance)| {
+            (sum + balance, c * invariant / (balance * n))
+        });
+    let c = c * invariant / (ann * n);
+    let b = sum + invariant / ann;
+
+    let mut balance = invariant;
+    for _ in 0..256 {
+        let next = (balance * balance + c) / (2.0 * balance + b - invariant);
+        if (next - balance).abs() <= balance * 1e-15 {
+            return next;
+        }
+        balance = next;
+    }
+
+    balance
+}
+
+fn relative_gap(program: f64, model: f64) -> f64 {
+    (program - model).abs() / model
+}
+
+fn random_op(rng: &mut Rng, pool: &ProgramPool) -> Op {
+    let token_count = pool.balances.len() as u64;
+    match rng.range(0, 4) {
+        0 => Op::Join(rng.range(1, pool.lp_supply / 10)),
+        // Keep the pool from draining: exits take at most a tenth of the supply
+        1 => Op::Exit(rng.range(1, pool.lp_supply / 10)),
+        _ => {
+            let index_in = rng.range(0, token_count) as usize;
+            let index_out = (index_in + rng.range(1, token_count) as usize) % token_count as usize;
+            let max_in = pool.balances[index_in] / 10;
+            Op::Swap(index_in, index_out, rng.range(1_000, max_in))
+        }
+    }
+}
+
+fn run_sequence(curve: Curve, balances: Vec<u64>, seed: u64) {
+    let max_drift = match curve {
+        Curve::Weighted { .. } => WEIGHTED_MAX_RELATIVE_DRIFT,
+        Curve::Stable { .. } => STABLE_MAX_RELATIVE_DRIFT,
+    };
+    let lp_supply = balances.iter().sum::<u64>();
+    let mut program = ProgramPool {
+        curve: curve.clone(),
+        balances: balances.clone(),
+        lp_supply,
+    };
+    let mut model = ModelPool {
+        curve,
+        balances: balances.iter().map(|&b| b as f64).collect(),
+        lp_supply: lp_supply as f64,
+    };
+    let mut rng = Rng(seed);
+    let mut invariant_per_lp = program.invariant() / program.lp_supply as f64;
+
+    for step in 0..OPS_PER_SEQUENCE {
+        let op = random_op(&mut rng, &program);
+        // The exact payout of a weighted swap on the pool as the program holds it
+        let exact_out = match (&program.curve, op) {
+            (Curve::Weighted { .. }, Op::Swap(index_in, index_out, amount_in)) => {
+                let exact = ModelPool {
+                    curve: program.curve.clone(),
+                    balances: program.balances.iter().map(|&b| b as f64).collect(),
+                    lp_supply: program.lp_supply as f64,
+                };
+                Some(exact.swap_out(index_in, index_out, amount_in as f64))
+            }
+            _ => None,
+        };
+        let amounts = program
+            .apply(op)
+            .unwrap_or_else(|| panic!("seed {seed}, step {step}: {op:?} failed"));
+        model.apply(op);
+
+        // Weighted swaps round against the trader, never paying out above the curve
+        if let Some(exact_out) = exact_out {
+            assert!(
+                amounts[0] as f64 <= exact_out,
+                "seed {seed}, step {step}: {op:?} paid {} over the exact {exact_out}",
+                amounts[0]
+            );
+        }
+
+        for (i, (&balance, &expected)) in program.balances.iter().zip(&model.balances).enumerate() {
+            let gap = relative_gap(balance as f64, expected);
+            assert!(
+                gap <= max_drift,
+                "seed {seed}, step {step}: balance {i} {balance} vs {expected} ({gap:e})"
+            );
+        }
+        let gap = relative_gap(program.lp_supply as f64, model.lp_supply);
+        assert!(
+            gap <= max_drift,
+            "seed {seed}, step {step}: LP supply ({gap:e})"
+        );
+
+        let invariant = program.invariant();
+        let gap = relative_gap(invariant, model.invariant());
+        assert!(
+            gap <= max_drift,
+            "seed {seed}, step {step}: invariant {invariant} ({gap:e})"
+        );
+
+        // Every rounding goes the pool's way, so LP tokens only ever gain value
+        let next_invariant_per_lp = invariant / program.lp_supply as f64;
+        assert!(
+            next_invariant_per_lp >= invariant_per_lp * (1.0 - 1e-12),
+            "seed {seed}, step {step}: {op:?} lowered invariant per LP \
+             {invariant_per_lp} -> {next_invariant_per_lp}"
+        );
+        invariant_per_lp = next_invariant_per_lp;
+    }
+}
+
+#[test]
+fn test_weighted_pool_tracks_model() {
+    for seed in SEEDS {
+        run_sequence(
+            Curve::Weighted {
+                weights: vec![ONE_U64 * 8 / 10, ONE_U64 * 2 / 10],
+            },
+            vec![8_000_000 * ONE_U64, 2_000_000 * ONE_U64],
+            seed,
+        );
+        run_sequence(
+            Curve::Weighted {
+                weights: vec![ONE_U64 / 3, ONE_U64 / 3, ONE_U64 - 2 * (ONE_U64 / 3)],
+            },
+            vec![500_000 * ONE_U64, 1_500_000 * ONE_U64, 1_000_000 * ONE_U64],
+            seed,
+        );
+    }
+}
+
+#[test]
+fn test_stable_pool_tracks_model() {
+    for seed in SEEDS {
+        run_sequence(
+            Curve::Stable {
+                amp: 100 * AMP_PRECISION,
+            },
+            vec![1_000_000 * ONE_U64, 1_200_000 * ONE_U64],
+            seed,
+        );
+        run_sequence(
+            Curve::Stable {
+                amp: 2_000 * AMP_PRECISION,
+            },
+            vec![
+                3_000_000 * ONE_U64,
+                2_500_000 * ONE_U64,
+                3_500_000 * ONE_U64,
+            ],
+            seed,
+        );
+    }
+}
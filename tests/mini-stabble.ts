@@ -7,6 +7,8 @@ import {
   mintTo,
   getAccount,
   getMint,
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import { PublicKey, Keypair } from "@solana/web3.js";
 import { expect } from "chai";
@@ -31,20 +33,11 @@ describe("mini-stabble", () => {
   let lpMint: Keypair;
   let stableLpMint: Keypair;
 
-  // PDAs
-  let authority: PublicKey;
-  let authorityBump: number;
-
   // User Token accounts
   let userTokenA: PublicKey;
   let userTokenB: PublicKey;
 
   before(async () => {
-    [authority, authorityBump] = PublicKey.findProgramAddressSync(
-      [AUTHORITY_SEED],
-      program.programId
-    );
-
     let tempMintA = await createMint(
       provider.connection,
       payer,
@@ -154,13 +147,18 @@ describe("mini-stabble", () => {
       await program.methods
         .initializeWeightedPool(
           new BN(3_000_000), // swap_fee (0.3%)
-          new BN(500000000) // weight_a (0.5 in SCALE = 5e17)
+          new BN(500000000), // weight_a (0.5 in SCALE = 5e17)
+          0 // creator_lock_days (no lock)
         )
         .accounts({
           lpMint: lpMint.publicKey,
           tokenMintA: mintA,
           tokenMintB: mintB,
+          tokenBadgeA: null,
+          tokenBadgeB: null,
           payer: payer.publicKey,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
         })
         .signers([lpMint])
         .rpc();
@@ -197,7 +195,8 @@ describe("mini-stabble", () => {
         .deposit(
           new BN(0), // lp_amount (0 = first deposit, calculated internally)
           depositAmount, // max token A
-          depositAmount // max token B
+          depositAmount, // max token B
+          false // dry_run
         )
         .accounts({
           pool,
@@ -207,6 +206,9 @@ describe("mini-stabble", () => {
           tokenBMint: mintB,
           userTokenA,
           userTokenB,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          creatorLockEscrow: null,
         })
         .rpc();
 
@@ -245,13 +247,20 @@ describe("mini-stabble", () => {
       const userBBefore = await getAccount(provider.connection, userTokenB);
 
       await program.methods
-        .swap(amountIn, minAmountOut)
+        .swap(amountIn, minAmountOut, false)
         .accounts({
           pool,
+          lpMint: lpMint.publicKey,
+          userStats: null,
+          traderAllowlistEntry: null,
+          swapCheckpoint: null,
+          poolFeeStats: null,
           mintIn: mintA,
           mintOut: mintB,
           userTokenIn: userTokenA,
           userTokenOut: userTokenB,
+          tokenProgramIn: TOKEN_PROGRAM_ID,
+          tokenProgramOut: TOKEN_PROGRAM_ID,
           vaultTokenIn: vaultA,
           vaultTokenOut: vaultB,
           user: payer.publicKey,
@@ -269,6 +278,43 @@ describe("mini-stabble", () => {
         Number(userBBefore.amount)
       );
     });
+    it("withdraws liquidity", async () => {
+      const pool = getPoolPDA();
+      const userLp = getAssociatedTokenAddressSync(
+        lpMint.publicKey,
+        payer.publicKey
+      );
+
+      const lpBefore = await getAccount(provider.connection, userLp);
+      const poolBefore = await program.account.weightedPool.fetch(pool);
+      const lpAmount = new BN(lpBefore.amount.toString()).divn(10);
+
+      await program.methods
+        .withdraw(lpAmount, new BN(1), new BN(1), false)
+        .accounts({
+          pool,
+          user: payer.publicKey,
+          lpMint: lpMint.publicKey,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          userTokenA,
+          userTokenB,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          userLp,
+        })
+        .rpc();
+
+      const lpAfter = await getAccount(provider.connection, userLp);
+      const poolAfter = await program.account.weightedPool.fetch(pool);
+      expect(lpAfter.amount.toString()).to.equal(
+        (lpBefore.amount - BigInt(lpAmount.toString())).toString()
+      );
+      expect(poolAfter.tokens[0].balance.lt(poolBefore.tokens[0].balance)).to
+        .be.true;
+      expect(poolAfter.tokens[1].balance.lt(poolBefore.tokens[1].balance)).to
+        .be.true;
+    });
   });
 
   describe("Stable Pool", async () => {
@@ -276,13 +322,19 @@ describe("mini-stabble", () => {
       let tx = await program.methods
         .initializeStablePool(
           new BN(3_000_000), // swap_fee
-          new BN(100) // amp (100 is typical for stables)
+          new BN(100), // amp (100 is typical for stables)
+          0, // creator_lock_days (no lock)
+          null // amp_ramp (no ramp scheduled)
         )
         .accounts({
           lpMint: stableLpMint.publicKey,
           tokenMintA: mintA,
           tokenMintB: mintB,
+          tokenBadgeA: null,
+          tokenBadgeB: null,
           payer: payer.publicKey,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
         })
         .signers([stableLpMint])
         .rpc();
@@ -303,8 +355,12 @@ describe("mini-stabble", () => {
         provider.connection,
         stableLpMint.publicKey
       );
+      const [stablePoolAuthority] = PublicKey.findProgramAddressSync(
+        [AUTHORITY_SEED, stablePool.toBuffer()],
+        program.programId
+      );
       expect(stableLpMintAccount.mintAuthority.toBase58()).to.equal(
-        authority.toBase58()
+        stablePoolAuthority.toBase58()
       );
       expect(Number(stableLpMintAccount.supply)).to.equal(0);
 
@@ -332,7 +388,8 @@ describe("mini-stabble", () => {
         .stableDeposit(
           depositAmount, // max_amount_a
           depositAmount, // max_amount_b
-          new BN(0) // lp_amount (0 for first deposit)
+          new BN(0), // lp_amount (0 for first deposit)
+          false // dry_run
         )
         .accounts({
           pool,
@@ -343,7 +400,11 @@ describe("mini-stabble", () => {
           vaultTokenB: vaultB,
           userTokenA,
           userTokenB,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
           user: payer.publicKey,
+          creatorLockEscrow: null,
+          lpPosition: null,
         })
         .rpc();
 
@@ -377,15 +438,23 @@ describe("mini-stabble", () => {
       const minAmountOut = new BN(1);
 
       await program.methods
-        .stableSwap(amountIn, minAmountOut)
+        .stableSwap(amountIn, minAmountOut, false)
         .accounts({
           pool,
+          lpMint: stableLpMint.publicKey,
           mintIn: mintA,
           mintOut: mintB,
           vaultTokenIn: vaultA,
           vaultTokenOut: vaultB,
           userTokenIn: userTokenA,
           userTokenOut: userTokenB,
+          tokenProgramIn: TOKEN_PROGRAM_ID,
+          tokenProgramOut: TOKEN_PROGRAM_ID,
+          secondaryRecipient: null,
+          userStats: null,
+          userRebateStats: null,
+          traderAllowlistEntry: null,
+          swapCheckpoint: null,
           user: payer.publicKey,
         })
         .rpc();